@@ -0,0 +1,110 @@
+//! Benchmarks for the core conversion paths.
+//!
+//! Run with `cargo bench -p extendr-api`. R must be installed as for the tests.
+//! See `benches/R/compare-rcpp.R` for the equivalent measurements from R,
+//! compared against Rcpp.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use extendr_api::prelude::*;
+
+const SIZES: [usize; 3] = [10, 1_000, 100_000];
+
+fn vector_conversion(c: &mut Criterion) {
+    extendr_engine::start_r();
+    let mut group = c.benchmark_group("vector conversion");
+    for size in SIZES {
+        let values: Vec<f64> = (0..size).map(|i| i as f64).collect();
+        group.bench_with_input(
+            BenchmarkId::new("Vec<f64> to Robj", size),
+            &values,
+            |b, v| b.iter(|| Robj::from(black_box(v.clone()))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("Doubles::from_values", size),
+            &values,
+            |b, v| b.iter(|| Doubles::from_values(black_box(v.iter().copied()))),
+        );
+
+        let robj = r!(values);
+        group.bench_with_input(
+            BenchmarkId::new("Robj to &[f64]", size),
+            &robj,
+            |b, robj| b.iter(|| black_box(robj.as_real_slice().unwrap().len())),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("Robj to Vec<f64>", size),
+            &robj,
+            |b, robj| b.iter(|| black_box(Vec::<f64>::try_from(robj).unwrap())),
+        );
+    }
+    group.finish();
+}
+
+fn string_construction(c: &mut Criterion) {
+    extendr_engine::start_r();
+    let mut group = c.benchmark_group("string construction");
+    for size in SIZES {
+        let values: Vec<String> = (0..size).map(|i| format!("s{}", i)).collect();
+        group.bench_with_input(
+            BenchmarkId::new("Vec<String> to Robj", size),
+            &values,
+            |b, v| b.iter(|| Robj::from(black_box(v))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("Strings::from_values", size),
+            &values,
+            |b, v| b.iter(|| Strings::from_values(black_box(v.iter()))),
+        );
+
+        let robj = r!(values);
+        group.bench_with_input(
+            BenchmarkId::new("Robj to Vec<&str>", size),
+            &robj,
+            |b, robj| b.iter(|| black_box(robj.as_str_vector().unwrap().len())),
+        );
+    }
+    group.finish();
+}
+
+fn list_building(c: &mut Criterion) {
+    extendr_engine::start_r();
+    let mut group = c.benchmark_group("list building");
+    for size in SIZES {
+        group.bench_with_input(
+            BenchmarkId::new("List::from_values", size),
+            &size,
+            |b, &n| b.iter(|| List::from_values((0..n).map(|i| r!(i as i32)))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("List::from_pairs", size),
+            &size,
+            |b, &n| b.iter(|| List::from_pairs((0..n).map(|i| (format!("x{}", i), r!(i as i32))))),
+        );
+    }
+    group.finish();
+}
+
+fn call_overhead(c: &mut Criterion) {
+    extendr_engine::start_r();
+    let mut group = c.benchmark_group("call overhead");
+    let x = r!([1.0, 2.0, 3.0]);
+    group.bench_function("call!(\"sum\")", |b| {
+        b.iter(|| call!("sum", black_box(&x)).unwrap())
+    });
+    let sum = R!("sum").unwrap().as_function().unwrap();
+    group.bench_function("Function::call", |b| {
+        b.iter(|| sum.call(pairlist!(black_box(&x))).unwrap())
+    });
+    group.bench_function("eval_string", |b| {
+        b.iter(|| eval_string(black_box("sum(1, 2, 3)")).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    vector_conversion,
+    string_construction,
+    list_building,
+    call_overhead
+);
+criterion_main!(benches);
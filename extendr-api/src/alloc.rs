@@ -0,0 +1,233 @@
+//! Growable buffers allocated by R.
+//!
+//! Memory allocated by rust is invisible to R: `gc()` does not report it
+//! and it does not count towards R's memory limits. An [RVec] is a growable
+//! buffer like `Vec`, but its elements are stored in an R vector of the
+//! matching type, so large temporary buffers show up in `gc()` and the
+//! garbage collector reclaims them once the `RVec` is dropped, including
+//! when an R error unwinds through rust code.
+//!
+//! An `RVec` converts to an R vector without a copy if its length equals
+//! its capacity.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::alloc::RVec;
+//! test! {
+//!     let mut squares = RVec::<f64>::with_capacity(4);
+//!     for i in 0..4 {
+//!         squares.push((i * i) as f64);
+//!     }
+//!     assert_eq!(squares[..], [0.0, 1.0, 4.0, 9.0]);
+//!     squares.iter_mut().for_each(|x| *x += 1.0);
+//!     assert_eq!(Robj::from(squares), r!([1.0, 2.0, 5.0, 10.0]));
+//! }
+//! ```
+use crate::scalar::{c64, Rbool, Rcplx, Rfloat, Rint};
+use crate::*;
+use libR_sys::*;
+use std::marker::PhantomData;
+
+/// The element types of an [RVec] and the R vector types that store them.
+///
+/// # Safety
+///
+/// `Self` must have the size and layout of an element of a `SEXPTYPE` vector.
+pub unsafe trait RVecElement: Copy + 'static {
+    /// The type of the R vector, such as `REALSXP`.
+    const SEXPTYPE: SEXPTYPE;
+}
+
+macro_rules! impl_rvec_element {
+    ($($type: ty => $sexptype: ident),*) => {
+        $(
+            unsafe impl RVecElement for $type {
+                const SEXPTYPE: SEXPTYPE = $sexptype;
+            }
+        )*
+    };
+}
+
+impl_rvec_element!(
+    f64 => REALSXP,
+    Rfloat => REALSXP,
+    i32 => INTSXP,
+    Rint => INTSXP,
+    Rbool => LGLSXP,
+    u8 => RAWSXP,
+    c64 => CPLXSXP,
+    Rcplx => CPLXSXP
+);
+
+/// A growable buffer stored in an R vector, see the [module documentation](self).
+///
+/// Like [Robj], an `RVec` can only be used on the R thread.
+pub struct RVec<T: RVecElement> {
+    robj: Robj,
+    len: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T: RVecElement> RVec<T> {
+    /// Make an empty buffer.
+    pub fn new() -> Self {
+        RVec::with_capacity(0)
+    }
+
+    /// Make an empty buffer with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        RVec {
+            robj: Robj::alloc_vector(T::SEXPTYPE, capacity),
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the buffer can hold without allocating.
+    pub fn capacity(&self) -> usize {
+        self.robj.len()
+    }
+
+    /// Make room for at least `additional` more elements.
+    ///
+    /// A new R vector is allocated and the elements are copied to it.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len + additional;
+        if needed <= self.capacity() {
+            return;
+        }
+        let capacity = needed.max(2 * self.capacity()).max(8);
+        let robj = Robj::alloc_vector(T::SEXPTYPE, capacity);
+        unsafe {
+            let new_data = DATAPTR(robj.get()) as *mut T;
+            std::ptr::copy_nonoverlapping(self.as_ptr(), new_data, self.len);
+        }
+        self.robj = robj;
+    }
+
+    /// Add an element at the end.
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+        self.len += 1;
+        let last = self.len - 1;
+        self[last] = value;
+    }
+
+    /// Remove the last element and return it, or `None` if there are none.
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.last().copied()?;
+        self.len -= 1;
+        Some(last)
+    }
+
+    /// Keep the first `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Remove all elements, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Add the elements of a slice at the end.
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        self.reserve(values.len());
+        let start = self.len;
+        self.len += values.len();
+        self[start..].copy_from_slice(values);
+    }
+
+    /// Convert to an R vector of length [RVec::len].
+    ///
+    /// The vector is copied if there is unused capacity.
+    pub fn into_robj(self) -> Robj {
+        if self.len == self.capacity() {
+            self.robj
+        } else {
+            self.robj.xlengthgets(self.len).unwrap()
+        }
+    }
+}
+
+impl<T: RVecElement> Default for RVec<T> {
+    fn default() -> Self {
+        RVec::new()
+    }
+}
+
+impl<T: RVecElement> Deref for RVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe {
+            let data = DATAPTR(self.robj.get()) as *const T;
+            std::slice::from_raw_parts(data, self.len)
+        }
+    }
+}
+
+impl<T: RVecElement> DerefMut for RVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe {
+            let data = DATAPTR(self.robj.get_mut()) as *mut T;
+            std::slice::from_raw_parts_mut(data, self.len)
+        }
+    }
+}
+
+impl<T: RVecElement + std::fmt::Debug> std::fmt::Debug for RVec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: RVecElement> Clone for RVec<T> {
+    fn clone(&self) -> Self {
+        let mut res = RVec::with_capacity(self.len);
+        res.extend_from_slice(self);
+        res
+    }
+}
+
+impl<T: RVecElement> Extend<T> for RVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|value| self.push(value));
+    }
+}
+
+impl<T: RVecElement> std::iter::FromIterator<T> for RVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut res = RVec::new();
+        res.extend(iter);
+        res
+    }
+}
+
+impl<T: RVecElement> From<&[T]> for RVec<T> {
+    fn from(values: &[T]) -> Self {
+        let mut res = RVec::with_capacity(values.len());
+        res.extend_from_slice(values);
+        res
+    }
+}
+
+impl<T: RVecElement> From<RVec<T>> for Robj {
+    fn from(values: RVec<T>) -> Self {
+        values.into_robj()
+    }
+}
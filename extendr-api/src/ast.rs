@@ -0,0 +1,442 @@
+//! A rust representation of R expressions.
+//!
+//! R code is made of calls, symbols and constants. [Expr] mirrors this, with
+//! the control flow calls (`function`, `if`, `for`, `while`, `repeat` and `{`)
+//! given their own variants so that they can be matched on directly.
+//!
+//! Expressions can be parsed from text with [Expr::parse], converted from
+//! language objects with `Expr::from(&robj)`, walked or rewritten in rust, and
+//! turned back into R with `Robj::from(&expr)` or [Expr::deparse].
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::ast::Expr;
+//! test! {
+//!     let exprs = Expr::parse("x + f(y, 2)")?;
+//!
+//!     // Find the symbols used.
+//!     let mut symbols = Vec::new();
+//!     exprs[0].walk(&mut |e| {
+//!         if let Expr::Symbol(name) = e {
+//!             symbols.push(name.clone());
+//!         }
+//!     });
+//!     assert_eq!(symbols, ["+", "x", "f", "y"]);
+//!
+//!     // Rename `y` to `z`.
+//!     let renamed = exprs[0].clone().transform(&mut |e| match e {
+//!         Expr::Symbol(name) if name == "y" => Expr::Symbol("z".into()),
+//!         e => e,
+//!     });
+//!     assert_eq!(renamed.deparse()?, "x + f(z, 2)");
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+use libR_sys::*;
+
+/// An R expression.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    /// A constant such as `1`, `"a"`, `TRUE` or `NULL`.
+    /// Any other object embedded in a call is also kept as a constant.
+    Constant(Robj),
+    /// A name such as `x`.
+    Symbol(String),
+    /// An empty argument, as in `x[, 1]`.
+    Missing,
+    /// A function call. Operators are calls too, `x + 1` calls `+`.
+    Call { function: Box<Expr>, args: Vec<Arg> },
+    /// `function(params) body`
+    Function { params: Vec<Param>, body: Box<Expr> },
+    /// `if (condition) then else otherwise`
+    If {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+    /// `for (var in seq) body`
+    For {
+        var: String,
+        seq: Box<Expr>,
+        body: Box<Expr>,
+    },
+    /// `while (condition) body`
+    While {
+        condition: Box<Expr>,
+        body: Box<Expr>,
+    },
+    /// `repeat body`
+    Repeat { body: Box<Expr> },
+    /// `{ ... }`
+    Block(Vec<Expr>),
+}
+
+/// An argument of a call, with an optional name.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub value: Expr,
+}
+
+/// A parameter of a function, with an optional default value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Param {
+    pub name: String,
+    pub default: Option<Expr>,
+}
+
+impl Arg {
+    /// An unnamed argument.
+    pub fn new(value: Expr) -> Self {
+        Arg { name: None, value }
+    }
+
+    /// A named argument, as in `f(name = value)`.
+    pub fn named(name: &str, value: Expr) -> Self {
+        Arg {
+            name: Some(name.to_string()),
+            value,
+        }
+    }
+}
+
+impl Expr {
+    /// Parse R code into expressions, one for each top level expression.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use extendr_api::ast::Expr;
+    /// test! {
+    ///     let exprs = Expr::parse("x <- 1; if (x) y")?;
+    ///     assert_eq!(exprs.len(), 2);
+    ///     assert!(matches!(exprs[1], Expr::If { .. }));
+    /// }
+    /// ```
+    pub fn parse(code: &str) -> Result<Vec<Expr>> {
+        Ok(parse(code)?
+            .values()
+            .map(|robj| Expr::from(&robj))
+            .collect())
+    }
+
+    /// A symbol.
+    pub fn symbol(name: &str) -> Self {
+        Expr::Symbol(name.to_string())
+    }
+
+    /// A constant.
+    pub fn constant<T: Into<Robj>>(value: T) -> Self {
+        Expr::Constant(value.into())
+    }
+
+    /// A call to a function by name with unnamed arguments.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use extendr_api::ast::Expr;
+    /// test! {
+    ///     let expr = Expr::call("+", [Expr::symbol("x"), Expr::constant(1)]);
+    ///     assert_eq!(expr.deparse()?, "x + 1L");
+    /// }
+    /// ```
+    pub fn call<A: IntoIterator<Item = Expr>>(function: &str, args: A) -> Self {
+        Expr::Call {
+            function: Box::new(Expr::symbol(function)),
+            args: args.into_iter().map(Arg::new).collect(),
+        }
+    }
+
+    /// Deparse the expression back into R code.
+    pub fn deparse(&self) -> Result<String> {
+        let lines: Strings = call!("deparse", Robj::from(self))?.try_into()?;
+        Ok(lines
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// The direct sub-expressions, in source order.
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Constant(_) | Expr::Symbol(_) | Expr::Missing => vec![],
+            Expr::Call { function, args } => std::iter::once(function.as_ref())
+                .chain(args.iter().map(|a| &a.value))
+                .collect(),
+            Expr::Function { params, body } => params
+                .iter()
+                .filter_map(|p| p.default.as_ref())
+                .chain(std::iter::once(body.as_ref()))
+                .collect(),
+            Expr::If {
+                condition,
+                then,
+                otherwise,
+            } => std::iter::once(condition.as_ref())
+                .chain(std::iter::once(then.as_ref()))
+                .chain(otherwise.as_deref())
+                .collect(),
+            Expr::For { seq, body, .. } => vec![seq.as_ref(), body.as_ref()],
+            Expr::While { condition, body } => vec![condition.as_ref(), body.as_ref()],
+            Expr::Repeat { body } => vec![body.as_ref()],
+            Expr::Block(exprs) => exprs.iter().collect(),
+        }
+    }
+
+    /// Call `f` on this expression and every sub-expression, parents before children.
+    pub fn walk<F: FnMut(&Expr)>(&self, f: &mut F) {
+        f(self);
+        for child in self.children() {
+            child.walk(f);
+        }
+    }
+
+    /// Rebuild the expression, replacing every sub-expression with the result of `f`.
+    ///
+    /// Children are transformed before their parents, so `f` sees the new children.
+    pub fn transform<F: FnMut(Expr) -> Expr>(self, f: &mut F) -> Expr {
+        let res = match self {
+            Expr::Constant(_) | Expr::Symbol(_) | Expr::Missing => self,
+            Expr::Call { function, args } => {
+                let function = boxed(function, f);
+                let args = args
+                    .into_iter()
+                    .map(|a| Arg {
+                        name: a.name,
+                        value: a.value.transform(f),
+                    })
+                    .collect();
+                Expr::Call { function, args }
+            }
+            Expr::Function { params, body } => {
+                let params = params
+                    .into_iter()
+                    .map(|p| Param {
+                        name: p.name,
+                        default: p.default.map(|d| d.transform(f)),
+                    })
+                    .collect();
+                Expr::Function {
+                    params,
+                    body: boxed(body, f),
+                }
+            }
+            Expr::If {
+                condition,
+                then,
+                otherwise,
+            } => Expr::If {
+                condition: boxed(condition, f),
+                then: boxed(then, f),
+                otherwise: otherwise.map(|e| boxed(e, f)),
+            },
+            Expr::For { var, seq, body } => Expr::For {
+                var,
+                seq: boxed(seq, f),
+                body: boxed(body, f),
+            },
+            Expr::While { condition, body } => Expr::While {
+                condition: boxed(condition, f),
+                body: boxed(body, f),
+            },
+            Expr::Repeat { body } => Expr::Repeat {
+                body: boxed(body, f),
+            },
+            Expr::Block(exprs) => Expr::Block(exprs.into_iter().map(|e| e.transform(f)).collect()),
+        };
+        f(res)
+    }
+}
+
+fn boxed<F: FnMut(Expr) -> Expr>(expr: Box<Expr>, f: &mut F) -> Box<Expr> {
+    Box::new(expr.transform(f))
+}
+
+/// Make a call to `function` with arguments named by `""` or a name.
+fn make_call<A>(function: Robj, args: A) -> Robj
+where
+    A: IntoIterator<Item = (String, Robj)>,
+{
+    let args = Pairlist::from_pairs(args.into_iter().collect::<Vec<_>>());
+    single_threaded(|| unsafe { Robj::from_sexp(Rf_lcons(function.get(), args.get())) })
+}
+
+fn special(name: &str, args: Vec<&Expr>) -> Robj {
+    make_call(
+        Symbol::from_string(name).into(),
+        args.into_iter().map(|e| (String::new(), Robj::from(e))),
+    )
+}
+
+impl From<&Expr> for Robj {
+    /// Convert an expression to an R language object, symbol or constant.
+    fn from(expr: &Expr) -> Self {
+        match expr {
+            Expr::Constant(robj) => robj.clone(),
+            Expr::Symbol(name) => Symbol::from_string(name).into(),
+            Expr::Missing => missing_arg().into(),
+            Expr::Call { function, args } => make_call(
+                function.as_ref().into(),
+                args.iter()
+                    .map(|a| (a.name.clone().unwrap_or_default(), Robj::from(&a.value))),
+            ),
+            Expr::Function { params, body } => {
+                let formals = Pairlist::from_pairs(
+                    params
+                        .iter()
+                        .map(|p| {
+                            let default = match &p.default {
+                                Some(e) => Robj::from(e),
+                                None => missing_arg().into(),
+                            };
+                            (p.name.clone(), default)
+                        })
+                        .collect::<Vec<_>>(),
+                );
+                make_call(
+                    Symbol::from_string("function").into(),
+                    [
+                        (String::new(), formals.into()),
+                        (String::new(), Robj::from(body.as_ref())),
+                    ],
+                )
+            }
+            Expr::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let mut args = vec![condition.as_ref(), then.as_ref()];
+                args.extend(otherwise.as_deref());
+                special("if", args)
+            }
+            Expr::For { var, seq, body } => make_call(
+                Symbol::from_string("for").into(),
+                [
+                    (String::new(), Symbol::from_string(var).into()),
+                    (String::new(), Robj::from(seq.as_ref())),
+                    (String::new(), Robj::from(body.as_ref())),
+                ],
+            ),
+            Expr::While { condition, body } => {
+                special("while", vec![condition.as_ref(), body.as_ref()])
+            }
+            Expr::Repeat { body } => special("repeat", vec![body.as_ref()]),
+            Expr::Block(exprs) => special("{", exprs.iter().collect()),
+        }
+    }
+}
+
+impl From<Expr> for Robj {
+    fn from(expr: Expr) -> Self {
+        Robj::from(&expr)
+    }
+}
+
+impl From<&Robj> for Expr {
+    /// Convert a language object, symbol or constant to an expression.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use extendr_api::ast::Expr;
+    /// test! {
+    ///     let expr = Expr::from(&lang!("f", sym!(x)));
+    ///     assert_eq!(expr, Expr::call("f", [Expr::symbol("x")]));
+    /// }
+    /// ```
+    fn from(robj: &Robj) -> Self {
+        if robj.is_missing_arg() {
+            Expr::Missing
+        } else if let Some(symbol) = robj.as_symbol() {
+            Expr::Symbol(symbol.as_str().to_string())
+        } else if let Some(language) = robj.as_language() {
+            from_language(&language)
+        } else {
+            Expr::Constant(robj.clone())
+        }
+    }
+}
+
+impl From<Robj> for Expr {
+    fn from(robj: Robj) -> Self {
+        Expr::from(&robj)
+    }
+}
+
+fn from_language(language: &Language) -> Expr {
+    let mut iter = language.iter();
+    let function = match iter.next() {
+        Some((_, function)) => function,
+        None => return Expr::Constant(language.as_robj().clone()),
+    };
+    let args: Vec<(&str, Robj)> = iter.collect();
+
+    if let (Some(name), true) = (function.as_symbol(), args.iter().all(|(n, _)| n.is_empty())) {
+        if let Some(expr) = from_special(name.as_str(), &args) {
+            return expr;
+        }
+    }
+
+    Expr::Call {
+        function: Box::new(Expr::from(&function)),
+        args: args
+            .into_iter()
+            .map(|(name, value)| Arg {
+                name: if name.is_empty() {
+                    None
+                } else {
+                    Some(name.to_string())
+                },
+                value: Expr::from(&value),
+            })
+            .collect(),
+    }
+}
+
+/// Convert the control flow calls, or return `None` for ordinary calls.
+fn from_special(name: &str, args: &[(&str, Robj)]) -> Option<Expr> {
+    let expr = |i: usize| Box::new(Expr::from(&args[i].1));
+    match (name, args.len()) {
+        // The parser adds a srcref as the third argument.
+        ("function", 2) | ("function", 3) => {
+            let formals = &args[0].1;
+            let params = if formals.is_null() {
+                vec![]
+            } else {
+                formals
+                    .as_pairlist()?
+                    .iter()
+                    .map(|(name, default)| Param {
+                        name: name.to_string(),
+                        default: if default.is_missing_arg() {
+                            None
+                        } else {
+                            Some(Expr::from(&default))
+                        },
+                    })
+                    .collect()
+            };
+            Some(Expr::Function {
+                params,
+                body: expr(1),
+            })
+        }
+        ("if", 2) | ("if", 3) => Some(Expr::If {
+            condition: expr(0),
+            then: expr(1),
+            otherwise: args.get(2).map(|(_, e)| Box::new(Expr::from(e))),
+        }),
+        ("for", 3) => Some(Expr::For {
+            var: args[0].1.as_symbol()?.as_str().to_string(),
+            seq: expr(1),
+            body: expr(2),
+        }),
+        ("while", 2) => Some(Expr::While {
+            condition: expr(0),
+            body: expr(1),
+        }),
+        ("repeat", 1) => Some(Expr::Repeat { body: expr(0) }),
+        ("{", _) => Some(Expr::Block(
+            args.iter().map(|(_, e)| Expr::from(e)).collect(),
+        )),
+        _ => None,
+    }
+}
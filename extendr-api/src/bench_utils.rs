@@ -0,0 +1,201 @@
+//! Timing helpers for measuring the cost of extendr code.
+//!
+//! [system_time] measures a closure like R's `system.time()` and returns a
+//! `proc_time` object that prints the same way. [bench] repeats a closure
+//! and reports the mean wall time, which is convenient when comparing
+//! extendr functions with Rcpp or base R equivalents.
+//!
+//! [extendr_bench_export!](crate::extendr_bench_export) defines an
+//! `#[extendr]` function along with a `<name>_bench()` companion that runs
+//! it repeatedly and returns the timings as a data frame, from [bench_data_frame].
+//!
+//! The criterion benchmarks in `extendr-api/benches` cover the core
+//! conversion paths; `extendr-api/benches/R` has scripts comparing them
+//! with Rcpp.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::bench_utils::{bench, system_time};
+//! test! {
+//!     let time = system_time(|| (0..1000).map(|i| i as f64).collect::<Doubles>())?;
+//!     assert!(time.inherits("proc_time"));
+//!
+//!     let result = bench("collect doubles", 10, || {
+//!         (0..1000).map(|i| i as f64).collect::<Doubles>()
+//!     });
+//!     assert_eq!(result.iterations, 10);
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+use std::time::{Duration, Instant};
+
+/// Time a closure and return its result along with the wall time taken.
+pub fn time<F, R>(f: F) -> (R, Duration)
+where
+    F: FnOnce() -> R,
+{
+    let start = Instant::now();
+    let res = f();
+    (res, start.elapsed())
+}
+
+/// Time a closure like `system.time(expr)`.
+///
+/// Returns a `proc_time` object with the user, system and elapsed times as
+/// reported by R's `proc.time()`. See also [r_system_time].
+pub fn system_time<F, R>(f: F) -> Result<Robj>
+where
+    F: FnOnce() -> R,
+{
+    let (_, time) = r_system_time(f)?;
+    Ok(time.into())
+}
+
+/// The result of [bench].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub name: String,
+    pub iterations: usize,
+    pub total: Duration,
+}
+
+impl BenchResult {
+    /// The mean wall time of one iteration.
+    pub fn mean(&self) -> Duration {
+        if self.iterations == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.iterations as u32
+        }
+    }
+}
+
+/// Convert to a list with `name`, `iterations`, `total` and `mean`
+/// (the times are in seconds).
+impl From<BenchResult> for Robj {
+    fn from(res: BenchResult) -> Self {
+        let mean = res.mean().as_secs_f64();
+        List::from_pairs([
+            ("name", r!(res.name)),
+            ("iterations", r!(res.iterations as i32)),
+            ("total", r!(res.total.as_secs_f64())),
+            ("mean", r!(mean)),
+        ])
+        .into()
+    }
+}
+
+/// Run `f` `iterations` times and report the total wall time.
+///
+/// The result of each call is dropped after the timer stops, so the cost of
+/// freeing R objects is not included.
+pub fn bench<F, R>(name: &str, iterations: usize, mut f: F) -> BenchResult
+where
+    F: FnMut() -> R,
+{
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let (res, elapsed) = time(&mut f);
+        total += elapsed;
+        drop(res);
+    }
+    BenchResult {
+        name: name.to_string(),
+        iterations,
+        total,
+    }
+}
+
+/// Run `f` `iterations` times and return the wall time of each run as a
+/// data frame with columns `expression`, `iteration` and `time` (in seconds).
+///
+/// The data frame can be combined with timings of R code by `rbind()`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::bench_utils::bench_data_frame;
+/// test! {
+///     let times = bench_data_frame("sum", 5, || (0..1000).sum::<i32>())?;
+///     assert!(times.inherits("data.frame"));
+///     assert_eq!(times.dollar("iteration")?, r!([1, 2, 3, 4, 5]));
+/// }
+/// ```
+pub fn bench_data_frame<F, R>(name: &str, iterations: usize, mut f: F) -> Result<Robj>
+where
+    F: FnMut() -> R,
+{
+    let times: Vec<f64> = (0..iterations)
+        .map(|_| {
+            let (res, elapsed) = time(&mut f);
+            drop(res);
+            elapsed.as_secs_f64()
+        })
+        .collect();
+    let expression = vec![name; iterations];
+    let iteration: Vec<i32> = (1..=iterations as i32).collect();
+    let data_frame = List::from_pairs([
+        ("expression", r!(expression)),
+        ("iteration", r!(iteration)),
+        ("time", r!(times)),
+    ]);
+    call!("as.data.frame", data_frame, stringsAsFactors = false)
+}
+
+/// Define an `#[extendr]` function and a companion that benchmarks it.
+///
+/// For a function `name`, the companion `name_bench()` takes the same
+/// arguments and `times`, which defaults to 100. It calls the function
+/// `times` times with clones of the arguments and returns the timings as a
+/// data frame, see [bench_data_frame](crate::bench_utils::bench_data_frame).
+/// Export both in `extendr_module!`:
+///
+/// ```ignore
+/// use extendr_api::prelude::*;
+///
+/// extendr_bench_export! {
+///     /// The sum of squares of `x`.
+///     fn sum_squares(x: &[f64]) -> f64 {
+///         x.iter().map(|x| x * x).sum()
+///     }
+/// }
+///
+/// extendr_module! {
+///     mod mypackage;
+///     fn sum_squares;
+///     fn sum_squares_bench;
+/// }
+/// ```
+///
+/// and in R:
+///
+/// ```r
+/// x <- runif(1e6)
+/// rust <- sum_squares_bench(x, times = 50)
+/// aggregate(time ~ expression, rust, median)
+/// ```
+///
+/// Arguments must be plain identifiers with types that implement `Clone`,
+/// as references, `Robj` and the wrapper types do.
+#[macro_export]
+macro_rules! extendr_bench_export {
+    (
+        $(#[$attr: meta])*
+        $vis: vis fn $name: ident ( $($arg: ident : $ty: ty),* $(,)? ) $(-> $ret: ty)? $body: block
+    ) => {
+        #[$crate::extendr]
+        $(#[$attr])*
+        $vis fn $name($($arg: $ty),*) $(-> $ret)? $body
+
+        $crate::paste::paste! {
+            /// Benchmark the function, returning a data frame of timings.
+            #[$crate::extendr]
+            $vis fn [<$name _bench>]($($arg: $ty,)* #[default = "100L"] times: i32) -> $crate::Result<$crate::Robj> {
+                $crate::bench_utils::bench_data_frame(
+                    stringify!($name),
+                    times.max(0) as usize,
+                    || $name($(::std::clone::Clone::clone(&$arg)),*),
+                )
+            }
+        }
+    };
+}
@@ -0,0 +1,516 @@
+//! Bin numbers into intervals as `cut()` and `hist()` do.
+//!
+//! [Cut] assigns each number to an interval between two breaks and makes
+//! a factor with `cut()`'s labels. [Histogram] counts the numbers in each
+//! interval with the rules of `hist()`, including its default `"Sturges"`
+//! breaks and the small fuzz it adds to the breaks. Both work on the
+//! values directly, without calling R for each vector.
+//!
+//! `NA`, `NaN` and numbers outside the breaks are `NA` in a [Cut]. A
+//! [Histogram] ignores values that are not finite, as `hist()` does.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::binning::{Cut, Histogram};
+//! test! {
+//!     let x = [0.5, 1.0, 1.5, 2.0, f64::NAN, 3.0];
+//!     let cut = Cut::new(&[0.0, 1.0, 2.0])?;
+//!     assert_eq!(cut.labels(), ["(0,1]", "(1,2]"]);
+//!     assert_eq!(cut.codes(&x), [Some(0), Some(0), Some(1), Some(1), None, None]);
+//!     assert_eq!(cut.factor(&x)?, R!("cut(c(0.5, 1, 1.5, 2, NaN, 3), c(0, 1, 2))")?);
+//!
+//!     let hist = Histogram::new(&[1.0, 2.0, 2.0, 3.0, 7.0])?;
+//!     assert_eq!(hist.breaks, [0.0, 2.0, 4.0, 6.0, 8.0]);
+//!     assert_eq!(hist.counts, [3, 1, 0, 1]);
+//! }
+//! ```
+use crate::*;
+
+/// Intervals between breaks, as used by `cut()`, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cut {
+    breaks: Vec<f64>,
+    labels: Option<Vec<String>>,
+    right: bool,
+    include_lowest: bool,
+    ordered: bool,
+}
+
+impl Cut {
+    /// Make intervals from two or more breaks, closed on the right like
+    /// `cut(x, breaks)`.
+    ///
+    /// The breaks are sorted. `NaN` and repeated breaks are an error.
+    pub fn new(breaks: &[f64]) -> Result<Self> {
+        Ok(Cut {
+            breaks: sorted_breaks(breaks)?,
+            labels: None,
+            right: true,
+            include_lowest: false,
+            ordered: false,
+        })
+    }
+
+    /// Close the intervals on the right, `(a,b]`, or on the left, `[a,b)`,
+    /// like the `right` argument of `cut()`.
+    pub fn with_right(mut self, right: bool) -> Self {
+        self.right = right;
+        self
+    }
+
+    /// Close the lowest interval on the left, or the highest on the right
+    /// if the intervals are closed on the left, like the `include.lowest`
+    /// argument of `cut()`.
+    /// ```
+    /// use extendr_api::binning::Cut;
+    /// let cut = Cut::new(&[0.0, 1.0, 2.0]).unwrap();
+    /// assert_eq!(cut.code(0.0), None);
+    ///
+    /// let cut = cut.with_include_lowest(true);
+    /// assert_eq!(cut.code(0.0), Some(0));
+    /// assert_eq!(cut.labels(), ["[0,1]", "(1,2]"]);
+    ///
+    /// let cut = cut.with_right(false);
+    /// assert_eq!(cut.code(2.0), Some(1));
+    /// assert_eq!(cut.labels(), ["[0,1)", "[1,2]"]);
+    /// ```
+    pub fn with_include_lowest(mut self, include_lowest: bool) -> Self {
+        self.include_lowest = include_lowest;
+        self
+    }
+
+    /// Use these labels for the levels of the factor, one per interval.
+    pub fn with_labels<I>(mut self, labels: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(Into::into).collect();
+        if labels.len() != self.len() {
+            return Err(Error::Other(format!(
+                "expected {} labels for the intervals, got {}",
+                self.len(),
+                labels.len()
+            )));
+        }
+        self.labels = Some(labels);
+        Ok(self)
+    }
+
+    /// Make an ordered factor, like the `ordered_result` argument of `cut()`.
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// The sorted breaks.
+    pub fn breaks(&self) -> &[f64] {
+        &self.breaks
+    }
+
+    /// The number of intervals, one less than the number of breaks.
+    pub fn len(&self) -> usize {
+        self.breaks.len() - 1
+    }
+
+    /// Always false, as there are at least two breaks.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The labels of the intervals, either those given or the ones `cut()`
+    /// makes, such as `(0,1]`.
+    ///
+    /// Breaks are written with three significant digits, or more if
+    /// needed to tell them apart, up to twelve. If that is not enough the
+    /// labels are `Range_1`, `Range_2`, ..., as in `cut()`.
+    pub fn labels(&self) -> Vec<String> {
+        if let Some(labels) = &self.labels {
+            return labels.clone();
+        }
+        let written = (3..=12)
+            .map(|digits| {
+                self.breaks
+                    .iter()
+                    .map(|&b| format_g(b, digits))
+                    .collect::<Vec<_>>()
+            })
+            .find(|written| written.windows(2).all(|w| w[0] != w[1]));
+        let written = match written {
+            Some(written) => written,
+            None => return (1..=self.len()).map(|i| format!("Range_{}", i)).collect(),
+        };
+        let (open, close) = if self.right { ("(", "]") } else { ("[", ")") };
+        let mut labels: Vec<String> = written
+            .windows(2)
+            .map(|w| format!("{}{},{}{}", open, w[0], w[1], close))
+            .collect();
+        if self.include_lowest {
+            if self.right {
+                labels[0].replace_range(..1, "[");
+            } else {
+                let last = labels.last_mut().unwrap();
+                last.replace_range(last.len() - 1.., "]");
+            }
+        }
+        labels
+    }
+
+    /// The zero-based index of the interval containing `x`, or `None` for
+    /// `NaN` and numbers outside the intervals.
+    pub fn code(&self, x: f64) -> Option<usize> {
+        bin_code(&self.breaks, x, self.right, self.include_lowest)
+    }
+
+    /// The interval of each number, see [Cut::code].
+    pub fn codes(&self, x: &[f64]) -> Vec<Option<usize>> {
+        x.iter().map(|&x| self.code(x)).collect()
+    }
+
+    /// Make a factor of the intervals, like `cut()`.
+    pub fn factor(&self, x: &[f64]) -> Result<Robj> {
+        let labels = self.labels();
+        let codes = x
+            .iter()
+            .map(|&x| match self.code(x) {
+                Some(code) => code as i32 + 1,
+                None => i32::na(),
+            })
+            .collect::<Vec<_>>();
+        let mut res = Robj::from(codes);
+        res.set_attrib(levels_symbol(), Strings::from_values(labels))?;
+        if self.ordered {
+            res.set_class(["ordered", "factor"])?;
+        } else {
+            res.set_class(["factor"])?;
+        }
+        Ok(res)
+    }
+
+    /// Count the finite numbers in each interval with the rules of `hist()`.
+    ///
+    /// As in `hist()`, the breaks are moved by a tiny amount so that
+    /// numbers that differ from a break only by rounding are counted on
+    /// the side given by [Cut::with_right] and [Cut::with_include_lowest].
+    /// It is an error if a finite number is outside the breaks.
+    /// ```
+    /// use extendr_api::binning::Cut;
+    /// let cut = Cut::new(&[0.0, 0.1, 0.2, 0.3]).unwrap().with_include_lowest(true);
+    /// let hist = cut.histogram(&[0.0, 0.1, 0.1 + 0.2, 0.3]).unwrap();
+    /// assert_eq!(hist.counts, [2, 0, 2]);
+    /// assert!(cut.histogram(&[0.4]).is_err());
+    /// ```
+    pub fn histogram(&self, x: &[f64]) -> Result<Histogram> {
+        let x: Vec<f64> = x.iter().copied().filter(|x| x.is_finite()).collect();
+        Histogram::count(&x, &self.breaks, self.right, self.include_lowest)
+    }
+}
+
+/// The counts and densities of a histogram, as returned by `hist(plot = FALSE)`.
+///
+/// Converts to an R object of class `histogram`, which `plot()` draws.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::binning::Histogram;
+/// test! {
+///     let x = [0.2, 0.4, 1.5, 2.5, f64::NAN];
+///     let hist = Histogram::with_breaks(&x, &[0.0, 1.0, 3.0])?;
+///     assert_eq!(hist.counts, [2, 2]);
+///     assert_eq!(hist.density, [0.5, 0.25]);
+///     assert_eq!(hist.mids, [0.5, 2.0]);
+///     assert!(!hist.equidist);
+///
+///     let robj = Robj::from(hist);
+///     let expected = R!("hist(c(0.2, 0.4, 1.5, 2.5, NaN), c(0, 1, 3), plot = FALSE)")?;
+///     assert_eq!(robj.dollar("counts")?, expected.dollar("counts")?);
+///     assert!(robj.inherits("histogram"));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// The sorted breaks.
+    pub breaks: Vec<f64>,
+    /// The number of values in each interval.
+    pub counts: Vec<i32>,
+    /// The counts divided by the number of values and the width of the interval.
+    pub density: Vec<f64>,
+    /// The middle of each interval.
+    pub mids: Vec<f64>,
+    /// True if the intervals have the same width.
+    pub equidist: bool,
+}
+
+impl Histogram {
+    /// Count the finite numbers with the breaks `hist()` chooses by default,
+    /// `pretty(range(x), nclass.Sturges(x), min.n = 1)`.
+    ///
+    /// It is an error if there are no finite numbers.
+    pub fn new(x: &[f64]) -> Result<Self> {
+        let x: Vec<f64> = x.iter().copied().filter(|x| x.is_finite()).collect();
+        if x.is_empty() {
+            return Err(Error::Other(
+                "a histogram needs at least one finite value".to_string(),
+            ));
+        }
+        let (lo, hi) = range(&x);
+        let sturges = ((x.len() as f64).log2() + 1.0).ceil() as usize;
+        let breaks = pretty_with(lo, hi, sturges, 1);
+        Histogram::count(&x, &breaks, true, true)
+    }
+
+    /// Count the finite numbers in intervals closed on the right, with the
+    /// lowest closed on the left, like `hist(x, breaks)`.
+    pub fn with_breaks(x: &[f64], breaks: &[f64]) -> Result<Self> {
+        Cut::new(breaks)?.with_include_lowest(true).histogram(x)
+    }
+
+    // Count finite `x` in sorted `breaks` as `hist.default()` does.
+    fn count(x: &[f64], breaks: &[f64], right: bool, include_lowest: bool) -> Result<Self> {
+        let nb = breaks.len();
+        let widths: Vec<f64> = breaks.windows(2).map(|w| w[1] - w[0]).collect();
+        let (min_width, max_width) = range(&widths);
+        let mean_width = widths.iter().sum::<f64>() / widths.len() as f64;
+        let equidist = max_width - min_width < 1e-7 * mean_width;
+
+        let diddle = 1e-7
+            * if nb > 5 {
+                median(&widths)
+            } else if nb <= 3 {
+                let (lo, hi) = if x.is_empty() { (0.0, 0.0) } else { range(x) };
+                hi - lo
+            } else {
+                min_width
+            };
+        let fuzzy: Vec<f64> = breaks
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                let border = if right { i == 0 } else { i == nb - 1 };
+                let outward = if right { -diddle } else { diddle };
+                match (border, include_lowest) {
+                    (true, true) => b + outward,
+                    _ => b - outward,
+                }
+            })
+            .collect();
+
+        let mut counts = vec![0; nb - 1];
+        for &x in x {
+            match bin_code(&fuzzy, x, right, include_lowest) {
+                Some(code) => counts[code] += 1,
+                None => return Err(Error::Other(
+                    "some values were not counted, the breaks do not span the range of the values"
+                        .to_string(),
+                )),
+            }
+        }
+
+        let n = x.len() as f64;
+        let density = counts
+            .iter()
+            .zip(&widths)
+            .map(|(&count, width)| count as f64 / (n * width))
+            .collect();
+        let mids = breaks.windows(2).map(|w| 0.5 * (w[0] + w[1])).collect();
+        Ok(Histogram {
+            breaks: breaks.to_vec(),
+            counts,
+            density,
+            mids,
+            equidist,
+        })
+    }
+}
+
+impl From<Histogram> for Robj {
+    fn from(hist: Histogram) -> Self {
+        let mut res: Robj = List::from_pairs([
+            ("breaks", Robj::from(hist.breaks)),
+            ("counts", Robj::from(hist.counts)),
+            ("density", Robj::from(hist.density)),
+            ("mids", Robj::from(hist.mids)),
+            ("xname", Robj::from("x")),
+            ("equidist", Robj::from(hist.equidist)),
+        ])
+        .into();
+        res.set_class(["histogram"]).unwrap();
+        res
+    }
+}
+
+/// Round numbers between `lo` and `hi` for about `n` intervals, like
+/// `pretty(c(lo, hi), n)`.
+///
+/// The breaks are 1, 2 or 5 times a power of ten apart.
+/// ```
+/// use extendr_api::binning::pretty;
+/// assert_eq!(pretty(0.0, 97.0, 5), [0.0, 20.0, 40.0, 60.0, 80.0, 100.0]);
+/// assert_eq!(pretty(-3.2, 4.7, 5), [-4.0, -2.0, 0.0, 2.0, 4.0, 6.0]);
+/// ```
+pub fn pretty(lo: f64, hi: f64, n: usize) -> Vec<f64> {
+    pretty_with(lo, hi, n, n / 3)
+}
+
+// R_pretty() from R's src/appl/pretty.c with the defaults of
+// pretty.default(): shrink.sml = 0.75, high.u.bias = 1.5, u5.bias = 2.75,
+// f.min = 2^-20 and no eps.correct.
+fn pretty_with(lo: f64, hi: f64, n: usize, min_n: usize) -> Vec<f64> {
+    const ROUNDING_EPS: f64 = 1e-10;
+    const SHRINK_SML: f64 = 0.75;
+    const H: f64 = 1.5;
+    const H5: f64 = 0.5 + 1.5 * H;
+
+    let ndiv = n.max(1) as f64;
+    let dx = hi - lo;
+    let (mut cell, small) = if dx == 0.0 && hi == 0.0 {
+        (1.0, true)
+    } else {
+        let cell = lo.abs().max(hi.abs());
+        let u = 1.0
+            + if H5 >= 1.5 * H + 0.5 {
+                1.0 / (1.0 + H)
+            } else {
+                1.5 / (1.0 + H5)
+            };
+        (cell, dx < cell * u * ndiv * f64::EPSILON * 3.0)
+    };
+    if small {
+        if cell > 10.0 {
+            cell = 9.0 + cell / 10.0;
+        }
+        cell *= SHRINK_SML;
+        if min_n > 1 {
+            cell /= min_n as f64;
+        }
+    } else {
+        cell = dx;
+        if n > 1 {
+            cell /= n as f64;
+        }
+    }
+    cell = cell.max(2f64.powi(-20) * f64::MIN_POSITIVE);
+
+    let base = 10f64.powf(cell.log10().floor());
+    let mut unit = base;
+    if 2.0 * base - cell < H * (cell - unit) {
+        unit = 2.0 * base;
+        if 5.0 * base - cell < H5 * (cell - unit) {
+            unit = 5.0 * base;
+            if 10.0 * base - cell < H * (cell - unit) {
+                unit = 10.0 * base;
+            }
+        }
+    }
+
+    let mut ns = (lo / unit + ROUNDING_EPS).floor();
+    let mut nu = (hi / unit - ROUNDING_EPS).ceil();
+    while ns * unit > lo + ROUNDING_EPS * unit {
+        ns -= 1.0;
+    }
+    while nu * unit < hi - ROUNDING_EPS * unit {
+        nu += 1.0;
+    }
+    let mut k = (0.5 + nu - ns) as usize;
+    if k < min_n {
+        let extra = (min_n - k) as f64;
+        let half = (extra / 2.0).floor();
+        if ns >= 0.0 {
+            nu += half;
+            ns -= extra - half;
+        } else {
+            ns -= half;
+            nu += extra - half;
+        }
+        k = min_n;
+    }
+
+    let from = (ns * unit).min(lo);
+    let to = (nu * unit).max(hi);
+    if k == 0 {
+        return vec![from];
+    }
+    // seq.int(from, to, length.out = k + 1), with rounding errors near zero removed.
+    let by = (to - from) / k as f64;
+    (0..=k)
+        .map(|i| if i == k { to } else { from + i as f64 * by })
+        .map(|b| if b.abs() < 1e-14 * by { 0.0 } else { b })
+        .collect()
+}
+
+fn sorted_breaks(breaks: &[f64]) -> Result<Vec<f64>> {
+    if breaks.len() < 2 || breaks.iter().any(|b| b.is_nan()) {
+        return Err(Error::Other(
+            "expected at least two breaks that are not NA".to_string(),
+        ));
+    }
+    let mut breaks = breaks.to_vec();
+    breaks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if breaks.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::Other("breaks are not unique".to_string()));
+    }
+    Ok(breaks)
+}
+
+// The zero-based interval of x in sorted breaks, as R's C bincode().
+fn bin_code(breaks: &[f64], x: f64, right: bool, include_lowest: bool) -> Option<usize> {
+    let (mut lo, mut hi) = (0, breaks.len() - 1);
+    let border = if right { breaks[lo] } else { breaks[hi] };
+    if x.is_nan() || x < breaks[lo] || breaks[hi] < x || (x == border && !include_lowest) {
+        return None;
+    }
+    while hi - lo >= 2 {
+        let mid = (hi + lo) / 2;
+        if x > breaks[mid] || (!right && x == breaks[mid]) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(lo)
+}
+
+fn range(x: &[f64]) -> (f64, f64) {
+    x.iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| {
+            (lo.min(x), hi.max(x))
+        })
+}
+
+fn median(x: &[f64]) -> f64 {
+    let mut x = x.to_vec();
+    x.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = x.len() / 2;
+    if x.len() % 2 == 0 {
+        0.5 * (x[mid - 1] + x[mid])
+    } else {
+        x[mid]
+    }
+}
+
+// Write a number like C's "%.<digits>g", as formatC() does.
+fn format_g(x: f64, digits: usize) -> String {
+    if x.is_infinite() {
+        return if x > 0.0 { "Inf" } else { "-Inf" }.to_string();
+    }
+    if x == 0.0 {
+        return "0".to_string();
+    }
+    let sci = format!("{:.*e}", digits - 1, x);
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    if exp < -4 || exp >= digits as i32 {
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", trim_zeros(mantissa), sign, exp.abs())
+    } else {
+        let decimals = (digits as i32 - 1 - exp) as usize;
+        trim_zeros(&format!("{:.*}", decimals, x)).to_string()
+    }
+}
+
+fn trim_zeros(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
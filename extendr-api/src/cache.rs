@@ -0,0 +1,232 @@
+//! Reuse rust values derived from R objects across calls.
+//!
+//! Functions that take a configuration, a pattern or a lookup table as an
+//! argument often spend most of their time rebuilding the same rust
+//! structure from the same R object on every call. [cached()] converts an
+//! object once and returns the saved value while the object is unchanged,
+//! and [Cached] does the same for a function argument.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::cache::cached;
+//! use std::cell::Cell;
+//! test! {
+//!     let calls = Cell::new(0);
+//!     let parse = |x: &Robj| -> Result<String> {
+//!         calls.set(calls.get() + 1);
+//!         Ok(x.as_str_vector().unwrap_or_default().join(","))
+//!     };
+//!
+//!     let config = r!(["a", "b"]);
+//!     assert_eq!(*cached(&config, parse)?, "a,b");
+//!     assert_eq!(*cached(&config, parse)?, "a,b");
+//!     assert_eq!(calls.get(), 1);
+//!
+//!     // An equal but different object is converted again.
+//!     assert_eq!(*cached(&r!(["a", "b"]), parse)?, "a,b");
+//!     assert_eq!(calls.get(), 2);
+//! }
+//! ```
+//!
+//! Entries are found by the address of the object and the type of the
+//! value. The cache keeps the object alive, so the address is not reused,
+//! and marks it not mutable, so R copies it before any change and the
+//! changed copy is converted again. Only the most recently used entries
+//! are kept, see [set_capacity()]. Nothing is cached unless these
+//! functions are used.
+use crate::*;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The number of entries kept by default.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+struct Entry {
+    robj: Robj,
+    type_id: TypeId,
+    value: Rc<dyn Any>,
+}
+
+struct Cache {
+    // Least recently used first.
+    entries: Vec<Entry>,
+    capacity: usize,
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::new(Cache {
+        entries: Vec::new(),
+        capacity: DEFAULT_CAPACITY,
+    });
+}
+
+/// Get the value made from `robj` by `convert`, calling `convert` only if
+/// there is no saved value for this object and type.
+///
+/// Errors are returned and not saved. `NULL` is converted every time.
+pub fn cached<T, F>(robj: &Robj, convert: F) -> Result<Rc<T>>
+where
+    T: 'static,
+    F: FnOnce(&Robj) -> Result<T>,
+{
+    if robj.is_null() {
+        return Ok(Rc::new(convert(robj)?));
+    }
+    let type_id = TypeId::of::<T>();
+    if let Some(value) = lookup(robj, type_id) {
+        if let Ok(value) = value.downcast::<T>() {
+            return Ok(value);
+        }
+    }
+
+    // Convert without borrowing the cache, as `convert` may use it.
+    let value = Rc::new(convert(robj)?);
+    single_threaded(|| unsafe { MARK_NOT_MUTABLE(robj.get()) });
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.entries.push(Entry {
+            robj: robj.clone(),
+            type_id,
+            value: value.clone(),
+        });
+        cache.trim();
+    });
+    Ok(value)
+}
+
+// Find an entry and make it the most recently used.
+fn lookup(robj: &Robj, type_id: TypeId) -> Option<Rc<dyn Any>> {
+    let sexp = unsafe { robj.get() };
+    CACHE.with(|cache| {
+        let entries = &mut cache.borrow_mut().entries;
+        let index = entries
+            .iter()
+            .position(|e| e.type_id == type_id && unsafe { e.robj.get() } == sexp)?;
+        let entry = entries.remove(index);
+        let value = entry.value.clone();
+        entries.push(entry);
+        Some(value)
+    })
+}
+
+impl Cache {
+    fn trim(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+    }
+}
+
+/// Set the number of entries to keep, dropping the least recently used
+/// entries if there are more. Zero turns caching off.
+pub fn set_capacity(capacity: usize) {
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        cache.capacity = capacity;
+        cache.trim();
+    });
+}
+
+/// The number of saved values.
+pub fn len() -> usize {
+    CACHE.with(|cache| cache.borrow().entries.len())
+}
+
+/// Drop all saved values, and the references to their R objects.
+///
+/// This is called when a package built with [extendr_module!](crate::extendr_module) is unloaded.
+pub fn clear() {
+    // Take the entries first, so values that use the cache in `drop` can.
+    let entries = CACHE.with(|cache| std::mem::take(&mut cache.borrow_mut().entries));
+    drop(entries);
+}
+
+/// A function argument converted with [cached()], so that passing the same
+/// R object again reuses the converted value.
+///
+/// `T` is converted with `TryFrom<&Robj>`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::cache::Cached;
+///
+/// #[derive(Debug)]
+/// struct Weights(Vec<f64>);
+///
+/// impl TryFrom<&Robj> for Weights {
+///     type Error = Error;
+///
+///     fn try_from(robj: &Robj) -> Result<Self> {
+///         let mut weights: Vec<f64> = robj.try_into()?;
+///         let total: f64 = weights.iter().sum();
+///         weights.iter_mut().for_each(|w| *w /= total);
+///         Ok(Weights(weights))
+///     }
+/// }
+///
+/// #[extendr]
+/// fn weighted_sum(weights: Cached<Weights>, x: &[f64]) -> f64 {
+///     weights.0.iter().zip(x).map(|(w, x)| w * x).sum()
+/// }
+///
+/// test! {
+///     let weights = Cached::<Weights>::try_from(&r!([1.0, 3.0]))?;
+///     assert_eq!(weights.0, [0.25, 0.75]);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Cached<T>(Rc<T>);
+
+impl<T> Cached<T> {
+    /// The shared converted value.
+    pub fn into_inner(self) -> Rc<T> {
+        self.0
+    }
+}
+
+impl<T> Clone for Cached<T> {
+    fn clone(&self) -> Self {
+        Cached(self.0.clone())
+    }
+}
+
+impl<T> std::ops::Deref for Cached<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> TryFrom<&Robj> for Cached<T>
+where
+    T: 'static,
+    for<'a> T: TryFrom<&'a Robj, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        cached(robj, |robj| T::try_from(robj)).map(Cached)
+    }
+}
+
+impl<'a, T> FromRobj<'a> for Cached<T>
+where
+    T: 'static,
+    for<'b> T: TryFrom<&'b Robj, Error = Error>,
+{
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        Self::try_from(robj).map_err(|_| "unable to convert value from R object")
+    }
+}
+
+impl<T> TryFrom<Robj> for Cached<T>
+where
+    T: 'static,
+    for<'a> T: TryFrom<&'a Robj, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
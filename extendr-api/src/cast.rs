@@ -0,0 +1,233 @@
+//! Checked conversions between integer and double vectors.
+//!
+//! Every integer is exactly representable as a double, so
+//! [integers_to_doubles] never loses information. Going the other way,
+//! [doubles_to_integers] has to decide what to do with fractions, values
+//! outside the range of an R integer and `NA`s; a [CastPolicy] says which.
+//! The default policy does what `as.integer()` does, while
+//! [CastPolicy::strict] only accepts whole numbers.
+//!
+//! Values that were changed are counted in a [CastWarnings], which can be
+//! raised as an R warning with [CastWarnings::warn].
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::cast::{doubles_to_integers, CastPolicy, Fractions};
+//! test! {
+//!     let x = Doubles::from_values([1.0, 2.7, -2.7, 3e10]);
+//!     let (ints, warnings) = doubles_to_integers(&x, &CastPolicy::default())?;
+//!     assert_eq!(ints.as_robj(), &R!("as.integer(c(1, 2.7, -2.7, 3e10))")?);
+//!     assert_eq!(warnings.fractions, 2);
+//!     assert_eq!(warnings.out_of_range, 1);
+//!
+//!     let policy = CastPolicy::default().fractions(Fractions::Round);
+//!     let (ints, _) = doubles_to_integers(&x, &policy)?;
+//!     assert_eq!(ints.elt(1), 3);
+//!
+//!     assert!(doubles_to_integers(&x, &CastPolicy::strict()).is_err());
+//! }
+//! ```
+use crate::scalar::{Rfloat, Scalar};
+use crate::*;
+
+/// What to do with a double that has a fractional part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fractions {
+    /// Fail.
+    Error,
+    /// Round towards zero, as `as.integer()` does.
+    Truncate,
+    /// Round to the nearest integer, with halves away from zero.
+    Round,
+    /// Give `NA`.
+    Na,
+}
+
+/// What to do with a double outside the range of an R integer, including infinities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// Fail.
+    Error,
+    /// Give `NA`, as `as.integer()` does.
+    Na,
+    /// Give the nearest integer that R can hold.
+    Saturate,
+}
+
+/// What to do with `NA` and `NaN`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NaPolicy {
+    /// Give `NA`.
+    Keep,
+    /// Fail.
+    Error,
+}
+
+/// How [doubles_to_integers] treats values that are not whole numbers in range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastPolicy {
+    pub na: NaPolicy,
+    pub out_of_range: OutOfRange,
+    pub fractions: Fractions,
+}
+
+impl Default for CastPolicy {
+    /// The policy of `as.integer()`: truncate fractions, `NA` for values out of range.
+    fn default() -> Self {
+        CastPolicy {
+            na: NaPolicy::Keep,
+            out_of_range: OutOfRange::Na,
+            fractions: Fractions::Truncate,
+        }
+    }
+}
+
+impl CastPolicy {
+    /// Accept only whole numbers in range and `NA`, failing on anything else.
+    pub fn strict() -> Self {
+        CastPolicy {
+            na: NaPolicy::Keep,
+            out_of_range: OutOfRange::Error,
+            fractions: Fractions::Error,
+        }
+    }
+
+    /// Set the treatment of `NA` and `NaN`.
+    pub fn na(mut self, na: NaPolicy) -> Self {
+        self.na = na;
+        self
+    }
+
+    /// Set the treatment of values out of range.
+    pub fn out_of_range(mut self, out_of_range: OutOfRange) -> Self {
+        self.out_of_range = out_of_range;
+        self
+    }
+
+    /// Set the treatment of fractions.
+    pub fn fractions(mut self, fractions: Fractions) -> Self {
+        self.fractions = fractions;
+        self
+    }
+}
+
+/// Counts of the values changed by [doubles_to_integers].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CastWarnings {
+    /// Fractions that were truncated, rounded or made `NA`.
+    pub fractions: usize,
+    /// Values out of range that were made `NA` or saturated.
+    pub out_of_range: usize,
+}
+
+impl CastWarnings {
+    /// Return true if no values were changed.
+    pub fn is_empty(&self) -> bool {
+        self.fractions == 0 && self.out_of_range == 0
+    }
+
+    /// Add the counts of another conversion, for casting several vectors.
+    pub fn merge(&mut self, other: &CastWarnings) {
+        self.fractions += other.fractions;
+        self.out_of_range += other.out_of_range;
+    }
+
+    /// Raise an R warning describing the changes, if there were any.
+    pub fn warn(&self) -> Result<()> {
+        if !self.is_empty() {
+            let message = r!(self.to_string());
+            eval_string_with_params("warning(param.0, call. = FALSE)", &[&message])?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CastWarnings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.fractions, self.out_of_range) {
+            (0, 0) => write!(f, "no values changed in conversion to integer"),
+            (n, 0) => write!(f, "{} fraction(s) changed in conversion to integer", n),
+            (0, m) => write!(f, "{} value(s) out of integer range", m),
+            (n, m) => write!(
+                f,
+                "{} fraction(s) changed and {} value(s) out of integer range in conversion to integer",
+                n, m
+            ),
+        }
+    }
+}
+
+/// Convert an integer vector to a double vector. `NA` stays `NA`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::cast::integers_to_doubles;
+/// test! {
+///     let x = integers_to_doubles(&Integers::from_values([1, i32::na(), 3]));
+///     assert_eq!(x.as_robj(), &R!("c(1, NA, 3)")?);
+/// }
+/// ```
+pub fn integers_to_doubles(integers: &Integers) -> Doubles {
+    Doubles::from_values(integers.iter().map(|&value| Rfloat::from(value)))
+}
+
+/// Convert a double vector to an integer vector, following `policy`.
+///
+/// Returns the integers and counts of the values that were changed.
+/// An error names the position of the first value the policy rejects.
+pub fn doubles_to_integers(
+    doubles: &Doubles,
+    policy: &CastPolicy,
+) -> Result<(Integers, CastWarnings)> {
+    let mut warnings = CastWarnings::default();
+    let mut values = Vec::with_capacity(doubles.len());
+    for (i, value) in doubles.iter().enumerate() {
+        let value = value.inner();
+        values.push(cast_one(value, policy, &mut warnings).ok_or_else(|| {
+            Error::Other(format!(
+                "cannot convert {} at position {} to an integer",
+                value,
+                i + 1
+            ))
+        })?);
+    }
+    Ok((Integers::from_values(values), warnings))
+}
+
+// The integer for `value`, or None if the policy rejects it.
+fn cast_one(value: f64, policy: &CastPolicy, warnings: &mut CastWarnings) -> Option<i32> {
+    if value.is_nan() {
+        return match policy.na {
+            NaPolicy::Keep => Some(i32::na()),
+            NaPolicy::Error => None,
+        };
+    }
+    let whole = match policy.fractions {
+        _ if value.is_infinite() || value.fract() == 0.0 => value,
+        Fractions::Error => return None,
+        Fractions::Truncate => value.trunc(),
+        Fractions::Round => value.round(),
+        Fractions::Na => f64::NAN,
+    };
+    if whole != value {
+        warnings.fractions += 1;
+    }
+    if whole.is_nan() {
+        return Some(i32::na());
+    }
+    // i32::MIN is NA in R.
+    let (min, max) = (-(i32::MAX as f64), i32::MAX as f64);
+    if whole < min || whole > max {
+        return match policy.out_of_range {
+            OutOfRange::Error => None,
+            OutOfRange::Na => {
+                warnings.out_of_range += 1;
+                Some(i32::na())
+            }
+            OutOfRange::Saturate => {
+                warnings.out_of_range += 1;
+                Some(if whole < 0.0 { -i32::MAX } else { i32::MAX })
+            }
+        };
+    }
+    Some(whole as i32)
+}
@@ -0,0 +1,42 @@
+//! Convert R objects to and from [CBOR](https://cbor.io).
+//!
+//! R objects are encoded as by [serializer](crate::serializer):
+//! vectors become arrays, or single values when of length one, named lists
+//! become maps and `NA` becomes null. Raw vectors are byte strings.
+//! When decoding, arrays of values of one type become atomic vectors.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::cbor::{from_cbor, to_cbor};
+//! test! {
+//!     let robj = list!(a = [1, 2, 3], b = "x", c = list!(TRUE, 1.5));
+//!     let bytes = to_cbor(&robj)?;
+//!     assert_eq!(from_cbor(&bytes)?, robj);
+//! }
+//! ```
+use crate::*;
+use std::io::{Read, Write};
+
+/// Encode an R object as CBOR.
+pub fn to_cbor(robj: &Robj) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    write_cbor(robj, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Encode an R object as CBOR, writing to `writer`.
+pub fn write_cbor<W: Write>(robj: &Robj, writer: W) -> Result<()> {
+    ciborium::ser::into_writer(robj, writer).map_err(|e| Error::Other(e.to_string()))
+}
+
+/// Decode an R object from CBOR.
+pub fn from_cbor(bytes: &[u8]) -> Result<Robj> {
+    read_cbor(bytes)
+}
+
+/// Decode an R object from CBOR, reading from `reader`.
+///
+/// Only one value is read, so several values can be read from a stream in turn.
+pub fn read_cbor<R: Read>(reader: R) -> Result<Robj> {
+    ciborium::de::from_reader(reader).map_err(|e| Error::Other(e.to_string()))
+}
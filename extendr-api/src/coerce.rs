@@ -0,0 +1,126 @@
+//! Coercion of numeric arguments, as R functions do.
+//!
+//! R functions take `1L` where they expect a double and `2` where they expect
+//! an integer, but `#[extendr]` conversions check the R type, so a function
+//! taking `f64` rejects `1L` and one taking `i32` rejects `2`. With
+//! `#[extendr(coerce = "lenient")]` on a function or an impl block, an
+//! argument that does not convert to its type is retried as a copy:
+//!
+//! * integers and logicals as doubles, like `as.double()`;
+//! * doubles as integers if every value is whole and fits, like `as.integer()`;
+//! * logicals as integers.
+//!
+//! Attributes such as `dim` and `names` are kept. Factors are never coerced,
+//! and doubles with a fraction are still rejected rather than truncated.
+//! `coerce = "strict"` keeps the exact checks. Without either, the R option
+//! `extendr.coerce` decides at run time, and is strict by default, see
+//! [crate::options].
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//!
+//! #[extendr(coerce = "lenient")]
+//! fn mean(x: &[f64]) -> f64 {
+//!     x.iter().sum::<f64>() / x.len() as f64
+//! }
+//!
+//! test! {
+//!     // mean(1:4) works as it does in R.
+//!     let x = R!("1:4")?;
+//!     let res = unsafe { Robj::from_sexp(wrap__mean(x.get())) };
+//!     assert_eq!(res, r!(2.5));
+//! }
+//! ```
+//!
+//! [coerced_eq()] compares values the same way, so `1L` equals `1`.
+use crate::*;
+use libR_sys::{INTSXP, REALSXP};
+
+/// The first of the numeric coercions of `robj` that `converts` accepts,
+/// or `None` if there is none.
+///
+/// The wrappers of `#[extendr(coerce = "lenient")]` call this only when an
+/// argument does not convert to its type, so arguments that do are neither
+/// copied nor converted twice. Only integer, logical and double vectors are
+/// tried, so other arguments cost a check of their type.
+pub fn coerce_arg<F>(robj: &Robj, converts: F) -> Option<Robj>
+where
+    F: Fn(&Robj) -> bool,
+{
+    numeric_coercions(robj)
+        .into_iter()
+        .find(|candidate| converts(candidate))
+}
+
+/// Like [coerce_arg()] if `options(extendr.coerce = "lenient")` is set,
+/// see [crate::options], and otherwise `None`.
+///
+/// This is called by the wrappers of functions without a `coerce` setting.
+pub fn coerce_arg_if_lenient<F>(robj: &Robj, converts: F) -> Option<Robj>
+where
+    F: Fn(&Robj) -> bool,
+{
+    match options::extendr_options() {
+        Ok(options) if options.coerce == options::Coercion::Lenient => coerce_arg(robj, converts),
+        // An invalid option is reported when the function is called.
+        _ => None,
+    }
+}
+
+/// True if `a` and `b` are identical once integers and logicals are read as
+/// doubles, so that `1L` equals `1` and `c(a = TRUE)` equals `c(a = 1)`.
+///
+/// Other differences of type, of attributes or of values are not ignored.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::coerce::coerced_eq;
+/// test! {
+///     assert!(coerced_eq(&r!(1), &r!(1.0)));
+///     assert!(coerced_eq(&R!("matrix(1:4, 2)")?, &R!("matrix(c(1, 2, 3, 4), 2)")?));
+///     assert!(!coerced_eq(&r!(1), &r!(1.5)));
+///     assert!(!coerced_eq(&r!(1), &r!("1")));
+/// }
+/// ```
+pub fn coerced_eq(a: &Robj, b: &Robj) -> bool {
+    if a == b {
+        return true;
+    }
+    match (as_doubles(a), as_doubles(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+// The copies of `robj` worth trying, in order.
+fn numeric_coercions(robj: &Robj) -> Vec<Robj> {
+    if robj.is_factor() {
+        return Vec::new();
+    }
+    match robj.rtype() {
+        Rtype::Integers => vec![robj.coerce_vector(REALSXP)],
+        Rtype::Logicals => vec![robj.coerce_vector(INTSXP), robj.coerce_vector(REALSXP)],
+        Rtype::Doubles if is_whole(robj) => vec![robj.coerce_vector(INTSXP)],
+        _ => Vec::new(),
+    }
+}
+
+// True if every double is NA or a whole number that fits an R integer.
+fn is_whole(robj: &Robj) -> bool {
+    let max = i32::MAX as f64;
+    robj.as_real_slice().map_or(false, |values| {
+        values
+            .iter()
+            .all(|x| x.is_nan() || (x.fract() == 0.0 && x.abs() <= max))
+    })
+}
+
+fn as_doubles(robj: &Robj) -> Option<Robj> {
+    if robj.is_factor() {
+        return None;
+    }
+    match robj.rtype() {
+        Rtype::Doubles => Some(robj.clone()),
+        Rtype::Integers | Rtype::Logicals => Some(robj.coerce_vector(REALSXP)),
+        _ => None,
+    }
+}
@@ -0,0 +1,535 @@
+//! Signal and catch classed R conditions.
+//!
+//! A [Condition] is an R condition object: a list with a `message`, a `call`
+//! and any other data, with a class such as `c("my_error", "error",
+//! "condition")`. Signalling one from an `#[extendr]` function reaches the
+//! `tryCatch()` and `withCallingHandlers()` handlers of the R code that
+//! called the function, just as `stop()`, `warning()` and `message()` do.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::conditions::{catch_condition, Condition};
+//! test! {
+//!     let caught = catch_condition(|| {
+//!         Condition::error("file not found")
+//!             .class("io_error")
+//!             .data("path", "data.csv")
+//!             .throw()
+//!     });
+//!     let cond = caught.unwrap_err();
+//!     assert!(cond.inherits("io_error") && cond.inherits("error"));
+//!     assert_eq!(cond.message_text(), "file not found");
+//!     assert_eq!(cond.get("path"), Some(&r!("data.csv")));
+//! }
+//! ```
+//!
+//! In R, the condition can be handled by class:
+//!
+//! ```r
+//! tryCatch(read_data(), io_error = function(e) e$path)
+//! ```
+//!
+//! If R code jumps out of a signal, for example to a `tryCatch()` handler,
+//! the rust frames in between are unwound with an [RUnwind] panic, which the
+//! `#[extendr]` wrapper or [catch_condition] resumes, see [unwind_protect].
+//! So signal conditions only from `#[extendr]` functions or [catch_condition].
+//!
+//! A warning signalled with [warning!] can end the function early, if a
+//! handler exits or `options(warn = 2)` makes it an error. [Warnings]
+//! collects warnings instead, and the wrapper of the `#[extendr]` function
+//! signals them in order once the function has returned, with the call of
+//! the R function as `warning()` would show it:
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::conditions::Warnings;
+//!
+//! #[extendr]
+//! fn parse_numbers(x: Vec<String>) -> Vec<Option<f64>> {
+//!     let mut warnings = Warnings::new();
+//!     let res = x
+//!         .iter()
+//!         .enumerate()
+//!         .map(|(i, text)| {
+//!             let number = text.parse().ok();
+//!             if number.is_none() {
+//!                 warnings.push(format!("element {} is not a number", i + 1));
+//!             }
+//!             number
+//!         })
+//!         .collect();
+//!     warnings.defer();
+//!     res
+//! }
+//! ```
+//!
+//! In R, `parse_numbers(c("1", "a", "b"))` returns `c(1, NA, NA)` with two
+//! warnings, `In parse_numbers(c("1", "a", "b")) : element 2 is not a number`
+//! and then the same for element 3.
+use crate as extendr_api;
+use crate::thread_safety::R_tryCatch;
+use crate::*;
+use std::cell::RefCell;
+use std::os::raw;
+
+/// The base class of a [Condition], which decides how it is signalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionKind {
+    /// Signalled with `stop()`.
+    Error,
+    /// Signalled with `warning()`.
+    Warning,
+    /// Signalled with `message()`.
+    Message,
+    /// Signalled with `signalCondition()`, which returns if no handler exits.
+    Condition,
+}
+
+impl ConditionKind {
+    fn base_classes(&self) -> &'static [&'static str] {
+        match self {
+            ConditionKind::Error => &["error", "condition"],
+            ConditionKind::Warning => &["warning", "condition"],
+            ConditionKind::Message => &["message", "condition"],
+            ConditionKind::Condition => &["condition"],
+        }
+    }
+}
+
+/// An R condition with extra classes and data, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    kind: ConditionKind,
+    message: String,
+    classes: Vec<String>,
+    data: Vec<(String, Robj)>,
+    call: Option<Robj>,
+}
+
+impl Condition {
+    /// Make a condition of the given kind.
+    pub fn new<S: Into<String>>(kind: ConditionKind, message: S) -> Self {
+        Condition {
+            kind,
+            message: message.into(),
+            classes: Vec::new(),
+            data: Vec::new(),
+            call: None,
+        }
+    }
+
+    /// Make an error.
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Condition::new(ConditionKind::Error, message)
+    }
+
+    /// Make a warning.
+    pub fn warning<S: Into<String>>(message: S) -> Self {
+        Condition::new(ConditionKind::Warning, message)
+    }
+
+    /// Make a message. R prints the message as it is, so end it with a newline.
+    pub fn message<S: Into<String>>(message: S) -> Self {
+        Condition::new(ConditionKind::Message, message)
+    }
+
+    /// Add a class, before the classes already added and the base classes.
+    pub fn class<S: Into<String>>(mut self, class: S) -> Self {
+        self.classes.insert(0, class.into());
+        self
+    }
+
+    /// Attach data, read in R with `cond$name`.
+    pub fn data<V: Into<Robj>>(mut self, name: &str, value: V) -> Self {
+        self.data.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Set the call that R shows with the message, such as `f(x)` in
+    /// `In f(x) : careful`. By default there is none.
+    pub fn call(mut self, call: Robj) -> Self {
+        self.call = Some(call);
+        self
+    }
+
+    /// The kind of condition.
+    pub fn kind(&self) -> ConditionKind {
+        self.kind
+    }
+
+    /// The message, `conditionMessage()` in R.
+    pub fn message_text(&self) -> &str {
+        &self.message
+    }
+
+    /// All the classes, ending with the base classes.
+    pub fn classes(&self) -> Vec<&str> {
+        self.classes
+            .iter()
+            .map(|class| class.as_str())
+            .chain(self.kind.base_classes().iter().copied())
+            .collect()
+    }
+
+    /// Return true if the condition has the class `class`.
+    pub fn inherits(&self, class: &str) -> bool {
+        self.classes().contains(&class)
+    }
+
+    /// Get attached data by name.
+    pub fn get(&self, name: &str) -> Option<&Robj> {
+        self.data
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Make the R condition object.
+    pub fn to_robj(&self) -> Robj {
+        let names = ["message", "call"]
+            .into_iter()
+            .chain(self.data.iter().map(|(name, _)| name.as_str()));
+        let call = self.call.clone().unwrap_or_else(|| r!(()));
+        let values = [r!(self.message.as_str()), call]
+            .into_iter()
+            .chain(self.data.iter().map(|(_, value)| value.clone()));
+        let mut robj: Robj =
+            List::from_names_and_values(names.collect::<Vec<_>>(), values.collect::<Vec<_>>())
+                .unwrap()
+                .into();
+        robj.set_class(self.classes()).unwrap();
+        robj
+    }
+
+    /// Signal the condition with the function for its kind.
+    ///
+    /// Returns when a warning or message is muffled or printed, or when no
+    /// handler exits for a plain condition. Errors never return.
+    pub fn signal(&self) {
+        let function = match self.kind {
+            ConditionKind::Error => "stop",
+            ConditionKind::Warning => "warning",
+            ConditionKind::Message => "message",
+            ConditionKind::Condition => "signalCondition",
+        };
+        signalling_eval(&lang!(function, self.to_robj()), &base_env());
+    }
+
+    /// Signal the condition as an error with `stop()`, whatever its kind.
+    pub fn throw(&self) -> ! {
+        signalling_eval(&lang!("stop", self.to_robj()), &base_env());
+        unreachable!("stop() returned")
+    }
+}
+
+impl TryFrom<&Robj> for Condition {
+    type Error = Error;
+
+    /// Read an R condition object, such as one caught by `tryCatch()`.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if !robj.inherits("condition") || !robj.is_list() {
+            return Err(Error::Other(format!(
+                "Expected a condition got {:?}",
+                robj.rtype()
+            )));
+        }
+        let kind = if robj.inherits("error") {
+            ConditionKind::Error
+        } else if robj.inherits("warning") {
+            ConditionKind::Warning
+        } else if robj.inherits("message") {
+            ConditionKind::Message
+        } else {
+            ConditionKind::Condition
+        };
+        let base = kind.base_classes();
+        let classes = robj
+            .class()
+            .map(|classes| {
+                classes
+                    .filter(|class| !base.contains(class))
+                    .map(|class| class.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut message = String::new();
+        let mut data = Vec::new();
+        let mut call = None;
+        for (name, value) in List::try_from(robj)?.iter() {
+            match name {
+                "message" => message = value.as_str().unwrap_or_default().to_string(),
+                "call" if !value.is_null() => call = Some(value),
+                "call" => (),
+                _ => data.push((name.to_string(), value)),
+            }
+        }
+        Ok(Condition {
+            kind,
+            message,
+            classes,
+            data,
+            call,
+        })
+    }
+}
+
+impl TryFrom<Robj> for Condition {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Condition::try_from(&robj)
+    }
+}
+
+impl From<Condition> for Robj {
+    fn from(cond: Condition) -> Self {
+        cond.to_robj()
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Signal an error of class `c(<class>, "error", "condition")`, as `rlang::abort()` does.
+pub fn throw_condition(cond: Condition) -> ! {
+    cond.throw()
+}
+
+/// Warnings to signal when the `#[extendr]` function in progress returns,
+/// see the [module documentation](self).
+///
+/// Like [Robj], `Warnings` can only be used on the R thread.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Warnings {
+    conditions: Vec<Condition>,
+}
+
+impl Warnings {
+    /// Make an empty collector.
+    pub fn new() -> Self {
+        Warnings::default()
+    }
+
+    /// Add a warning with a message.
+    pub fn push<S: Into<String>>(&mut self, message: S) {
+        self.conditions.push(Condition::warning(message));
+    }
+
+    /// Add a condition, such as one with a class or data. It is signalled
+    /// as a warning whatever its kind.
+    pub fn push_condition(&mut self, cond: Condition) {
+        self.conditions.push(Condition {
+            kind: ConditionKind::Warning,
+            ..cond
+        });
+    }
+
+    /// The number of warnings.
+    pub fn len(&self) -> usize {
+        self.conditions.len()
+    }
+
+    /// Return true if there are no warnings.
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// The warnings, in the order they were added.
+    pub fn iter(&self) -> std::slice::Iter<'_, Condition> {
+        self.conditions.iter()
+    }
+
+    /// Signal the warnings now, in order.
+    ///
+    /// Like [Condition::signal], this unwinds the rust frames if a handler exits.
+    pub fn signal(self) {
+        for cond in self.conditions {
+            cond.signal();
+        }
+    }
+
+    /// Signal the warnings after the `#[extendr]` function in progress
+    /// returns, after any deferred before them.
+    ///
+    /// They are signalled even if the function returns an error, but not
+    /// if it panics or an R error unwinds it. Outside an `#[extendr]`
+    /// function they are signalled now.
+    pub fn defer(self) {
+        let rest = DEFERRED.with(|deferred| match deferred.borrow_mut().last_mut() {
+            Some(warnings) => {
+                warnings.extend(self.conditions);
+                None
+            }
+            None => Some(self),
+        });
+        if let Some(warnings) = rest {
+            warnings.signal();
+        }
+    }
+}
+
+thread_local! {
+    // The deferred warnings of each `#[extendr]` call in progress, innermost last.
+    static DEFERRED: RefCell<Vec<Vec<Condition>>> = RefCell::new(Vec::new());
+}
+
+/// Collects the warnings deferred during an `#[extendr]` call. Used by
+/// `#[extendr]` wrappers.
+#[doc(hidden)]
+pub struct WarningsScope(());
+
+impl WarningsScope {
+    pub fn enter() -> Self {
+        DEFERRED.with(|deferred| deferred.borrow_mut().push(Vec::new()));
+        WarningsScope(())
+    }
+
+    /// Signal the deferred warnings, unless `result` is a panic or an R
+    /// error unwinding through the call, and then return `result`.
+    ///
+    /// If a handler exits from a warning, the value is dropped and the
+    /// jump is returned as the panic, for the wrapper to resume.
+    pub fn signal_deferred<T>(self, result: std::thread::Result<T>) -> std::thread::Result<T> {
+        let conditions =
+            DEFERRED.with(|deferred| std::mem::take(deferred.borrow_mut().last_mut().unwrap()));
+        drop(self);
+        if conditions.is_empty() || result.is_err() {
+            return result;
+        }
+        let call = caller_call();
+        let signal = || {
+            for cond in conditions {
+                match cond.call {
+                    Some(_) => cond.signal(),
+                    None => cond.call(call.clone()).signal(),
+                }
+            }
+        };
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(signal)) {
+            Ok(()) => result,
+            Err(payload) => {
+                drop(result);
+                Err(payload)
+            }
+        }
+    }
+}
+
+impl Drop for WarningsScope {
+    fn drop(&mut self) {
+        DEFERRED.with(|deferred| deferred.borrow_mut().pop());
+    }
+}
+
+// The call of the R function running `.Call()`, such as `f(x)`, or NULL if
+// rust was not called from an R function.
+fn caller_call() -> Robj {
+    let env = current_env();
+    if env == base_env() || env == global_env() {
+        return r!(());
+    }
+    lang!("sys.call")
+        .eval_with_env(&env)
+        .unwrap_or_else(|_| r!(()))
+}
+
+/// Evaluate `expr` in `env` so that its conditions reach the handlers of the calling R code.
+///
+/// Unlike [Robj::eval], an error is not returned but signalled, unwinding
+/// the rust frames with an [RUnwind] panic, see [unwind_protect].
+pub fn signalling_eval(expr: &Robj, env: &Environment) -> Robj {
+    unsafe {
+        let (expr, env) = (expr.get(), env.get());
+        Robj::from_sexp(unwind_protect(|| Rf_eval(expr, env)))
+    }
+}
+
+/// Run `f`, returning the first condition it signals, like `tryCatch(f(), condition = identity)`.
+///
+/// Conditions signalled by [Condition::signal], the [warning!] and
+/// [message!] macros and [signalling_eval] are caught. R code run by
+/// `call!` or `R!` is evaluated apart from the handlers of its caller, and
+/// its errors are returned as `Err` instead.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::conditions::{catch_condition, signalling_eval};
+/// test! {
+///     let caught = catch_condition(|| signalling_eval(&lang!("warning", "careful"), &global_env()));
+///     assert_eq!(caught.unwrap_err().message_text(), "careful");
+///
+///     let value = catch_condition(|| 42);
+///     assert_eq!(value, Ok(42));
+/// }
+/// ```
+pub fn catch_condition<F, T>(f: F) -> std::result::Result<T, Condition>
+where
+    F: FnOnce() -> T,
+{
+    try_catch(&["condition"], f).map_err(|cond| {
+        Condition::try_from(&cond)
+            .unwrap_or_else(|_| Condition::new(ConditionKind::Condition, "unknown condition"))
+    })
+}
+
+// Run `f`, returning the first condition it signals that inherits from one
+// of `classes`. Other jumps out of `f` unwind the rust stack as an
+// [RUnwind] panic, see [unwind_protect].
+pub(crate) fn try_catch<F, T>(classes: &[&str], f: F) -> std::result::Result<T, Robj>
+where
+    F: FnOnce() -> T,
+{
+    struct Body<F, T> {
+        f: Option<F>,
+        value: Option<T>,
+        panic: Option<Box<dyn std::any::Any + Send>>,
+    }
+
+    unsafe extern "C" fn body<F: FnOnce() -> T, T>(data: *mut raw::c_void) -> SEXP {
+        let data = &mut *(data as *mut Body<F, T>);
+        let f = data.f.take().unwrap();
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+            Ok(value) => data.value = Some(value),
+            // Continue a jump, to the handler of R_tryCatch or beyond, once
+            // the frames of `f` are unwound. Nothing in this frame is left
+            // to drop.
+            Err(payload) => data.panic = Some(RUnwind::resume_or_return(payload)),
+        }
+        R_NilValue
+    }
+
+    unsafe extern "C" fn handler(cond: SEXP, data: *mut raw::c_void) -> SEXP {
+        let caught = &mut *(data as *mut Option<Robj>);
+        *caught = Some(Robj::from_sexp(cond));
+        R_NilValue
+    }
+
+    let mut data = Body {
+        f: Some(f),
+        value: None,
+        panic: None,
+    };
+    let mut caught: Option<Robj> = None;
+    let conds = Robj::from(classes.to_vec());
+    unwind_protect(|| unsafe {
+        R_tryCatch(
+            Some(body::<F, T>),
+            &mut data as *mut Body<F, T> as *mut raw::c_void,
+            conds.get(),
+            Some(handler),
+            &mut caught as *mut Option<Robj> as *mut raw::c_void,
+            None,
+            std::ptr::null_mut(),
+        );
+    });
+    if let Some(payload) = data.panic {
+        std::panic::resume_unwind(payload);
+    }
+    match (data.value, caught) {
+        (Some(value), _) => Ok(value),
+        (None, Some(cond)) => Err(cond),
+        (None, None) => unreachable!("R_tryCatch returned without a value or a condition"),
+    }
+}
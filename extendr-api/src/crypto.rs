@@ -0,0 +1,161 @@
+//! Hashes, HMACs and random bytes for raw and character vectors.
+//!
+//! Raw vectors are hashed as a whole and give a raw digest. Character
+//! vectors are hashed element by element, as UTF-8, and give lower case hex
+//! digests, with `NA` for `NA`. Strings in other encodings are translated to
+//! UTF-8 first, so the digest of a string does not depend on the locale.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::crypto::{hmac, sha2, Sha2};
+//! test! {
+//!     let digest = sha2(&r!([Some("abc"), None]), Sha2::Sha256)?;
+//!     assert_eq!(
+//!         digest,
+//!         r!([Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"), None])
+//!     );
+//!
+//!     let signature = hmac(&r!("key"), &r!("message"), Sha2::Sha256)?;
+//!     assert_eq!(signature.len(), 1);
+//! }
+//! ```
+use crate::*;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+/// A SHA-2 hash function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sha2 {
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Sha2 {
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Sha2::Sha224 => Sha224::digest(data).to_vec(),
+            Sha2::Sha256 => Sha256::digest(data).to_vec(),
+            Sha2::Sha384 => Sha384::digest(data).to_vec(),
+            Sha2::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    fn hmac(self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        fn mac<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+            // HMAC accepts keys of any length.
+            let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key).unwrap();
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        match self {
+            Sha2::Sha224 => mac::<Hmac<Sha224>>(key, data),
+            Sha2::Sha256 => mac::<Hmac<Sha256>>(key, data),
+            Sha2::Sha384 => mac::<Hmac<Sha384>>(key, data),
+            Sha2::Sha512 => mac::<Hmac<Sha512>>(key, data),
+        }
+    }
+}
+
+/// Hash a raw vector, giving a raw digest, or each string of a character vector,
+/// giving hex digests.
+pub fn sha2(x: &Robj, algorithm: Sha2) -> Result<Robj> {
+    map_bytes(x, |data| algorithm.digest(data))
+}
+
+/// The HMAC of a raw vector, or of each string of a character vector, with `key`.
+///
+/// `key` is a raw vector or a single string, which must not be `NA`.
+pub fn hmac(key: &Robj, x: &Robj, algorithm: Sha2) -> Result<Robj> {
+    let key = key_bytes(key)?;
+    map_bytes(x, |data| algorithm.hmac(&key, data))
+}
+
+/// Make a raw vector of `n` cryptographically secure random bytes.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::crypto::random_bytes;
+/// test! {
+///     assert_eq!(random_bytes(16)?.len(), 16);
+/// }
+/// ```
+pub fn random_bytes(n: usize) -> Result<Raw> {
+    let mut bytes = vec![0; n];
+    getrandom::getrandom(&mut bytes).map_err(|e| Error::Other(e.to_string()))?;
+    Ok(Raw::from_bytes(&bytes))
+}
+
+/// Compare two raw vectors or single strings in a time that does not depend on
+/// where they differ, for checking signatures and tokens.
+///
+/// The time does depend on the lengths. `NA` strings are never equal.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::crypto::constant_time_eq;
+/// test! {
+///     assert!(constant_time_eq(&r!("secret"), &r!("secret"))?);
+///     assert!(!constant_time_eq(&r!("secret"), &r!("secreT"))?);
+///     assert!(!constant_time_eq(&r!(NA_STRING), &r!(NA_STRING))?);
+/// }
+/// ```
+pub fn constant_time_eq(a: &Robj, b: &Robj) -> Result<bool> {
+    let (a, b) = match (scalar_bytes(a)?, scalar_bytes(b)?) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Ok(false),
+    };
+    if a.len() != b.len() {
+        return Ok(false);
+    }
+    // Visit every byte rather than stopping at the first difference.
+    let diff = a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y));
+    Ok(diff == 0)
+}
+
+/// Encode bytes as lower case hex.
+/// ```
+/// use extendr_api::crypto::to_hex;
+/// assert_eq!(to_hex(&[0, 15, 255]), "000fff");
+/// ```
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn map_bytes<F: Fn(&[u8]) -> Vec<u8>>(x: &Robj, f: F) -> Result<Robj> {
+    if let Some(data) = x.as_raw_slice() {
+        return Ok(Raw::from_bytes(&f(data)).into());
+    }
+    let strings = Strings::try_from(x)
+        .map_err(|_| Error::Other("expected a raw or character vector".to_string()))?;
+    let views = strings.str_views();
+    let digests = views.iter().map(|s| {
+        let s: &str = s;
+        if s.is_na() {
+            None
+        } else {
+            Some(to_hex(&f(s.as_bytes())))
+        }
+    });
+    Ok(digests.collect::<Vec<_>>().into())
+}
+
+// The bytes of a raw vector or a single string, or None for NA.
+fn scalar_bytes(x: &Robj) -> Result<Option<Vec<u8>>> {
+    if let Some(data) = x.as_raw_slice() {
+        return Ok(Some(data.to_vec()));
+    }
+    let strings = Strings::try_from(x)?;
+    if strings.len() != 1 {
+        return Err(Error::ExpectedScalar(x.clone()));
+    }
+    let s = strings.elt(0);
+    if s.is_na() {
+        Ok(None)
+    } else {
+        Ok(Some(s.as_str_view().as_bytes().to_vec()))
+    }
+}
+
+fn key_bytes(key: &Robj) -> Result<Vec<u8>> {
+    scalar_bytes(key)?.ok_or_else(|| Error::MustNotBeNA(key.clone()))
+}
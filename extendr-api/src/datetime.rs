@@ -0,0 +1,445 @@
+//! Scalar dates, date-times and time differences with their R classes.
+//!
+//! R stores a `Date` as days since 1970-01-01, a `POSIXct` date-time as
+//! seconds since 1970-01-01 UTC and a `difftime` as a number with a
+//! `"units"` attribute. Returning a plain `f64` loses the class, so R
+//! prints `19723` rather than `"2024-01-01"`. [RDate], [RTimestamp] and
+//! [RDuration] keep the class when converted to an [Robj], and check it
+//! when converted from one, so they can be used as the arguments and
+//! results of `#[extendr]` functions.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::datetime::{DurationUnits, RDate, RDuration};
+//! test! {
+//!     let date = RDate::from_ymd(2024, 1, 1).unwrap();
+//!     assert_eq!(date.days(), 19723.0);
+//!     assert_eq!(Robj::from(date), R!("as.Date('2024-01-01')")?);
+//!     assert_eq!(R!("format({{date}})")?, r!("2024-01-01"));
+//!
+//!     let week = RDuration::new(1.0, DurationUnits::Weeks);
+//!     assert_eq!(week.as_secs(), 604800.0);
+//!     assert_eq!(Robj::from(week), R!("as.difftime(1, units = 'weeks')")?);
+//!
+//!     // Plain numbers are not dates.
+//!     assert!(RDate::try_from(r!(19723.0)).is_err());
+//! }
+//! ```
+//!
+//! All three are `NA` if their value is `NA`, see [CanBeNA]. For the time
+//! zones of date-times see [tz](crate::tz).
+use crate::*;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// A `Date`: a number of days since 1970-01-01.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct RDate(f64);
+
+impl RDate {
+    /// Make a date from days since 1970-01-01.
+    pub fn from_days(days: f64) -> Self {
+        RDate(days)
+    }
+
+    /// Make a date from a year, a month from 1 to 12 and a day of the month,
+    /// or `None` if there is no such day.
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let days = days_from_civil(year as i64, month as i64, day as i64);
+        // 31 February is 2 or 3 March.
+        if civil_from_days(days) == (year as i64, month, day) {
+            Some(RDate(days as f64))
+        } else {
+            None
+        }
+    }
+
+    /// The number of days since 1970-01-01.
+    pub fn days(&self) -> f64 {
+        self.0
+    }
+
+    /// The year, month and day, or `None` if the date is `NA` or not finite.
+    /// ```
+    /// use extendr_api::datetime::RDate;
+    /// assert_eq!(RDate::from_days(-1.0).to_ymd(), Some((1969, 12, 31)));
+    /// ```
+    pub fn to_ymd(&self) -> Option<(i32, u32, u32)> {
+        if !self.0.is_finite() {
+            return None;
+        }
+        let (year, month, day) = civil_from_days(self.0.floor() as i64);
+        Some((year as i32, month, day))
+    }
+}
+
+impl CanBeNA for RDate {
+    fn is_na(&self) -> bool {
+        self.0.is_na()
+    }
+
+    fn na() -> Self {
+        RDate(f64::na())
+    }
+}
+
+impl fmt::Display for RDate {
+    /// Write the date as `format()` does, like `2024-01-01`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_ymd() {
+            Some((year, month, day)) => write!(f, "{:04}-{:02}-{:02}", year, month, day),
+            None => write!(f, "NA"),
+        }
+    }
+}
+
+impl From<RDate> for Robj {
+    fn from(date: RDate) -> Self {
+        let mut robj = r!(date.0);
+        robj.set_class(["Date"]).unwrap()
+    }
+}
+
+impl TryFrom<&Robj> for RDate {
+    type Error = Error;
+
+    /// Convert a `Date` of length one, which may be stored as integers.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        classed_scalar(robj, "Date").map(RDate)
+    }
+}
+
+impl TryFrom<Robj> for RDate {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        RDate::try_from(&robj)
+    }
+}
+
+impl<'a> FromRobj<'a> for RDate {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        RDate::try_from(robj).map_err(|_| "expected a Date of length one")
+    }
+}
+
+/// A `POSIXct` date-time: a number of seconds since 1970-01-01 UTC, shown
+/// in a time zone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RTimestamp {
+    secs: f64,
+    tz: Option<String>,
+}
+
+impl RTimestamp {
+    /// Make a date-time from seconds since 1970-01-01 UTC, shown in the
+    /// local time zone.
+    pub fn from_secs(secs: f64) -> Self {
+        RTimestamp { secs, tz: None }
+    }
+
+    /// Show the date-time in the time zone `tz`, which is stored in the
+    /// `"tzone"` attribute. The time is unchanged.
+    pub fn with_tz(self, tz: &str) -> Self {
+        RTimestamp {
+            tz: Some(tz.to_string()),
+            ..self
+        }
+    }
+
+    /// The number of seconds since 1970-01-01 UTC.
+    pub fn secs(&self) -> f64 {
+        self.secs
+    }
+
+    /// The time zone, or `None` for the local time zone.
+    pub fn tz(&self) -> Option<&str> {
+        self.tz.as_deref()
+    }
+
+    /// The date in the time zone of the date-time, like `as.Date(x)`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use extendr_api::datetime::{RDate, RTimestamp};
+    /// test! {
+    ///     // 2021-07-01 20:00:00 UTC is the next morning in Tokyo.
+    ///     let time = RTimestamp::from_secs(1625169600.0);
+    ///     assert_eq!(time.clone().with_tz("UTC").to_date()?, RDate::from_ymd(2021, 7, 1).unwrap());
+    ///     assert_eq!(time.with_tz("Asia/Tokyo").to_date()?, RDate::from_ymd(2021, 7, 2).unwrap());
+    /// }
+    /// ```
+    pub fn to_date(&self) -> Result<RDate> {
+        if !self.secs.is_finite() {
+            return Ok(RDate::na());
+        }
+        let offset = tz::utc_offset(self.tz.as_deref().unwrap_or(""), self.secs)?;
+        Ok(RDate(((self.secs + offset as f64) / 86400.0).floor()))
+    }
+}
+
+impl CanBeNA for RTimestamp {
+    fn is_na(&self) -> bool {
+        self.secs.is_na()
+    }
+
+    fn na() -> Self {
+        RTimestamp::from_secs(f64::na())
+    }
+}
+
+impl From<SystemTime> for RTimestamp {
+    fn from(time: SystemTime) -> Self {
+        let secs = match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        };
+        RTimestamp::from_secs(secs)
+    }
+}
+
+impl From<RTimestamp> for Robj {
+    fn from(time: RTimestamp) -> Self {
+        let mut robj = r!(time.secs);
+        if let Some(tz) = time.tz {
+            tz::set_tzone(&mut robj, &tz).unwrap();
+        }
+        robj.set_class(["POSIXct", "POSIXt"]).unwrap()
+    }
+}
+
+impl TryFrom<&Robj> for RTimestamp {
+    type Error = Error;
+
+    /// Convert a `POSIXct` of length one, keeping its `"tzone"` attribute.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let secs = classed_scalar(robj, "POSIXct")?;
+        Ok(RTimestamp {
+            secs,
+            tz: tz::tzone(robj),
+        })
+    }
+}
+
+impl TryFrom<Robj> for RTimestamp {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        RTimestamp::try_from(&robj)
+    }
+}
+
+impl<'a> FromRobj<'a> for RTimestamp {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        RTimestamp::try_from(robj).map_err(|_| "expected a POSIXct of length one")
+    }
+}
+
+/// The units of a `difftime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationUnits {
+    Secs,
+    Mins,
+    Hours,
+    Days,
+    Weeks,
+}
+
+impl DurationUnits {
+    /// The name R uses, such as `"secs"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DurationUnits::Secs => "secs",
+            DurationUnits::Mins => "mins",
+            DurationUnits::Hours => "hours",
+            DurationUnits::Days => "days",
+            DurationUnits::Weeks => "weeks",
+        }
+    }
+
+    /// The number of seconds in one unit.
+    pub fn secs(&self) -> f64 {
+        match self {
+            DurationUnits::Secs => 1.0,
+            DurationUnits::Mins => 60.0,
+            DurationUnits::Hours => 3600.0,
+            DurationUnits::Days => 86400.0,
+            DurationUnits::Weeks => 604800.0,
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "secs" => Some(DurationUnits::Secs),
+            "mins" => Some(DurationUnits::Mins),
+            "hours" => Some(DurationUnits::Hours),
+            "days" => Some(DurationUnits::Days),
+            "weeks" => Some(DurationUnits::Weeks),
+            _ => None,
+        }
+    }
+}
+
+/// A `difftime`: a time difference in some units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RDuration {
+    value: f64,
+    units: DurationUnits,
+}
+
+impl RDuration {
+    /// Make a time difference of `value` `units`.
+    pub fn new(value: f64, units: DurationUnits) -> Self {
+        RDuration { value, units }
+    }
+
+    /// Make a time difference in seconds.
+    pub fn from_secs(secs: f64) -> Self {
+        RDuration::new(secs, DurationUnits::Secs)
+    }
+
+    /// The value in [RDuration::units].
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The units of the value.
+    pub fn units(&self) -> DurationUnits {
+        self.units
+    }
+
+    /// The time difference in seconds.
+    pub fn as_secs(&self) -> f64 {
+        self.value * self.units.secs()
+    }
+
+    /// The same time difference in other units, like `units(x) <- units`.
+    pub fn to_units(&self, units: DurationUnits) -> Self {
+        RDuration::new(self.as_secs() / units.secs(), units)
+    }
+
+    /// Convert to a `std::time::Duration`, or `None` if the time difference
+    /// is negative, `NA` or too large.
+    pub fn to_std(&self) -> Option<Duration> {
+        let secs = self.as_secs();
+        if (0.0..u64::MAX as f64).contains(&secs) {
+            Some(Duration::from_secs_f64(secs))
+        } else {
+            None
+        }
+    }
+}
+
+impl CanBeNA for RDuration {
+    fn is_na(&self) -> bool {
+        self.value.is_na()
+    }
+
+    fn na() -> Self {
+        RDuration::from_secs(f64::na())
+    }
+}
+
+impl From<Duration> for RDuration {
+    fn from(duration: Duration) -> Self {
+        RDuration::from_secs(duration.as_secs_f64())
+    }
+}
+
+impl From<RDuration> for Robj {
+    fn from(duration: RDuration) -> Self {
+        let mut robj = r!(duration.value);
+        robj.set_attrib(sym!(units), duration.units.as_str())
+            .unwrap();
+        robj.set_class(["difftime"]).unwrap()
+    }
+}
+
+impl TryFrom<&Robj> for RDuration {
+    type Error = Error;
+
+    /// Convert a `difftime` of length one in any of R's units.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let value = classed_scalar(robj, "difftime")?;
+        let units = robj.get_attrib(sym!(units));
+        let name = units
+            .as_ref()
+            .and_then(|units| units.as_str())
+            .unwrap_or("");
+        match DurationUnits::from_name(name) {
+            Some(units) => Ok(RDuration::new(value, units)),
+            None => Err(Error::Other(format!("unknown difftime units '{}'", name))),
+        }
+    }
+}
+
+impl TryFrom<Robj> for RDuration {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        RDuration::try_from(&robj)
+    }
+}
+
+impl<'a> FromRobj<'a> for RDuration {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        RDuration::try_from(robj).map_err(|_| "expected a difftime of length one")
+    }
+}
+
+// The value of a numeric vector of length one with the class `class`.
+fn classed_scalar(robj: &Robj, class: &str) -> Result<f64> {
+    if !robj.inherits(class) {
+        return Err(Error::Other(format!(
+            "expected an object of class {}",
+            class
+        )));
+    }
+    if robj.len() != 1 {
+        return Err(Error::ExpectedScalar(robj.clone()));
+    }
+    match robj.rtype() {
+        Rtype::Doubles => Ok(robj.as_real_slice().unwrap()[0]),
+        Rtype::Integers => {
+            let value = robj.as_integer_slice().unwrap()[0];
+            Ok(if value.is_na() {
+                f64::na()
+            } else {
+                value as f64
+            })
+        }
+        _ => Err(Error::TypeMismatch(robj.clone())),
+    }
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+// See http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+    (year, month, day)
+}
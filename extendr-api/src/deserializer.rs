@@ -2,10 +2,10 @@
 //!
 use crate::error::{Error, Result};
 use crate::na::CanBeNA;
-use crate::robj::{Attributes, Length, Robj, Types};
+use crate::robj::{Attributes, Length, Rinternals, Robj, Types};
 use crate::scalar::{Rbool, Rfloat, Rint};
-use crate::wrapper::{Doubles, Integers, List, Logicals, Rstr, Strings};
-use crate::Rany;
+use crate::wrapper::{Doubles, Integers, List, Logicals, Raw, Rstr, Strings};
+use crate::{Rany, Rtype};
 use serde::de::{
     Deserialize, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess,
     Visitor,
@@ -1147,3 +1147,172 @@ impl<'de> Deserialize<'de> for Rstr {
         deserializer.deserialize_any(RstrVisitor)
     }
 }
+
+struct RobjVisitor;
+
+impl<'de> Visitor<'de> for RobjVisitor {
+    type Value = Robj;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a value convertable to Robj")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.into())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        // NA_integer_ is i32::MIN, so it is stored as a double.
+        match i32::try_from(v) {
+            Ok(v) if !v.is_na() => Ok(v.into()),
+            _ => Ok((v as f64).into()),
+        }
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match i32::try_from(v) {
+            Ok(v) => Ok(v.into()),
+            Err(_) => Ok((v as f64).into()),
+        }
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.into())
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.into())
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Raw::from_bytes(v).into())
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(().into())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(().into())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element::<Robj>()? {
+            values.push(value);
+        }
+        Ok(simplify_seq(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut names = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        let mut values = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((name, value)) = map.next_entry::<String, Robj>()? {
+            names.push(name);
+            values.push(value);
+        }
+        use serde::de::Error;
+        let list = List::from_names_and_values(names, values).map_err(A::Error::custom)?;
+        Ok(list.into())
+    }
+}
+
+// Sequences of scalars of one type (or NULL, for NA) become atomic vectors,
+// the inverse of the serialization of vectors. Anything else is a list.
+fn simplify_seq(values: Vec<Robj>) -> Robj {
+    let mut rtype = None;
+    for value in values.iter().filter(|v| !v.is_null()) {
+        let this = value.rtype();
+        let scalar = value.len() == 1
+            && matches!(
+                this,
+                Rtype::Logicals | Rtype::Integers | Rtype::Doubles | Rtype::Strings
+            );
+        rtype = match (rtype, this) {
+            _ if !scalar => return values.into(),
+            (None, this) => Some(this),
+            (Some(Rtype::Integers), Rtype::Doubles) | (Some(Rtype::Doubles), Rtype::Integers) => {
+                Some(Rtype::Doubles)
+            }
+            (Some(prev), this) if prev == this => Some(prev),
+            _ => return values.into(),
+        };
+    }
+    match rtype {
+        Some(Rtype::Logicals) => values
+            .iter()
+            .map(|v| {
+                v.as_logical_slice()
+                    .and_then(|s| Option::<bool>::from(s[0]))
+            })
+            .collect::<Vec<_>>()
+            .into(),
+        Some(Rtype::Integers) => values
+            .iter()
+            .map(|v| v.as_integer_slice().map(|s| s[0]))
+            .collect::<Vec<_>>()
+            .into(),
+        Some(Rtype::Doubles) => values
+            .iter()
+            .map(|v| {
+                v.as_real_slice()
+                    .map(|s| s[0])
+                    .or_else(|| v.as_integer_slice().map(|s| s[0] as f64))
+            })
+            .collect::<Vec<_>>()
+            .into(),
+        Some(Rtype::Strings) => values.iter().map(|v| v.as_str()).collect::<Vec<_>>().into(),
+        _ => values.into(),
+    }
+}
+
+/// Build an R object from any self-describing format, such as JSON,
+/// MessagePack or CBOR.
+///
+/// Maps become named lists, sequences of scalars become atomic vectors,
+/// other sequences become lists and nulls become `NULL` or `NA`.
+impl<'de> Deserialize<'de> for Robj {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Robj, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(RobjVisitor)
+    }
+}
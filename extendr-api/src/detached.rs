@@ -0,0 +1,224 @@
+//! Copies of R objects that do not depend on R.
+//!
+//! [Robj::detach] deep-copies a vector, list or factor, with its attributes,
+//! into memory owned by rust. A [DetachedRobj] holds no `SEXP`, so it is
+//! `Send` and `Sync`, can be processed on other threads without touching
+//! the R API, and is unaffected by garbage collection or shutting R down.
+//! [DetachedRobj::to_robj] makes a new R object from it.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::detached::{DetachedData, DetachedRobj};
+//! test! {
+//!     let robj = R!("c(a = 1.5, b = NA)")?;
+//!     let detached = robj.detach()?;
+//!
+//!     // Process the copy on another thread.
+//!     let total = std::thread::spawn(move || match detached.data() {
+//!         DetachedData::Doubles(values) => values.iter().filter(|x| !x.is_na()).sum::<f64>(),
+//!         _ => 0.0,
+//!     })
+//!     .join()
+//!     .unwrap();
+//!     assert_eq!(total, 1.5);
+//!
+//!     let detached = robj.detach()?;
+//!     assert_eq!(detached.to_robj()?, robj);
+//! }
+//! ```
+use crate::scalar::{Rcplx, Scalar};
+use crate::*;
+
+/// The data of a [DetachedRobj], by R type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetachedData {
+    Null,
+    /// Logical values as R stores them: 0, 1 or `NA_LOGICAL`.
+    Logicals(Vec<i32>),
+    Integers(Vec<i32>),
+    Doubles(Vec<f64>),
+    /// Real and imaginary parts.
+    Complexes(Vec<(f64, f64)>),
+    /// Strings, with `None` for `NA`.
+    Strings(Vec<Option<String>>),
+    Raw(Vec<u8>),
+    List(Vec<DetachedRobj>),
+}
+
+/// A deep copy of an R object owned by rust, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetachedRobj {
+    data: DetachedData,
+    attributes: Vec<(String, DetachedRobj)>,
+}
+
+impl DetachedRobj {
+    /// Make a detached object from data, with no attributes.
+    pub fn new(data: DetachedData) -> Self {
+        DetachedRobj {
+            data,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Add an attribute, such as `names` or `class`.
+    pub fn with_attribute(mut self, name: &str, value: DetachedRobj) -> Self {
+        self.attributes.push((name.to_string(), value));
+        self
+    }
+
+    /// The data.
+    pub fn data(&self) -> &DetachedData {
+        &self.data
+    }
+
+    /// The attributes, in the order R stores them.
+    pub fn attributes(&self) -> &[(String, DetachedRobj)] {
+        &self.attributes
+    }
+
+    /// Get an attribute by name.
+    pub fn attribute(&self, name: &str) -> Option<&DetachedRobj> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Split into data and attributes.
+    pub fn into_parts(self) -> (DetachedData, Vec<(String, DetachedRobj)>) {
+        (self.data, self.attributes)
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        match &self.data {
+            DetachedData::Null => 0,
+            DetachedData::Logicals(v) | DetachedData::Integers(v) => v.len(),
+            DetachedData::Doubles(v) => v.len(),
+            DetachedData::Complexes(v) => v.len(),
+            DetachedData::Strings(v) => v.len(),
+            DetachedData::Raw(v) => v.len(),
+            DetachedData::List(v) => v.len(),
+        }
+    }
+
+    /// Return true if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Make a new R object with the data and attributes.
+    pub fn to_robj(&self) -> Result<Robj> {
+        let mut robj: Robj = match &self.data {
+            DetachedData::Null => return Ok(().into()),
+            DetachedData::Logicals(v) => {
+                Logicals::from_values(v.iter().map(|&x| Rbool::from(x))).into()
+            }
+            DetachedData::Integers(v) => Integers::from_values(v.iter().copied()).into(),
+            DetachedData::Doubles(v) => Doubles::from_values(v.iter().copied()).into(),
+            DetachedData::Complexes(v) => {
+                Complexes::from_values(v.iter().map(|&(re, im)| Rcplx::new(re, im))).into()
+            }
+            DetachedData::Strings(v) => Strings::from_values(v.iter().cloned()).into(),
+            DetachedData::Raw(v) => Raw::from_bytes(v).into(),
+            DetachedData::List(v) => {
+                let values = v
+                    .iter()
+                    .map(|elt| elt.to_robj())
+                    .collect::<Result<Vec<_>>>()?;
+                List::from_values(values).into()
+            }
+        };
+        for (name, value) in &self.attributes {
+            robj.set_attrib(Symbol::from_string(name), value.to_robj()?)?;
+        }
+        Ok(robj)
+    }
+}
+
+impl TryFrom<&DetachedRobj> for Robj {
+    type Error = Error;
+
+    fn try_from(detached: &DetachedRobj) -> Result<Self> {
+        detached.to_robj()
+    }
+}
+
+impl TryFrom<DetachedRobj> for Robj {
+    type Error = Error;
+
+    fn try_from(detached: DetachedRobj) -> Result<Self> {
+        detached.to_robj()
+    }
+}
+
+impl Robj {
+    /// Deep-copy a vector or list and its attributes into rust memory,
+    /// see [DetachedRobj].
+    ///
+    /// Environments, functions, language objects and external pointers can't
+    /// be detached, here or in attributes or list elements.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let factor = R!("factor(c('x', 'y', NA))")?;
+    ///     assert_eq!(factor.detach()?.to_robj()?, factor);
+    ///     assert!(R!("list(a = 1, f = function() 1)")?.detach().is_err());
+    /// }
+    /// ```
+    pub fn detach(&self) -> Result<DetachedRobj> {
+        let data = match self.rtype() {
+            Rtype::Null => DetachedData::Null,
+            Rtype::Logicals => DetachedData::Logicals(
+                self.as_logical_slice()
+                    .unwrap()
+                    .iter()
+                    .map(|x| x.inner())
+                    .collect(),
+            ),
+            Rtype::Integers => DetachedData::Integers(self.as_integer_slice().unwrap().to_vec()),
+            Rtype::Doubles => DetachedData::Doubles(self.as_real_slice().unwrap().to_vec()),
+            Rtype::Complexes => DetachedData::Complexes(
+                Complexes::try_from(self)?
+                    .iter()
+                    .map(|x| (x.re().inner(), x.im().inner()))
+                    .collect(),
+            ),
+            Rtype::Strings => DetachedData::Strings(
+                Strings::try_from(self)?
+                    .iter()
+                    .map(|s| {
+                        if s.is_na() {
+                            None
+                        } else {
+                            Some(s.as_str().to_string())
+                        }
+                    })
+                    .collect(),
+            ),
+            Rtype::Raw => DetachedData::Raw(self.as_raw_slice().unwrap().to_vec()),
+            Rtype::List => DetachedData::List(
+                List::try_from(self)?
+                    .values()
+                    .map(|elt| elt.detach())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            _ => {
+                return Err(Error::Other(format!(
+                    "can't detach an R object of type {:?}",
+                    self.rtype()
+                )))
+            }
+        };
+        let attrib = unsafe { Robj::from_sexp(ATTRIB(self.get())) };
+        let attributes = match attrib.as_pairlist() {
+            Some(attrib) => attrib
+                .iter()
+                .map(|(name, value)| Ok((name.to_string(), value.detach()?)))
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Ok(DetachedRobj { data, attributes })
+    }
+}
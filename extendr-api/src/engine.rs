@@ -0,0 +1,14 @@
+//! Embedding R in rust programs, re-exported from extendr-engine.
+//!
+//! R packages never need this: R is already running when their functions
+//! are called. It is for tests, benchmarks and stand-alone programs.
+//!
+//! ```
+//! use extendr_api::engine::with_r;
+//! use extendr_api::prelude::*;
+//! with_r(|| {
+//!     assert_eq!(eval_string("1 + 1").unwrap(), r!(2.0));
+//! });
+//! ```
+
+pub use extendr_engine::*;
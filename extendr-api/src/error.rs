@@ -30,7 +30,9 @@ pub fn unwrap_or_throw_error<T>(r: std::result::Result<T, Error>) -> T {
 pub enum Error {
     Panic(Robj),
     NotFound(Robj),
+    #[deprecated(note = "R errors are returned as `Error::RError`")]
     EvalError(Robj),
+    RError(String),
     ParseError(Robj),
     NamesLengthMismatch(Robj),
 
@@ -58,6 +60,7 @@ pub enum Error {
     ExpectedWeakRef(Robj),
     ExpectedRaw(Robj),
     ExpectedS4(Robj),
+    ExpectedR6(Robj),
     ExpectedPrimitive(Robj),
 
     ExpectedScalar(Robj),
@@ -72,10 +75,15 @@ pub enum Error {
     MustNotBeNA(Robj),
     ExpectedNonZeroLength(Robj),
     ExpectedWholeNumber(Robj),
+    ExpectedChar(Robj),
     OutOfLimits(Robj),
     TypeMismatch(Robj),
     NamespaceNotFound(Robj),
     NoGraphicsDevices(Robj),
+    Interrupted,
+    Timeout,
+    Aliased(Robj),
+    Consumed(Robj),
 
     ExpectedExternalPtrType(Robj, String),
     Other(String),
@@ -88,11 +96,13 @@ pub enum Error {
 }
 
 impl std::fmt::Display for Error {
+    #[allow(deprecated)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Panic(robj) => write!(f, "Panic detected {:?}.", robj),
             Error::NotFound(robj) => write!(f, "Not found. {:?}", robj),
             Error::EvalError(robj) => write!(f, "Evaluation error in {:?}.", robj),
+            Error::RError(msg) => write!(f, "{}", msg),
             Error::ParseError(code) => write!(f, "Parse error in {:?}.", code),
             Error::NamesLengthMismatch(robj) => {
                 write!(f, "Length of names does not match vector. {:?}", robj)
@@ -130,6 +140,7 @@ impl std::fmt::Display for Error {
             Error::ExpectedWeakRef(robj) => write!(f, "Expected WeakRef got {:?}", robj.rtype()),
             Error::ExpectedRaw(robj) => write!(f, "Expected Raw got {:?}", robj.rtype()),
             Error::ExpectedS4(robj) => write!(f, "Expected S4 got {:?}", robj.rtype()),
+            Error::ExpectedR6(robj) => write!(f, "Expected R6 got {:?}", robj.rtype()),
             Error::ExpectedPrimitive(robj) => {
                 write!(f, "Expected Primitive got {:?}", robj.rtype())
             }
@@ -154,6 +165,9 @@ impl std::fmt::Display for Error {
                     robj
                 )
             }
+            Error::ExpectedChar(robj) => {
+                write!(f, "Expected a single character, got {:?}", robj)
+            }
             Error::OutOfLimits(robj) => write!(f, "The value is too big: {:?}", robj),
             Error::TypeMismatch(_robj) => write!(f, "Type mismatch"),
 
@@ -161,7 +175,15 @@ impl std::fmt::Display for Error {
             Error::ExpectedExternalPtrType(_robj, type_name) => {
                 write!(f, "Incorrect external pointer type {}", type_name)
             }
+            Error::Consumed(_robj) => write!(f, "The object has already been consumed."),
             Error::NoGraphicsDevices(_robj) => write!(f, "No graphics devices active."),
+            Error::Interrupted => write!(f, "Interrupted by the user."),
+            Error::Timeout => write!(f, "The time limit was reached."),
+            Error::Aliased(robj) => write!(
+                f,
+                "Input and output share memory, pass a copy of the {:?} vector instead.",
+                robj.rtype()
+            ),
             Error::Other(str) => write!(f, "{}", str),
 
             #[cfg(feature = "ndarray")]
@@ -0,0 +1,160 @@
+//! Run R code and capture everything it does, for notebook and knitr style engines.
+//!
+//! [evaluate_code] runs each top level expression in turn, like the R console,
+//! and returns a list of [EvalEvent]s in the order they happened.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::evaluate::{evaluate_code, EvalEvent};
+//! test! {
+//!     let events = evaluate_code("cat('hi\\n'); message('note'); 1 + 1")?;
+//!     assert_eq!(events[0], EvalEvent::Output("hi\n".into()));
+//!     assert_eq!(events[1], EvalEvent::Message("note\n".into()));
+//!     assert_eq!(events[2], EvalEvent::Value(r!(2.0)));
+//!     assert_eq!(events[3], EvalEvent::Output("[1] 2\n".into()));
+//! }
+//! ```
+use crate::*;
+
+/// Something that happened while evaluating code.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvalEvent {
+    /// The visible value of a top level expression. It is then printed,
+    /// as at the console, so an [EvalEvent::Output] with the printed text follows.
+    Value(Robj),
+    /// Text written to the console.
+    Output(String),
+    /// The text of a message, including the final newline.
+    Message(String),
+    /// The text of a warning.
+    Warning(String),
+    /// The text of an error. Evaluation continues with the next expression.
+    Error(String),
+    /// A plot, recorded with `recordPlot()`. It can be redrawn on another
+    /// device with `replayPlot()`, or with `RecordedPlot` from the `graphics` feature.
+    Plot(Robj),
+}
+
+// Evaluates each expression with output sunk to a text connection and
+// conditions caught, so events are recorded in order. Plots go to a
+// `pdf(NULL)` device and are recorded when the display list changes.
+const EVALUATOR: &str = r#"function(exprs, envir) {
+  events <- list()
+  add <- function(type, value) {
+    events[[length(events) + 1L]] <<- list(type, value)
+  }
+
+  output <- character()
+  seen <- 0L
+  con <- textConnection("output", "w", local = TRUE)
+  sinks <- sink.number()
+  flush_output <- function() {
+    if (length(output) > seen) {
+      add("output", paste0(output[seq_along(output) > seen], "\n", collapse = ""))
+      seen <<- length(output)
+    }
+  }
+
+  grDevices::pdf(file = NULL)
+  grDevices::dev.control(displaylist = "enable")
+  device <- grDevices::dev.cur()
+  last_plot <- NULL
+  flush_plot <- function() {
+    if (grDevices::dev.cur() != device) return()
+    plot <- grDevices::recordPlot()
+    if (length(plot[[1]]) > 0 && !identical(plot[[1]], last_plot[[1]])) {
+      add("plot", plot)
+      last_plot <<- plot
+    }
+  }
+
+  on.exit({
+    while (sink.number() > sinks) sink()
+    close(con)
+    if (device %in% grDevices::dev.list()) grDevices::dev.off(device)
+  })
+
+  run <- function(f) {
+    withCallingHandlers(
+      tryCatch(f(), error = function(e) {
+        flush_output()
+        add("error", conditionMessage(e))
+        NULL
+      }),
+      message = function(m) {
+        flush_output()
+        add("message", conditionMessage(m))
+        invokeRestart("muffleMessage")
+      },
+      warning = function(w) {
+        flush_output()
+        add("warning", conditionMessage(w))
+        invokeRestart("muffleWarning")
+      }
+    )
+  }
+
+  for (expr in exprs) {
+    sink(con)
+    res <- run(function() withVisible(eval(expr, envir)))
+    if (!is.null(res) && res$visible) {
+      flush_output()
+      add("value", res$value)
+      run(function() print(res$value))
+    }
+    sink()
+    flush_output()
+    flush_plot()
+  }
+  events
+}"#;
+
+/// Evaluate R code in the global environment, recording output, conditions and plots.
+///
+/// A parse error is returned as an error, as no code has run.
+pub fn evaluate_code(code: &str) -> Result<Vec<EvalEvent>> {
+    evaluate_code_in(code, &global_env())
+}
+
+/// Evaluate R code in `env`, recording output, conditions and plots.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::evaluate::{evaluate_code_in, EvalEvent};
+/// test! {
+///     let env = new_env(global_env(), true, 10);
+///     let events = evaluate_code_in("x <- 1; stop('oops'); warning('careful'); x", &env)?;
+///     assert_eq!(events, [
+///         EvalEvent::Error("oops".into()),
+///         EvalEvent::Warning("careful".into()),
+///         EvalEvent::Value(r!(1.0)),
+///         EvalEvent::Output("[1] 1\n".into()),
+///     ]);
+/// }
+/// ```
+pub fn evaluate_code_in(code: &str, env: &Environment) -> Result<Vec<EvalEvent>> {
+    let exprs = parse(code)?;
+    let evaluator: Function = eval_string(EVALUATOR)?.try_into()?;
+    let events: List = evaluator
+        .call(Pairlist::from_pairs([
+            ("", Robj::from(exprs)),
+            ("", Robj::from(env.clone())),
+        ]))?
+        .try_into()?;
+    events.values().map(|event| to_event(&event)).collect()
+}
+
+fn to_event(event: &Robj) -> Result<EvalEvent> {
+    let event: List = event.try_into()?;
+    let kind = event.elt(0)?;
+    let value = event.elt(1)?;
+    let text = || -> Result<String> { Ok(value.try_as_str()?.to_string()) };
+    Ok(match kind.try_as_str()? {
+        "value" => EvalEvent::Value(value.clone()),
+        "output" => EvalEvent::Output(text()?),
+        "message" => EvalEvent::Message(text()?),
+        "warning" => EvalEvent::Warning(text()?),
+        "error" => EvalEvent::Error(text()?),
+        "plot" => EvalEvent::Plot(value.clone()),
+        other => return Err(Error::Other(format!("unknown event '{}'", other))),
+    })
+}
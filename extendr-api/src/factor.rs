@@ -0,0 +1,332 @@
+//! Build factors one label at a time, and convert them to and from codes and levels.
+//!
+//! [FactorBuilder] interns labels as they arrive, so a reader can make a
+//! factor in one pass without keeping every label as a string. This is
+//! much cheaper than collecting a character vector and calling `factor()`.
+//!
+//! [Categorical] holds zero-based codes into a vector of levels, the
+//! representation of dictionary arrays in arrow and polars.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! test! {
+//!     let mut builder = FactorBuilder::new();
+//!     for label in ["b", "a", "b"] {
+//!         builder.push(label);
+//!     }
+//!     builder.push_na();
+//!     let factor = builder.build()?;
+//!     assert_eq!(factor, R!("factor(c('b', 'a', 'b', NA), levels = c('b', 'a'))")?);
+//! }
+//! ```
+use crate::*;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// The order of the levels of a factor made by a [FactorBuilder].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelOrder {
+    /// In the order the labels first appear.
+    FirstAppearance,
+    /// Sorted in byte order, as `sort(method = "radix")` does.
+    Sorted,
+}
+
+/// Build a factor from a stream of labels, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct FactorBuilder {
+    order: LevelOrder,
+    index: HashMap<String, i32>,
+    levels: Vec<String>,
+    codes: Vec<i32>,
+}
+
+impl Default for FactorBuilder {
+    fn default() -> Self {
+        FactorBuilder::new()
+    }
+}
+
+impl FactorBuilder {
+    /// Make a builder that gives the levels in order of first appearance.
+    pub fn new() -> Self {
+        FactorBuilder::with_order(LevelOrder::FirstAppearance)
+    }
+
+    /// Make a builder that gives sorted levels, like `factor()`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut builder = FactorBuilder::sorted();
+    ///     builder.extend(["b", "c", "a", "c"]);
+    ///     assert_eq!(builder.build()?, R!("factor(c('b', 'c', 'a', 'c'))")?);
+    ///
+    ///     let unsorted = ["b", "c", "a", "c"].into_iter().collect::<FactorBuilder>().build()?;
+    ///     assert_eq!(unsorted.levels().unwrap().collect::<Vec<_>>(), ["b", "c", "a"]);
+    /// }
+    /// ```
+    pub fn sorted() -> Self {
+        FactorBuilder::with_order(LevelOrder::Sorted)
+    }
+
+    /// Make a builder with the given order of levels.
+    pub fn with_order(order: LevelOrder) -> Self {
+        FactorBuilder {
+            order,
+            index: HashMap::new(),
+            levels: Vec::new(),
+            codes: Vec::new(),
+        }
+    }
+
+    /// Reserve space for `additional` more labels.
+    pub fn reserve(&mut self, additional: usize) {
+        self.codes.reserve(additional);
+    }
+
+    /// Add a label. The `NA` string adds `NA`.
+    pub fn push(&mut self, label: &str) {
+        if label.is_na() {
+            return self.push_na();
+        }
+        let code = match self.index.get(label) {
+            Some(&code) => code,
+            None => {
+                self.levels.push(label.to_string());
+                let code = self.levels.len() as i32;
+                self.index.insert(label.to_string(), code);
+                code
+            }
+        };
+        self.codes.push(code);
+    }
+
+    /// Add an `NA`.
+    pub fn push_na(&mut self) {
+        self.codes.push(i32::na());
+    }
+
+    /// Add a label, or `NA` for `None`.
+    pub fn push_option(&mut self, label: Option<&str>) {
+        match label {
+            Some(label) => self.push(label),
+            None => self.push_na(),
+        }
+    }
+
+    /// The number of labels added so far.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// Return true if no labels have been added.
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// The levels found so far, in order of first appearance.
+    pub fn levels(&self) -> &[String] {
+        &self.levels
+    }
+
+    /// Make the factor.
+    pub fn build(self) -> Result<Robj> {
+        let FactorBuilder {
+            order,
+            mut levels,
+            mut codes,
+            ..
+        } = self;
+        if order == LevelOrder::Sorted {
+            // new_code[old_code - 1] is the code of the level in sorted order.
+            let mut by_label: Vec<usize> = (0..levels.len()).collect();
+            by_label.sort_unstable_by(|&a, &b| levels[a].cmp(&levels[b]));
+            let mut new_code = vec![0; levels.len()];
+            for (i, &old) in by_label.iter().enumerate() {
+                new_code[old] = i as i32 + 1;
+            }
+            for code in codes.iter_mut().filter(|code| !code.is_na()) {
+                *code = new_code[*code as usize - 1];
+            }
+            levels = by_label.into_iter().map(|i| levels[i].clone()).collect();
+        }
+        make_factor(codes, levels, false)
+    }
+}
+
+impl<'a> Extend<&'a str> for FactorBuilder {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|label| self.push(label));
+    }
+}
+
+impl<'a> Extend<Option<&'a str>> for FactorBuilder {
+    fn extend<I: IntoIterator<Item = Option<&'a str>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|label| self.push_option(label));
+    }
+}
+
+impl<'a> FromIterator<&'a str> for FactorBuilder {
+    /// Collect labels into a builder with levels in order of first appearance.
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut builder = FactorBuilder::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+/// A categorical vector as zero-based codes into `levels`, like arrow's dictionary arrays.
+///
+/// Converting to a factor keeps the order of the levels, and codes that are
+/// not a level give `NA`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::factor::Categorical;
+/// test! {
+///     let categorical = Categorical::new(vec![1, 0, 7], vec!["lo".into(), "hi".into()]);
+///     let factor = categorical.to_factor()?;
+///     assert_eq!(factor, R!("factor(c('hi', 'lo', NA), levels = c('lo', 'hi'))")?);
+///
+///     let back = Categorical::try_from(&factor)?;
+///     assert_eq!(back.codes, [Some(1), Some(0), None]);
+///     assert_eq!(back.levels, ["lo", "hi"]);
+///     assert!(!back.ordered);
+///
+///     let ordered = Categorical::try_from(&R!("factor('b', levels = c('b', 'a'), ordered = TRUE)")?)?;
+///     assert!(ordered.ordered);
+///     assert_eq!(ordered.to_factor()?.class().unwrap().collect::<Vec<_>>(), ["ordered", "factor"]);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Categorical {
+    /// The zero-based codes, with `None` for `NA`.
+    pub codes: Vec<Option<u32>>,
+    /// The levels, which must be unique.
+    pub levels: Vec<String>,
+    /// True for an ordered factor.
+    pub ordered: bool,
+}
+
+impl Categorical {
+    /// Make an unordered categorical vector from codes and levels.
+    pub fn new(codes: Vec<u32>, levels: Vec<String>) -> Self {
+        Categorical {
+            codes: codes.into_iter().map(Some).collect(),
+            levels,
+            ordered: false,
+        }
+    }
+
+    /// Make the vector ordered, or not.
+    pub fn with_ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Split into codes and levels.
+    pub fn into_parts(self) -> (Vec<Option<u32>>, Vec<String>) {
+        (self.codes, self.levels)
+    }
+
+    /// Make an R factor, or an ordered factor.
+    ///
+    /// Duplicated levels are an error, as R does not allow them.
+    pub fn to_factor(&self) -> Result<Robj> {
+        let mut seen = std::collections::HashSet::new();
+        if let Some(level) = self
+            .levels
+            .iter()
+            .find(|level| !seen.insert(level.as_str()))
+        {
+            return Err(Error::Other(format!("duplicated factor level '{}'", level)));
+        }
+        let nlevels = self.levels.len();
+        let codes = self
+            .codes
+            .iter()
+            .map(|code| match code {
+                Some(code) if (*code as usize) < nlevels => *code as i32 + 1,
+                _ => i32::na(),
+            })
+            .collect();
+        make_factor(codes, self.levels.iter(), self.ordered)
+    }
+}
+
+impl From<(Vec<u32>, Vec<String>)> for Categorical {
+    fn from((codes, levels): (Vec<u32>, Vec<String>)) -> Self {
+        Categorical::new(codes, levels)
+    }
+}
+
+impl TryFrom<&Robj> for Categorical {
+    type Error = Error;
+
+    /// Read a factor or ordered factor.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let codes = match robj.as_integer_slice() {
+            Some(codes) if robj.is_factor() => codes,
+            _ => {
+                return Err(Error::Other(format!(
+                    "Expected a factor got {:?}",
+                    robj.rtype()
+                )))
+            }
+        };
+        let levels = match robj.levels() {
+            Some(levels) => levels.map(|level| level.to_string()).collect(),
+            None => Vec::new(),
+        };
+        let codes = codes
+            .iter()
+            .map(|&code| {
+                if code.is_na() || code < 1 {
+                    None
+                } else {
+                    Some(code as u32 - 1)
+                }
+            })
+            .collect();
+        Ok(Categorical {
+            codes,
+            levels,
+            ordered: robj.inherits("ordered"),
+        })
+    }
+}
+
+impl TryFrom<Robj> for Categorical {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Categorical::try_from(&robj)
+    }
+}
+
+impl TryFrom<Categorical> for Robj {
+    type Error = Error;
+
+    fn try_from(categorical: Categorical) -> Result<Self> {
+        categorical.to_factor()
+    }
+}
+
+fn make_factor<L>(codes: Vec<i32>, levels: L, ordered: bool) -> Result<Robj>
+where
+    L: IntoIterator,
+    L::IntoIter: ExactSizeIterator,
+    L::Item: AsRef<str>,
+{
+    let mut res = Robj::from(codes);
+    res.set_attrib(levels_symbol(), Strings::from_values(levels))?;
+    if ordered {
+        res.set_class(["ordered", "factor"])?;
+    } else {
+        res.set_class(["factor"])?;
+    }
+    Ok(res)
+}
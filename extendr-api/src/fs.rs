@@ -0,0 +1,288 @@
+//! File system utilities with the same output as base R.
+//!
+//! These are implemented in Rust, but return objects with the same columns
+//! and classes as `list.files()`, `file.info()`, `file.copy(recursive = TRUE)`
+//! and `unlink(recursive = TRUE)`.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::fs::{file_info, list_files};
+//! test! {
+//!     let dir = std::env::temp_dir().join("extendr_fs_doc");
+//!     std::fs::create_dir_all(&dir).unwrap();
+//!     std::fs::write(dir.join("a.txt"), "hello").unwrap();
+//!
+//!     let files = list_files(&dir, false, true)?;
+//!     let info = file_info(&files)?;
+//!     assert!(info.inherits("data.frame"));
+//!     assert_eq!(info.dollar("size")?, r!(5.0));
+//!     std::fs::remove_dir_all(&dir).unwrap();
+//! }
+//! ```
+use crate::wrapper::symbol::row_names_symbol;
+use crate::*;
+#[cfg(all(unix, feature = "libc"))]
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::Other(format!("{}: {}", path.display(), err))
+}
+
+fn seconds_since_epoch(time: std::io::Result<SystemTime>) -> Option<f64> {
+    let time = time.ok()?;
+    Some(match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    })
+}
+
+fn posixct(values: Vec<Option<f64>>) -> Result<Robj> {
+    let mut robj = r!(values);
+    robj.set_class(["POSIXct", "POSIXt"])
+}
+
+/// List the files in a directory, like `list.files(path, recursive = recursive, full.names = full_names)`.
+///
+/// Hidden files are skipped and directories are omitted when listing recursively,
+/// as in R. The result is sorted.
+pub fn list_files<P: AsRef<Path>>(
+    path: P,
+    recursive: bool,
+    full_names: bool,
+) -> Result<Vec<PathBuf>> {
+    fn visit(dir: &Path, rel: &Path, recursive: bool, out: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+            let entry = entry.map_err(|e| io_error(dir, e))?;
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let rel = rel.join(&name);
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if recursive && is_dir {
+                visit(&entry.path(), &rel, recursive, out)?;
+            } else {
+                out.push(rel);
+            }
+        }
+        Ok(())
+    }
+
+    let path = path.as_ref();
+    let mut files = Vec::new();
+    let prefix = if full_names { path } else { Path::new("") };
+    visit(path, prefix, recursive, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Get information about files, like `file.info(paths)`.
+///
+/// Returns a data.frame with one row per path, named by the path.
+/// Missing files have `NA` in every column.
+pub fn file_info<P: AsRef<Path>>(paths: &[P]) -> Result<Robj> {
+    let meta: Vec<Option<std::fs::Metadata>> = paths
+        .iter()
+        .map(|p| std::fs::metadata(p.as_ref()).ok())
+        .collect();
+
+    let size: Vec<Option<f64>> = meta
+        .iter()
+        .map(|m| m.as_ref().map(|m| m.len() as f64))
+        .collect();
+    let isdir: Vec<Option<bool>> = meta
+        .iter()
+        .map(|m| m.as_ref().map(|m| m.is_dir()))
+        .collect();
+    let mtime = meta
+        .iter()
+        .map(|m| m.as_ref().and_then(|m| seconds_since_epoch(m.modified())))
+        .collect();
+    let atime = meta
+        .iter()
+        .map(|m| m.as_ref().and_then(|m| seconds_since_epoch(m.accessed())))
+        .collect();
+
+    let mut columns: Vec<(&str, Robj)> = vec![("size", r!(size)), ("isdir", r!(isdir))];
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let mode: Vec<Option<i32>> = meta
+            .iter()
+            .map(|m| m.as_ref().map(|m| (m.mode() & 0o7777) as i32))
+            .collect();
+        let ctime = meta
+            .iter()
+            .map(|m| {
+                m.as_ref()
+                    .map(|m| m.ctime() as f64 + m.ctime_nsec() as f64 * 1e-9)
+            })
+            .collect();
+        let uid: Vec<Option<i32>> = meta
+            .iter()
+            .map(|m| m.as_ref().map(|m| m.uid() as i32))
+            .collect();
+        let gid: Vec<Option<i32>> = meta
+            .iter()
+            .map(|m| m.as_ref().map(|m| m.gid() as i32))
+            .collect();
+        let uname: Vec<Option<String>> = meta
+            .iter()
+            .map(|m| m.as_ref().and_then(|m| user_name(m.uid())))
+            .collect();
+        let grname: Vec<Option<String>> = meta
+            .iter()
+            .map(|m| m.as_ref().and_then(|m| group_name(m.gid())))
+            .collect();
+        columns.push(("mode", r!(mode).set_class(["octmode"])?));
+        columns.push(("mtime", posixct(mtime)?));
+        columns.push(("ctime", posixct(ctime)?));
+        columns.push(("atime", posixct(atime)?));
+        columns.push(("uid", r!(uid)));
+        columns.push(("gid", r!(gid)));
+        columns.push(("uname", r!(uname)));
+        columns.push(("grname", r!(grname)));
+    }
+
+    #[cfg(windows)]
+    {
+        // R reports 0777/0666 style modes on Windows, depending on read-only.
+        let mode: Vec<Option<i32>> = meta
+            .iter()
+            .map(|m| {
+                m.as_ref()
+                    .map(|m| match (m.is_dir(), m.permissions().readonly()) {
+                        (true, _) => 0o777,
+                        (false, true) => 0o444,
+                        (false, false) => 0o666,
+                    })
+            })
+            .collect();
+        let ctime = meta
+            .iter()
+            .map(|m| m.as_ref().and_then(|m| seconds_since_epoch(m.created())))
+            .collect();
+        let exe: Vec<Option<String>> = paths
+            .iter()
+            .zip(meta.iter())
+            .map(|(p, m)| {
+                m.as_ref().map(|_| {
+                    let ext = p.as_ref().extension().map(|e| e.to_ascii_lowercase());
+                    match ext.as_ref().and_then(|e| e.to_str()) {
+                        Some("exe") | Some("com") => "win64".to_string(),
+                        _ => "no".to_string(),
+                    }
+                })
+            })
+            .collect();
+        columns.push(("mode", r!(mode).set_class(["octmode"])?));
+        columns.push(("mtime", posixct(mtime)?));
+        columns.push(("ctime", posixct(ctime)?));
+        columns.push(("atime", posixct(atime)?));
+        columns.push(("exe", r!(exe)));
+    }
+
+    let row_names: Vec<String> = paths
+        .iter()
+        .map(|p| p.as_ref().to_string_lossy().into_owned())
+        .collect();
+    let mut res = r!(List::from_pairs(columns));
+    res.set_attrib(row_names_symbol(), row_names)?;
+    res.set_class(["data.frame"])
+}
+
+#[cfg(all(unix, feature = "libc"))]
+fn user_name(uid: u32) -> Option<String> {
+    unsafe {
+        let pw = libc::getpwuid(uid);
+        if pw.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr((*pw).pw_name).to_string_lossy().into_owned())
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "libc"))]
+fn group_name(gid: u32) -> Option<String> {
+    unsafe {
+        let gr = libc::getgrgid(gid);
+        if gr.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr((*gr).gr_name).to_string_lossy().into_owned())
+        }
+    }
+}
+
+// User and group names need libc; without it they are NA.
+#[cfg(all(unix, not(feature = "libc")))]
+fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(all(unix, not(feature = "libc")))]
+fn group_name(_gid: u32) -> Option<String> {
+    None
+}
+
+/// Copy a file or directory tree, like `file.copy(from, to, recursive = TRUE)`
+/// where `to` is the destination path itself.
+///
+/// Existing files are only replaced if `overwrite` is true.
+/// Returns the number of files copied.
+pub fn copy_recursive<P: AsRef<Path>, Q: AsRef<Path>>(
+    from: P,
+    to: Q,
+    overwrite: bool,
+) -> Result<usize> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    let meta = std::fs::metadata(from).map_err(|e| io_error(from, e))?;
+    if meta.is_dir() {
+        std::fs::create_dir_all(to).map_err(|e| io_error(to, e))?;
+        let mut count = 0;
+        for entry in std::fs::read_dir(from).map_err(|e| io_error(from, e))? {
+            let entry = entry.map_err(|e| io_error(from, e))?;
+            count += copy_recursive(entry.path(), to.join(entry.file_name()), overwrite)?;
+        }
+        Ok(count)
+    } else if to.exists() && !overwrite {
+        Ok(0)
+    } else {
+        std::fs::copy(from, to).map_err(|e| io_error(from, e))?;
+        Ok(1)
+    }
+}
+
+/// Delete a file or directory tree, like `unlink(path, recursive = TRUE)`.
+///
+/// As in R, deleting a path that does not exist is not an error.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::fs::{copy_recursive, remove_recursive};
+/// test! {
+///     let dir = std::env::temp_dir().join("extendr_fs_copy_doc");
+///     std::fs::create_dir_all(dir.join("sub")).unwrap();
+///     std::fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+///
+///     let copy = std::env::temp_dir().join("extendr_fs_copy_doc2");
+///     assert_eq!(copy_recursive(&dir, &copy, true)?, 1);
+///     assert!(copy.join("sub").join("b.txt").exists());
+///
+///     remove_recursive(&dir)?;
+///     remove_recursive(&copy)?;
+///     assert!(!copy.exists());
+///     remove_recursive(&copy)?;
+/// }
+/// ```
+pub fn remove_recursive<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    match std::fs::symlink_metadata(path) {
+        Err(_) => Ok(()),
+        Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(path).map_err(|e| io_error(path, e)),
+        Ok(_) => std::fs::remove_file(path).map_err(|e| io_error(path, e)),
+    }
+}
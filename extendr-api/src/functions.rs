@@ -300,3 +300,289 @@ pub fn find_namespaced_function(name: &str) -> Result<Language> {
         _ => Err(Error::NotFound(r!(name))),
     }
 }
+
+/// Return [Error::Interrupted] if the user has pressed Ctrl-C (or Esc in RGui),
+/// so that long-running loops can be cancelled from R with `?`.
+///
+/// Unlike `R_CheckUserInterrupt()`, this returns rather than jumping out of
+/// the rust code, so destructors run and the session is not affected. Each
+/// check also lets R process GUI events, so in tight loops check every few
+/// thousand iterations rather than every one.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let mut total = 0_u64;
+///     for i in 0..1_000_000_u64 {
+///         if i % 10_000 == 0 {
+///             check_user_interrupt()?;
+///         }
+///         total += i;
+///     }
+///     assert_eq!(total, 499_999_500_000);
+/// }
+/// ```
+pub fn check_user_interrupt() -> Result<()> {
+    if interrupt_pending() {
+        Err(Error::Interrupted)
+    } else {
+        Ok(())
+    }
+}
+
+/// Run `f`, returning [Error::Interrupted] if the user interrupts R code
+/// that it calls.
+///
+/// R handles Ctrl-C by jumping out of the code that checks for it, such as
+/// `R_CheckUserInterrupt()` or most R functions. This catches that jump and
+/// unwinds the rust frames in `f`, so their values are dropped. Other R
+/// errors are not caught.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::conditions::{catch_condition, signalling_eval};
+/// test! {
+///     assert_eq!(catch_user_interrupt(|| 1 + 1), Ok(2));
+///
+///     // Signal the condition that Ctrl-C signals.
+///     let interrupt = R!("structure(list(), class = c('interrupt', 'condition'))")?;
+///     let res = catch_user_interrupt(|| {
+///         signalling_eval(&lang!("signalCondition", interrupt.clone()), &global_env())
+///     });
+///     assert_eq!(res, Err(Error::Interrupted));
+///
+///     // Errors are passed on.
+///     let res = catch_condition(|| {
+///         catch_user_interrupt(|| signalling_eval(&lang!("stop", "boom"), &global_env()))
+///     });
+///     assert_eq!(res.unwrap_err().message_text(), "boom");
+/// }
+/// ```
+pub fn catch_user_interrupt<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> T,
+{
+    crate::conditions::try_catch(&["interrupt"], f).map_err(|_| Error::Interrupted)
+}
+
+// Return true if the user has pressed Ctrl-C (or Esc in RGui).
+//
+// The interrupt is consumed, so the caller is expected to stop and return an error.
+pub(crate) fn interrupt_pending() -> bool {
+    unsafe extern "C" fn check_interrupt(_: *mut std::os::raw::c_void) {
+        R_CheckUserInterrupt();
+    }
+    single_threaded(|| unsafe { R_ToplevelExec(Some(check_interrupt), std::ptr::null_mut()) == 0 })
+}
+
+/// Sleep for `duration`, like `Sys.sleep()`.
+///
+/// The sleep is split into short intervals and checks for user interrupts
+/// in between, returning [Error::Interrupted] if the user interrupts.
+/// This must be called from the R thread.
+/// ```
+/// use extendr_api::prelude::*;
+/// use std::time::{Duration, Instant};
+/// test! {
+///    let start = Instant::now();
+///    r_sleep(Duration::from_millis(30))?;
+///    assert!(start.elapsed() >= Duration::from_millis(30));
+/// }
+/// ```
+pub fn r_sleep(duration: std::time::Duration) -> Result<()> {
+    const INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+    let end = std::time::Instant::now() + duration;
+    loop {
+        if interrupt_pending() {
+            return Err(Error::Interrupted);
+        }
+        let now = std::time::Instant::now();
+        if now >= end {
+            return Ok(());
+        }
+        std::thread::sleep(INTERVAL.min(end - now));
+    }
+}
+
+// The flag that R's SIGINT handler sets when the user interrupts, declared
+// for front-ends in `Rinterface.h`. `R_CheckUserInterrupt()` clears it when
+// it raises the interrupt.
+#[cfg(unix)]
+extern "C" {
+    static mut R_interrupts_pending: std::os::raw::c_int;
+}
+
+/// Run `f`, interrupting R if it takes longer than `timeout`.
+///
+/// A watchdog thread raises an interrupt when the time is up, as if the user
+/// had pressed Ctrl-C, and [Error::Timeout] is returned if the interrupt
+/// stopped `f`. Other errors of `f` are returned as they are, and an
+/// interrupt that `f` did not reach is withdrawn. Like a user interrupt,
+/// this only stops R code at the points where it checks for interrupts,
+/// which includes most loops and calls, and not rust code.
+/// This must be called from the R thread. It is not available on Windows,
+/// where R has no documented way to raise an interrupt from another thread.
+/// ```
+/// use extendr_api::prelude::*;
+/// use std::time::Duration;
+/// test! {
+///    let res = eval_with_timeout(|| eval_string("1 + 1"), Duration::from_secs(10));
+///    assert_eq!(res, Ok(r!(2.0)));
+///
+///    let res = eval_with_timeout(|| eval_string("repeat {}"), Duration::from_millis(50));
+///    assert_eq!(res, Err(Error::Timeout));
+///
+///    // Errors that are not caused by the interrupt are passed on.
+///    let res = eval_with_timeout(
+///        || {
+///            std::thread::sleep(Duration::from_millis(100));
+///            Err::<Robj, _>(Error::Other("boom".into()))
+///        },
+///        Duration::from_millis(50),
+///    );
+///    assert_eq!(res, Err(Error::Other("boom".into())));
+/// }
+/// ```
+#[cfg(unix)]
+pub fn eval_with_timeout<F, T>(f: F, timeout: std::time::Duration) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    use std::ptr::{addr_of_mut, read_volatile, write_volatile};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+
+    let (done, finished) = mpsc::channel::<()>();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let watchdog = {
+        let timed_out = timed_out.clone();
+        std::thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = finished.recv_timeout(timeout) {
+                timed_out.store(true, Ordering::SeqCst);
+                // A single volatile store, as R's own signal handler does.
+                unsafe { write_volatile(addr_of_mut!(R_interrupts_pending), 1) };
+            }
+        })
+    };
+
+    let res = f();
+    let _ = done.send(());
+    let _ = watchdog.join();
+
+    if timed_out.load(Ordering::SeqCst) {
+        unsafe {
+            if read_volatile(addr_of_mut!(R_interrupts_pending)) != 0 {
+                // `f` finished before R checked for the interrupt, so don't
+                // leave it for later code.
+                write_volatile(addr_of_mut!(R_interrupts_pending), 0);
+            } else if res.is_err() {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+    res
+}
+
+/// Evaluate a string of R code, interrupting it if it takes longer than `timeout`.
+///
+/// See [eval_with_timeout].
+/// ```
+/// use extendr_api::prelude::*;
+/// use std::time::Duration;
+/// test! {
+///    let res = eval_string_with_timeout("Sys.sleep(5)", Duration::from_millis(50));
+///    assert_eq!(res, Err(Error::Timeout));
+/// }
+/// ```
+#[cfg(unix)]
+pub fn eval_string_with_timeout(code: &str, timeout: std::time::Duration) -> Result<Robj> {
+    eval_with_timeout(|| eval_string(code), timeout)
+}
+
+/// Times in seconds, as returned by `proc.time()` and `system.time()`.
+///
+/// Converts to an R `proc_time` object.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ProcTime {
+    pub user: f64,
+    pub system: f64,
+    pub elapsed: f64,
+    pub user_child: f64,
+    pub system_child: f64,
+}
+
+impl ProcTime {
+    /// The current times, like `proc.time()`.
+    pub fn now() -> Result<Self> {
+        let times = call!("proc.time")?.try_as_real_vector()?;
+        match times[..] {
+            [user, system, elapsed, user_child, system_child, ..] => Ok(ProcTime {
+                user,
+                system,
+                elapsed,
+                user_child: user_child.max(0.0),
+                system_child: system_child.max(0.0),
+            }),
+            _ => Err(Error::Other("unexpected result from proc.time()".into())),
+        }
+    }
+}
+
+impl std::ops::Sub for ProcTime {
+    type Output = ProcTime;
+
+    fn sub(self, rhs: ProcTime) -> ProcTime {
+        ProcTime {
+            user: self.user - rhs.user,
+            system: self.system - rhs.system,
+            elapsed: self.elapsed - rhs.elapsed,
+            user_child: self.user_child - rhs.user_child,
+            system_child: self.system_child - rhs.system_child,
+        }
+    }
+}
+
+impl From<ProcTime> for Robj {
+    fn from(time: ProcTime) -> Self {
+        let mut res = r!([
+            time.user,
+            time.system,
+            time.elapsed,
+            time.user_child,
+            time.system_child
+        ]);
+        res.set_names([
+            "user.self",
+            "sys.self",
+            "elapsed",
+            "user.child",
+            "sys.child",
+        ])
+        .unwrap();
+        res.set_class(["proc_time"]).unwrap();
+        res
+    }
+}
+
+/// Time a closure, like `system.time()`, returning its value with the user,
+/// system and elapsed times.
+/// ```
+/// use extendr_api::prelude::*;
+/// use std::time::Duration;
+/// test! {
+///    let (res, time) = r_system_time(|| r_sleep(Duration::from_millis(20)))?;
+///    assert_eq!(res, Ok(()));
+///    assert!(time.elapsed >= 0.01);
+///    assert!(Robj::from(time).inherits("proc_time"));
+///
+///    // The result of a fallible closure is kept, errors included.
+///    let (res, _) = r_system_time(|| eval_string("stop('boom')"))?;
+///    assert!(res.is_err());
+/// }
+/// ```
+pub fn r_system_time<F, R>(f: F) -> Result<(R, ProcTime)>
+where
+    F: FnOnce() -> R,
+{
+    let before = ProcTime::now()?;
+    let res = f();
+    Ok((res, ProcTime::now()? - before))
+}
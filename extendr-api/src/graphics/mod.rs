@@ -85,10 +85,12 @@ pub use libR_sys::{DevDesc, R_GE_gcontext};
 pub mod color;
 pub mod device_descriptor;
 pub mod device_driver;
+mod recorded_plot;
 
 use color::Color;
 pub use device_descriptor::*;
 pub use device_driver::*;
+pub use recorded_plot::RecordedPlot;
 
 pub struct Context {
     context: R_GE_gcontext,
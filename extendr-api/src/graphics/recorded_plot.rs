@@ -0,0 +1,107 @@
+use super::*;
+
+/// A snapshot of the display list of a device, the same object as made by
+/// `recordPlot()` in R.
+///
+/// A recorded plot can be stored, passed to R or replayed on any device,
+/// so plots can be cached or re-rendered at another size.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::graphics::RecordedPlot;
+/// test! {
+///     R!("pdf(NULL); dev.control(displaylist = 'enable'); plot(1:10)")?;
+///     let plot = RecordedPlot::record()?;
+///     assert!(plot.as_robj().inherits("recordedplot"));
+///
+///     R!("plot.new()")?;
+///     plot.replay()?;
+///     R!("dev.off()")?;
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedPlot {
+    robj: Robj,
+}
+
+impl RecordedPlot {
+    /// Record the display list of the current device, like `recordPlot()`.
+    pub fn record() -> Result<RecordedPlot> {
+        if unsafe { Rf_NoDevices() } != 0 {
+            return Err(Error::NoGraphicsDevices(r!(())));
+        }
+        Device::current()?.record_plot()
+    }
+
+    /// Draw the plot on the current device, like `replayPlot()`.
+    ///
+    /// A device is opened if there is none.
+    pub fn replay(&self) -> Result<()> {
+        Device::current()?.replay_plot(self)
+    }
+
+    /// The plot as an R object of class `recordedplot`.
+    pub fn as_robj(&self) -> &Robj {
+        &self.robj
+    }
+}
+
+impl Device {
+    /// Record the display list of this device, like `recordPlot()`.
+    ///
+    /// The display list is only kept if it is enabled for the device,
+    /// for example with `dev.control(displaylist = "enable")`.
+    pub fn record_plot(&self) -> Result<RecordedPlot> {
+        let dd = self.inner();
+        let mut robj = single_threaded(|| unsafe {
+            catch_r_error(move || GEcreateSnapshot(dd)).map(|sexp| Robj::from_sexp(sexp))
+        })?;
+        // The attributes set by `recordPlot()`, used by `replayPlot()`.
+        robj.set_attrib(Symbol::from_string("pid"), std::process::id() as i32)?;
+        robj.set_attrib(
+            Symbol::from_string("Rversion"),
+            eval_string("getRversion()")?,
+        )?;
+        robj.set_class(["recordedplot"])?;
+        Ok(RecordedPlot { robj })
+    }
+
+    /// Draw a recorded plot on this device, replacing the current page.
+    pub fn replay_plot(&self, plot: &RecordedPlot) -> Result<()> {
+        let dd = self.inner();
+        let snapshot = plot.robj.get();
+        single_threaded(|| unsafe {
+            catch_r_error(move || {
+                GEplaySnapshot(snapshot, dd);
+                R_NilValue
+            })
+        })?;
+        Ok(())
+    }
+}
+
+impl From<RecordedPlot> for Robj {
+    fn from(plot: RecordedPlot) -> Self {
+        plot.robj
+    }
+}
+
+impl TryFrom<&Robj> for RecordedPlot {
+    type Error = Error;
+
+    /// Use a plot recorded in R with `recordPlot()`.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if robj.inherits("recordedplot") {
+            Ok(RecordedPlot { robj: robj.clone() })
+        } else {
+            Err(Error::Other("expected a recordedplot".to_string()))
+        }
+    }
+}
+
+impl TryFrom<Robj> for RecordedPlot {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        RecordedPlot::try_from(&robj)
+    }
+}
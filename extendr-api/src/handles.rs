@@ -0,0 +1,292 @@
+//! Persistent handles for keeping R objects alive across calls.
+//!
+//! [keep_alive] stores an object in a registry environment and returns a
+//! lightweight [HandleId] that can be returned to R and used later to get
+//! the object back. Objects stay alive until they are [release]d, or until
+//! the package is unloaded (see `R_unload_<pkg>_extendr` generated by
+//! [extendr_module!](crate::extendr_module)).
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::handles::{keep_alive, get_handle, release};
+//! test! {
+//!     let id = keep_alive(r!([1, 2, 3]));
+//!     assert_eq!(get_handle(id)?, r!([1, 2, 3]));
+//!     assert!(release(id));
+//!     assert!(get_handle(id).is_err());
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+use libR_sys::{R_ExternalPtrAddr, R_PreserveObject, SEXP, SEXPREC};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicI32, AtomicPtr, Ordering};
+
+// The registry environment. It is bound in the package namespace, or
+// preserved when there is no package, so R keeps it alive and this is only
+// a cache.
+static REGISTRY: AtomicPtr<SEXPREC> = AtomicPtr::new(std::ptr::null_mut());
+static NEXT_ID: AtomicI32 = AtomicI32::new(1);
+
+// The name of the registry in the package namespace.
+const REGISTRY_NAME: &str = ".__extendr_handles__";
+
+extern "C" {
+    fn R_removeVarFromFrame(name: SEXP, env: SEXP);
+}
+
+/// An identifier for an object in the handle registry.
+///
+/// Converts to and from an R integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HandleId(pub i32);
+
+impl HandleId {
+    fn key(&self) -> Symbol {
+        Symbol::from_string(self.0.to_string())
+    }
+}
+
+impl From<HandleId> for Robj {
+    fn from(id: HandleId) -> Self {
+        r!(id.0)
+    }
+}
+
+impl TryFrom<&Robj> for HandleId {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        Ok(HandleId(robj.try_into()?))
+    }
+}
+
+impl TryFrom<Robj> for HandleId {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+/// Keep the registry in the namespace of the package that loads `info`, as
+/// `.__extendr_handles__`.
+///
+/// This is called by the init function generated by [extendr_module!](crate::extendr_module),
+/// while the namespace is loading. The package is the one named like the
+/// library, as R names the init function after it, so the name of the module
+/// does not matter. If there is no such namespace, as when R is embedded,
+/// the registry is made on first use and preserved for the session instead.
+///
+/// # Safety
+///
+/// `info` must be the `DllInfo` that R passed to the init function.
+pub unsafe fn init_registry(info: *mut DllInfo) {
+    let namespace = match dll_name(info).and_then(|name| registered_namespace(&name)) {
+        Some(namespace) => namespace,
+        None => return,
+    };
+    let registry = match namespace.local(Symbol::from_string(REGISTRY_NAME)) {
+        Ok(registry) if registry.is_environment() => registry,
+        _ => {
+            let registry = new_env(empty_env(), true, 32);
+            if namespace.set(REGISTRY_NAME, registry.clone()).is_err() {
+                return;
+            }
+            registry.into()
+        }
+    };
+    REGISTRY.store(registry.get(), Ordering::Release);
+}
+
+// The name of the library loaded with `info`, from `getLoadedDLLs()`.
+// R adds the library to the list before it calls the init function.
+fn dll_name(info: *mut DllInfo) -> Option<String> {
+    let dlls = List::try_from(call!("getLoadedDLLs").ok()?).ok()?;
+    let is_info = |dll: &List| {
+        dll.iter().any(|(name, value)| {
+            name == "info"
+                && value.rtype() == Rtype::ExternalPtr
+                && unsafe { R_ExternalPtrAddr(value.get()) } == info as *mut std::ffi::c_void
+        })
+    };
+    dlls.values()
+        .filter_map(|dll| List::try_from(dll).ok())
+        .find(|dll| is_info(dll))
+        .and_then(|dll| {
+            dll.iter()
+                .find(|(name, _)| *name == "name")
+                .and_then(|(_, value)| value.as_str().map(str::to_string))
+        })
+}
+
+// The namespace called `name` if it is in the namespace registry, without loading it.
+fn registered_namespace(name: &str) -> Option<Environment> {
+    let namespace = namespace_registry().local(Symbol::from_string(name)).ok()?;
+    namespace.try_into().ok()
+}
+
+/// The environment that holds the registered objects.
+///
+/// The names in the environment are the handle ids.
+pub fn handle_registry() -> Environment {
+    single_threaded(|| unsafe {
+        let mut registry = REGISTRY.load(Ordering::Acquire);
+        if registry.is_null() {
+            registry = new_env(empty_env(), true, 32).get();
+            R_PreserveObject(registry);
+            REGISTRY.store(registry, Ordering::Release);
+        }
+        Robj::from_sexp(registry).try_into().unwrap()
+    })
+}
+
+/// Store `robj` in the registry and return its id.
+///
+/// Ids are reused after they wrap around, skipping those still in use.
+pub fn keep_alive<T: Into<Robj>>(robj: T) -> HandleId {
+    let registry = handle_registry();
+    let id = (0..i32::MAX)
+        .map(|_| {
+            let id = NEXT_ID.load(Ordering::Relaxed);
+            NEXT_ID.store(id.checked_add(1).unwrap_or(1), Ordering::Relaxed);
+            HandleId(id)
+        })
+        .find(|id| !registry.exists_local(id.key()))
+        .expect("all handle ids are in use");
+    registry.set_local(id.key(), robj.into());
+    id
+}
+
+/// Get an object from the registry.
+pub fn get_handle(id: HandleId) -> Result<Robj> {
+    match handle_registry().local(id.key()) {
+        Ok(robj) if !robj.is_unbound_value() => Ok(robj),
+        _ => Err(Error::Other(format!("Handle {} not found", id.0))),
+    }
+}
+
+/// Remove an object from the registry.
+/// Returns `false` if there was no such handle.
+pub fn release(id: HandleId) -> bool {
+    let registry = handle_registry();
+    let key = id.key();
+    if !registry.exists_local(key.clone()) {
+        return false;
+    }
+    single_threaded(|| unsafe { R_removeVarFromFrame(key.get(), registry.get()) });
+    true
+}
+
+/// Remove all objects from the registry.
+///
+/// This is called when a package built with [extendr_module!](crate::extendr_module) is unloaded.
+pub fn release_all() {
+    let registry = handle_registry();
+    let keys: Vec<Symbol> = registry.names().map(Symbol::from_string).collect();
+    single_threaded(|| {
+        for key in keys {
+            unsafe { R_removeVarFromFrame(key.get(), registry.get()) };
+        }
+    });
+}
+
+/// A typed handle to an object in the registry.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::handles::Handle;
+/// test! {
+///     let handle = Handle::new(Doubles::from_values([1.0, 2.0]));
+///     let id = handle.id();
+///     let handle: Handle<Doubles> = Handle::from_id(id);
+///     assert_eq!(handle.get()?.len(), 2);
+///     handle.release();
+/// }
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Handle<T> {
+    id: HandleId,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T>
+where
+    T: Into<Robj> + TryFrom<Robj>,
+    <T as TryFrom<Robj>>::Error: Into<Error>,
+{
+    /// Store `value` in the registry.
+    pub fn new(value: T) -> Self {
+        Self::from_id(keep_alive(value))
+    }
+
+    /// Make a handle from an id, for example one passed back from R.
+    pub fn from_id(id: HandleId) -> Self {
+        Self {
+            id,
+            marker: PhantomData,
+        }
+    }
+
+    /// The id of this handle.
+    pub fn id(&self) -> HandleId {
+        self.id
+    }
+
+    /// Get the object from the registry.
+    pub fn get(&self) -> Result<T> {
+        T::try_from(get_handle(self.id)?).map_err(|e| e.into())
+    }
+
+    /// Remove the object from the registry.
+    pub fn release(self) -> bool {
+        release(self.id)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> From<Handle<T>> for Robj {
+    fn from(handle: Handle<T>) -> Self {
+        handle.id.into()
+    }
+}
+
+/// A package-lifetime R object that is made on first use, see [lazy_r_static!](crate::lazy_r_static).
+///
+/// The object is kept in the handle registry, so it is protected once and
+/// released when the package is unloaded. It is made again if it is used
+/// after the registry has been cleared.
+#[derive(Debug)]
+pub struct LazyRStatic {
+    init: fn() -> Result<Robj>,
+    id: AtomicI32,
+}
+
+impl LazyRStatic {
+    /// Make a static that calls `init` when first used.
+    pub const fn new(init: fn() -> Result<Robj>) -> Self {
+        Self {
+            init,
+            id: AtomicI32::new(0),
+        }
+    }
+
+    /// Get the object, making it if needed.
+    pub fn get(&self) -> Result<Robj> {
+        let id = self.id.load(Ordering::Acquire);
+        if id != 0 {
+            if let Ok(robj) = get_handle(HandleId(id)) {
+                return Ok(robj);
+            }
+        }
+        let robj = (self.init)()?;
+        self.id.store(keep_alive(robj.clone()).0, Ordering::Release);
+        Ok(robj)
+    }
+}
@@ -0,0 +1,319 @@
+//! Run a rust service in the background and call it from R over a socket.
+//!
+//! [serve] starts a server thread on a TCP or unix socket address. Each request
+//! is decoded into a rust type, passed to the handler and the reply is sent
+//! back, all without touching R, so the service can keep state, such as a
+//! database connection or a loaded model, between calls.
+//!
+//! [Client] connects to a server from R and sends R objects, which are
+//! converted with [msgpack](crate::msgpack), so a request is anything that
+//! serde can read from an R object and a reply anything that can be serialized.
+//!
+//! Addresses are `tcp://host:port` or `unix:///path/to/socket`. Use port
+//! `0` to pick a free port and [Server::address] to find it.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::ipc::{serve, Client};
+//! test! {
+//!     let mut total = 0.0;
+//!     let server = serve("tcp://127.0.0.1:0", move |values: Vec<f64>| {
+//!         total += values.iter().sum::<f64>();
+//!         total
+//!     })?;
+//!
+//!     let mut client = Client::connect(server.address())?;
+//!     assert_eq!(client.call(&r!([1.0, 2.0]))?, r!(3.0));
+//!     assert_eq!(client.call(&r!([4.0, 5.0]))?, r!(12.0));
+//!     assert!(client.call(&r!("x")).is_err());
+//!     server.stop();
+//! }
+//! ```
+//!
+//! To use a server from R, export functions that start it and send requests:
+//!
+//! ```ignore
+//! #[extendr]
+//! fn start_engine(address: &str) -> Result<ExternalPtr<Server>> {
+//!     let server = serve(address, |request: Query| engine().run(request))?;
+//!     Ok(ExternalPtr::new(server))
+//! }
+//!
+//! #[extendr]
+//! fn engine_request(address: &str, request: Robj) -> Result<Robj> {
+//!     ipc::request(address, &request)
+//! }
+//! ```
+//!
+//! The server stops when R frees the external pointer.
+use crate::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+enum Address<'a> {
+    Tcp(&'a str),
+    #[cfg(unix)]
+    Unix(&'a str),
+}
+
+fn parse_address(address: &str) -> Result<Address> {
+    if let Some(addr) = address.strip_prefix("tcp://") {
+        return Ok(Address::Tcp(addr));
+    }
+    #[cfg(unix)]
+    if let Some(path) = address.strip_prefix("unix://") {
+        return Ok(Address::Unix(path));
+    }
+    Err(Error::Other(format!("unsupported address '{}'", address)))
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Other(e.to_string())
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn connect(address: &str) -> Result<Stream> {
+        match parse_address(address)? {
+            Address::Tcp(addr) => Ok(Stream::Tcp(TcpStream::connect(addr).map_err(io_error)?)),
+            #[cfg(unix)]
+            Address::Unix(path) => Ok(Stream::Unix(UnixStream::connect(path).map_err(io_error)?)),
+        }
+    }
+
+    fn inner(&mut self) -> &mut dyn ReadWrite {
+        match self {
+            Stream::Tcp(s) => s,
+            #[cfg(unix)]
+            Stream::Unix(s) => s,
+        }
+    }
+
+    // Messages are a four byte big endian length followed by MessagePack.
+    fn read_message(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        let mut len = [0; 4];
+        match self.inner().read_exact(&mut len) {
+            Ok(()) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let mut message = vec![0; u32::from_be_bytes(len) as usize];
+        self.inner().read_exact(&mut message)?;
+        Ok(Some(message))
+    }
+
+    fn write_message(&mut self, message: &[u8]) -> std::io::Result<()> {
+        let len = u32::try_from(message.len()).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "message too long")
+        })?;
+        let stream = self.inner();
+        stream.write_all(&len.to_be_bytes())?;
+        stream.write_all(message)?;
+        stream.flush()
+    }
+}
+
+trait ReadWrite: Read + Write {}
+
+impl<T: Read + Write> ReadWrite for T {}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, String),
+}
+
+impl Listener {
+    fn accept(&self) -> std::io::Result<Stream> {
+        match self {
+            Listener::Tcp(l) => l.accept().map(|(s, _)| Stream::Tcp(s)),
+            #[cfg(unix)]
+            Listener::Unix(l, _) => l.accept().map(|(s, _)| Stream::Unix(s)),
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// The reply to a request, sent as a map with `ok` and `error` entries.
+#[derive(Serialize)]
+struct Reply<T> {
+    ok: Option<T>,
+    error: Option<String>,
+}
+
+/// A running server made by [serve].
+///
+/// The server stops when this is dropped.
+#[derive(Debug)]
+pub struct Server {
+    address: String,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    /// The address of the server, with the actual port if port `0` was used.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Stop accepting connections. Open connections are served until they close.
+    pub fn stop(self) {}
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        // Wake the thread waiting for a connection.
+        let _ = Stream::connect(&self.address);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Start a server at `address` that calls `handler` for each request.
+///
+/// Connections are served on their own threads, and calls to the handler are
+/// made one at a time. A request that can't be decoded as `Req` gets an error reply.
+pub fn serve<Req, Resp, H>(address: &str, handler: H) -> Result<Server>
+where
+    Req: DeserializeOwned + 'static,
+    Resp: Serialize + 'static,
+    H: FnMut(Req) -> Resp + Send + 'static,
+{
+    let (listener, address) = match parse_address(address)? {
+        Address::Tcp(addr) => {
+            let listener = TcpListener::bind(addr).map_err(io_error)?;
+            let local = listener.local_addr().map_err(io_error)?;
+            (Listener::Tcp(listener), format!("tcp://{}", local))
+        }
+        #[cfg(unix)]
+        Address::Unix(path) => {
+            let listener = UnixListener::bind(path).map_err(io_error)?;
+            (
+                Listener::Unix(listener, path.to_string()),
+                address.to_string(),
+            )
+        }
+    };
+
+    let stopping = Arc::new(AtomicBool::new(false));
+    let handler = Arc::new(Mutex::new(handler));
+    let thread = {
+        let stopping = stopping.clone();
+        std::thread::spawn(move || loop {
+            let stream = listener.accept();
+            if stopping.load(Ordering::SeqCst) {
+                break;
+            }
+            if let Ok(stream) = stream {
+                let handler = handler.clone();
+                std::thread::spawn(move || serve_connection(stream, &handler));
+            }
+        })
+    };
+
+    Ok(Server {
+        address,
+        stopping,
+        thread: Some(thread),
+    })
+}
+
+fn serve_connection<Req, Resp, H>(mut stream: Stream, handler: &Mutex<H>)
+where
+    Req: DeserializeOwned,
+    Resp: Serialize,
+    H: FnMut(Req) -> Resp,
+{
+    while let Ok(Some(message)) = stream.read_message() {
+        let reply = match rmp_serde::from_slice::<Req>(&message) {
+            Ok(request) => {
+                let mut handler = match handler.lock() {
+                    Ok(handler) => handler,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                Reply {
+                    ok: Some(handler(request)),
+                    error: None,
+                }
+            }
+            Err(e) => Reply {
+                ok: None,
+                error: Some(format!("bad request: {}", e)),
+            },
+        };
+        let reply = rmp_serde::to_vec_named(&reply).unwrap_or_else(|e| {
+            let reply: Reply<()> = Reply {
+                ok: None,
+                error: Some(format!("bad reply: {}", e)),
+            };
+            rmp_serde::to_vec_named(&reply).unwrap()
+        });
+        if stream.write_message(&reply).is_err() {
+            break;
+        }
+    }
+}
+
+/// Send one request to the server at `address` and wait for the reply.
+///
+/// Use a [Client] to send several requests over one connection.
+pub fn request(address: &str, request: &Robj) -> Result<Robj> {
+    Client::connect(address)?.call(request)
+}
+
+/// A connection to a server started with [serve].
+pub struct Client {
+    stream: Stream,
+}
+
+impl Client {
+    /// Connect to the server at `address`.
+    pub fn connect(address: &str) -> Result<Client> {
+        Ok(Client {
+            stream: Stream::connect(address)?,
+        })
+    }
+
+    /// Send `request` to the server and wait for the reply.
+    ///
+    /// An error reported by the server, such as a request of the wrong type, is returned as an error.
+    pub fn call(&mut self, request: &Robj) -> Result<Robj> {
+        let message = msgpack::to_msgpack(request)?;
+        self.stream.write_message(&message).map_err(io_error)?;
+        let reply = self
+            .stream
+            .read_message()
+            .map_err(io_error)?
+            .ok_or_else(|| Error::Other("the server closed the connection".to_string()))?;
+        let mut reply = List::try_from(msgpack::from_msgpack(&reply)?)?.into_hashmap();
+        match reply.remove("error") {
+            Some(error) if !error.is_null() => {
+                let error = error.as_str().unwrap_or("unknown error");
+                Err(Error::Other(error.to_string()))
+            }
+            _ => Ok(reply.remove("ok").unwrap_or_else(|| ().into())),
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Variable and value labels compatible with the haven and labelled packages.
+//!
+//! Statistical files from SPSS, Stata and SAS carry metadata that haven
+//! keeps in attributes:
+//!
+//! * `"label"`, a variable label, which any vector can have.
+//! * `"labels"`, a named vector of value labels, on vectors of class
+//!   `haven_labelled`. The values have the type of the vector and the
+//!   names are the labels.
+//! * `"na_values"` and `"na_range"`, values that SPSS treats as missing,
+//!   on vectors of class `haven_labelled_spss`.
+//!
+//! These functions build the same objects as `haven::labelled()` and
+//! `haven::labelled_spss()` without calling into haven.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::labelled::{new_labelled, value_labels, var_label};
+//! test! {
+//!     let labels = r!([1.0, 2.0]).set_names(["No", "Yes"])?;
+//!     let x = new_labelled(r!([1.0, 2.0, 1.0]), labels.clone(), Some("Smoker"))?;
+//!     assert!(x.inherits("haven_labelled"));
+//!     assert_eq!(var_label(&x).as_deref(), Some("Smoker"));
+//!     assert_eq!(value_labels(&x), Some(labels));
+//! }
+//! ```
+use crate::*;
+
+/// The variable label of `x`, from its `"label"` attribute.
+pub fn var_label(x: &Robj) -> Option<String> {
+    let label = x.get_attrib(label_symbol())?;
+    label.as_str().map(String::from)
+}
+
+/// Set the variable label of `x`, or remove it if `label` is `None`.
+pub fn set_var_label(x: &mut Robj, label: Option<&str>) -> Result<()> {
+    match label {
+        Some(label) => x.set_attrib(label_symbol(), label)?,
+        None => x.set_attrib(label_symbol(), ())?,
+    };
+    Ok(())
+}
+
+/// The value labels of `x`, a named vector, from its `"labels"` attribute.
+pub fn value_labels(x: &Robj) -> Option<Robj> {
+    x.get_attrib(labels_symbol())
+}
+
+/// The label of `value` in `x`, if it has one.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::labelled::{new_labelled, value_label};
+/// test! {
+///     let labels = r!([1, 2]).set_names(["No", "Yes"])?;
+///     let x = new_labelled(r!([1, 2, 3]), labels, None)?;
+///     assert_eq!(value_label(&x, &r!(2)).as_deref(), Some("Yes"));
+///     assert_eq!(value_label(&x, &r!(3)), None);
+/// }
+/// ```
+pub fn value_label(x: &Robj, value: &Robj) -> Option<String> {
+    let labels = value_labels(x)?;
+    let names: Vec<&str> = labels.names()?.collect();
+    (0..labels.len())
+        .position(|i| same_value(&labels, i, value, 0))
+        .map(|i| names[i].to_string())
+}
+
+/// Make a labelled vector, like `haven::labelled(x, labels, label)`.
+///
+/// `x` must be a double, integer or character vector and `labels` a named
+/// vector of the same type with a label for each value.
+pub fn new_labelled(x: Robj, labels: Robj, label: Option<&str>) -> Result<Robj> {
+    let type_class = type_class(&x)?;
+    validate_labels(&x, &labels)?;
+    let mut res = x;
+    res.set_attrib(labels_symbol(), labels)?;
+    set_var_label(&mut res, label)?;
+    res.set_class(["haven_labelled", "vctrs_vctr", type_class])?;
+    Ok(res)
+}
+
+/// Make a labelled vector with SPSS user-defined missing values, like
+/// `haven::labelled_spss(x, labels, na_values, na_range, label)`.
+///
+/// `na_values` must have the type of `x`. `na_range` is an inclusive range
+/// of missing values, for numeric vectors only.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::labelled::{is_missing, new_labelled_spss, zap_labels};
+/// test! {
+///     let labels = r!([1.0, 99.0]).set_names(["Yes", "Refused"])?;
+///     let x = r!([1.0, 99.0, 2.0, -1.0]);
+///     let x = new_labelled_spss(x, labels, Some(r!(99.0)), Some((-9.0, -1.0)), None)?;
+///     assert!(x.inherits("haven_labelled_spss"));
+///     assert_eq!(is_missing(&x)?, vec![false, true, false, true]);
+///     assert_eq!(zap_labels(&x)?, r!([Some(1.0), None, Some(2.0), None]));
+/// }
+/// ```
+pub fn new_labelled_spss(
+    x: Robj,
+    labels: Robj,
+    na_values: Option<Robj>,
+    na_range: Option<(f64, f64)>,
+    label: Option<&str>,
+) -> Result<Robj> {
+    let mut res = new_labelled(x, labels, label)?;
+    if let Some(na_values) = na_values {
+        if na_values.rtype() != res.rtype() {
+            return Err(Error::Other(
+                "na_values must have the same type as the vector".to_string(),
+            ));
+        }
+        res.set_attrib(na_values_symbol(), na_values)?;
+    }
+    if let Some((low, high)) = na_range {
+        if res.is_string() {
+            return Err(Error::Other(
+                "na_range is only for numeric vectors".to_string(),
+            ));
+        }
+        if low.is_nan() || high.is_nan() || low > high {
+            return Err(Error::Other(
+                "na_range must be a range from low to high".to_string(),
+            ));
+        }
+        res.set_attrib(na_range_symbol(), [low, high])?;
+    }
+    let type_class = type_class(&res)?;
+    res.set_class([
+        "haven_labelled_spss",
+        "haven_labelled",
+        "vctrs_vctr",
+        type_class,
+    ])?;
+    Ok(res)
+}
+
+/// The SPSS user-defined missing values of `x`, from its `"na_values"` attribute.
+pub fn na_values(x: &Robj) -> Option<Robj> {
+    x.get_attrib(na_values_symbol())
+}
+
+/// The SPSS range of missing values of `x`, from its `"na_range"` attribute.
+pub fn na_range(x: &Robj) -> Option<(f64, f64)> {
+    let range = x.get_attrib(na_range_symbol())?;
+    let range: Vec<f64> = range
+        .as_real_slice()
+        .map(|range| range.to_vec())
+        .or_else(|| {
+            range
+                .as_integer_slice()
+                .map(|range| range.iter().map(|&x| x as f64).collect())
+        })?;
+    match range[..] {
+        [low, high] => Some((low, high)),
+        _ => None,
+    }
+}
+
+/// For each element of `x`, true if it is `NA` or an SPSS user-defined
+/// missing value, like `is.na()` on a `haven_labelled_spss` vector.
+pub fn is_missing(x: &Robj) -> Result<Vec<bool>> {
+    type_class(x)?;
+    let na_values = na_values(x);
+    let na_range = na_range(x);
+    let numbers = numeric_values(x);
+    Ok((0..x.len())
+        .map(|i| {
+            if element_is_na(x, i) {
+                return true;
+            }
+            if let Some(na_values) = &na_values {
+                if (0..na_values.len()).any(|j| same_value(na_values, j, x, i)) {
+                    return true;
+                }
+            }
+            match (&numbers, na_range) {
+                (Some(numbers), Some((low, high))) => low <= numbers[i] && numbers[i] <= high,
+                _ => false,
+            }
+        })
+        .collect())
+}
+
+/// Remove the value labels of `x`, like `haven::zap_labels()`.
+///
+/// SPSS user-defined missing values become `NA`. The variable label is kept.
+/// Vectors that are not labelled are returned unchanged.
+pub fn zap_labels(x: &Robj) -> Result<Robj> {
+    if !x.inherits("haven_labelled") {
+        return Ok(x.clone());
+    }
+    let missing = if x.inherits("haven_labelled_spss") {
+        Some(is_missing(x)?)
+    } else {
+        None
+    };
+    let mut res = x.duplicate();
+    if let Some(missing) = missing {
+        match res.rtype() {
+            Rtype::Doubles => {
+                for (value, missing) in res.as_real_slice_mut().unwrap().iter_mut().zip(missing) {
+                    if missing {
+                        *value = f64::na();
+                    }
+                }
+            }
+            Rtype::Integers => {
+                for (value, missing) in res.as_integer_slice_mut().unwrap().iter_mut().zip(missing)
+                {
+                    if missing {
+                        *value = i32::na();
+                    }
+                }
+            }
+            _ => {
+                let mut strings = Strings::try_from(res)?;
+                for (i, missing) in missing.into_iter().enumerate() {
+                    if missing {
+                        strings.set_elt(i, Rstr::na());
+                    }
+                }
+                res = strings.into();
+            }
+        }
+    }
+    for name in [labels_symbol(), na_values_symbol(), na_range_symbol()] {
+        res.set_attrib(name, ())?;
+    }
+    res.set_attrib(class_symbol(), ())?;
+    Ok(res)
+}
+
+// The type in the class of a labelled vector.
+fn type_class(x: &Robj) -> Result<&'static str> {
+    match x.rtype() {
+        Rtype::Doubles => Ok("double"),
+        Rtype::Integers => Ok("integer"),
+        Rtype::Strings => Ok("character"),
+        _ => Err(Error::Other(
+            "labelled vectors must be double, integer or character".to_string(),
+        )),
+    }
+}
+
+fn validate_labels(x: &Robj, labels: &Robj) -> Result<()> {
+    if labels.rtype() != x.rtype() {
+        return Err(Error::Other(
+            "labels must have the same type as the vector".to_string(),
+        ));
+    }
+    let names = labels
+        .names()
+        .ok_or_else(|| Error::Other("labels must be named".to_string()))?;
+    for name in names {
+        if name.is_empty() || name.is_na() {
+            return Err(Error::Other("labels must be named".to_string()));
+        }
+    }
+    for i in 0..labels.len() {
+        if (0..i).any(|j| same_value(labels, j, labels, i)) {
+            return Err(Error::Other("labels must be unique".to_string()));
+        }
+    }
+    Ok(())
+}
+
+// The elements of a numeric vector as doubles, with NaN for NA.
+fn numeric_values(x: &Robj) -> Option<Vec<f64>> {
+    if let Some(values) = x.as_real_slice() {
+        return Some(values.to_vec());
+    }
+    x.as_integer_slice().map(|values| {
+        values
+            .iter()
+            .map(|&x| if x.is_na() { f64::NAN } else { x as f64 })
+            .collect()
+    })
+}
+
+fn element_is_na(x: &Robj, i: usize) -> bool {
+    match x.rtype() {
+        Rtype::Doubles => x.as_real_slice().unwrap()[i].is_nan(),
+        Rtype::Integers => x.as_integer_slice().unwrap()[i].is_na(),
+        Rtype::Strings => x.as_str_iter().unwrap().nth(i).unwrap().is_na(),
+        _ => false,
+    }
+}
+
+// True if element `i` of `x` equals element `j` of `y`, where either may
+// be integer or double.
+fn same_value(x: &Robj, i: usize, y: &Robj, j: usize) -> bool {
+    match (numeric_values(x), numeric_values(y)) {
+        (Some(x), Some(y)) => x.get(i).is_some() && x.get(i) == y.get(j),
+        _ => match (x.as_str_iter(), y.as_str_iter()) {
+            (Some(mut x), Some(mut y)) => {
+                let x = x.nth(i);
+                x.is_some() && x == y.nth(j)
+            }
+            _ => false,
+        },
+    }
+}
+
+fn label_symbol() -> Symbol {
+    Symbol::from_string("label")
+}
+
+fn labels_symbol() -> Symbol {
+    Symbol::from_string("labels")
+}
+
+fn na_values_symbol() -> Symbol {
+    Symbol::from_string("na_values")
+}
+
+fn na_range_symbol() -> Symbol {
+    Symbol::from_string("na_range")
+}
@@ -253,6 +253,24 @@
 //! }
 //! ```
 //!
+//! ## Generic arguments
+//!
+//! Arguments of `#[extendr]` functions can be `impl Into<T>` or `impl AsRef<T>`,
+//! or a type parameter with one of these bounds. The wrapper converts the R
+//! object to `T` or `&T` respectively and passes that.
+//!
+//! ```ignore
+//! #[extendr]
+//! fn total(x: impl Into<Doubles>) -> f64 {
+//!     x.into().iter().map(|x| x.inner()).sum()
+//! }
+//!
+//! #[extendr]
+//! fn shout<S: AsRef<str>>(s: S) -> String {
+//!     s.as_ref().to_uppercase()
+//! }
+//! ```
+//!
 //! ## Returning Result<T,E> to R
 //!
 //! Currently, `throw_r_error()` leaks memory because it jumps to R without properly dropping
@@ -319,14 +337,49 @@
 //!
 //! ```
 //!
+//! ## Imports
+//!
+//! `use extendr_api::prelude::*` brings in everything most code needs.
+//! Code that would rather name what it uses can import from the modules:
+//!
+//! - [robj]: [Robj] and its traits, like [Length](robj::Length) and [Types](robj::Types).
+//! - [wrapper]: typed wrappers like [List](wrapper::List) and [Function](wrapper::Function).
+//! - [scalar]: `Rint`, `Rfloat`, `Rbool`, `Rcplx` and the [Scalar](scalar::Scalar) trait.
+//! - [macros]: every macro, from [r!](macros::r) to [extendr](macros::extendr).
+//! - `engine`, with the `engine` feature: starting R in stand-alone programs.
+//!
+//! The glob re-exports at the crate root are kept for older code. They
+//! bring hundreds of names like `parse` and `symbol` into scope, so new code
+//! should use the prelude or the modules.
+//!
+//! ```
+//! use extendr_api::macros::{r, test};
+//! use extendr_api::robj::{Rinternals, Robj};
+//! use extendr_api::scalar::Rint;
+//! test! {
+//!     let x: Robj = r!(1);
+//!     assert!(x.is_integer());
+//!     assert_eq!(Rint::try_from(x)?, 1);
+//! }
+//! ```
+//!
 //! ## Feature gates
 //!
 //! extendr-api has some optional features behind these feature gates:
 //!
 //! - `ndarray`: provides the conversion between R's matrices and [ndarray](https://docs.rs/ndarray/latest/ndarray/).
+//! - `nalgebra`: provides the conversion between R's matrices and vectors and [nalgebra](https://docs.rs/nalgebra/latest/nalgebra/)'s `DMatrix<f64>` and `DVector<f64>`.
+//! - `chrono`: provides the conversion between R's `Date`, `POSIXct` and `difftime` vectors and [chrono](https://docs.rs/chrono/latest/chrono/)'s `NaiveDate`, `DateTime<Utc>` and `Duration`.
 //! - `num-complex`: provides the conversion between R's complex numbers and [num-complex](https://docs.rs/num-complex/latest/num_complex/).
-//! - `serde`: provides the [Serde](https://serde.rs/) support.
+//! - `serde`: provides the [Serde](https://serde.rs/) support, including `#[extendr(serialize)]`
+//!   for wrapped objects that survive `saveRDS()` (see the `persist` module).
 //! - `graphics`: provides the functionality to control or implement graphics devices.
+//! - `rayon`: lets [threads::ThreadLimit] run closures in a rayon pool sized from R's options, and adds [threads::par_chunks()], [parallel::run()] and [parallel::par_map()].
+//! - `engine`: re-exports [extendr-engine](https://docs.rs/extendr-engine) as `extendr_api::engine`, for running R from rust programs.
+//! - `strict-checks`: validates SEXP types and indices before unchecked element access and
+//!   bounds-checks ALTREP element callbacks. Violations panic (or raise an R error inside
+//!   ALTREP callbacks) instead of causing undefined behaviour. Use it together with the
+//!   ASAN/UBSAN builds of R-devel to verify the crate's safety claims.
 //!
 //! extendr-api supports three ways of returning a Result<T,E> to R. Only one behavior feature can be enabled at a time.
 //! - `result_panic`: Default behavior, return `Ok` as is, panic! on any `Err`
@@ -340,14 +393,41 @@
     html_logo_url = "https://raw.githubusercontent.com/extendr/extendr/master/extendr-logo-256.png"
 )]
 
+pub mod alloc;
+pub mod ast;
+pub mod bench_utils;
+pub mod binning;
+pub mod cache;
+pub mod cast;
+pub mod coerce;
+pub mod conditions;
+pub mod datetime;
+pub mod detached;
+
+#[cfg(feature = "engine")]
+pub mod engine;
+
 pub mod error;
+pub mod evaluate;
+pub mod factor;
+pub mod fs;
 pub mod functions;
+pub mod handles;
 pub mod io;
 pub mod iter;
+pub mod labelled;
 pub mod lang_macros;
+pub mod macros;
 pub mod metadata;
+pub mod native;
+pub mod numeric;
+pub mod optimize;
+pub mod options;
 pub mod ownership;
+pub mod packages;
+pub mod parallel;
 pub mod prelude;
+pub mod returns;
 pub mod rmacros;
 
 #[cfg(feature = "serde")]
@@ -356,12 +436,45 @@ pub mod serializer;
 #[cfg(feature = "serde")]
 pub mod deserializer;
 
+#[cfg(feature = "serde")]
+pub mod persist;
+
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+#[cfg(feature = "cbor")]
+pub mod cbor;
+
+#[cfg(feature = "ipc")]
+pub mod ipc;
+
+#[cfg(feature = "text")]
+pub mod text;
+
+#[cfg(feature = "crypto")]
+pub mod crypto;
+
 #[cfg(feature = "graphics")]
 pub mod graphics;
 
+#[cfg(all(unix, feature = "shm"))]
+pub mod shm;
+
+#[cfg(all(unix, feature = "mmap"))]
+pub mod mmap;
+
 pub mod robj;
 pub mod scalar;
+pub mod storage;
+pub mod sys;
+pub mod table;
 pub mod thread_safety;
+pub mod threads;
+pub mod tidyeval;
+pub mod trace;
+pub mod tz;
+pub mod vctrs;
+pub mod weighted;
 pub mod wrapper;
 
 pub mod na;
@@ -385,9 +498,12 @@ pub use error::*;
 pub use functions::*;
 pub use lang_macros::*;
 pub use na::*;
+pub use returns::{Invisible, NoReturn, Null};
 pub use rmacros::*;
 pub use robj::*;
-pub use thread_safety::{catch_r_error, handle_panic, single_threaded, throw_r_error};
+pub use thread_safety::{
+    catch_r_error, handle_panic, single_threaded, throw_r_error, unwind_protect, RUnwind,
+};
 pub use wrapper::*;
 
 pub use extendr_macros::*;
@@ -420,6 +536,9 @@ pub const NA_LOGICAL: Rbool = Rbool::na_value();
 #[doc(hidden)]
 pub use std::collections::HashMap;
 
+#[doc(hidden)]
+pub use paste;
+
 #[doc(hidden)]
 pub use libR_sys::DllInfo;
 
@@ -492,6 +611,23 @@ pub unsafe fn register_call_methods(info: *mut libR_sys::DllInfo, metadata: Meta
     libR_sys::R_forceSymbols(info, 0);
 }
 
+// Internal function used to run the R code bundled with `extendr_module!`.
+// This is called when the library is loaded, after the namespace has been
+// created, from the code generated by the extendr_module! macro.
+#[doc(hidden)]
+pub fn eval_module_r_code(package: &str, r_code: &[&str]) -> Result<()> {
+    if r_code.is_empty() {
+        return Ok(());
+    }
+    let env = Namespace::load(package)?.as_environment();
+    for code in r_code {
+        for expr in parse(code)?.values() {
+            expr.eval_with_env(&env)?;
+        }
+    }
+    Ok(())
+}
+
 /// Type of R objects used by [Robj::rtype].
 #[derive(Debug, PartialEq)]
 pub enum Rtype {
@@ -0,0 +1,28 @@
+//! All the macros of extendr-api in one place.
+//!
+//! `use extendr_api::prelude::*` brings these into scope along with
+//! everything else. Code that would rather not glob-import, for example
+//! because it has its own `list` or `sym`, can name the macros it uses:
+//!
+//! ```
+//! use extendr_api::macros::{r, test};
+//! use extendr_api::robj::Length;
+//! use extendr_api::wrapper::List;
+//! test! {
+//!     let list = List::from_values([r!(1), r!("a")]);
+//!     assert_eq!(list.len(), 2);
+//! }
+//! ```
+//!
+//! Some macros, like `call!` and `R!`, expand to paths that start with
+//! `extendr_api`, so the crate must be a direct dependency.
+
+pub use crate::{
+    data_frame, extendr_bench_export, factor, global, lazy_r_static, message, r, reprint,
+    reprintln, rmatch, rprint, rprintln, sym, test, var, warning,
+};
+
+pub use extendr_macros::{
+    call, extendr, extendr_module, list, pairlist, FromDataframeRow, IntoDataFrameRow, IntoRobj,
+    Rraw, TryFromRobj, R,
+};
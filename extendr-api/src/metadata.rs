@@ -33,6 +33,10 @@ pub struct Impl {
     pub doc: &'static str,
     pub name: &'static str,
     pub methods: Vec<Func>,
+    /// True if objects are stored in an environment, see [crate::storage].
+    pub env_storage: bool,
+    /// True if the R wrapper is an R6 class, made with `#[extendr(r6)]`.
+    pub r6: bool,
 }
 
 /// Module metadata.
@@ -41,6 +45,9 @@ pub struct Metadata {
     pub name: &'static str,
     pub functions: Vec<Func>,
     pub impls: Vec<Impl>,
+    /// R code from `r_source` and `r_code` in `extendr_module!`, run in the
+    /// package namespace when the library is loaded.
+    pub r_code: Vec<&'static str>,
 }
 
 struct RArg {
@@ -54,7 +61,11 @@ impl RArg {
     }
 
     fn to_actual_arg(&self) -> String {
-        self.name.clone()
+        if self.name == "..." {
+            "list(...)".to_string()
+        } else {
+            self.name.clone()
+        }
     }
 
     fn to_formal_arg(&self) -> String {
@@ -67,6 +78,13 @@ impl RArg {
 
 impl From<&Arg> for RArg {
     fn from(arg: &Arg) -> Self {
+        // `Dots` arguments capture R's `...`.
+        if arg.arg_type == "Dots" {
+            return Self {
+                name: "...".to_string(),
+                default: None,
+            };
+        }
         Self {
             name: sanitize_identifier(arg.name),
             default: arg.default,
@@ -165,6 +183,190 @@ fn join_str(input: impl Iterator<Item = String>, sep: &str) -> String {
     input.collect::<Vec<String>>().join(sep)
 }
 
+/// Translate the sections of a rust doc comment to roxygen tags.
+///
+/// The items of an `# Arguments` list, like ``* `x` - The value.``, become
+/// `@param` tags, the text of a `# Returns` section becomes `@return`, and
+/// other headings become `@section`s. `# Examples` sections are dropped,
+/// as they are rust code. Roxygen tags in the comment are kept as they are
+/// and end the section they are in.
+fn roxygen_from_rust(doc: &str) -> String {
+    enum Section {
+        Text,
+        Arguments,
+        Returns { started: bool },
+        Skipped,
+    }
+    let mut section = Section::Text;
+    let mut in_code = false;
+    let mut lines: Vec<String> = Vec::new();
+    for line in doc.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+        }
+        if !in_code {
+            if let Some(heading) = trimmed.strip_prefix("# ") {
+                let heading = heading.trim();
+                section = match heading.to_lowercase().as_str() {
+                    "arguments" | "parameters" => Section::Arguments,
+                    "returns" | "return value" => Section::Returns { started: false },
+                    "examples" | "example" => Section::Skipped,
+                    _ => {
+                        lines.push(format!(" @section {}:", heading));
+                        Section::Text
+                    }
+                };
+                continue;
+            }
+        }
+        if !in_code && trimmed.starts_with('@') {
+            section = Section::Text;
+        }
+        match &mut section {
+            Section::Text => lines.push(line.to_string()),
+            Section::Skipped => (),
+            Section::Arguments if !in_code => {
+                let item = trimmed
+                    .strip_prefix("* ")
+                    .or_else(|| trimmed.strip_prefix("- "));
+                match item.and_then(split_argument_item) {
+                    Some((name, text)) => lines.push(format!(" @param {} {}", name, text)),
+                    None if !trimmed.is_empty() => lines.push(line.to_string()),
+                    None => (),
+                }
+            }
+            Section::Returns { started } if !in_code && !*started => {
+                if !trimmed.is_empty() {
+                    *started = true;
+                    lines.push(format!(" @return {}", trimmed));
+                }
+            }
+            // Blank lines would end the tags of these sections.
+            _ if trimmed.is_empty() => (),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.join("\n")
+}
+
+/// Split an argument list item, like `` `x` - The value.`` or `x: The value.`,
+/// into the name and the description.
+fn split_argument_item(item: &str) -> Option<(&str, &str)> {
+    let (name, rest) = match item.strip_prefix('`') {
+        Some(quoted) => quoted.split_once('`')?,
+        None => item.split_at(item.find(|c: char| c == ':' || c.is_whitespace())?),
+    };
+    let rest = rest.trim_start();
+    let text = rest
+        .strip_prefix('-')
+        .or_else(|| rest.strip_prefix(':'))
+        .unwrap_or(rest)
+        .trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some((name, text))
+    }
+}
+
+/// The `@return` tag for a function that doesn't document its value.
+fn roxygen_return(func: &Func) -> Option<String> {
+    match func.return_type {
+        "!" | "NoReturn" => None,
+        "()" => Some(" @return `NULL`, invisibly.".to_string()),
+        "Null" => Some(" @return `NULL`.".to_string()),
+        "Invisible" => Some(" @return The value, invisibly.".to_string()),
+        return_type => Some(format!(" @return `{}`", return_type)),
+    }
+}
+
+/// Complete the doc comment of an exported function with the parts roxygen
+/// needs: a title, if there is only tags, a `@param` for each argument
+/// that doesn't have one, giving its rust type, and a `@return`.
+fn roxygen_skeleton(func: &Func) -> String {
+    let doc = roxygen_from_rust(func.doc);
+    let mut lines: Vec<String> = doc.lines().map(String::from).collect();
+    let has_title = lines
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .map_or(false, |line| !line.starts_with('@'));
+    if !has_title {
+        lines.insert(0, format!(" {}", func.r_name));
+    }
+    let documented: Vec<&str> = doc
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("@param "))
+        .flat_map(|rest| rest.split_whitespace().next())
+        .flat_map(|names| names.split(','))
+        .collect();
+    let export = lines.iter().position(|line| line.trim() == "@export");
+    let mut params: Vec<String> = func
+        .args
+        .iter()
+        .map(|arg| (RArg::from(arg).name, arg.arg_type))
+        .filter(|(name, _)| !documented.contains(&name.as_str()))
+        .map(|(name, arg_type)| format!(" @param {} `{}`", name, arg_type))
+        .collect();
+    if !lines.iter().any(|line| line.trim().starts_with("@return")) {
+        params.extend(roxygen_return(func));
+    }
+    // The tags go before `@export`, which is usually last.
+    let at = export.unwrap_or(lines.len());
+    lines.splice(at..at, params);
+    lines.join("\n")
+}
+
+/// The doc comment of an implementation block, with a `Methods` section
+/// describing the methods that have doc comments.
+fn impl_roxygen(imp: &Impl) -> String {
+    let doc = roxygen_from_rust(imp.doc);
+    if doc.trim().is_empty() {
+        return doc;
+    }
+    let items: Vec<String> = imp
+        .methods
+        .iter()
+        .filter(|func| !func.hidden && !func.doc.trim().is_empty())
+        .map(|func| {
+            let formals = func
+                .args
+                .iter()
+                .map(RArg::from)
+                .skip_while(|a| a.is_self())
+                .map(|a| a.to_formal_arg());
+            // The description, without the tags.
+            let description = roxygen_from_rust(func.doc)
+                .lines()
+                .map(|line| line.trim())
+                .take_while(|line| !line.starts_with('@'))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                " \\item{{\\code{{{}({})}}}}{{{}}}",
+                sanitize_identifier(func.r_name),
+                join_str(formals, ", "),
+                description
+            )
+        })
+        .collect();
+    if items.is_empty() {
+        return doc;
+    }
+    let mut lines: Vec<String> = doc.lines().map(String::from).collect();
+    let mut section = vec![" @section Methods:".to_string(), " \\describe{".to_string()];
+    section.extend(items);
+    section.push(" }".to_string());
+    let at = lines
+        .iter()
+        .position(|line| line.trim() == "@export")
+        .unwrap_or(lines.len());
+    lines.splice(at..at, section);
+    lines.join("\n")
+}
+
 /// Generate a wrapper for a non-method function.
 fn write_function_wrapper(
     w: &mut Vec<u8>,
@@ -176,13 +378,17 @@ fn write_function_wrapper(
         return Ok(());
     }
 
-    write_doc(w, func.doc)?;
+    if is_exported(func.doc) {
+        write_doc(w, &roxygen_skeleton(func))?;
+    } else {
+        write_doc(w, &roxygen_from_rust(func.doc))?;
+    }
 
     let r_args: Vec<RArg> = func.args.iter().map(Into::into).collect();
     let actual_args = r_args.iter().map(|a| a.to_actual_arg());
     let formal_args = r_args.iter().map(|a| a.to_formal_arg());
 
-    if func.return_type == "()" {
+    if crate::returns::is_invisible_return(func.return_type) {
         write!(
             w,
             "{} <- function({}) invisible(.Call(",
@@ -212,7 +418,7 @@ fn write_function_wrapper(
         write!(w, ", PACKAGE = \"{}\"", package_name)?;
     }
 
-    if func.return_type == "()" {
+    if crate::returns::is_invisible_return(func.return_type) {
         writeln!(w, "))\n")?;
     } else {
         writeln!(w, ")\n")?;
@@ -245,7 +451,7 @@ fn write_method_wrapper(
 
     // Both `class_name` and `func.name` should be processed
     // because they are exposed to R
-    if func.return_type == "()" {
+    if crate::returns::is_invisible_return(func.return_type) {
         write!(
             w,
             "{}${} <- function({}) invisible(.Call(",
@@ -278,7 +484,7 @@ fn write_method_wrapper(
         write!(w, ", PACKAGE = \"{}\"", package_name)?;
     }
 
-    if func.return_type == "()" {
+    if crate::returns::is_invisible_return(func.return_type) {
         writeln!(w, "))\n")?;
     } else {
         writeln!(w, ")\n")?;
@@ -296,7 +502,7 @@ fn write_impl_wrapper(
 ) -> std::io::Result<()> {
     let exported = imp.doc.contains("@export");
 
-    write_doc(w, imp.doc)?;
+    write_doc(w, &impl_roxygen(imp))?;
 
     let imp_name_fixed = sanitize_identifier(imp.name);
 
@@ -304,7 +510,6 @@ fn write_impl_wrapper(
     writeln!(w, "{} <- new.env(parent = emptyenv())\n", imp_name_fixed)?;
 
     for func in &imp.methods {
-        // write_doc(& mut w, func.doc)?;
         // `imp.name` is passed as is and sanitized within the function
         write_method_wrapper(w, func, package_name, use_symbols, imp.name)?;
     }
@@ -323,11 +528,179 @@ fn write_impl_wrapper(
     // LHS with dollar operator is wrapped in ``, so pass name as is,
     // but in the body `imp_name_fixed` is called as valid R function,
     // so we pass preprocessed value
-    writeln!(w, "`$.{}` <- function (self, name) {{ func <- {}[[name]]; environment(func) <- environment(); func }}\n", imp.name, imp_name_fixed)?;
+    if imp.env_storage {
+        // Fields stored in the environment are read directly and take
+        // precedence over methods.
+        writeln!(w, "`$.{}` <- function (self, name) {{ if (exists(name, envir = self, inherits = FALSE)) return(get(name, envir = self, inherits = FALSE)); func <- {}[[name]]; environment(func) <- environment(); func }}\n", imp.name, imp_name_fixed)?;
+    } else {
+        writeln!(w, "`$.{}` <- function (self, name) {{ func <- {}[[name]]; environment(func) <- environment(); func }}\n", imp.name, imp_name_fixed)?;
+    }
 
     writeln!(w, "#' @export")?;
     writeln!(w, "`[[.{}` <- `$.{}`\n", imp.name, imp.name)?;
 
+    if imp.env_storage {
+        writeln!(w, "#' @export")?;
+        writeln!(w, "`$<-.{}` <- function (self, name, value) {{ if (!exists(name, envir = self, inherits = FALSE)) stop(\"no field '\", name, \"' in {}\"); assign(name, value, envir = self); self }}\n", imp.name, imp.name)?;
+
+        writeln!(w, "#' @export")?;
+        writeln!(w, "`[[<-.{}` <- `$<-.{}`\n", imp.name, imp.name)?;
+    }
+
+    Ok(())
+}
+
+/// Generate an R6 class for an implementation block.
+///
+/// The object holds the external pointer in `.ptr`. `new()` is the
+/// constructor, other functions without `self` are added to the class
+/// generator, a getter `x(&self)` with a setter `set_x(&mut self, value)`
+/// becomes the active binding `x`, and methods returning `()` return the
+/// object invisibly so that calls can be chained.
+fn write_r6_wrapper(
+    w: &mut Vec<u8>,
+    imp: &Impl,
+    package_name: &str,
+    use_symbols: bool,
+    r6_classes: &[&str],
+) -> std::io::Result<()> {
+    let class_name = sanitize_identifier(imp.name);
+    let has_self = |func: &Func| func.args.first().map_or(false, |arg| arg.name == "self");
+    let methods: Vec<&Func> = imp.methods.iter().filter(|func| !func.hidden).collect();
+
+    // The arguments without `self`.
+    let args = |func: &Func| -> Vec<RArg> {
+        let skip = if has_self(func) { 1 } else { 0 };
+        func.args.iter().skip(skip).map(Into::into).collect()
+    };
+    let formals = |func: &Func| join_str(args(func).iter().map(|a| a.to_formal_arg()), ", ");
+
+    // A `.Call()` of the wrapper, passing the pointers of R6 objects.
+    // Returned objects of R6 classes are wrapped if `wrap` is true.
+    let call = |func: &Func, actual_args: Vec<String>, wrap: bool| {
+        let mut res = if use_symbols {
+            format!(".Call(wrap__{}__{}", imp.name, func.mod_name)
+        } else {
+            format!(".Call(\"wrap__{}__{}\"", imp.name, func.mod_name)
+        };
+        if has_self(func) {
+            res.push_str(", self$.ptr");
+        }
+        let skip = if has_self(func) { 1 } else { 0 };
+        for (arg, actual) in func.args.iter().skip(skip).zip(actual_args) {
+            if r6_classes.contains(&arg.arg_type) || arg.arg_type == "Self" {
+                res.push_str(&format!(", {}$.ptr", actual));
+            } else {
+                res.push_str(&format!(", {}", actual));
+            }
+        }
+        if !use_symbols {
+            res.push_str(&format!(", PACKAGE = \"{}\"", package_name));
+        }
+        res.push(')');
+        let return_type = match func.return_type {
+            "Self" => imp.name,
+            return_type => return_type,
+        };
+        match r6_classes.iter().find(|class| **class == return_type) {
+            Some(class) if wrap => format!("{}$new(.ptr = {})", sanitize_identifier(class), res),
+            _ => res,
+        }
+    };
+    let actual =
+        |func: &Func| -> Vec<String> { args(func).iter().map(|a| a.to_actual_arg()).collect() };
+
+    // Getters with a matching setter are active bindings.
+    let mut active = Vec::new();
+    for get in methods
+        .iter()
+        .filter(|func| has_self(func) && func.args.len() == 1)
+    {
+        let set_name = format!("set_{}", get.r_name);
+        if let Some(set) = methods
+            .iter()
+            .find(|func| func.r_name == set_name && has_self(func) && func.args.len() == 2)
+        {
+            active.push((*get, *set));
+        }
+    }
+    let is_active = |func: &Func| {
+        active
+            .iter()
+            .any(|(get, set)| std::ptr::eq(*get, func) || std::ptr::eq(*set, func))
+    };
+
+    write_doc(w, &impl_roxygen(imp))?;
+    writeln!(w, "{} <- R6::R6Class(\"{}\",", class_name, imp.name)?;
+    writeln!(w, "  public = list(")?;
+    let mut public = vec!["    .ptr = NULL".to_string()];
+    match methods
+        .iter()
+        .find(|func| !has_self(func) && func.r_name == "new")
+    {
+        Some(new) => {
+            let formals = formals(new);
+            let sep = if formals.is_empty() { "" } else { ", " };
+            public.push(format!(
+                "    initialize = function({}{}.ptr = NULL) {{ if (is.null(.ptr)) .ptr <- {}; self$.ptr <- .ptr }}",
+                formals,
+                sep,
+                call(new, actual(new), false)
+            ));
+        }
+        None => public.push("    initialize = function(.ptr) { self$.ptr <- .ptr }".to_string()),
+    }
+    for func in methods
+        .iter()
+        .filter(|func| has_self(func) && !is_active(func))
+    {
+        let body = if crate::returns::is_invisible_return(func.return_type) {
+            format!("{{ {}; invisible(self) }}", call(func, actual(func), true))
+        } else {
+            call(func, actual(func), true)
+        };
+        public.push(format!(
+            "    {} = function({}) {}",
+            sanitize_identifier(func.r_name),
+            formals(func),
+            body
+        ));
+    }
+    writeln!(w, "{}", public.join(",\n"))?;
+    write!(w, "  )")?;
+
+    if !active.is_empty() {
+        writeln!(w, ",\n  active = list(")?;
+        let bindings: Vec<String> = active
+            .iter()
+            .map(|(get, set)| {
+                format!(
+                    "    {} = function(value) {{ if (missing(value)) {} else {{ {}; invisible(self) }} }}",
+                    sanitize_identifier(get.r_name),
+                    call(get, vec![], true),
+                    call(set, vec!["value".to_string()], true)
+                )
+            })
+            .collect();
+        writeln!(w, "{}", bindings.join(",\n"))?;
+        write!(w, "  )")?;
+    }
+    writeln!(w, "\n)\n")?;
+
+    for func in methods
+        .iter()
+        .filter(|func| !has_self(func) && func.r_name != "new")
+    {
+        writeln!(
+            w,
+            "{}${} <- function({}) {}\n",
+            class_name,
+            sanitize_identifier(func.r_name),
+            formals(func),
+            call(func, actual(func), true)
+        )?;
+    }
+
     Ok(())
 }
 
@@ -362,9 +735,102 @@ impl Metadata {
             write_function_wrapper(&mut w, func, package_name, use_symbols)?;
         }
 
+        let r6_classes: Vec<&str> = self
+            .impls
+            .iter()
+            .filter(|imp| imp.r6)
+            .map(|imp| imp.name)
+            .collect();
         for imp in &self.impls {
-            write_impl_wrapper(&mut w, imp, package_name, use_symbols)?;
+            if imp.r6 {
+                write_r6_wrapper(&mut w, imp, package_name, use_symbols, &r6_classes)?;
+            } else {
+                write_impl_wrapper(&mut w, imp, package_name, use_symbols)?;
+            }
         }
         unsafe { Ok(String::from_utf8_unchecked(w)) }
     }
+
+    /// Write the R wrappers to `R/extendr-wrappers.R` and the NAMESPACE file
+    /// in the package directory `package_dir`, replacing any existing files.
+    ///
+    /// This makes the R side of a package without rextendr, for example
+    /// from a test that calls `get_<module>_metadata()`.
+    pub fn write_package_files<P: AsRef<std::path::Path>>(
+        &self,
+        package_dir: P,
+        package_name: &str,
+        use_symbols: bool,
+    ) -> std::io::Result<()> {
+        let package_dir = package_dir.as_ref();
+        let r_dir = package_dir.join("R");
+        std::fs::create_dir_all(&r_dir)?;
+        std::fs::write(
+            r_dir.join("extendr-wrappers.R"),
+            self.make_r_wrappers(use_symbols, package_name)?,
+        )?;
+        std::fs::write(
+            package_dir.join("NAMESPACE"),
+            self.make_namespace(package_name),
+        )
+    }
+
+    /// Generate the NAMESPACE directives for the wrappers made by [Metadata::make_r_wrappers].
+    ///
+    /// Functions and classes are exported if their doc comments have an `@export` tag,
+    /// which can be added with `#[extendr(export)]`. The S3 methods used to call
+    /// methods on classes are always registered. The directives are sorted,
+    /// as in a NAMESPACE file written by roxygen.
+    pub fn make_namespace(&self, package_name: &str) -> String {
+        let mut directives = vec![format!("useDynLib({}, .registration = TRUE)", package_name)];
+
+        for func in &self.functions {
+            if !func.hidden && is_exported(func.doc) {
+                directives.push(format!("export({})", namespace_name(func.r_name)));
+            }
+        }
+
+        for imp in &self.impls {
+            if is_exported(imp.doc) {
+                directives.push(format!("export({})", namespace_name(imp.name)));
+            }
+            if imp.r6 {
+                continue;
+            }
+            let mut methods = vec!["$", "[["];
+            if imp.env_storage {
+                methods.extend(["$<-", "[[<-"]);
+            }
+            for method in methods {
+                directives.push(format!(
+                    "S3method(\"{}\",{})",
+                    method,
+                    namespace_name(imp.name)
+                ));
+            }
+        }
+
+        directives.sort();
+        directives.dedup();
+        let mut res = String::from("# Generated by extendr: Do not edit by hand\n\n");
+        for directive in directives {
+            res.push_str(&directive);
+            res.push('\n');
+        }
+        res
+    }
+}
+
+/// Quote names that are not valid R identifiers, as roxygen does in NAMESPACE files.
+fn namespace_name(name: &str) -> String {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    if name.starts_with('_') {
+        format!("\"{}\"", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn is_exported(doc: &str) -> bool {
+    doc.lines().any(|line| line.trim() == "@export")
 }
@@ -0,0 +1,221 @@
+//! Vectors backed by memory-mapped files, on unix only.
+//!
+//! [Doubles::mmap] and [Integers::mmap] map part of a binary file of
+//! native-endian values as an ALTREP vector. Nothing is read up front: the
+//! operating system loads pages of the file as R touches them and can drop
+//! them again under memory pressure, so vectors larger than memory can be
+//! scanned.
+//!
+//! The file is never written. The mapping is private, so if R modifies the
+//! vector the changed pages are copied in memory.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! test! {
+//!     let path = std::env::temp_dir().join("extendr_mmap_doctest.bin");
+//!     let values = [1.5_f64, 2.5, 3.5, 4.5];
+//!     let bytes: Vec<u8> = values.iter().flat_map(|x| x.to_ne_bytes()).collect();
+//!     std::fs::write(&path, bytes).unwrap();
+//!
+//!     // Skip the first value.
+//!     let x = Doubles::mmap(&path, 8, 3)?;
+//!     assert_eq!(x.as_robj(), &r!([2.5, 3.5, 4.5]));
+//!     assert_eq!(R!("sum({{x}})")?, r!(10.5));
+//! }
+//! ```
+use crate::scalar::{Rfloat, Rint};
+use crate::*;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::rc::Rc;
+
+fn os_error(what: &str, path: &Path) -> Error {
+    Error::Other(format!(
+        "{} '{}': {}",
+        what,
+        path.display(),
+        std::io::Error::last_os_error()
+    ))
+}
+
+impl Doubles {
+    /// Map `len` doubles starting `offset` bytes into the file at `path`,
+    /// see the [module documentation](crate::mmap).
+    ///
+    /// `offset` must be a multiple of 8 and the file must hold all the values.
+    pub fn mmap<P: AsRef<Path>>(path: P, offset: usize, len: usize) -> Result<Doubles> {
+        map_file(path.as_ref(), offset, len, Rtype::Doubles)?.try_into()
+    }
+}
+
+impl Integers {
+    /// Map `len` integers starting `offset` bytes into the file at `path`,
+    /// see the [module documentation](crate::mmap).
+    ///
+    /// `offset` must be a multiple of 4 and the file must hold all the values.
+    /// The value `i32::MIN` is `NA`.
+    pub fn mmap<P: AsRef<Path>>(path: P, offset: usize, len: usize) -> Result<Integers> {
+        map_file(path.as_ref(), offset, len, Rtype::Integers)?.try_into()
+    }
+}
+
+fn map_file(path: &Path, offset: usize, len: usize, rtype: Rtype) -> Result<Robj> {
+    let elt_size = match rtype {
+        Rtype::Doubles => 8,
+        _ => 4,
+    };
+    if offset % elt_size != 0 {
+        return Err(Error::Other(format!(
+            "offset {} is not a multiple of {}",
+            offset, elt_size
+        )));
+    }
+    let cpath =
+        CString::new(path.as_os_str().as_bytes()).map_err(|e| Error::Other(e.to_string()))?;
+    let data_len = len
+        .checked_mul(elt_size)
+        .ok_or_else(|| Error::Other(format!("can't map {} values", len)))?;
+
+    let mapping = unsafe {
+        let fd = libc::open(cpath.as_ptr(), libc::O_RDONLY);
+        if fd < 0 {
+            return Err(os_error("can't open", path));
+        }
+        let mut stat: libc::stat = std::mem::zeroed();
+        if libc::fstat(fd, &mut stat) != 0 {
+            libc::close(fd);
+            return Err(os_error("can't read", path));
+        }
+        let file_size = stat.st_size as usize;
+        if offset > file_size || data_len > file_size - offset {
+            libc::close(fd);
+            return Err(Error::Other(format!(
+                "'{}' has {} bytes, too few for {} values at offset {}",
+                path.display(),
+                file_size,
+                len,
+                offset
+            )));
+        }
+        if data_len == 0 {
+            libc::close(fd);
+            return Ok(match rtype {
+                Rtype::Doubles => Doubles::new(0).into(),
+                _ => Integers::new(0).into(),
+            });
+        }
+        // The offset of a mapping must be a multiple of the page size.
+        let page_size = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+        let skip = offset % page_size;
+        let size = skip + data_len;
+        // A private mapping can be written without affecting the file.
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE,
+            fd,
+            (offset - skip) as libc::off_t,
+        );
+        libc::close(fd);
+        if ptr == libc::MAP_FAILED {
+            return Err(os_error("can't map", path));
+        }
+        Mapping {
+            ptr: ptr as *mut u8,
+            size,
+            skip,
+        }
+    };
+
+    let state = MappedVector {
+        mapping: Rc::new(mapping),
+        len,
+    };
+    let class = match rtype {
+        Rtype::Doubles => Altrep::make_altreal_class::<MappedVector>("extendr_mmap", "extendr"),
+        _ => Altrep::make_altinteger_class::<MappedVector>("extendr_mmap", "extendr"),
+    };
+    Ok(Altrep::from_state_and_class(state, class, false).into())
+}
+
+// A private mapping of a file, unmapped when the last vector using it is freed.
+#[derive(Debug)]
+struct Mapping {
+    ptr: *mut u8,
+    size: usize,
+    skip: usize,
+}
+
+impl Mapping {
+    fn data(&self) -> *mut u8 {
+        unsafe { self.ptr.add(self.skip) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.size) };
+    }
+}
+
+// The ALTREP state of a vector in a mapped file.
+#[derive(Debug, Clone)]
+struct MappedVector {
+    mapping: Rc<Mapping>,
+    len: usize,
+}
+
+impl MappedVector {
+    fn read<T: Copy>(&self, index: usize) -> T {
+        assert!(index < self.len);
+        unsafe { *(self.mapping.data() as *const T).add(index) }
+    }
+
+    fn read_region<T: Copy>(&self, index: usize, data: &mut [T]) -> usize {
+        if index >= self.len {
+            return 0;
+        }
+        let n = data.len().min(self.len - index);
+        unsafe {
+            let src = (self.mapping.data() as *const T).add(index);
+            std::ptr::copy_nonoverlapping(src, data.as_mut_ptr(), n);
+        }
+        n
+    }
+}
+
+impl AltrepImpl for MappedVector {
+    fn length(&self) -> usize {
+        self.len
+    }
+
+    fn dataptr(x: SEXP, _writeable: bool) -> *mut u8 {
+        Altrep::get_state::<MappedVector>(x).mapping.data()
+    }
+
+    fn dataptr_or_null(x: SEXP) -> *const u8 {
+        Altrep::get_state::<MappedVector>(x).mapping.data()
+    }
+}
+
+impl AltIntegerImpl for MappedVector {
+    fn elt(&self, index: usize) -> Rint {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [Rint]) -> usize {
+        self.read_region(index, data)
+    }
+}
+
+impl AltRealImpl for MappedVector {
+    fn elt(&self, index: usize) -> Rfloat {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [Rfloat]) -> usize {
+        self.read_region(index, data)
+    }
+}
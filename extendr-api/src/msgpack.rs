@@ -0,0 +1,42 @@
+//! Convert R objects to and from [MessagePack](https://msgpack.org).
+//!
+//! R objects are encoded as by [serializer](crate::serializer):
+//! vectors become arrays, or single values when of length one, named lists
+//! become maps and `NA` becomes nil. When decoding, arrays of values of one type
+//! become atomic vectors.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::msgpack::{from_msgpack, to_msgpack};
+//! test! {
+//!     let robj = list!(a = [1, 2, 3], b = "x", c = list!(TRUE, 1.5));
+//!     let bytes = to_msgpack(&robj)?;
+//!     assert_eq!(from_msgpack(&bytes)?, robj);
+//! }
+//! ```
+use crate::*;
+use std::io::{Read, Write};
+
+/// Encode an R object as MessagePack.
+pub fn to_msgpack(robj: &Robj) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    write_msgpack(robj, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Encode an R object as MessagePack, writing to `writer`.
+pub fn write_msgpack<W: Write>(robj: &Robj, mut writer: W) -> Result<()> {
+    rmp_serde::encode::write(&mut writer, robj).map_err(|e| Error::Other(e.to_string()))
+}
+
+/// Decode an R object from MessagePack.
+pub fn from_msgpack(bytes: &[u8]) -> Result<Robj> {
+    read_msgpack(bytes)
+}
+
+/// Decode an R object from MessagePack, reading from `reader`.
+///
+/// Only one value is read, so several values can be read from a stream in turn.
+pub fn read_msgpack<R: Read>(reader: R) -> Result<Robj> {
+    rmp_serde::decode::from_read(reader).map_err(|e| Error::Other(e.to_string()))
+}
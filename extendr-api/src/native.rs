@@ -0,0 +1,135 @@
+//! Find and call the C routines of other packages.
+//!
+//! [get_native_routine] looks up a routine registered by a package for
+//! `.Call()`, or made available to other packages with `R_RegisterCCallable()`,
+//! so rust code can call it directly instead of through the package's R functions.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::native::get_native_routine;
+//! test! {
+//!     // The routine used by `cor()`, which R calls as `C_cor`.
+//!     let routine = get_native_routine("stats", "cor")?;
+//!     assert_eq!(routine.package(), "stats");
+//!     assert!(get_native_routine("stats", "no_such_routine").is_err());
+//! }
+//! ```
+use crate::*;
+use std::ffi::CString;
+use std::os::raw::c_void;
+
+/// A C routine of a package, found with [get_native_routine].
+#[derive(Debug, Clone)]
+pub struct NativeRoutine {
+    package: String,
+    name: String,
+    ptr: unsafe extern "C" fn() -> *mut c_void,
+}
+
+/// Find the C routine `symbol` of `package`, loading the package's namespace if needed.
+///
+/// Routines registered for `.Call()` and routines registered with
+/// `R_RegisterCCallable()` are found.
+pub fn get_native_routine(package: &str, symbol: &str) -> Result<NativeRoutine> {
+    use crate as extendr_api;
+    let not_found = || Error::NotFound(r!(format!("{}::{}", package, symbol)));
+    call!("loadNamespace", package)?;
+    let cpackage = CString::new(package).map_err(|_| not_found())?;
+    let csymbol = CString::new(symbol).map_err(|_| not_found())?;
+
+    let registered = single_threaded(|| unsafe {
+        R_FindSymbol(csymbol.as_ptr(), cpackage.as_ptr(), std::ptr::null_mut())
+    });
+    let ptr = match registered {
+        Some(ptr) => ptr,
+        None => {
+            // R_GetCCallable throws an R error if the routine is not registered.
+            let (p, s) = (cpackage.as_ptr(), csymbol.as_ptr());
+            let ptr = catch_r_error(move || unsafe {
+                match R_GetCCallable(p, s) {
+                    Some(ptr) => ptr as SEXP,
+                    None => std::ptr::null_mut(),
+                }
+            })
+            .map_err(|_| not_found())?;
+            if ptr.is_null() {
+                return Err(not_found());
+            }
+            unsafe { std::mem::transmute::<SEXP, unsafe extern "C" fn() -> *mut c_void>(ptr) }
+        }
+    };
+
+    Ok(NativeRoutine {
+        package: package.to_string(),
+        name: symbol.to_string(),
+        ptr,
+    })
+}
+
+impl NativeRoutine {
+    /// The package of the routine.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// The name of the routine.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The address of the routine, to cast to its C signature.
+    pub fn as_ptr(&self) -> *const c_void {
+        self.ptr as *const c_void
+    }
+
+    /// Call a routine that uses the `.Call()` convention, taking up to
+    /// eight `SEXP` arguments and returning a `SEXP`.
+    ///
+    /// R errors raised by the routine are returned as errors.
+    ///
+    /// # Safety
+    ///
+    /// The routine must have the `.Call()` convention and take `args.len()` arguments.
+    pub unsafe fn call(&self, args: &[&Robj]) -> Result<Robj> {
+        if args.len() > 8 {
+            return Err(Error::Other(format!(
+                "{} can be called with at most 8 arguments",
+                self.name
+            )));
+        }
+        type S = SEXP;
+        let f = self.ptr;
+        let a: Vec<SEXP> = args.iter().map(|a| a.get()).collect();
+        let a = a.as_slice();
+        let res = catch_r_error(move || {
+            let p = f as *const ();
+            match a.len() {
+                0 => std::mem::transmute::<_, unsafe extern "C" fn() -> S>(p)(),
+                1 => std::mem::transmute::<_, unsafe extern "C" fn(S) -> S>(p)(a[0]),
+                2 => std::mem::transmute::<_, unsafe extern "C" fn(S, S) -> S>(p)(a[0], a[1]),
+                3 => std::mem::transmute::<_, unsafe extern "C" fn(S, S, S) -> S>(p)(
+                    a[0], a[1], a[2],
+                ),
+                4 => std::mem::transmute::<_, unsafe extern "C" fn(S, S, S, S) -> S>(p)(
+                    a[0], a[1], a[2], a[3],
+                ),
+                5 => std::mem::transmute::<_, unsafe extern "C" fn(S, S, S, S, S) -> S>(p)(
+                    a[0], a[1], a[2], a[3], a[4],
+                ),
+                6 => std::mem::transmute::<_, unsafe extern "C" fn(S, S, S, S, S, S) -> S>(p)(
+                    a[0], a[1], a[2], a[3], a[4], a[5],
+                ),
+                7 => std::mem::transmute::<_, unsafe extern "C" fn(S, S, S, S, S, S, S) -> S>(p)(
+                    a[0], a[1], a[2], a[3], a[4], a[5], a[6],
+                ),
+                8 => {
+                    std::mem::transmute::<_, unsafe extern "C" fn(S, S, S, S, S, S, S, S) -> S>(p)(
+                        a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7],
+                    )
+                }
+                _ => std::ptr::null_mut(),
+            }
+        });
+        res.map(|sexp| Robj::from_sexp(sexp))
+    }
+}
@@ -0,0 +1,346 @@
+//! Reading and writing numbers as base R does.
+//!
+//! [parse_real] and [parse_integer] follow `as.numeric()` and `as.integer()`
+//! on strings: surrounding whitespace is ignored, `"NA"` and blank strings
+//! are `NA`, `"NaN"`, `"Inf"` and `"infinity"` are read in any case, and
+//! hexadecimal numbers like `"0x1Fp-2"` are accepted. Where R would give
+//! `NA` with the warning "NAs introduced by coercion", they return `None`,
+//! so that readers can report the bad value.
+//!
+//! [format_real] follows `format()` and `print()`: numbers are rounded to
+//! `digits` significant digits and shown in fixed or scientific notation,
+//! whichever is narrower, with a penalty of `scipen` for scientific.
+//!
+//! [NumberFormat] sets the decimal marker, as the `dec` argument of
+//! `read.csv()` and the `OutDec` option do.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::numeric::{format_real, parse_real, NumberFormat};
+//! test! {
+//!     assert_eq!(parse_real(" 1.5e3 "), Some(Rfloat::from(1500.0)));
+//!     assert_eq!(parse_real("0x10"), Some(Rfloat::from(16.0)));
+//!     assert!(parse_real("NA").unwrap().is_na());
+//!     assert_eq!(parse_real("1.5x"), None);
+//!
+//!     assert_eq!(format_real(Rfloat::from(100000.0), 7), "1e+05");
+//!     assert_eq!(format_real(Rfloat::from(123456.0), 7), "123456");
+//!     assert_eq!(format_real(Rfloat::from(1.0 / 3.0), 7), "0.3333333");
+//!
+//!     let european = NumberFormat { dec: ',', ..Default::default() };
+//!     assert_eq!(european.parse_real("2,5"), Some(Rfloat::from(2.5)));
+//!     assert_eq!(european.format_real(Rfloat::from(2.5)), "2,5");
+//! }
+//! ```
+use crate as extendr_api;
+use crate::scalar::{Rfloat, Rint, Scalar};
+use crate::*;
+
+/// How numbers are read and written, see the [module documentation](crate::numeric).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    /// The number of significant digits written, as `getOption("digits")`.
+    pub digits: usize,
+    /// The penalty for scientific notation, as `getOption("scipen")`.
+    pub scipen: i32,
+    /// The decimal marker, an ASCII character.
+    pub dec: char,
+}
+
+impl Default for NumberFormat {
+    /// R's defaults: 7 digits, no penalty and `.` as the decimal marker.
+    fn default() -> Self {
+        NumberFormat {
+            digits: 7,
+            scipen: 0,
+            dec: '.',
+        }
+    }
+}
+
+/// Read a double as `as.numeric()` does, see the [module documentation](crate::numeric).
+pub fn parse_real(s: &str) -> Option<Rfloat> {
+    NumberFormat::default().parse_real(s)
+}
+
+/// Read an integer as `as.integer()` does, see the [module documentation](crate::numeric).
+pub fn parse_integer(s: &str) -> Option<Rint> {
+    NumberFormat::default().parse_integer(s)
+}
+
+/// Write a double as `format(x, digits = digits)` does.
+///
+/// `as.character()` on doubles uses 15 digits.
+pub fn format_real(x: Rfloat, digits: usize) -> String {
+    NumberFormat {
+        digits,
+        ..Default::default()
+    }
+    .format_real(x)
+}
+
+impl NumberFormat {
+    /// The format set by the `digits`, `scipen` and `OutDec` options.
+    pub fn from_options() -> Result<Self> {
+        let option = |name: &str| call!("getOption", name);
+        let number = |robj: Robj| robj.as_real().or_else(|| robj.as_integer().map(f64::from));
+        let default = NumberFormat::default();
+        Ok(NumberFormat {
+            digits: number(option("digits")?).map_or(default.digits, |x| x as usize),
+            scipen: number(option("scipen")?).map_or(default.scipen, |x| x as i32),
+            dec: option("OutDec")?
+                .as_str()
+                .and_then(|dec| dec.chars().next())
+                .unwrap_or(default.dec),
+        })
+    }
+
+    /// Read a double as `as.numeric()` does.
+    ///
+    /// Blank strings and `"NA"` are `NA`. Strings R can't read are `None`.
+    pub fn parse_real(&self, s: &str) -> Option<Rfloat> {
+        let s = s.as_bytes();
+        if is_blank(s) {
+            return Some(Rfloat::na());
+        }
+        let (x, end) = strtod(s, self.dec_byte());
+        if is_blank(&s[end..]) {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    /// Read an integer as `as.integer()` does.
+    ///
+    /// The string is read as a double and truncated, so `"1.9"` is 1 and
+    /// `"1e3"` is 1000. Strings that can't be read and numbers outside the
+    /// range of R's integers are `None`.
+    pub fn parse_integer(&self, s: &str) -> Option<Rint> {
+        let x = self.parse_real(s)?;
+        if x.is_na() || x.is_nan() {
+            return Some(Rint::na());
+        }
+        let x = x.inner();
+        if x >= 2147483648.0 || x <= -2147483648.0 {
+            None
+        } else {
+            Some(Rint::from(x as i32))
+        }
+    }
+
+    /// Write a double as `format()` does.
+    pub fn format_real(&self, x: Rfloat) -> String {
+        if x.is_na() {
+            return "NA".to_string();
+        }
+        let x = x.inner();
+        if x.is_nan() {
+            return "NaN".to_string();
+        } else if x.is_infinite() {
+            return if x > 0.0 { "Inf" } else { "-Inf" }.to_string();
+        } else if x == 0.0 {
+            return "0".to_string();
+        }
+
+        // Round to `digits` and drop the trailing zeros to get the
+        // significant digits and the exponent.
+        let digits = self.digits.clamp(1, 22);
+        let sci = format!("{:.*e}", digits - 1, x.abs());
+        let (mantissa, exp) = sci.split_once('e').unwrap();
+        let exp: i32 = exp.parse().unwrap();
+        let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+        let nsig = mantissa.bytes().filter(u8::is_ascii_digit).count() as i32;
+
+        let neg = (x < 0.0) as i32;
+        let sci_width = neg + nsig + (nsig > 1) as i32 + if exp.abs() >= 100 { 5 } else { 4 };
+        let rgt = (nsig - exp - 1).max(0);
+        let left = if exp >= 0 { exp + 1 } else { 1 };
+        let fixed_width = neg + left + if rgt > 0 { rgt + 1 } else { 0 };
+
+        let res = if fixed_width <= sci_width + self.scipen {
+            format!("{:.*}", rgt as usize, x)
+        } else {
+            format!(
+                "{}{}e{}{:02}",
+                if neg == 1 { "-" } else { "" },
+                mantissa,
+                if exp < 0 { '-' } else { '+' },
+                exp.abs()
+            )
+        };
+        if self.dec == '.' {
+            res
+        } else {
+            res.replace('.', &self.dec.to_string())
+        }
+    }
+
+    /// Write an integer as `format()` does.
+    pub fn format_integer(&self, x: Rint) -> String {
+        match Option::<i32>::from(x) {
+            Some(x) => x.to_string(),
+            None => "NA".to_string(),
+        }
+    }
+
+    fn dec_byte(&self) -> u8 {
+        if self.dec.is_ascii() {
+            self.dec as u8
+        } else {
+            0
+        }
+    }
+}
+
+fn is_space(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+fn is_blank(s: &[u8]) -> bool {
+    s.iter().all(|c| is_space(*c))
+}
+
+// Read the longest prefix of `s` that is a number, as `R_strtod` does,
+// returning the number and the end of the prefix.
+// `NA` at position 0 is returned if there is no number.
+fn strtod(s: &[u8], dec: u8) -> (Rfloat, usize) {
+    let mut p = 0;
+    while p < s.len() && is_space(s[p]) {
+        p += 1;
+    }
+    if s[p..].starts_with(b"NA") {
+        return (Rfloat::na(), p + 2);
+    }
+    let mut sign = 1.0;
+    match s.get(p) {
+        Some(b'-') => {
+            sign = -1.0;
+            p += 1;
+        }
+        Some(b'+') => p += 1,
+        _ => (),
+    }
+    let rest = &s[p..];
+    let starts_with =
+        |word: &[u8]| rest.len() >= word.len() && rest[..word.len()].eq_ignore_ascii_case(word);
+    if starts_with(b"nan") {
+        return (f64::NAN.into(), p + 3);
+    } else if starts_with(b"infinity") {
+        return ((sign * f64::INFINITY).into(), p + 8);
+    } else if starts_with(b"inf") {
+        return ((sign * f64::INFINITY).into(), p + 3);
+    }
+
+    if rest.len() > 2 && rest[0] == b'0' && (rest[1] == b'x' || rest[1] == b'X') {
+        // Hexadecimal, with an optional fraction and binary exponent.
+        p += 2;
+        let mut ans = 0.0;
+        let mut exph: i32 = -1;
+        while let Some(&c) = s.get(p) {
+            let d = match c {
+                b'0'..=b'9' => c - b'0',
+                b'a'..=b'f' => c - b'a' + 10,
+                b'A'..=b'F' => c - b'A' + 10,
+                _ if c == dec => {
+                    exph = 0;
+                    p += 1;
+                    continue;
+                }
+                _ => break,
+            };
+            ans = 16.0 * ans + d as f64;
+            if exph >= 0 {
+                exph += 4;
+            }
+            p += 1;
+        }
+        let mut expn = 0;
+        if matches!(s.get(p), Some(b'p') | Some(b'P')) {
+            let (n, end) = read_exponent(s, p + 1);
+            expn = n;
+            p = end;
+        }
+        if ans != 0.0 {
+            ans = ldexp(ans, expn - exph.max(0));
+        }
+        return ((sign * ans).into(), p);
+    }
+
+    // Decimal, with an optional fraction and exponent.
+    let start = p;
+    while s.get(p).map_or(false, u8::is_ascii_digit) {
+        p += 1;
+    }
+    let int = &s[start..p];
+    let mut frac: &[u8] = &[];
+    if s.get(p) == Some(&dec) {
+        p += 1;
+        let start = p;
+        while s.get(p).map_or(false, u8::is_ascii_digit) {
+            p += 1;
+        }
+        frac = &s[start..p];
+    }
+    if int.is_empty() && frac.is_empty() {
+        return (Rfloat::na(), 0);
+    }
+    let mut expn = 0;
+    if matches!(s.get(p), Some(b'e') | Some(b'E')) {
+        let (n, end) = read_exponent(s, p + 1);
+        expn = n;
+        p = end;
+    }
+    // Rust's parser rounds correctly.
+    let text = format!(
+        "{}.{}e{}",
+        if int.is_empty() {
+            "0"
+        } else {
+            std::str::from_utf8(int).unwrap()
+        },
+        if frac.is_empty() {
+            "0"
+        } else {
+            std::str::from_utf8(frac).unwrap()
+        },
+        expn
+    );
+    let ans: f64 = text.parse().unwrap();
+    ((sign * ans).into(), p)
+}
+
+// Read an exponent with an optional sign, which may have no digits.
+// Like R, stop growing it at 9999.
+fn read_exponent(s: &[u8], mut p: usize) -> (i32, usize) {
+    let mut sign = 1;
+    match s.get(p) {
+        Some(b'-') => {
+            sign = -1;
+            p += 1;
+        }
+        Some(b'+') => p += 1,
+        _ => (),
+    }
+    let mut n = 0;
+    while let Some(c) = s.get(p).filter(|c| c.is_ascii_digit()) {
+        if n < 9999 {
+            n = n * 10 + (c - b'0') as i32;
+        }
+        p += 1;
+    }
+    (sign * n, p)
+}
+
+// x * 2^e, in steps so that the power of two doesn't overflow.
+fn ldexp(mut x: f64, mut e: i32) -> f64 {
+    while e > 1000 {
+        x *= 2f64.powi(1000);
+        e -= 1000;
+    }
+    while e < -1000 {
+        x *= 2f64.powi(-1000);
+        e += 1000;
+    }
+    x * 2f64.powi(e)
+}
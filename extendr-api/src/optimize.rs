@@ -0,0 +1,220 @@
+//! Typed wrappers for R's `optim()`, `uniroot()` and `integrate()`.
+//!
+//! The objective functions are Rust closures that R calls back into.
+//! An `Err` returned by the closure stops the R routine and is returned
+//! unchanged; errors raised by R itself are returned as [Error::RError].
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::optimize::uniroot;
+//! test! {
+//!     let res = uniroot(|x| Ok(x * x - 2.0), 0.0, 2.0)?;
+//!     assert!((res.root - 2f64.sqrt()).abs() < 1e-3);
+//! }
+//! ```
+use crate::scalar::Scalar;
+use crate::*;
+use std::cell::RefCell;
+
+/// The result of [optim].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimResult {
+    /// The best set of parameters found.
+    pub par: Vec<f64>,
+    /// The value of the objective at `par`.
+    pub value: f64,
+    /// Number of calls to the objective.
+    pub fn_count: Option<i32>,
+    /// `0` for successful completion, see `?optim` for other codes.
+    pub convergence: i32,
+    /// Additional information from the optimizer, if any.
+    pub message: Option<String>,
+}
+
+/// The result of [uniroot].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnirootResult {
+    /// The location of the root.
+    pub root: f64,
+    /// The value of the function at the root.
+    pub f_root: f64,
+    /// Number of iterations used.
+    pub iter: i32,
+    /// Approximate precision of the root.
+    pub estim_prec: f64,
+}
+
+/// The result of [integrate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrateResult {
+    /// The estimated integral.
+    pub value: f64,
+    /// Estimate of the modulus of the absolute error.
+    pub abs_error: f64,
+    /// Number of subintervals used.
+    pub subdivisions: i32,
+}
+
+/// Call the `stats` function `name` with a Rust closure as the argument `fn_arg`.
+///
+/// If the closure fails, its error is returned in preference to the R error
+/// that it causes.
+fn call_with_callback<F>(
+    name: &str,
+    fn_arg: &str,
+    f: F,
+    mut args: Vec<(&str, Robj)>,
+) -> Result<List>
+where
+    F: Fn(List) -> Result<Robj>,
+{
+    let callback_error: RefCell<Option<Error>> = RefCell::new(None);
+    let callback = |args: List| {
+        f(args).map_err(|e| {
+            let msg = e.to_string();
+            callback_error.borrow_mut().get_or_insert(e);
+            Error::Other(msg)
+        })
+    };
+    let stats_fn = Namespace::load("stats")?.function(name)?;
+    let res = Function::with_closure(callback, |callback| {
+        args.push((fn_arg, callback.clone().into()));
+        stats_fn.call(Pairlist::from_pairs(args))
+    });
+    match (res, callback_error.into_inner()) {
+        (Err(_), Some(err)) => Err(err),
+        (res, _) => res?.try_into(),
+    }
+}
+
+fn first_arg_f64(args: &List) -> Result<f64> {
+    args.elt(0)?.try_into()
+}
+
+fn field(list: &List, name: &str) -> Result<Robj> {
+    list.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, v)| v)
+        .ok_or_else(|| Error::NotFound(r!(name)))
+}
+
+/// Minimise `f` starting from `par` with `optim(par, fn, method = method)`.
+///
+/// `method` is one of the methods supported by `optim()`, e.g. `"Nelder-Mead"` or `"BFGS"`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::optimize::optim;
+/// test! {
+///     let res = optim(&[1.0, 1.0], "Nelder-Mead", |p| {
+///         Ok((p[0] - 3.0).powi(2) + (p[1] + 1.0).powi(2))
+///     })?;
+///     assert_eq!(res.convergence, 0);
+///     assert!((res.par[0] - 3.0).abs() < 1e-2);
+///     assert!((res.par[1] + 1.0).abs() < 1e-2);
+///
+///     let err = optim(&[1.0], "BFGS", |_| Err(Error::Other("bad objective".into())));
+///     assert_eq!(err, Err(Error::Other("bad objective".into())));
+/// }
+/// ```
+pub fn optim<F>(par: &[f64], method: &str, f: F) -> Result<OptimResult>
+where
+    F: Fn(&[f64]) -> Result<f64>,
+{
+    let objective = |args: List| {
+        let par: Doubles = args.elt(0)?.try_into()?;
+        let par: Vec<f64> = par.iter().map(|x| x.inner()).collect();
+        f(&par).map(Robj::from)
+    };
+    let res = call_with_callback(
+        "optim",
+        "fn",
+        objective,
+        vec![("par", r!(par)), ("method", r!(method))],
+    )?;
+
+    let par: Doubles = field(&res, "par")?.try_into()?;
+    let counts = field(&res, "counts")?;
+    let message = field(&res, "message")?;
+    Ok(OptimResult {
+        par: par.iter().map(|x| x.inner()).collect(),
+        value: field(&res, "value")?.try_into()?,
+        fn_count: counts.as_integer_slice().and_then(|c| c.first().cloned()),
+        convergence: field(&res, "convergence")?.try_into()?,
+        message: message.as_str().map(|s| s.to_string()),
+    })
+}
+
+/// Find a root of `f` in the interval `lower..upper` with `uniroot()`.
+///
+/// `f(lower)` and `f(upper)` must have opposite signs.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::optimize::uniroot;
+/// test! {
+///     let res = uniroot(|x| Ok(x.cos() - x), 0.0, 1.0)?;
+///     assert!(res.f_root.abs() < 1e-3);
+///
+///     // R reports the sign error.
+///     assert!(uniroot(|x| Ok(x * x + 1.0), 0.0, 1.0).is_err());
+/// }
+/// ```
+pub fn uniroot<F>(f: F, lower: f64, upper: f64) -> Result<UnirootResult>
+where
+    F: Fn(f64) -> Result<f64>,
+{
+    let objective = |args: List| f(first_arg_f64(&args)?).map(Robj::from);
+    let res = call_with_callback(
+        "uniroot",
+        "f",
+        objective,
+        vec![("lower", r!(lower)), ("upper", r!(upper))],
+    )?;
+
+    Ok(UnirootResult {
+        root: field(&res, "root")?.try_into()?,
+        f_root: field(&res, "f.root")?.try_into()?,
+        iter: field(&res, "iter")?.try_into()?,
+        estim_prec: field(&res, "estim.prec")?.try_into()?,
+    })
+}
+
+/// Integrate `f` over `lower..upper` with `integrate()`.
+///
+/// Infinite bounds are allowed.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::optimize::integrate;
+/// test! {
+///     let res = integrate(|x| Ok(x * x), 0.0, 3.0)?;
+///     assert!((res.value - 9.0).abs() < 1e-8);
+///
+///     let res = integrate(|x| Ok((-x * x / 2.0).exp()), f64::NEG_INFINITY, f64::INFINITY)?;
+///     assert!((res.value - (2.0 * std::f64::consts::PI).sqrt()).abs() < 1e-6);
+/// }
+/// ```
+pub fn integrate<F>(f: F, lower: f64, upper: f64) -> Result<IntegrateResult>
+where
+    F: Fn(f64) -> Result<f64>,
+{
+    // integrate() calls the function with a vector of points.
+    let objective = |args: List| {
+        let x: Doubles = args.elt(0)?.try_into()?;
+        let y = x
+            .iter()
+            .map(|x| f(x.inner()))
+            .collect::<Result<Vec<f64>>>()?;
+        Ok(Robj::from(y))
+    };
+    let res = call_with_callback(
+        "integrate",
+        "f",
+        objective,
+        vec![("lower", r!(lower)), ("upper", r!(upper))],
+    )?;
+
+    Ok(IntegrateResult {
+        value: field(&res, "value")?.try_into()?,
+        abs_error: field(&res, "abs.error")?.try_into()?,
+        subdivisions: field(&res, "subdivisions")?.try_into()?,
+    })
+}
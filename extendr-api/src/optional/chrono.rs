@@ -0,0 +1,337 @@
+/*!
+Defines conversions between R's dates, date-times and time differences and the [`chrono`](https://docs.rs/chrono/latest/chrono/) crate.
+
+To enable these conversions, you must first enable the `chrono` feature for extendr:
+```toml
+[dependencies]
+extendr-api = { version = "0.6", features = ["chrono"] }
+```
+
+Specifically, extendr supports the following conversions:
+* `Date` ↔ [`NaiveDate`]. Fractions of a day are dropped, like `format()` does.
+* `POSIXct` ↔ [`DateTime<Utc>`]. Any time zone is accepted, since the time is the same in all of them,
+  and R objects are made with `tzone = "UTC"`. Times are rounded to the microsecond, the precision of a `POSIXct`.
+* `difftime` ↔ [`Duration`]. Any of R's units is accepted, and R objects are made in seconds.
+
+Each type converts as a scalar, as an `Option` for a scalar that may be `NA`,
+and as a `Vec` of either, which is an error if a value is `NA` or out of range for `chrono`.
+
+```rust
+use extendr_api::prelude::*;
+
+#[extendr(use_try_from = true)]
+fn weekdays(dates: Vec<Option<NaiveDate>>) -> Vec<Option<String>> {
+    dates
+        .iter()
+        .map(|date| date.map(|date| date.format("%a").to_string()))
+        .collect()
+}
+
+test! {
+    let dates = R!("as.Date(c('2024-01-01', NA))")?;
+    let res = unsafe { Robj::from_sexp(wrap__weekdays(dates.get())) };
+    assert_eq!(res, r!([Some("Mon"), None]));
+}
+```
+
+Use [crate::datetime] to keep the time zone of a date-time or the units of a time difference.
+*/
+use ::chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+
+use crate::datetime::DurationUnits;
+use crate::*;
+
+// 1970-01-01 is day 719163 of the common era, which starts on day 1.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+// The R class of a chrono type and the conversion of its values, which are
+// not NA.
+trait Temporal: Sized {
+    const EXPECTED: &'static str;
+
+    // The values of an object of the class, in the units of `to_value()`.
+    fn values(robj: &Robj) -> Result<Vec<f64>>;
+
+    fn from_value(value: f64) -> Result<Self>;
+
+    fn to_value(&self) -> f64;
+
+    // Add the class to a vector of values.
+    fn classed(robj: Robj) -> Robj;
+}
+
+impl Temporal for NaiveDate {
+    const EXPECTED: &'static str = "expected a Date";
+
+    fn values(robj: &Robj) -> Result<Vec<f64>> {
+        classed_values(robj, "Date")
+    }
+
+    fn from_value(days: f64) -> Result<Self> {
+        let days = days.floor();
+        if days.abs() < (i32::MAX - UNIX_EPOCH_DAYS_FROM_CE) as f64 {
+            if let Some(date) =
+                NaiveDate::from_num_days_from_ce_opt(days as i32 + UNIX_EPOCH_DAYS_FROM_CE)
+            {
+                return Ok(date);
+            }
+        }
+        Err(Error::OutOfRange(r!(days)))
+    }
+
+    fn to_value(&self) -> f64 {
+        (self.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE) as f64
+    }
+
+    fn classed(mut robj: Robj) -> Robj {
+        robj.set_class(["Date"]).unwrap()
+    }
+}
+
+impl Temporal for DateTime<Utc> {
+    const EXPECTED: &'static str = "expected a POSIXct";
+
+    fn values(robj: &Robj) -> Result<Vec<f64>> {
+        classed_values(robj, "POSIXct")
+    }
+
+    fn from_value(secs: f64) -> Result<Self> {
+        let whole = secs.floor();
+        let micros = ((secs - whole) * 1e6).round() as u32;
+        // 0.9999999 seconds rounds up to the next second.
+        let (whole, micros) = if micros == 1_000_000 {
+            (whole + 1.0, 0)
+        } else {
+            (whole, micros)
+        };
+        if whole.abs() < i64::MAX as f64 {
+            if let Some(time) = Utc.timestamp_opt(whole as i64, micros * 1000).single() {
+                return Ok(time);
+            }
+        }
+        Err(Error::OutOfRange(r!(secs)))
+    }
+
+    fn to_value(&self) -> f64 {
+        self.timestamp() as f64 + self.timestamp_subsec_nanos() as f64 / 1e9
+    }
+
+    fn classed(mut robj: Robj) -> Robj {
+        tz::set_tzone(&mut robj, "UTC").unwrap();
+        robj.set_class(["POSIXct", "POSIXt"]).unwrap()
+    }
+}
+
+impl Temporal for Duration {
+    const EXPECTED: &'static str = "expected a difftime";
+
+    fn values(robj: &Robj) -> Result<Vec<f64>> {
+        let values = classed_values(robj, "difftime")?;
+        let units = robj.get_attrib(sym!(units));
+        let name = units
+            .as_ref()
+            .and_then(|units| units.as_str())
+            .unwrap_or("");
+        match DurationUnits::from_name(name) {
+            Some(units) => Ok(values.into_iter().map(|x| x * units.secs()).collect()),
+            None => Err(Error::Other(format!("unknown difftime units '{}'", name))),
+        }
+    }
+
+    fn from_value(secs: f64) -> Result<Self> {
+        // A Duration holds up to i64::MAX milliseconds.
+        if secs.abs() < (i64::MAX / 1000) as f64 {
+            let whole = secs.trunc();
+            let micros = ((secs - whole) * 1e6).round() as i64;
+            if let Some(duration) =
+                Duration::seconds(whole as i64).checked_add(&Duration::microseconds(micros))
+            {
+                return Ok(duration);
+            }
+        }
+        Err(Error::OutOfRange(r!(secs)))
+    }
+
+    fn to_value(&self) -> f64 {
+        let secs = self.num_seconds();
+        let nanos = (*self - Duration::seconds(secs))
+            .num_nanoseconds()
+            .unwrap_or(0);
+        secs as f64 + nanos as f64 / 1e9
+    }
+
+    fn classed(mut robj: Robj) -> Robj {
+        robj.set_attrib(sym!(units), "secs").unwrap();
+        robj.set_class(["difftime"]).unwrap()
+    }
+}
+
+// The values of a numeric vector with the class `class`, with NA for NA.
+fn classed_values(robj: &Robj, class: &str) -> Result<Vec<f64>> {
+    if !robj.inherits(class) {
+        return Err(Error::Other(format!(
+            "expected an object of class {}",
+            class
+        )));
+    }
+    match robj.rtype() {
+        Rtype::Doubles => Ok(robj.as_real_slice().unwrap().to_vec()),
+        Rtype::Integers => Ok(robj
+            .as_integer_slice()
+            .unwrap()
+            .iter()
+            .map(|x| if x.is_na() { f64::na() } else { *x as f64 })
+            .collect()),
+        _ => Err(Error::TypeMismatch(robj.clone())),
+    }
+}
+
+fn from_values<T: Temporal>(robj: &Robj) -> Result<Vec<Option<T>>> {
+    T::values(robj)?
+        .into_iter()
+        .map(|value| {
+            if value.is_nan() {
+                Ok(None)
+            } else {
+                T::from_value(value).map(Some)
+            }
+        })
+        .collect()
+}
+
+fn from_scalar<T: Temporal>(robj: &Robj) -> Result<Option<T>> {
+    let mut values = from_values(robj)?;
+    if values.len() == 1 {
+        Ok(values.pop().unwrap())
+    } else {
+        Err(Error::ExpectedScalar(robj.clone()))
+    }
+}
+
+fn to_robj<T: Temporal>(values: &[Option<T>]) -> Robj {
+    let values: Vec<f64> = values
+        .iter()
+        .map(|value| value.as_ref().map_or(f64::na(), T::to_value))
+        .collect();
+    T::classed(Robj::from(values))
+}
+
+macro_rules! make_chrono_conversions {
+    ($type: ty, $name: expr) => {
+        impl TryFrom<&Robj> for $type {
+            type Error = Error;
+
+            /// Convert a value of length one that is not `NA`.
+            fn try_from(robj: &Robj) -> Result<Self> {
+                from_scalar(robj)?.ok_or_else(|| Error::MustNotBeNA(robj.clone()))
+            }
+        }
+
+        impl TryFrom<&Robj> for Option<$type> {
+            type Error = Error;
+
+            /// Convert a value of length one, which is `None` if it is `NA`.
+            fn try_from(robj: &Robj) -> Result<Self> {
+                from_scalar(robj)
+            }
+        }
+
+        impl TryFrom<&Robj> for Vec<$type> {
+            type Error = Error;
+
+            /// Convert the values, which must not be `NA`.
+            fn try_from(robj: &Robj) -> Result<Self> {
+                from_values(robj)?
+                    .into_iter()
+                    .map(|value| value.ok_or_else(|| Error::MustNotBeNA(robj.clone())))
+                    .collect()
+            }
+        }
+
+        impl TryFrom<&Robj> for Vec<Option<$type>> {
+            type Error = Error;
+
+            /// Convert the values, with `None` for `NA`.
+            fn try_from(robj: &Robj) -> Result<Self> {
+                from_values(robj)
+            }
+        }
+
+        impl TryFrom<Robj> for $type {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+
+        impl TryFrom<Robj> for Option<$type> {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+
+        impl TryFrom<Robj> for Vec<$type> {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+
+        impl TryFrom<Robj> for Vec<Option<$type>> {
+            type Error = Error;
+
+            fn try_from(robj: Robj) -> Result<Self> {
+                Self::try_from(&robj)
+            }
+        }
+
+        impl<'a> FromRobj<'a> for $type {
+            fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                Self::try_from(robj).map_err(|_| <$type as Temporal>::EXPECTED)
+            }
+        }
+
+        impl<'a> FromRobj<'a> for Option<$type> {
+            fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                Self::try_from(robj).map_err(|_| <$type as Temporal>::EXPECTED)
+            }
+        }
+
+        impl From<$type> for Robj {
+            #[doc = concat!("Make a ", $name, " of length one.")]
+            fn from(value: $type) -> Self {
+                to_robj(&[Some(value)])
+            }
+        }
+
+        impl From<Option<$type>> for Robj {
+            #[doc = concat!("Make a ", $name, " of length one, which is `NA` for `None`.")]
+            fn from(value: Option<$type>) -> Self {
+                to_robj(&[value])
+            }
+        }
+
+        impl From<Vec<$type>> for Robj {
+            #[doc = concat!("Make a ", $name, " vector.")]
+            fn from(values: Vec<$type>) -> Self {
+                let values: Vec<_> = values.into_iter().map(Some).collect();
+                to_robj(&values)
+            }
+        }
+
+        impl From<Vec<Option<$type>>> for Robj {
+            #[doc = concat!("Make a ", $name, " vector, with `NA` for `None`.")]
+            fn from(values: Vec<Option<$type>>) -> Self {
+                to_robj(&values)
+            }
+        }
+    };
+}
+
+make_chrono_conversions!(NaiveDate, "`Date`");
+make_chrono_conversions!(DateTime<Utc>, "`POSIXct` in UTC");
+make_chrono_conversions!(Duration, "`difftime` in seconds");
@@ -1,7 +1,11 @@
 /*!
 A set of optional features and third-party crate integrations, usually hidden behind feature gates.
 */
+#[cfg(feature = "chrono")]
+pub mod chrono;
 #[cfg(feature = "either")]
 pub mod either;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra;
 #[cfg(feature = "ndarray")]
 pub mod ndarray;
@@ -0,0 +1,189 @@
+/*!
+Defines conversions between R objects and the [`nalgebra`](https://docs.rs/nalgebra/latest/nalgebra/) crate, the linear algebra library used by many numerical Rust crates.
+
+To enable these conversions, you must first enable the `nalgebra` feature for extendr:
+```toml
+[dependencies]
+extendr-api = { version = "0.6", features = ["nalgebra"] }
+```
+
+Specifically, extendr supports the following conversions:
+* `Robj` → [`DMatrix<f64>`], for a numeric matrix. The values are copied in R's column-major order, which nalgebra shares.
+* `Robj` → [`DVector<f64>`], for a numeric vector or a one-column matrix.
+* [`DMatrix<f64>`] and [`DVector<f64>`] → `Robj`, giving a matrix with a `dim` attribute and a plain vector.
+* `Robj` ↔ [`DimnamedMatrix`], a matrix with the row and column names of the R matrix, which nalgebra can't hold.
+
+```rust
+use extendr_api::prelude::*;
+use extendr_api::optional::nalgebra::DimnamedMatrix;
+
+#[extendr]
+fn crossprod(x: DMatrix<f64>) -> DMatrix<f64> {
+    x.transpose() * x
+}
+
+#[extendr(use_try_from = true)]
+fn scale_columns(x: DimnamedMatrix) -> DimnamedMatrix {
+    let mut matrix = x.matrix;
+    for mut column in matrix.column_iter_mut() {
+        let max = column.amax();
+        column /= max;
+    }
+    DimnamedMatrix { matrix, ..x }
+}
+```
+
+Integer and logical matrices must be converted with `as.double()` in R first.
+*/
+use ::nalgebra::{DMatrix, DVector};
+
+use crate::prelude::{dim_symbol, dimnames_symbol};
+use crate::*;
+
+impl TryFrom<&Robj> for DMatrix<f64> {
+    type Error = Error;
+
+    /// Copy a numeric matrix.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if !robj.is_matrix() {
+            return Err(Error::ExpectedMatrix(robj.clone()));
+        }
+        let values = robj
+            .as_real_slice()
+            .ok_or_else(|| Error::ExpectedReal(robj.clone()))?;
+        Ok(DMatrix::from_column_slice(
+            robj.nrows(),
+            robj.ncols(),
+            values,
+        ))
+    }
+}
+
+impl TryFrom<Robj> for DMatrix<f64> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<'a> FromRobj<'a> for DMatrix<f64> {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        Self::try_from(robj).map_err(|_| "Not a floating point matrix.")
+    }
+}
+
+impl TryFrom<&Robj> for DVector<f64> {
+    type Error = Error;
+
+    /// Copy a numeric vector, or a matrix with one column.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if robj.is_matrix() && robj.ncols() != 1 {
+            return Err(Error::ExpectedVector(robj.clone()));
+        }
+        let values = robj
+            .as_real_slice()
+            .ok_or_else(|| Error::ExpectedReal(robj.clone()))?;
+        Ok(DVector::from_column_slice(values))
+    }
+}
+
+impl TryFrom<Robj> for DVector<f64> {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<'a> FromRobj<'a> for DVector<f64> {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        Self::try_from(robj).map_err(|_| "Not a floating point vector.")
+    }
+}
+
+impl From<&DMatrix<f64>> for Robj {
+    /// Copy a matrix into an R matrix.
+    fn from(matrix: &DMatrix<f64>) -> Self {
+        let dim = [matrix.nrows() as i32, matrix.ncols() as i32];
+        let mut robj = Robj::from(matrix.as_slice());
+        robj.set_attrib(dim_symbol(), dim).unwrap()
+    }
+}
+
+impl From<DMatrix<f64>> for Robj {
+    fn from(matrix: DMatrix<f64>) -> Self {
+        Robj::from(&matrix)
+    }
+}
+
+impl From<&DVector<f64>> for Robj {
+    /// Copy a vector into an R vector without a `dim` attribute.
+    fn from(vector: &DVector<f64>) -> Self {
+        Robj::from(vector.as_slice())
+    }
+}
+
+impl From<DVector<f64>> for Robj {
+    fn from(vector: DVector<f64>) -> Self {
+        Robj::from(&vector)
+    }
+}
+
+/// A [`DMatrix<f64>`] with the `dimnames` of the R matrix it came from.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::optional::nalgebra::DimnamedMatrix;
+/// test! {
+///     let robj = R!("matrix(1:4 + 0, 2, dimnames = list(c('a', 'b'), c('x', 'y')))")?;
+///     let mut named = DimnamedMatrix::try_from(&robj)?;
+///     named.matrix *= 2.0;
+///     assert_eq!(Robj::from(named), R!("{{robj}} * 2")?);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimnamedMatrix {
+    pub matrix: DMatrix<f64>,
+    /// The `dimnames` attribute: a list of two names vectors or `NULL`s.
+    pub dimnames: Option<Robj>,
+}
+
+impl TryFrom<&Robj> for DimnamedMatrix {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        Ok(DimnamedMatrix {
+            matrix: DMatrix::try_from(robj)?,
+            dimnames: robj.get_attrib(dimnames_symbol()),
+        })
+    }
+}
+
+impl TryFrom<Robj> for DimnamedMatrix {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<'a> FromRobj<'a> for DimnamedMatrix {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        Self::try_from(robj).map_err(|_| "Not a floating point matrix.")
+    }
+}
+
+impl From<DimnamedMatrix> for Robj {
+    /// Copy the matrix and set its `dimnames`.
+    ///
+    /// The names are dropped if they no longer fit the shape of the matrix.
+    fn from(named: DimnamedMatrix) -> Self {
+        let mut robj = Robj::from(&named.matrix);
+        if let Some(dimnames) = named.dimnames {
+            // R checks the names before setting them.
+            let _ = robj.set_attrib(dimnames_symbol(), dimnames);
+        }
+        robj
+    }
+}
@@ -39,8 +39,23 @@ Specifically, extendr supports the following conversions:
 The item type (ie the `T` in [`Array2<T>`]) can be a variety of Rust types that can represent scalars: [`u32`], [`i32`], [`f64`] and, if you have the `num_complex` compiled feature
 enabled, `Complex<f64>`. Items can also be extendr's wrapper types: [`Rbool`], [`Rint`], [`Rfloat`] and [`Rcplx`].
 
-Note that the extendr-ndarray integration only supports accessing R arrays as [`ArrayView`], which are immutable.
-Therefore, instead of directly editing the input array, it is recommended that you instead return a new array from your `#[extendr]`-annotated function, which you allocate in Rust.
+* `Robj` → [`ArrayViewD`] and [`ArrayView3`], for arrays with any `dim` attribute. Element `[i, j, k]` is `x[i + 1, j + 1, k + 1]` in R, as the data is read in R's column-major order without a copy.
+* `&mut Robj` → [`ArrayViewMut1`], [`ArrayViewMut2`], [`ArrayViewMut3`] and [`ArrayViewMutD`], read-write views that change the R vector in place:
+    ```rust
+    use extendr_api::prelude::*;
+
+    #[extendr]
+    fn double_in_place(mut x: Robj) -> Robj {
+        let mut view = <ArrayViewMutD<f64>>::try_from(&mut x).unwrap();
+        view *= 2.0;
+        x
+    }
+    ```
+    An argument passed from R is usually bound to an R variable as well, which sees the change.
+    Call [`Robj::make_unique()`] first to work on a copy when the object is shared; the mutable views do not copy it.
+    Strings have no mutable views.
+
+Views avoid copying large arrays. For a result that needs new memory, it is simpler to return a new array from your `#[extendr]`-annotated function, which you allocate in Rust.
 It will then be copied into a new block of memory managed by R.
 This is made easier by the fact that [ndarray allocates a new array automatically when performing operations on array references](ArrayBase#binary-operators-with-array-and-scalar):
 ```rust
@@ -56,7 +71,10 @@ For all array uses in Rust, refer to the [`ndarray::ArrayBase`] documentation, w
 */
 #[doc(hidden)]
 use ndarray::prelude::*;
-use ndarray::{Data, ShapeBuilder};
+use ndarray::{
+    ArrayView3, ArrayViewD, ArrayViewMut1, ArrayViewMut2, ArrayViewMut3, ArrayViewMutD, Data,
+    IxDyn, ShapeBuilder,
+};
 
 use crate::prelude::{c64, dim_symbol, Rcplx, Rfloat, Rint};
 use crate::*;
@@ -159,6 +177,128 @@ make_array_view_2!(
 make_array_view_2!(c64, "Not a complex number matrix.", Error::ExpectedComplex);
 make_array_view_2!(Rstr, "Not a string matrix.", Error::ExpectedString);
 
+// The shape of an R array from its `dim` attribute, or the length of a vector.
+fn r_shape(robj: &Robj) -> Vec<usize> {
+    match robj.dim() {
+        Some(dim) => dim.iter().map(|d| d.inner() as usize).collect(),
+        None => vec![robj.len()],
+    }
+}
+
+macro_rules! make_array_view_dyn {
+    ($type: ty, $error_str: expr, $error_fn: expr) => {
+        impl<'a> TryFrom<&'a Robj> for ArrayViewD<'a, $type> {
+            type Error = crate::Error;
+
+            /// A view of an array of any number of dimensions, in R's column-major order.
+            fn try_from(robj: &'a Robj) -> Result<Self> {
+                let shape = IxDyn(&r_shape(robj)).f();
+                let v: &'a [$type] = robj
+                    .as_typed_slice()
+                    .ok_or_else(|| $error_fn(robj.clone()))?;
+                ArrayViewD::from_shape(shape, v).map_err(Error::NDArrayShapeError)
+            }
+        }
+
+        impl<'a> FromRobj<'a> for ArrayViewD<'a, $type> {
+            fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                <ArrayViewD<'a, $type>>::try_from(robj).map_err(|_| $error_str)
+            }
+        }
+
+        impl<'a> TryFrom<&'a Robj> for ArrayView3<'a, $type> {
+            type Error = crate::Error;
+
+            fn try_from(robj: &'a Robj) -> Result<Self> {
+                <ArrayViewD<'a, $type>>::try_from(robj)?
+                    .into_dimensionality()
+                    .map_err(Error::NDArrayShapeError)
+            }
+        }
+
+        impl<'a> FromRobj<'a> for ArrayView3<'a, $type> {
+            fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                <ArrayView3<'a, $type>>::try_from(robj).map_err(|_| $error_str)
+            }
+        }
+    };
+}
+
+macro_rules! make_array_view_mut {
+    ($type: ty, $error_fn: expr) => {
+        impl<'a> TryFrom<&'a mut Robj> for ArrayViewMutD<'a, $type> {
+            type Error = crate::Error;
+
+            /// A read-write view of an array of any number of dimensions,
+            /// in R's column-major order. Changes are made in place.
+            fn try_from(robj: &'a mut Robj) -> Result<Self> {
+                let shape = IxDyn(&r_shape(robj)).f();
+                if AsTypedSlice::<$type>::as_typed_slice(&*robj).is_none() {
+                    return Err($error_fn(robj.clone()));
+                }
+                let v: &'a mut [$type] = robj.as_typed_slice_mut().unwrap();
+                ArrayViewMutD::from_shape(shape, v).map_err(Error::NDArrayShapeError)
+            }
+        }
+
+        impl<'a> TryFrom<&'a mut Robj> for ArrayViewMut1<'a, $type> {
+            type Error = crate::Error;
+
+            /// A read-write view of all of the elements, like [ArrayView1].
+            fn try_from(robj: &'a mut Robj) -> Result<Self> {
+                if AsTypedSlice::<$type>::as_typed_slice(&*robj).is_none() {
+                    return Err($error_fn(robj.clone()));
+                }
+                let v: &'a mut [$type] = robj.as_typed_slice_mut().unwrap();
+                Ok(ArrayViewMut1::from(v))
+            }
+        }
+
+        impl<'a> TryFrom<&'a mut Robj> for ArrayViewMut2<'a, $type> {
+            type Error = crate::Error;
+
+            fn try_from(robj: &'a mut Robj) -> Result<Self> {
+                if !robj.is_matrix() {
+                    return Err(Error::ExpectedMatrix(robj.clone()));
+                }
+                <ArrayViewMutD<'a, $type>>::try_from(robj)?
+                    .into_dimensionality()
+                    .map_err(Error::NDArrayShapeError)
+            }
+        }
+
+        impl<'a> TryFrom<&'a mut Robj> for ArrayViewMut3<'a, $type> {
+            type Error = crate::Error;
+
+            fn try_from(robj: &'a mut Robj) -> Result<Self> {
+                <ArrayViewMutD<'a, $type>>::try_from(robj)?
+                    .into_dimensionality()
+                    .map_err(Error::NDArrayShapeError)
+            }
+        }
+    };
+}
+
+make_array_view_dyn!(Rbool, "Not a logical array.", Error::ExpectedLogical);
+make_array_view_dyn!(Rint, "Not an integer array.", Error::ExpectedInteger);
+make_array_view_dyn!(i32, "Not an integer array.", Error::ExpectedInteger);
+make_array_view_dyn!(u32, "Not an integer array.", Error::ExpectedInteger);
+make_array_view_dyn!(Rfloat, "Not a floating point array.", Error::ExpectedReal);
+make_array_view_dyn!(f64, "Not a floating point array.", Error::ExpectedReal);
+make_array_view_dyn!(Rcplx, "Not a complex number array.", Error::ExpectedComplex);
+make_array_view_dyn!(c64, "Not a complex number array.", Error::ExpectedComplex);
+make_array_view_dyn!(Rstr, "Not a string array.", Error::ExpectedString);
+
+// Strings are left out: their elements can't be written without R's write barrier.
+make_array_view_mut!(Rbool, Error::ExpectedLogical);
+make_array_view_mut!(Rint, Error::ExpectedInteger);
+make_array_view_mut!(i32, Error::ExpectedInteger);
+make_array_view_mut!(u32, Error::ExpectedInteger);
+make_array_view_mut!(Rfloat, Error::ExpectedReal);
+make_array_view_mut!(f64, Error::ExpectedReal);
+make_array_view_mut!(Rcplx, Error::ExpectedComplex);
+make_array_view_mut!(c64, Error::ExpectedComplex);
+
 impl<A, S, D> TryFrom<&ArrayBase<S, D>> for Robj
 where
     S: Data<Elem = A>,
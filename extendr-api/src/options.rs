@@ -0,0 +1,295 @@
+//! Options that change how `#[extendr]` functions behave, set from R.
+//!
+//! These are read from R's `options()`, so users of a package can change
+//! them at run time without rebuilding it:
+//!
+//! * `extendr.coerce`: `"strict"`, the default, or `"lenient"` to convert
+//!   arguments of the wrong numeric type as `#[extendr(coerce = "lenient")]`
+//!   does, see [crate::coerce]. It applies to functions without their own
+//!   `coerce` setting.
+//! * `extendr.na`: `"allow"`, the default, or `"error"` to reject arguments
+//!   that contain `NA`, as `anyNA()` finds them.
+//! * `extendr.log_level`: `"off"`, `"error"`, `"warn"`, the default,
+//!   `"info"`, `"debug"` or `"trace"`. With `"trace"` the conversions of
+//!   arguments and results are logged to the console, see [crate::trace].
+//! * `extendr.panic`: `"generic"`, the default, for an R error that names
+//!   the function that panicked, or `"detailed"` to add the panic message.
+//!
+//! [extendr_options()] reads them from rust. During a call from R to an
+//! `#[extendr]` function the values are read once and kept until the call
+//! returns, including any calls back into rust that R code makes in the
+//! meantime, so a function sees the same options throughout. An invalid
+//! value is reported with a warning when the function is called, and the
+//! defaults are used instead, so one bad option doesn't break every function.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::options::{extendr_options, Coercion, LogLevel};
+//! test! {
+//!     assert_eq!(extendr_options()?.coerce, Coercion::Strict);
+//!
+//!     R!("options(extendr.coerce = 'lenient', extendr.log_level = 'debug')")?;
+//!     let options = extendr_options()?;
+//!     assert_eq!(options.coerce, Coercion::Lenient);
+//!     assert!(options.logs(LogLevel::Info));
+//!     assert!(!options.logs(LogLevel::Trace));
+//!
+//!     R!("options(extendr.coerce = 'sloppy')")?;
+//!     assert!(extendr_options().is_err());
+//!     R!("options(extendr.coerce = NULL, extendr.log_level = NULL)")?;
+//! }
+//! ```
+use crate::*;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+
+/// The conversion of arguments of the wrong numeric type, `extendr.coerce`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Coercion {
+    /// Reject them.
+    #[default]
+    Strict,
+    /// Convert integers and logicals to doubles, and whole doubles to
+    /// integers, like [crate::coerce::coerce_arg()].
+    Lenient,
+}
+
+/// The handling of `NA` in arguments, `extendr.na`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NaPolicy {
+    /// Pass `NA` to the function.
+    #[default]
+    Allow,
+    /// Make an argument that contains `NA` an error.
+    Error,
+}
+
+/// The messages logged, `extendr.log_level`, from the fewest to the most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    Off,
+    Error,
+    #[default]
+    Warn,
+    Info,
+    Debug,
+    /// Also log each conversion of an argument or result.
+    Trace,
+}
+
+/// The R error raised for a panic, `extendr.panic`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicMessage {
+    /// `user function panicked: f`.
+    #[default]
+    Generic,
+    /// `user function panicked: f: ` followed by the message of the panic.
+    Detailed,
+}
+
+/// The values of the `extendr.` options, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExtendrOptions {
+    pub coerce: Coercion,
+    pub na: NaPolicy,
+    pub log_level: LogLevel,
+    pub panic: PanicMessage,
+}
+
+impl ExtendrOptions {
+    /// True if messages at `level` are logged.
+    pub fn logs(&self, level: LogLevel) -> bool {
+        level != LogLevel::Off && level <= self.log_level
+    }
+
+    /// True if the arguments of `#[extendr]` functions are traced or checked
+    /// for `NA`. Otherwise the wrappers skip those steps.
+    pub fn checks_args(&self) -> bool {
+        self.na != NaPolicy::Allow || self.logs(LogLevel::Trace) || trace::is_tracing()
+    }
+}
+
+thread_local! {
+    // The options read during the current call from R, and the number of
+    // calls to `#[extendr]` functions in progress.
+    static CACHE: RefCell<Option<ExtendrOptions>> = RefCell::new(None);
+    static DEPTH: Cell<usize> = Cell::new(0);
+}
+
+/// The current values of the `extendr.` options.
+///
+/// Outside a call from R the options are read every time. Errors if an
+/// option has a value that is not allowed.
+pub fn extendr_options() -> Result<ExtendrOptions> {
+    if DEPTH.with(|depth| depth.get()) == 0 {
+        return read_options();
+    }
+    if let Some(options) = CACHE.with(|cache| *cache.borrow()) {
+        return Ok(options);
+    }
+    let options = read_options()?;
+    CACHE.with(|cache| *cache.borrow_mut() = Some(options));
+    Ok(options)
+}
+
+/// The options for a call from R to an `#[extendr]` function.
+/// Used by `#[extendr]` wrappers.
+///
+/// If an option is invalid, a warning is signalled after the call and the
+/// defaults are used for the rest of the call.
+#[doc(hidden)]
+pub fn call_options() -> ExtendrOptions {
+    extendr_options().unwrap_or_else(|err| {
+        defer_warning!("{}, using the defaults", err);
+        let options = ExtendrOptions::default();
+        if DEPTH.with(|depth| depth.get()) > 0 {
+            CACHE.with(|cache| *cache.borrow_mut() = Some(options));
+        }
+        options
+    })
+}
+
+/// Keeps the options read by [extendr_options()] until the outermost
+/// `CallScope` is dropped. Used by `#[extendr]` wrappers.
+#[doc(hidden)]
+pub struct CallScope(());
+
+impl CallScope {
+    pub fn enter() -> Self {
+        DEPTH.with(|depth| depth.set(depth.get() + 1));
+        CallScope(())
+    }
+}
+
+impl Drop for CallScope {
+    fn drop(&mut self) {
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() - 1);
+            depth.get()
+        });
+        if depth == 0 {
+            CACHE.with(|cache| *cache.borrow_mut() = None);
+        }
+    }
+}
+
+/// Check argument `arg` of `function` for `NA` with `options(extendr.na = "error")`.
+/// Used by `#[extendr]` wrappers.
+#[doc(hidden)]
+pub fn check_na(function: &str, arg: &str, robj: &Robj) -> Result<()> {
+    if extendr_options()?.na == NaPolicy::Allow {
+        return Ok(());
+    }
+    if has_na(robj) {
+        Err(Error::Other(format!(
+            "argument '{}' of {}() must not contain NA",
+            arg, function
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+// True if `robj` contains `NA`, as `anyNA()` finds it: `NA` or `NaN` in an
+// atomic vector, or a list element of length one that is `NA`.
+fn has_na(robj: &Robj) -> bool {
+    match robj.rtype() {
+        Rtype::Logicals => robj
+            .as_logical_slice()
+            .map_or(false, |values| values.iter().any(|x| x.is_na())),
+        Rtype::Integers => robj
+            .as_integer_slice()
+            .map_or(false, |values| values.iter().any(|x| *x == i32::MIN)),
+        Rtype::Doubles => robj
+            .as_real_slice()
+            .map_or(false, |values| values.iter().any(|x| x.is_nan())),
+        Rtype::Complexes => robj.as_complex_slice().map_or(false, |values| {
+            values.iter().any(|x| x.re.is_nan() || x.im.is_nan())
+        }),
+        Rtype::Strings => {
+            Strings::try_from(robj).map_or(false, |values| values.iter().any(|x| x.is_na()))
+        }
+        Rtype::List => List::try_from(robj).map_or(false, |list| {
+            list.values()
+                .any(|x| x.len() == 1 && !x.is_list() && has_na(&x))
+        }),
+        _ => false,
+    }
+}
+
+/// The message of the R error for a panic in `function`, with the
+/// message of the panic if `options(extendr.panic = "detailed")` is set.
+/// Used by `#[extendr]` wrappers.
+#[doc(hidden)]
+pub fn panic_message(function: &str, payload: &(dyn Any + Send)) -> String {
+    let generic = format!("user function panicked: {}", function);
+    // The options may be what is wrong, so they don't hide the panic.
+    let detailed =
+        extendr_options().map_or(false, |options| options.panic == PanicMessage::Detailed);
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+    match message {
+        Some(message) if detailed => format!("{}: {}", generic, message),
+        _ => generic,
+    }
+}
+
+fn read_options() -> Result<ExtendrOptions> {
+    Ok(ExtendrOptions {
+        coerce: read_option(
+            "extendr.coerce",
+            &[("strict", Coercion::Strict), ("lenient", Coercion::Lenient)],
+        )?,
+        na: read_option(
+            "extendr.na",
+            &[("allow", NaPolicy::Allow), ("error", NaPolicy::Error)],
+        )?,
+        log_level: read_option(
+            "extendr.log_level",
+            &[
+                ("off", LogLevel::Off),
+                ("error", LogLevel::Error),
+                ("warn", LogLevel::Warn),
+                ("info", LogLevel::Info),
+                ("debug", LogLevel::Debug),
+                ("trace", LogLevel::Trace),
+            ],
+        )?,
+        panic: read_option(
+            "extendr.panic",
+            &[
+                ("generic", PanicMessage::Generic),
+                ("detailed", PanicMessage::Detailed),
+            ],
+        )?,
+    })
+}
+
+// The value of the option `name`, or the default if it is not set. The
+// option is looked up without evaluating R code, as this is done for every
+// call from R to an `#[extendr]` function.
+fn read_option<T: Copy + Default>(name: &str, values: &[(&str, T)]) -> Result<T> {
+    let symbol = Symbol::from_string(name);
+    let option = single_threaded(|| unsafe { Robj::from_sexp(Rf_GetOption1(symbol.get())) });
+    if option.is_null() {
+        return Ok(T::default());
+    }
+    let text = option.as_str();
+    values
+        .iter()
+        .find(|(allowed, _)| Some(*allowed) == text)
+        .map(|(_, value)| *value)
+        .ok_or_else(|| {
+            let allowed: Vec<_> = values
+                .iter()
+                .map(|(allowed, _)| format!("\"{}\"", allowed))
+                .collect();
+            Error::Other(format!(
+                "option {} must be one of {}",
+                name,
+                allowed.join(", ")
+            ))
+        })
+}
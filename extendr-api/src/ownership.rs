@@ -6,15 +6,19 @@
 //! Objects are reference counted, so multiple calls are possible,
 //! unlike `R_PreserveObject`.
 //!
-//! This module exports two functions, `protect(sexp)` and `unprotect(sexp)`.
+//! This module exports two functions, `protect(sexp)` and `unprotect(sexp)`,
+//! and [build_result] for building objects from raw `SEXP`s in several steps.
 
 use once_cell::sync::Lazy;
+use std::cell::RefCell;
 use std::collections::hash_map::{Entry, HashMap};
 use std::sync::Mutex;
 
+use crate::{single_threaded, GetSexp, Result, Robj};
+
 use libR_sys::{
     R_NilValue, R_PreserveObject, R_ReleaseObject, R_xlen_t, Rf_allocVector, Rf_protect,
-    Rf_unprotect, LENGTH, SET_VECTOR_ELT, SEXP, VECSXP, VECTOR_ELT,
+    Rf_unprotect, LENGTH, SET_VECTOR_ELT, SEXP, SEXPTYPE, VECSXP, VECTOR_ELT,
 };
 
 static OWNERSHIP: Lazy<Mutex<Ownership>> = Lazy::new(|| Mutex::new(Ownership::new()));
@@ -29,6 +33,12 @@ pub(crate) unsafe fn unprotect(sexp: SEXP) {
     own.unprotect(sexp);
 }
 
+// The number of `Robj`s that refer to `sexp`.
+pub(crate) unsafe fn ref_count(sexp: SEXP) -> usize {
+    let mut own = OWNERSHIP.lock().expect("ref_count failed");
+    own.ref_count(sexp)
+}
+
 pub const INITIAL_PRESERVATION_SIZE: usize = 100000;
 pub const EXTRA_PRESERVATION_SIZE: usize = 100000;
 
@@ -136,7 +146,6 @@ impl Ownership {
         }
     }
 
-    #[allow(dead_code)]
     unsafe fn ref_count(&mut self, sexp: SEXP) -> usize {
         let Ownership {
             preservation: _,
@@ -224,6 +233,94 @@ impl Ownership {
     }
 }
 
+/// Run `f` with a [ProtectScope] that protects intermediate objects.
+///
+/// Everything protected by the scope is released together when `f` returns,
+/// whether it succeeds, returns early with an error or panics.
+/// The result is an [Robj], which has its own protection, so it outlives the scope.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::ownership::build_result;
+/// use libR_sys::*;
+/// test! {
+///     let res = build_result(|scope| unsafe {
+///         let names = scope.alloc_vector(STRSXP, 2);
+///         SET_STRING_ELT(names, 0, scope.keep(r!("a")));
+///         SET_STRING_ELT(names, 1, scope.keep(r!("b")));
+///         Ok(Robj::from_sexp(names))
+///     })?;
+///     assert_eq!(res, r!(["a", "b"]));
+///
+///     let res = build_result(|scope| -> Result<Robj> {
+///         scope.alloc_vector(INTSXP, 10);
+///         Err(Error::Other("failed".into()))
+///     });
+///     assert!(res.is_err());
+/// }
+/// ```
+pub fn build_result<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce(&ProtectScope) -> Result<T>,
+{
+    let scope = ProtectScope::new();
+    f(&scope)
+}
+
+/// Protection for the intermediate objects of a multi-step construction, see [build_result].
+///
+/// Objects protected by the scope stay protected until the scope is dropped.
+pub struct ProtectScope {
+    sexps: RefCell<Vec<SEXP>>,
+}
+
+impl ProtectScope {
+    fn new() -> Self {
+        ProtectScope {
+            sexps: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Protect `sexp` until the end of the scope and return it.
+    ///
+    /// # Safety
+    ///
+    /// `sexp` must be a valid R object.
+    pub unsafe fn protect(&self, sexp: SEXP) -> SEXP {
+        protect(sexp);
+        self.sexps.borrow_mut().push(sexp);
+        sexp
+    }
+
+    /// Allocate a vector protected until the end of the scope.
+    pub fn alloc_vector(&self, sexptype: SEXPTYPE, len: usize) -> SEXP {
+        single_threaded(|| unsafe { self.protect(Rf_allocVector(sexptype, len as R_xlen_t)) })
+    }
+
+    /// Keep `robj` alive until the end of the scope and return its `SEXP`.
+    pub fn keep<T: Into<Robj>>(&self, robj: T) -> SEXP {
+        let robj = robj.into();
+        unsafe { self.protect(robj.get()) }
+    }
+
+    /// The number of objects protected by the scope.
+    pub fn len(&self) -> usize {
+        self.sexps.borrow().len()
+    }
+
+    /// Return true if the scope protects no objects.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for ProtectScope {
+    fn drop(&mut self) {
+        for sexp in self.sexps.get_mut().drain(..) {
+            unsafe { unprotect(sexp) };
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -333,4 +430,21 @@ mod test {
             });
         }
     }
+
+    #[test]
+    fn scope_test() {
+        test! {
+            let mut sexp = R_NilValue;
+            let res = build_result(|scope| -> Result<()> {
+                sexp = scope.alloc_vector(VECSXP, 2);
+                assert_eq!(scope.len(), 1);
+                let count = unsafe { OWNERSHIP.lock().expect("lock failed").ref_count(sexp) };
+                assert_eq!(count, 1);
+                Err(Error::Other("failed".into()))
+            });
+            assert!(res.is_err());
+            let count = unsafe { OWNERSHIP.lock().expect("lock failed").ref_count(sexp) };
+            assert_eq!(count, 0);
+        }
+    }
 }
@@ -0,0 +1,164 @@
+//! Installed packages, loaded namespaces and package versions.
+//!
+//! These let rust code decide what to do from the packages that are
+//! available, without evaluating R code from strings.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::packages::{is_namespace_loaded, package_version, require_namespace, PackageVersion};
+//! test! {
+//!     assert!(is_namespace_loaded("base"));
+//!     assert!(require_namespace("stats"));
+//!     assert!(!require_namespace("not.a.package"));
+//!
+//!     // Make tibbles only if a recent enough tibble is installed.
+//!     let use_tibble = require_namespace("tibble")
+//!         && package_version("tibble")? >= "3.0".parse::<PackageVersion>()?;
+//!     if use_tibble {
+//!         assert!(is_namespace_loaded("tibble"));
+//!     }
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+
+/// The names of the packages installed in the library paths, `.libPaths()`.
+pub fn installed_packages() -> Result<Vec<String>> {
+    let packages =
+        base_function(".packages")?.call(Pairlist::from_pairs([("all.available", r!(true))]))?;
+    Ok(packages.as_str_iter().map_or_else(Vec::new, |names| {
+        names.map(|name| name.to_string()).collect()
+    }))
+}
+
+/// Return true if `name` is installed in the library paths.
+pub fn is_installed(name: &str) -> bool {
+    is_namespace_loaded(name) || find_package(name).is_ok()
+}
+
+/// The names of the loaded namespaces, like `loadedNamespaces()`.
+pub fn loaded_namespaces() -> Vec<String> {
+    namespace_registry()
+        .names()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Return true if the namespace of `name` is loaded, like `isNamespaceLoaded(name)`.
+///
+/// This does not load the namespace.
+pub fn is_namespace_loaded(name: &str) -> bool {
+    match namespace_registry().local(Symbol::from_string(name)) {
+        Ok(ns) => !ns.is_unbound_value(),
+        Err(_) => false,
+    }
+}
+
+/// Load the namespace of `name` if it is installed, returning false if it
+/// is not, like `requireNamespace(name, quietly = TRUE)`.
+pub fn require_namespace(name: &str) -> bool {
+    if is_namespace_loaded(name) {
+        return true;
+    }
+    match call!("requireNamespace", name, quietly = true) {
+        Ok(loaded) => loaded.as_bool() == Some(true),
+        Err(_) => false,
+    }
+}
+
+/// The version of an installed package, like `packageVersion(name)`.
+///
+/// The version of a loaded namespace is that of the namespace, otherwise
+/// it is read from the package's `DESCRIPTION` file.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::packages::{package_version, PackageVersion};
+/// test! {
+///     let version = package_version("base")?;
+///     assert_eq!(version, PackageVersion::parse(&R!("as.character(getRversion())")?.as_str().unwrap())?);
+///     assert!(version >= PackageVersion::parse("3.6")?);
+///     assert!(package_version("not.a.package").is_err());
+/// }
+/// ```
+pub fn package_version(name: &str) -> Result<PackageVersion> {
+    let version = if is_namespace_loaded(name) {
+        call!("getNamespaceVersion", find_namespace(name)?)?
+    } else {
+        let path = find_package(name)?;
+        let description = call!("file.path", path, "DESCRIPTION")?;
+        call!("read.dcf", description, fields = "Version")?
+    };
+    match version.as_str_iter().and_then(|mut v| v.next()) {
+        Some(version) if !version.is_na() => PackageVersion::parse(version),
+        _ => Err(Error::Other(format!("package '{}' has no version", name))),
+    }
+}
+
+// The installation directory of a package, like `find.package(name, quiet = TRUE)`.
+fn find_package(name: &str) -> Result<Robj> {
+    let path = call!("find.package", name, quiet = true)?;
+    if path.len() == 1 {
+        Ok(path)
+    } else {
+        Err(Error::NamespaceNotFound(r!(name)))
+    }
+}
+
+fn base_function(name: &str) -> Result<Function> {
+    base_namespace()
+        .local(Symbol::from_string(name))?
+        .try_into()
+}
+
+/// A package version such as `1.2.3` or `0.9-1`, compared component by
+/// component like R's `package_version`.
+/// ```
+/// use extendr_api::packages::PackageVersion;
+/// let version: PackageVersion = "1.10-2".parse().unwrap();
+/// assert_eq!(version.components(), [1, 10, 2]);
+/// assert_eq!(version.to_string(), "1.10.2");
+/// assert!(version > PackageVersion::parse("1.9.5").unwrap());
+/// assert!("1.x".parse::<PackageVersion>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageVersion(Vec<u32>);
+
+impl PackageVersion {
+    /// Parse numbers separated by `.` or `-`.
+    pub fn parse(version: &str) -> Result<Self> {
+        version
+            .trim()
+            .split(|c| c == '.' || c == '-')
+            .map(|part| part.parse::<u32>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map(PackageVersion)
+            .map_err(|_| Error::Other(format!("invalid package version '{}'", version)))
+    }
+
+    /// The numbers of the version, such as `[1, 2, 3]` for `1.2.3`.
+    pub fn components(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for PackageVersion {
+    type Err = Error;
+
+    fn from_str(version: &str) -> Result<Self> {
+        PackageVersion::parse(version)
+    }
+}
+
+impl std::fmt::Display for PackageVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(|part| part.to_string()).collect();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+impl From<PackageVersion> for Robj {
+    /// Convert to a version string, which R's `package_version()` accepts.
+    fn from(version: PackageVersion) -> Self {
+        r!(version.to_string())
+    }
+}
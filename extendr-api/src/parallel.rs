@@ -0,0 +1,158 @@
+//! Copy R data out to worker threads and bring the results back.
+//!
+//! R may only be called from the thread that runs it, and an [Robj] is
+//! neither `Send` nor `Sync`. [Robj::to_owned_vector] copies the elements
+//! of a vector into a `Vec` owned by rust, on the R thread. The element
+//! types, those implementing [OwnedElement], are all `Send`, so the copy can
+//! be moved to other threads while R carries on, and the compiler rejects
+//! any attempt to take an R object along.
+//!
+//! With the `rayon` feature, [run()] and [par_map()] do the work in a rayon
+//! pool of at most [max_threads()](crate::threads::max_threads) threads.
+//! Their closures are `Send` and only see rust values, so they can not
+//! touch the R API; the results are converted to R after the workers have
+//! finished.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! test! {
+//!     let robj = R!("c(1, 4, 9, NA)")?;
+//!     let values: Vec<f64> = robj.to_owned_vector()?;
+//!     let roots = std::thread::spawn(move || values.iter().map(|x| x.sqrt()).collect::<Vec<_>>())
+//!         .join()
+//!         .unwrap();
+//!     let res = Robj::from(roots);
+//!     assert_eq!(res.as_real_slice().unwrap()[..3], [1.0, 2.0, 3.0]);
+//!     assert!(res.as_real_slice().unwrap()[3].is_nan());
+//! }
+//! ```
+//!
+//! For reading a large vector in place, without a copy, see
+//! [SendableSlice](crate::threads::SendableSlice). To copy whole objects
+//! with their attributes, see [Robj::detach].
+use crate::scalar::{c64, Rbool, Rcplx, Rfloat, Rint};
+use crate::*;
+
+/// The types of elements that [Robj::to_owned_vector] copies.
+///
+/// All are `Send + 'static`, so the copies can be moved to other threads.
+/// Strings are copied as `Option<String>`, with `None` for `NA`.
+pub trait OwnedElement: Sized + Send + 'static {
+    /// Copy the elements of `robj`, or fail if it is a vector of another type.
+    fn to_owned_vector(robj: &Robj) -> Result<Vec<Self>>;
+}
+
+macro_rules! impl_owned_element {
+    ($($type: ty),*) => {
+        $(
+            impl OwnedElement for $type {
+                fn to_owned_vector(robj: &Robj) -> Result<Vec<Self>> {
+                    single_threaded(|| {
+                        let slice: Option<&[$type]> = robj.as_typed_slice();
+                        slice
+                            .map(|slice| slice.to_vec())
+                            .ok_or_else(|| Error::TypeMismatch(robj.clone()))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_owned_element!(i32, u32, f64, u8, c64, Rbool, Rint, Rfloat, Rcplx);
+
+impl OwnedElement for Option<String> {
+    fn to_owned_vector(robj: &Robj) -> Result<Vec<Self>> {
+        single_threaded(|| {
+            let strings = robj
+                .as_str_iter()
+                .ok_or_else(|| Error::ExpectedString(robj.clone()))?;
+            Ok(strings
+                .map(|s| if s.is_na() { None } else { Some(s.to_string()) })
+                .collect())
+        })
+    }
+}
+
+impl Robj {
+    /// Copy the elements of a vector into memory owned by rust.
+    ///
+    /// The vector must have the R type of `T`, for example doubles for
+    /// `f64` and [Rfloat], integers for `i32` and [Rint], logicals for
+    /// [Rbool] and strings for `Option<String>`. Attributes are not copied.
+    /// This must be called from the R thread.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let robj = R!("1:3")?;
+    ///     assert_eq!(robj.to_owned_vector::<i32>()?, [1, 2, 3]);
+    ///     assert!(robj.to_owned_vector::<f64>().is_err());
+    ///
+    ///     let strings = r!([Some("a"), None]).to_owned_vector::<Option<String>>()?;
+    ///     assert_eq!(strings, [Some("a".to_string()), None]);
+    /// }
+    /// ```
+    pub fn to_owned_vector<T: OwnedElement>(&self) -> Result<Vec<T>> {
+        T::to_owned_vector(self)
+    }
+}
+
+/// Run `f` with `input` in a rayon pool of at most
+/// [max_threads()](crate::threads::max_threads) threads, and return its result.
+///
+/// `input` and the result are `Send`, so they can not hold R objects, and
+/// `f` can use rayon's parallel iterators but can not call R. A panic in
+/// `f` is returned as an error. This must be called from the R thread.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::parallel::run;
+/// use rayon::prelude::*;
+/// test! {
+///     let x: Vec<f64> = R!("as.double(1:1000)")?.to_owned_vector()?;
+///     let y: Vec<f64> = R!("rev(as.double(1:1000))")?.to_owned_vector()?;
+///     let dot = run((x, y), |(x, y)| x.par_iter().zip(&y).map(|(a, b)| a * b).sum::<f64>())?;
+///     assert_eq!(dot, R!("sum(1:1000 * 1000:1)")?.as_real().unwrap());
+/// }
+/// ```
+#[cfg(feature = "rayon")]
+pub fn run<I, O, F>(input: I, f: F) -> Result<O>
+where
+    I: Send,
+    O: Send,
+    F: FnOnce(I) -> O + Send,
+{
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    threads::ThreadLimit::from_r_options()
+        .install(move || catch_unwind(AssertUnwindSafe(move || f(input))))?
+        .map_err(|_| Error::Other("Worker thread panicked".into()))
+}
+
+/// Copy a vector with [Robj::to_owned_vector], apply `f` to each element in
+/// parallel with [run()], and convert the results to an R vector.
+///
+/// The order of the elements is kept. Attributes such as names are not.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::parallel::par_map;
+/// use extendr_api::scalar::Rfloat;
+/// test! {
+///     let squares = par_map(&R!("c(1, 2, NA)")?, |x: Rfloat| x * x)?;
+///     assert_eq!(squares, R!("c(1, 4, NA)")?);
+///
+///     let lengths = par_map(&r!(["a", "bcd"]), |s: Option<String>| s.map_or(0, |s| s.len() as i32))?;
+///     assert_eq!(lengths, r!([1, 3]));
+/// }
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_map<T, U, F>(robj: &Robj, f: F) -> Result<Robj>
+where
+    T: OwnedElement,
+    U: Send,
+    Vec<U>: Into<Robj>,
+    F: Fn(T) -> U + Send + Sync,
+{
+    use rayon::prelude::*;
+    let values = robj.to_owned_vector::<T>()?;
+    let results: Vec<U> = run(values, |values| values.into_par_iter().map(f).collect())?;
+    Ok(results.into())
+}
@@ -0,0 +1,124 @@
+//! Wrapped rust objects that survive `saveRDS()` and session restarts.
+//!
+//! An `#[extendr]` impl block wraps its type in an external pointer. R can
+//! save the pointer object, but the address is lost and the loaded object
+//! is a null pointer. With `#[extendr(serialize)]` the object's state is
+//! also kept, serialized with [serde](crate::serializer), in the protected
+//! slot of the external pointer. R saves this slot with the object, so when
+//! a loaded object is next passed to rust it is rebuilt from the saved state.
+//!
+//! ```ignore
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Counter {
+//!     count: i32,
+//! }
+//!
+//! #[extendr(serialize)]
+//! impl Counter {
+//!     fn new() -> Self {
+//!         Self { count: 0 }
+//!     }
+//!     fn increment(&mut self) {
+//!         self.count += 1;
+//!     }
+//!     fn count(&self) -> i32 {
+//!         self.count
+//!     }
+//! }
+//! ```
+//!
+//! ```r
+//! x <- Counter$new()
+//! x$increment()
+//! saveRDS(x, "counter.rds")
+//! # ... in a new session, after loading the package
+//! y <- readRDS("counter.rds")
+//! y$count() # 1
+//! ```
+//!
+//! The state is refreshed after every method taking `&mut self`, so keep
+//! serialized objects small or mutate them in few calls.
+//! The generated `$serialize()` method returns the state as an R list and
+//! `$deserialize(state)` makes a new object from it, for example to store
+//! objects in other formats.
+//! Converting `&T` to `Robj` keeps only the state, without cloning `T`;
+//! the object is rebuilt from it when first used.
+//!
+//! No `refhook` is needed for `saveRDS()`, `save()` or `serialize()`.
+use crate::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Make an external pointer holding `value` and its serialized state.
+///
+/// # Safety
+///
+/// `finalizer` must free a `Box<T>`.
+#[doc(hidden)]
+pub unsafe fn make_persistent_ptr<T: Serialize>(
+    value: T,
+    class: &str,
+    finalizer: libR_sys::R_CFinalizer_t,
+) -> Result<Robj> {
+    let state = serializer::to_robj(&value)?;
+    let ptr = Box::into_raw(Box::new(value));
+    let mut res = Robj::make_external_ptr(ptr, state);
+    res.set_attrib(class_symbol(), class)?;
+    res.register_c_finalizer(finalizer);
+    Ok(res)
+}
+
+/// Make an external pointer holding only the serialized state of `value`.
+///
+/// The address is null, like an object loaded with `readRDS()`, so the
+/// object is rebuilt from the state by [restore] when it is next used.
+/// This persists a borrowed value without cloning it.
+#[doc(hidden)]
+pub fn make_persistent_state<T: Serialize>(value: &T, class: &str) -> Result<Robj> {
+    let state = serializer::to_robj(value)?;
+    let mut res = unsafe { Robj::make_external_ptr(std::ptr::null_mut::<T>(), state) };
+    res.set_attrib(class_symbol(), class)?;
+    Ok(res)
+}
+
+/// Store the current state of the object in the external pointer.
+///
+/// # Safety
+///
+/// `robj` must be an external pointer made by [make_persistent_ptr] for `T`.
+#[doc(hidden)]
+pub unsafe fn refresh<T: Serialize>(robj: &Robj) -> Result<()> {
+    let ptr = robj.external_ptr_addr::<T>();
+    if ptr.is_null() {
+        return Err(Error::ExpectedExternalPtr(robj.clone()));
+    }
+    let state = serializer::to_robj(&*ptr)?;
+    single_threaded(|| libR_sys::R_SetExternalPtrProtected(robj.get(), state.get()));
+    Ok(())
+}
+
+/// Get the address of the object, rebuilding it from the saved state
+/// if the pointer was loaded from a file.
+///
+/// # Safety
+///
+/// `robj` must be an external pointer made by [make_persistent_ptr] for `T`
+/// and `finalizer` must free a `Box<T>`.
+#[doc(hidden)]
+pub unsafe fn restore<T: DeserializeOwned>(
+    robj: &Robj,
+    finalizer: libR_sys::R_CFinalizer_t,
+) -> Result<*mut T> {
+    let ptr = robj.external_ptr_addr::<T>();
+    if !ptr.is_null() {
+        return Ok(ptr);
+    }
+    let state = robj.external_ptr_protected();
+    if state.is_null() {
+        return Err(Error::ExpectedExternalPtr(robj.clone()));
+    }
+    let value: T = deserializer::from_robj(&state)?;
+    let ptr = Box::into_raw(Box::new(value));
+    single_threaded(|| libR_sys::R_SetExternalPtrAddr(robj.get(), ptr as *mut _));
+    robj.register_c_finalizer(finalizer);
+    Ok(ptr)
+}
@@ -12,13 +12,19 @@ pub use super::na::*;
 
 pub use super::error::{Error, Result};
 
+pub use super::returns::{Invisible, NoReturn, Null};
+
 pub use super::functions::{
-    base_env, base_namespace, blank_scalar_string, blank_string, current_env, empty_env,
-    eval_string, eval_string_with_params, find_namespace, find_namespaced_function, global_env,
-    global_function, global_var, local_var, na_string, namespace_registry, new_env, nil_value,
-    parse, srcref,
+    base_env, base_namespace, blank_scalar_string, blank_string, catch_user_interrupt,
+    check_user_interrupt, current_env, empty_env, eval_string, eval_string_with_params,
+    find_namespace, find_namespaced_function, global_env, global_function, global_var, local_var,
+    na_string, namespace_registry, new_env, nil_value, parse, r_sleep, r_system_time, srcref,
+    ProcTime,
 };
 
+#[cfg(unix)]
+pub use super::functions::{eval_string_with_timeout, eval_with_timeout};
+
 pub use super::wrapper::symbol::{
     base_symbol, brace_symbol, bracket_2_symbol, bracket_symbol, class_symbol, device_symbol,
     dim_symbol, dimnames_symbol, dollar_symbol, dot_defined, dot_method, dot_package_name,
@@ -33,38 +39,47 @@ pub use crate::{append, append_lang, append_with_name, args, lang, make_lang};
 
 // Exported macros have crate scope.
 pub use crate::{
-    data_frame, factor, global, list, r, reprint, reprintln, rprint, rprintln, sym, test, var,
+    data_frame, extendr_bench_export, factor, global, lazy_r_static, list, message, r, reprint,
+    reprintln, rmatch, rprint, rprintln, sym, test, var, warning,
 };
 
 pub use super::wrapper::{
     AltComplexImpl, AltIntegerImpl, AltLogicalImpl, AltRawImpl, AltRealImpl, AltStringImpl, Altrep,
-    AltrepImpl, RArray, RColumn, RMatrix, RMatrix3D,
+    AltrepClass, AltrepImpl, RArray, RColumn, RMatrix, RMatrix3D,
 };
 
 #[cfg(use_r_altlist)]
 pub use super::wrapper::AltListImpl;
 
-pub use super::wrapper::s4::S4;
+pub use super::wrapper::s4::{S4DispatchContext, S4};
 
 pub use super::wrapper::{Conversions, MatrixConversions};
 
 pub use super::robj::{
     AsStrIter, Attributes, Eval, GetSexp, IntoRobj, Length, Operators, Rinternals, Robj,
-    RobjItertools, Slices, Types,
+    RobjItertools, RobjMut, Slices, Types,
 };
 
-pub use super::thread_safety::{catch_r_error, handle_panic, single_threaded, throw_r_error};
+pub use super::thread_safety::{
+    catch_r_error, handle_panic, single_threaded, throw_r_error, unwind_protect, RUnwind,
+};
 
 pub use super::wrapper::{
-    Complexes, Dataframe, Doubles, EnvIter, Environment, Expressions, ExternalPtr, FromList,
-    Function, Integers, IntoDataFrameRow, Language, List, ListIter, Logicals, Nullable, Pairlist,
-    Primitive, Promise, Raw, Rstr, Strings, Symbol,
+    Arity, Complexes, Dataframe, DataframeBuilder, Dots, Doubles, EnvIter, Environment,
+    Expressions, ExternalPtr, FromColumnElt, FromDataframeRow, FromList, Function, Integers,
+    IntoDataFrameRow, Language, List, ListIter, Logicals, Namespace, Nullable, Pairlist, Primitive,
+    Promise, Raw, Rstr, Sortedness, Strings, Symbol, TypedFunction, R6,
 };
 
-pub use extendr_macros::{call, extendr, extendr_module, pairlist, IntoDataFrameRow, Rraw, R};
+pub use extendr_macros::{
+    call, extendr, extendr_module, pairlist, FromDataframeRow, IntoDataFrameRow, IntoRobj, Rraw,
+    TryFromRobj, R,
+};
 
 pub use super::iter::StrIter;
 
+pub use super::factor::{FactorBuilder, LevelOrder};
+
 pub use std::convert::{TryFrom, TryInto};
 
 pub use super::scalar::*;
@@ -76,5 +91,11 @@ pub use super::optional::*;
 #[cfg(feature = "ndarray")]
 pub use ::ndarray::*;
 
+#[cfg(feature = "nalgebra")]
+pub use ::nalgebra::{DMatrix, DVector};
+
+#[cfg(feature = "chrono")]
+pub use ::chrono::{DateTime, NaiveDate, Utc};
+
 #[cfg(feature = "either")]
 pub use ::either::*;
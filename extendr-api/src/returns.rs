@@ -0,0 +1,85 @@
+//! Return types that set how an `#[extendr]` function returns to R.
+//!
+//! The R wrappers made by [crate::metadata] return the result of a function
+//! visibly, except for `()`, which R sees as an invisible `NULL`. These
+//! types give the other conventions:
+//!
+//! * [Invisible] returns a value invisibly, like `invisible(x)`, for functions
+//!   called for their effect that also return something useful.
+//! * [Null] returns a visible `NULL`.
+//! * [NoReturn] is for functions that never return normally, such as those
+//!   that only signal conditions.
+//!
+//! ```ignore
+//! #[extendr]
+//! fn set_option(value: i32) -> Invisible<i32> {
+//!     let old = OPTION.swap(value);
+//!     Invisible(old)
+//! }
+//!
+//! #[extendr]
+//! fn abort_io(path: &str) -> NoReturn {
+//!     Condition::error("file not found").class("io_error").data("path", path).throw()
+//! }
+//! ```
+use crate::*;
+
+/// A value to be returned to R invisibly, like `invisible(x)`.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     assert_eq!(Robj::from(Invisible(1)), r!(1));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Invisible<T>(pub T);
+
+impl<T> Invisible<T> {
+    /// Unwrap the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Into<Robj>> From<Invisible<T>> for Robj {
+    fn from(val: Invisible<T>) -> Self {
+        val.0.into()
+    }
+}
+
+/// A visible `NULL`, unlike `()` which R sees as an invisible `NULL`.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     assert!(Robj::from(Null).is_null());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Null;
+
+impl From<Null> for Robj {
+    fn from(_: Null) -> Self {
+        ().into()
+    }
+}
+
+/// The return type of a function that never returns normally.
+///
+/// There are no values of this type, so a function returning it must end
+/// by diverging, for example with [throw_r_error] or
+/// [conditions::throw_condition], which return `!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoReturn {}
+
+impl From<NoReturn> for Robj {
+    fn from(val: NoReturn) -> Self {
+        match val {}
+    }
+}
+
+/// Return true if R wrappers of a function with this return type are `invisible()`.
+///
+/// `return_type` is the simplified type name in [metadata::Func].
+pub(crate) fn is_invisible_return(return_type: &str) -> bool {
+    matches!(return_type, "()" | "Invisible" | "NoReturn" | "!")
+}
@@ -29,7 +29,7 @@
 #[macro_export]
 macro_rules! r {
     ($e: expr) => {
-        Robj::from($e)
+        $crate::Robj::from($e)
     };
 }
 
@@ -52,7 +52,7 @@ TODO: As of R 4.1.0, base env cannot be modifed, which makes it difficult to
 #[macro_export]
 macro_rules! var {
     ($($tokens: tt)*) => {{
-        local_var(sym!($($tokens)*))
+        $crate::local_var($crate::sym!($($tokens)*))
     }};
 }
 
@@ -69,7 +69,7 @@ macro_rules! var {
 #[macro_export]
 macro_rules! global {
     ($($tokens: tt)*) => {{
-        global_var(sym!($($tokens)*))
+        $crate::global_var($crate::sym!($($tokens)*))
     }};
 }
 
@@ -87,7 +87,7 @@ macro_rules! global {
 #[macro_export]
 macro_rules! sym {
     ($($tokens: tt)*) => {
-        Robj::from(Symbol::from_string(stringify!($($tokens)*)))
+        $crate::Robj::from($crate::Symbol::from_string(stringify!($($tokens)*)))
     };
 }
 
@@ -107,10 +107,10 @@ macro_rules! sym {
 #[macro_export]
 macro_rules! data_frame {
     () => {
-        call!("data.frame").unwrap()
+        $crate::call!("data.frame").unwrap()
     };
     ($($rest: tt)*) => {
-        call!("data.frame", $($rest)*).unwrap()
+        $crate::call!("data.frame", $($rest)*).unwrap()
     };
 }
 
@@ -131,7 +131,7 @@ macro_rules! data_frame {
 #[macro_export]
 macro_rules! factor {
     ($($rest: tt)*) => {
-        call!("factor", $($rest)*).unwrap()
+        $crate::call!("factor", $($rest)*).unwrap()
     };
 }
 
@@ -144,7 +144,7 @@ macro_rules! rprint {
     () => {
     };
     ($($rest: tt)*) => {
-        print_r_output(format!($($rest)*));
+        $crate::print_r_output(format!($($rest)*));
     };
 }
 
@@ -155,11 +155,11 @@ macro_rules! rprint {
 #[macro_export]
 macro_rules! rprintln {
     () => {
-        print_r_output("\n");
+        $crate::print_r_output("\n");
     };
     ($($rest: tt)*) => {
-        print_r_output(format!($($rest)*));
-        print_r_output("\n");
+        $crate::print_r_output(format!($($rest)*));
+        $crate::print_r_output("\n");
     };
 }
 
@@ -169,7 +169,7 @@ macro_rules! reprint {
     () => {
     };
     ($($rest: tt)*) => {
-        print_r_error(format!($($rest)*));
+        $crate::print_r_error(format!($($rest)*));
     };
 }
 
@@ -177,14 +177,151 @@ macro_rules! reprint {
 #[macro_export]
 macro_rules! reprintln {
     () => {
-        print_r_error("\n");
+        $crate::print_r_error("\n");
     };
     ($($rest: tt)*) => {
-        print_r_error(format!($($rest)*));
-        print_r_error("\n");
+        $crate::print_r_error(format!($($rest)*));
+        $crate::print_r_error("\n");
     };
 }
 
+/// Signal an R warning from an `#[extendr]` function, with `format!` arguments.
+///
+/// Unlike printing, the warning reaches `tryCatch()` and `withCallingHandlers()`
+/// in the calling R code. Use [conditions::Condition] for classed warnings.
+#[macro_export]
+macro_rules! warning {
+    ($($rest: tt)*) => {
+        $crate::conditions::Condition::warning(format!($($rest)*)).signal()
+    };
+}
+
+/// Signal an R warning when the `#[extendr]` function returns, with `format!` arguments.
+///
+/// The function carries on, and the warning is signalled after those
+/// deferred before it, see [conditions::Warnings].
+#[macro_export]
+macro_rules! defer_warning {
+    ($($rest: tt)*) => {{
+        let mut warnings = $crate::conditions::Warnings::new();
+        warnings.push(format!($($rest)*));
+        warnings.defer()
+    }};
+}
+
+/// Signal an R message from an `#[extendr]` function, with `format!` arguments.
+///
+/// Like `message()`, a newline is added, and the message can be
+/// suppressed with `suppressMessages()`.
+#[macro_export]
+macro_rules! message {
+    ($($rest: tt)*) => {
+        $crate::conditions::Condition::message(format!("{}\n", format!($($rest)*))).signal()
+    };
+}
+
+/// Destructure an R object by its shape, with an error for each part that doesn't fit.
+///
+/// `rmatch!(robj, list { x: T, y: U })` reads the elements `x` and `y` of a
+/// list, converting them with `TryFrom<Robj>`, and gives a `Result<(T, U)>`.
+/// Missing elements are read as `NULL`, so `Option<T>` fields are optional.
+/// The error names every field that is missing or has the wrong type.
+///
+/// `rmatch!(robj, { T => f, U => g })` tries the conversions in order and
+/// calls the function of the first that succeeds, giving a `Result` of its
+/// value. The error lists why each one failed.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let point = list!(x = 1.5, label = "a");
+///     let (x, label, weight) = rmatch!(point, list { x: f64, label: String, weight: Option<f64> })?;
+///     assert_eq!((x, label.as_str(), weight), (1.5, "a", None));
+///
+///     let err = rmatch!(point, list { x: String, y: f64 }).unwrap_err();
+///     assert_eq!(
+///         err.to_string(),
+///         "field 'x': Expected Strings got Doubles; missing field 'y'"
+///     );
+///
+///     let describe = |robj: Robj| rmatch!(robj, {
+///         i32 => |n| format!("{} items", n),
+///         String => |s| format!("named {}", s),
+///     });
+///     assert_eq!(describe(r!(3))?, "3 items");
+///     assert_eq!(describe(r!("x"))?, "named x");
+///     assert!(describe(r!([1, 2])).is_err());
+/// }
+/// ```
+#[macro_export]
+macro_rules! rmatch {
+    ($robj: expr, list { $($field: ident : $type: ty),* $(,)? }) => {
+        {
+            let robj: &$crate::Robj = &$robj;
+            (|| -> $crate::Result<_> {
+                let mut values = $crate::rmatch_fields(robj, &[$(stringify!($field)),*])?.into_iter();
+                $(
+                    let $field = $crate::rmatch_field::<$type>(values.next().unwrap(), stringify!($field));
+                )*
+                let errors: Vec<String> = vec![$($field.as_ref().err().map(|e| e.to_string())),*]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                if !errors.is_empty() {
+                    return Err($crate::Error::Other(errors.join("; ")));
+                }
+                Ok(($(match $field {
+                    Ok(value) => value,
+                    Err(_) => unreachable!(),
+                },)*))
+            })()
+        }
+    };
+    ($robj: expr, { $($type: ty => $body: expr),+ $(,)? }) => {
+        {
+            let robj: &$crate::Robj = &$robj;
+            (|| -> $crate::Result<_> {
+                let mut errors: Vec<String> = Vec::new();
+                $(
+                    match <$type as $crate::TryFrom<$crate::Robj>>::try_from(robj.clone()) {
+                        Ok(value) => return Ok(($body)(value)),
+                        Err(err) => errors.push(format!("{}: {}", stringify!($type), err)),
+                    }
+                )+
+                Err($crate::Error::Other(format!("no pattern matched: {}", errors.join("; "))))
+            })()
+        }
+    };
+}
+
+/// The elements of a list by name, for [rmatch!]. `None` for missing elements.
+#[doc(hidden)]
+pub fn rmatch_fields(
+    robj: &crate::Robj,
+    names: &[&str],
+) -> crate::Result<Vec<Option<crate::Robj>>> {
+    let list = crate::List::try_from(robj.clone())?;
+    Ok(names
+        .iter()
+        .map(|name| list.iter().find(|(n, _)| n == name).map(|(_, value)| value))
+        .collect())
+}
+
+/// Convert an element of a list for [rmatch!], naming it in errors.
+#[doc(hidden)]
+pub fn rmatch_field<T>(value: Option<crate::Robj>, name: &str) -> crate::Result<T>
+where
+    T: TryFrom<crate::Robj>,
+    T::Error: std::fmt::Display,
+{
+    match value {
+        Some(value) => {
+            T::try_from(value).map_err(|e| crate::Error::Other(format!("field '{}': {}", name, e)))
+        }
+        None => T::try_from(().into())
+            .map_err(|_| crate::Error::Other(format!("missing field '{}'", name))),
+    }
+}
+
 /// Macro for running tests.
 ///
 /// This starts up the underlying [`extendr_engine`] so that interactions with R will work.
@@ -205,7 +342,7 @@ macro_rules! test {
 
             // this helper function must reside in the macro so it doesn't get compiled
             // unless the macro actually gets used (e.g., in testing code)
-            fn test<F: FnOnce() -> Result<()>>(f: F) {
+            fn test<F: FnOnce() -> $crate::Result<()>>(f: F) {
                 extendr_engine::start_r();
                 f().unwrap();
             }
@@ -217,3 +354,38 @@ macro_rules! test {
         }
     };
 }
+
+/// Declare package-lifetime R objects that are made when first used.
+///
+/// Each initializer is an expression giving a `Result` of something that
+/// converts to `Robj`. It is run the first time `get()` is called and the
+/// object is then protected and reused, which saves parsing formulas or
+/// building lookup tables on every call. The objects are released when the
+/// package is unloaded, see [LazyRStatic](crate::handles::LazyRStatic).
+/// ```
+/// use extendr_api::prelude::*;
+/// lazy_r_static! {
+///     /// Colours for plots.
+///     static PALETTE = Ok(Strings::from_values(["#1b9e77", "#d95f02", "#7570b3"]));
+///     pub static FORMULA = R!("y ~ x + z");
+/// }
+/// test! {
+///     assert_eq!(PALETTE.get()?.len(), 3);
+///     let formula = FORMULA.get()?;
+///     assert!(formula.inherits("formula"));
+///     // The same object is returned each time.
+///     assert_eq!(unsafe { FORMULA.get()?.get() }, unsafe { formula.get() });
+/// }
+/// ```
+#[macro_export]
+macro_rules! lazy_r_static {
+    ($($(#[$attr: meta])* $vis: vis static $name: ident = $init: expr;)*) => {
+        $(
+            $(#[$attr])*
+            $vis static $name: $crate::handles::LazyRStatic = $crate::handles::LazyRStatic::new(|| {
+                let value: $crate::Result<_> = $init;
+                ::std::result::Result::Ok($crate::Robj::from(value?))
+            });
+        )*
+    };
+}
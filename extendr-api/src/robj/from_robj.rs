@@ -100,10 +100,23 @@ impl<'a> FromRobj<'a> for String {
     }
 }
 
+impl<'a> FromRobj<'a> for char {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        match char::try_from(robj) {
+            Ok(c) => Ok(c),
+            Err(Error::ExpectedChar(_)) => Err("Input must be a single character."),
+            Err(Error::MustNotBeNA(_)) => Err("Input must not be NA."),
+            Err(_) => Err("Not a string object."),
+        }
+    }
+}
+
 impl<'a> FromRobj<'a> for Vec<i32> {
     fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
         if let Some(v) = robj.as_integer_slice() {
             Ok(Vec::from(v))
+        } else if robj.is_real() {
+            Vec::<i32>::try_from(robj).map_err(|_| "not an integer or logical vector")
         } else {
             Err("not an integer or logical vector")
         }
@@ -114,6 +127,8 @@ impl<'a> FromRobj<'a> for Vec<f64> {
     fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
         if let Some(v) = robj.as_real_slice() {
             Ok(Vec::from(v))
+        } else if robj.is_integer() {
+            Vec::<f64>::try_from(robj).map_err(|_| "not a floating point vector")
         } else {
             Err("not a floating point vector")
         }
@@ -139,6 +154,17 @@ impl<'a> FromRobj<'a> for Vec<String> {
     }
 }
 
+impl<'a> FromRobj<'a> for Vec<char> {
+    fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+        match <Vec<char>>::try_from(robj) {
+            Ok(v) => Ok(v),
+            Err(Error::ExpectedChar(_)) => Err("Input elements must be single characters."),
+            Err(Error::MustNotBeNA(_)) => Err("Input vector cannot contain NA's."),
+            Err(_) => Err("Input must be a character vector."),
+        }
+    }
+}
+
 macro_rules! impl_iter_from_robj {
     ($t: ty, $iter_fn: ident, $msg: expr) => {
         impl<'a> FromRobj<'a> for $t {
@@ -281,6 +281,16 @@ impl_complex_tvv!(c64);
 impl_complex_tvv!(Rcplx);
 impl_complex_tvv!((f64, f64));
 
+impl ToVectorValue for Option<c64> {
+    fn sexptype() -> SEXPTYPE {
+        CPLXSXP
+    }
+
+    fn to_complex(&self) -> Rcomplex {
+        self.unwrap_or_else(c64::na).to_complex()
+    }
+}
+
 macro_rules! impl_integer_tvv {
     ($t: ty) => {
         impl ToVectorValue for $t {
@@ -394,6 +404,65 @@ macro_rules! impl_str_tvv {
 impl_str_tvv! {&str}
 impl_str_tvv! {String}
 
+macro_rules! impl_path_tvv {
+    ($t: ty) => {
+        impl ToVectorValue for $t {
+            fn sexptype() -> SEXPTYPE {
+                STRSXP
+            }
+
+            /// Paths that are not valid unicode are converted lossily.
+            fn to_sexp(&self) -> SEXP
+            where
+                Self: Sized,
+            {
+                str_to_character(&self.to_string_lossy())
+            }
+        }
+    };
+}
+
+macro_rules! impl_char_tvv {
+    ($t: ty) => {
+        impl ToVectorValue for $t {
+            fn sexptype() -> SEXPTYPE {
+                STRSXP
+            }
+
+            fn to_sexp(&self) -> SEXP
+            where
+                Self: Sized,
+            {
+                str_to_character(self.encode_utf8(&mut [0; 4]))
+            }
+        }
+    };
+}
+
+impl_char_tvv! {char}
+impl_char_tvv! {&char}
+
+impl ToVectorValue for Option<char> {
+    fn sexptype() -> SEXPTYPE {
+        STRSXP
+    }
+
+    fn to_sexp(&self) -> SEXP
+    where
+        Self: Sized,
+    {
+        if let Some(c) = self {
+            str_to_character(c.encode_utf8(&mut [0; 4]))
+        } else {
+            unsafe { R_NaString }
+        }
+    }
+}
+
+impl_path_tvv! {PathBuf}
+impl_path_tvv! {&PathBuf}
+impl_path_tvv! {&Path}
+
 impl ToVectorValue for bool {
     fn sexptype() -> SEXPTYPE {
         LGLSXP
@@ -472,47 +541,7 @@ where
         let sexptype = I::Item::sexptype();
         if sexptype != 0 {
             let res = Robj::alloc_vector(sexptype, len);
-            let sexp = res.get();
-            match sexptype {
-                REALSXP => {
-                    let ptr = REAL(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_real();
-                    }
-                }
-                CPLXSXP => {
-                    let ptr = COMPLEX(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_complex();
-                    }
-                }
-                INTSXP => {
-                    let ptr = INTEGER(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_integer();
-                    }
-                }
-                LGLSXP => {
-                    let ptr = LOGICAL(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_logical();
-                    }
-                }
-                STRSXP => {
-                    for (i, v) in iter.enumerate() {
-                        SET_STRING_ELT(sexp, i as isize, v.to_sexp());
-                    }
-                }
-                RAWSXP => {
-                    let ptr = RAW(sexp);
-                    for (i, v) in iter.enumerate() {
-                        *ptr.add(i) = v.to_raw();
-                    }
-                }
-                _ => {
-                    panic!("unexpected SEXPTYPE in collect_robj");
-                }
-            }
+            fill_vector(res.get(), sexptype, 0..len, &mut iter.fuse());
             res
         } else {
             Robj::from(())
@@ -520,6 +549,69 @@ where
     })
 }
 
+// Set the elements in `range` of a vector from `iter`, stopping early if
+// `iter` ends. Returns the index after the last element set.
+// Not thread safe.
+unsafe fn fill_vector<I>(sexp: SEXP, sexptype: u32, range: Range<usize>, iter: &mut I) -> usize
+where
+    I: Iterator,
+    I::Item: ToVectorValue,
+{
+    let mut i = range.start;
+    let values = iter.take(range.len());
+    match sexptype {
+        REALSXP => {
+            let ptr = REAL(sexp);
+            for v in values {
+                *ptr.add(i) = v.to_real();
+                i += 1;
+            }
+        }
+        CPLXSXP => {
+            let ptr = COMPLEX(sexp);
+            for v in values {
+                *ptr.add(i) = v.to_complex();
+                i += 1;
+            }
+        }
+        INTSXP => {
+            let ptr = INTEGER(sexp);
+            for v in values {
+                *ptr.add(i) = v.to_integer();
+                i += 1;
+            }
+        }
+        LGLSXP => {
+            let ptr = LOGICAL(sexp);
+            for v in values {
+                *ptr.add(i) = v.to_logical();
+                i += 1;
+            }
+        }
+        STRSXP => {
+            for v in values {
+                SET_STRING_ELT(sexp, i as isize, v.to_sexp());
+                i += 1;
+            }
+        }
+        RAWSXP => {
+            let ptr = RAW(sexp);
+            for v in values {
+                *ptr.add(i) = v.to_raw();
+                i += 1;
+            }
+        }
+        _ => {
+            panic!("unexpected SEXPTYPE in collect_robj");
+        }
+    }
+    i
+}
+
+/// The number of elements [RobjItertools::collect_robj_chunked()] sets
+/// between checks for user interrupts.
+pub const COLLECT_CHUNK_LEN: usize = 1 << 16;
+
 /// Extensions to iterators for R objects including [RobjItertools::collect_robj()].
 pub trait RobjItertools: Iterator {
     /// Convert a wide range of iterators to Robj.
@@ -561,6 +653,67 @@ pub trait RobjItertools: Iterator {
         vec.into_iter().collect_robj()
     }
 
+    /// Collect `len` elements into a vector allocated once by R, without a
+    /// rust buffer, checking for user interrupts every
+    /// [COLLECT_CHUNK_LEN] elements.
+    ///
+    /// This is for very long iterators, where [RobjItertools::collect_robj()]
+    /// would first collect into a `Vec` if the length is not known, and
+    /// where the user may want to stop with Ctrl-C. An interrupt returns
+    /// [Error::Interrupted], and an iterator with more or fewer than `len`
+    /// elements is an error.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let squares = (0..1_000_000_u64)
+    ///         .filter(|x| x % 2 == 0)
+    ///         .map(|x| (x * x) as f64)
+    ///         .collect_robj_chunked(500_000)?;
+    ///     assert_eq!(squares.len(), 500_000);
+    ///     assert_eq!(squares.as_real_slice().unwrap()[2], 16.0);
+    ///
+    ///     assert!((0..10).collect_robj_chunked(11).is_err());
+    ///     assert!((0..10).collect_robj_chunked(9).is_err());
+    /// }
+    /// ```
+    fn collect_robj_chunked(self, len: usize) -> Result<Robj>
+    where
+        Self: Iterator,
+        Self: Sized,
+        Self::Item: ToVectorValue,
+    {
+        let sexptype = Self::Item::sexptype();
+        if sexptype == 0 {
+            return Ok(Robj::from(()));
+        }
+        let res = Robj::alloc_vector(sexptype, len);
+        let mut iter = self.fuse();
+        let mut filled = 0;
+        while filled < len {
+            let end = (filled + COLLECT_CHUNK_LEN).min(len);
+            let set = single_threaded(|| unsafe {
+                fill_vector(res.get(), sexptype, filled..end, &mut iter)
+            });
+            if set < end {
+                return Err(Error::Other(format!(
+                    "expected {} elements, the iterator ended after {}",
+                    len, set
+                )));
+            }
+            filled = end;
+            if crate::functions::interrupt_pending() {
+                return Err(Error::Interrupted);
+            }
+        }
+        if iter.next().is_some() {
+            return Err(Error::Other(format!(
+                "expected {} elements, the iterator has more",
+                len
+            )));
+        }
+        Ok(res)
+    }
+
     /// Collects an iterable into an [`RArray`].
     /// The iterable must yield items column by column (aka Fortan order)
     ///
@@ -682,6 +835,52 @@ impl<T: ToVectorValue> From<Vec<T>> for Robj {
     }
 }
 
+impl<T: ToVectorValue + Clone> From<&VecDeque<T>> for Robj {
+    fn from(value: &VecDeque<T>) -> Self {
+        let len = value.len();
+        fixed_size_collect(value.iter().cloned(), len)
+    }
+}
+
+impl<T: ToVectorValue> From<VecDeque<T>> for Robj {
+    fn from(value: VecDeque<T>) -> Self {
+        let len = value.len();
+        fixed_size_collect(value.into_iter(), len)
+    }
+}
+
+impl<T: ToVectorValue + Clone> From<&BTreeSet<T>> for Robj {
+    /// Convert a set into a vector in ascending order.
+    fn from(value: &BTreeSet<T>) -> Self {
+        let len = value.len();
+        fixed_size_collect(value.iter().cloned(), len)
+    }
+}
+
+impl<T: ToVectorValue> From<BTreeSet<T>> for Robj {
+    /// Convert a set into a vector in ascending order.
+    fn from(value: BTreeSet<T>) -> Self {
+        let len = value.len();
+        fixed_size_collect(value.into_iter(), len)
+    }
+}
+
+impl<T: ToVectorValue + Clone, S> From<&HashSet<T, S>> for Robj {
+    /// Convert a set into a vector. The order of the elements is unspecified.
+    fn from(value: &HashSet<T, S>) -> Self {
+        let len = value.len();
+        fixed_size_collect(value.iter().cloned(), len)
+    }
+}
+
+impl<T: ToVectorValue, S> From<HashSet<T, S>> for Robj {
+    /// Convert a set into a vector. The order of the elements is unspecified.
+    fn from(value: HashSet<T, S>) -> Self {
+        let len = value.len();
+        fixed_size_collect(value.into_iter(), len)
+    }
+}
+
 impl<'a, T> From<&'a [T]> for Robj
 where
     Self: 'a,
@@ -703,6 +902,25 @@ impl From<Vec<Robj>> for Robj {
     }
 }
 
+impl<T: ToVectorValue> From<Vec<Vec<T>>> for Robj {
+    /// Convert a vector of vectors into a list, such as a list column of a data frame.
+    fn from(val: Vec<Vec<T>>) -> Self {
+        List::from_values(val.into_iter().map(Robj::from)).into()
+    }
+}
+
+impl<T: ToVectorValue> From<Vec<Option<Vec<T>>>> for Robj {
+    /// Convert a vector of optional vectors into a list with `NULL` for `None`,
+    /// such as a list column of a data frame.
+    fn from(val: Vec<Option<Vec<T>>>) -> Self {
+        List::from_values(
+            val.into_iter()
+                .map(|v| v.map_or_else(|| Robj::from(()), Robj::from)),
+        )
+        .into()
+    }
+}
+
 impl From<Vec<Rstr>> for Robj {
     /// Convert a vector of Rstr into strings.
     fn from(val: Vec<Rstr>) -> Self {
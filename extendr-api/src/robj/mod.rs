@@ -16,9 +16,10 @@ use std::os::raw;
 use crate::*;
 
 use crate::scalar::{Rbool, Rfloat, Rint};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::iter::IntoIterator;
 use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
 
 // deprecated
 mod from_robj;
@@ -27,7 +28,9 @@ mod debug;
 mod into_robj;
 mod operators;
 mod rinternals;
+mod robj_mut;
 mod try_from_robj;
+mod try_getters;
 
 #[cfg(test)]
 mod tests;
@@ -38,6 +41,7 @@ pub use iter::*;
 pub use operators::Operators;
 pub use operators::*;
 pub use rinternals::Rinternals;
+pub use robj_mut::RobjMut;
 
 /// Wrapper for an R S-expression pointer (SEXP).
 ///
@@ -167,6 +171,8 @@ pub trait Slices: GetSexp {
     /// Unless the type is correct, this will cause undefined behaviour.
     /// Creating this slice will also instatiate and Altrep objects.
     unsafe fn as_typed_slice_raw<T>(&self) -> &[T] {
+        #[cfg(feature = "strict-checks")]
+        check_typed_slice::<T>(self.get());
         let len = XLENGTH(self.get()) as usize;
         let data = DATAPTR_RO(self.get()) as *const T;
         std::slice::from_raw_parts(data, len)
@@ -180,6 +186,8 @@ pub trait Slices: GetSexp {
     /// Creating this slice will also instatiate and Altrep objects.
     /// Not all obejects (especially not list and strings) support this.
     unsafe fn as_typed_slice_raw_mut<T>(&mut self) -> &mut [T] {
+        #[cfg(feature = "strict-checks")]
+        check_typed_slice::<T>(self.get());
         let len = XLENGTH(self.get()) as usize;
         let data = DATAPTR(self.get_mut()) as *mut T;
         std::slice::from_raw_parts_mut(data, len)
@@ -188,6 +196,53 @@ pub trait Slices: GetSexp {
 
 impl Slices for Robj {}
 
+/// With `strict-checks`, panic unless `sexp` has one of the `expected` types.
+///
+/// Used to guard unchecked accessors such as `INTEGER_ELT` and `REAL_ELT`.
+#[cfg(feature = "strict-checks")]
+pub(crate) fn check_sexptype(sexp: SEXP, expected: &[SEXPTYPE], context: &str) {
+    let actual = unsafe { TYPEOF(sexp) as SEXPTYPE };
+    if !expected.contains(&actual) {
+        panic!(
+            "strict-checks: {} expected SEXPTYPE {:?}, got {}",
+            context, expected, actual
+        );
+    }
+}
+
+/// With `strict-checks`, panic if `index` is out of bounds for a vector of length `len`.
+#[cfg(feature = "strict-checks")]
+pub(crate) fn check_index(index: usize, len: usize, context: &str) {
+    if index >= len {
+        panic!(
+            "strict-checks: {} index {} out of bounds for length {}",
+            context, index, len
+        );
+    }
+}
+
+/// With `strict-checks`, panic unless `sexp` is a vector whose
+/// elements have the same size as `T`.
+#[cfg(feature = "strict-checks")]
+pub(crate) fn check_typed_slice<T>(sexp: SEXP) {
+    let actual = unsafe { TYPEOF(sexp) as SEXPTYPE };
+    let elt_size = match actual {
+        LGLSXP | INTSXP => std::mem::size_of::<i32>(),
+        REALSXP => std::mem::size_of::<f64>(),
+        CPLXSXP => std::mem::size_of::<Rcomplex>(),
+        RAWSXP => std::mem::size_of::<u8>(),
+        STRSXP | VECSXP | EXPRSXP => std::mem::size_of::<SEXP>(),
+        _ => panic!("strict-checks: SEXPTYPE {} has no data pointer", actual),
+    };
+    if elt_size != std::mem::size_of::<T>() {
+        panic!(
+            "strict-checks: cannot view SEXPTYPE {} as a slice of {}",
+            actual,
+            std::any::type_name::<T>()
+        );
+    }
+}
+
 pub trait Length: GetSexp {
     /// Get the extended length of the object.
     /// ```
@@ -358,6 +413,68 @@ impl Robj {
         }
     }
 
+    /// The addresses of the data of a vector, or `None` for other objects and
+    /// ALTREP vectors that have not been expanded.
+    fn data_range(&self) -> Option<Range<usize>> {
+        let elt_size = match self.sexptype() {
+            LGLSXP | INTSXP => std::mem::size_of::<i32>(),
+            REALSXP => std::mem::size_of::<f64>(),
+            CPLXSXP => std::mem::size_of::<Rcomplex>(),
+            RAWSXP => std::mem::size_of::<u8>(),
+            STRSXP | VECSXP | EXPRSXP => std::mem::size_of::<SEXP>(),
+            _ => return None,
+        };
+        let start = unsafe { DATAPTR_OR_NULL(self.get()) } as usize;
+        if start == 0 {
+            None
+        } else {
+            Some(start..start + self.len() * elt_size)
+        }
+    }
+
+    /// Return true if the two objects are the same or their data share memory.
+    ///
+    /// R passes the same vector to two arguments of a function in calls like
+    /// `f(x, x)`, so a function that writes to one argument while reading
+    /// another should check this first.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let x = r!([1.0, 2.0]);
+    ///     let y = x.clone();
+    ///     assert!(x.overlaps(&y));
+    ///     assert!(!x.overlaps(&r!([1.0, 2.0])));
+    /// }
+    /// ```
+    pub fn overlaps(&self, other: &Robj) -> bool {
+        if unsafe { self.get() == other.get() } {
+            return true;
+        }
+        match (self.data_range(), other.data_range()) {
+            (Some(a), Some(b)) => a.start < b.end && b.start < a.end,
+            _ => false,
+        }
+    }
+
+    /// Return true if the data of this vector share memory with `slice`.
+    pub fn overlaps_slice<T>(&self, slice: &[T]) -> bool {
+        let start = slice.as_ptr() as usize;
+        let end = start + std::mem::size_of_val(slice);
+        match self.data_range() {
+            Some(range) => range.start < end && start < range.end,
+            None => false,
+        }
+    }
+
+    /// Return [Error::Aliased] if this object and `other` share memory, see [Robj::overlaps].
+    pub fn check_no_alias(&self, other: &Robj) -> Result<()> {
+        if self.overlaps(other) {
+            Err(Error::Aliased(self.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Get a read-only reference to the content of an integer vector.
     /// ```
     /// use extendr_api::prelude::*;
@@ -488,7 +605,30 @@ impl Robj {
         self.as_real_slice().map(|value| value.to_vec())
     }
 
-    /// Get a read-only reference to the content of an integer or logical vector.
+    /// Get a read-only reference to the content of a complex vector.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let robj = r!([c64::new(1., 1.), c64::new(2., -1.)]);
+    ///     let slice = robj.as_complex_slice().unwrap();
+    ///     assert_eq!(slice, [c64::new(1., 1.), c64::new(2., -1.)]);
+    /// }
+    /// ```
+    pub fn as_complex_slice(&self) -> Option<&[c64]> {
+        self.as_typed_slice()
+    }
+
+    /// Get an iterator over complex elements of this slice.
+    pub fn as_complex_iter(&self) -> Option<impl Iterator<Item = &c64>> {
+        self.as_complex_slice().map(|slice| slice.iter())
+    }
+
+    /// Get a `Vec<c64>` copied from the object.
+    pub fn as_complex_vector(&self) -> Option<Vec<c64>> {
+        self.as_complex_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-only reference to the content of a raw vector.
     /// ```
     /// use extendr_api::prelude::*;
     /// test! {
@@ -500,8 +640,21 @@ impl Robj {
         self.as_typed_slice()
     }
 
-    /// Get a read-write reference to the content of an integer or logical vector.
+    /// Get a `Vec<u8>` copied from a raw vector.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let robj = r!(vec![1_u8, 2, 3]);
+    ///     assert_eq!(robj.as_raw_vector().unwrap(), vec![1, 2, 3]);
+    /// }
+    /// ```
+    pub fn as_raw_vector(&self) -> Option<Vec<u8>> {
+        self.as_raw_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-write reference to the content of an integer vector.
     /// Note that rust slices are 0-based so `slice[1]` is the middle value.
+    /// Objects from R are usually shared, so call [Robj::make_unique()] first.
     /// ```
     /// use extendr_api::prelude::*;
     /// test! {
@@ -515,8 +668,23 @@ impl Robj {
         self.as_typed_slice_mut()
     }
 
+    /// Get a read-write reference to the content of a logical vector.
+    /// Objects from R are usually shared, so call [Robj::make_unique()] first.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut robj = r!([TRUE, FALSE]);
+    ///     robj.as_logical_slice_mut().unwrap()[1] = NA_LOGICAL;
+    ///     assert_eq!(robj, r!([TRUE, NA_LOGICAL]));
+    /// }
+    /// ```
+    pub fn as_logical_slice_mut(&mut self) -> Option<&mut [Rbool]> {
+        self.as_typed_slice_mut()
+    }
+
     /// Get a read-write reference to the content of a double vector.
     /// Note that rust slices are 0-based so `slice[1]` is the middle value.
+    /// Objects from R are usually shared, so call [Robj::make_unique()] first.
     /// ```
     /// use extendr_api::prelude::*;
     /// test! {
@@ -530,7 +698,22 @@ impl Robj {
         self.as_typed_slice_mut()
     }
 
+    /// Get a read-write reference to the content of a complex vector.
+    /// Objects from R are usually shared, so call [Robj::make_unique()] first.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut robj = r!([c64::new(1., 0.), c64::new(2., 0.)]);
+    ///     robj.as_complex_slice_mut().unwrap()[1] = c64::new(0., 1.);
+    ///     assert_eq!(robj, R!("c(1+0i, 0+1i)")?);
+    /// }
+    /// ```
+    pub fn as_complex_slice_mut(&mut self) -> Option<&mut [c64]> {
+        self.as_typed_slice_mut()
+    }
+
     /// Get a read-write reference to the content of a raw vector.
+    /// Objects from R are usually shared, so call [Robj::make_unique()] first.
     /// ```
     /// use extendr_api::prelude::*;
     /// test! {
@@ -640,6 +823,14 @@ impl Robj {
         }
     }
 
+    /// Get a scalar complex number. `NA` is `None`.
+    pub fn as_complex(&self) -> Option<c64> {
+        match self.as_complex_slice() {
+            Some(slice) if slice.len() == 1 && !slice[0].is_na() => Some(slice[0]),
+            _ => None,
+        }
+    }
+
     /// Get a scalar rust boolean.
     /// ```
     /// use extendr_api::prelude::*;
@@ -694,12 +885,18 @@ pub trait Eval: GetSexp {
     }
 
     /// Evaluate the expression in R and return an error or an R object.
+    ///
+    /// An R error during evaluation is returned as [Error::RError] with the
+    /// message of the error, and does not jump over the calling rust frames.
     /// ```
     /// use extendr_api::prelude::*;
     /// test! {
     ///
     ///    let add = lang!("+", 1, 2);
     ///    assert_eq!(add.eval_with_env(&global_env()).unwrap(), r!(3));
+    ///
+    ///    let err = lang!("stop", "boom").eval_with_env(&global_env()).unwrap_err();
+    ///    assert!(matches!(&err, Error::RError(msg) if msg.contains("boom")));
     /// }
     /// ```
     fn eval_with_env(&self, env: &Environment) -> Result<Robj> {
@@ -707,7 +904,7 @@ pub trait Eval: GetSexp {
             let mut error: raw::c_int = 0;
             let res = R_tryEval(self.get(), env.get(), &mut error as *mut raw::c_int);
             if error != 0 {
-                Err(Error::EvalError(Robj::from_sexp(self.get())))
+                Err(Error::RError(last_error_message()))
             } else {
                 Ok(Robj::from_sexp(res))
             }
@@ -734,6 +931,17 @@ pub trait Eval: GetSexp {
 
 impl Eval for Robj {}
 
+// The message of the last R error, as from `geterrmessage()`.
+unsafe fn last_error_message() -> String {
+    let call = Robj::from_sexp(Rf_lang1(Symbol::from_string("geterrmessage").get()));
+    let mut error: raw::c_int = 0;
+    let message = Robj::from_sexp(R_tryEval(call.get(), R_BaseEnv, &mut error));
+    match message.as_str() {
+        Some(message) if error == 0 => message.trim_end().to_string(),
+        _ => "unknown R error".to_string(),
+    }
+}
+
 /// Generic access to typed slices in an Robj.
 pub trait AsTypedSlice<'a, T>
 where
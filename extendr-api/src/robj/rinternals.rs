@@ -40,6 +40,11 @@ pub trait Rinternals: Types + Conversions {
         unsafe { Rf_isEnvironment(self.get()) != 0 }
     }
 
+    /// Return true if this is an R6 object, an environment inheriting from `R6`.
+    fn is_r6(&self) -> bool {
+        self.is_environment() && self.as_robj().inherits("R6")
+    }
+
     /// Return true if this is an environment.
     fn is_promise(&self) -> bool {
         self.sexptype() == PROMSXP
@@ -258,6 +263,27 @@ pub trait Rinternals: Types + Conversions {
         R_ExternalPtrAddr(self.get()) as *mut T
     }
 
+    /// Move the value out of an external pointer made by `#[extendr]` impl
+    /// or [ExternalPtr], and clear the pointer.
+    ///
+    /// Later attempts to use the pointer return [Error::Consumed] and the
+    /// finalizer does nothing.
+    #[doc(hidden)]
+    unsafe fn take_external_ptr<T>(&self) -> Result<Box<T>> {
+        if !self.check_external_ptr_type::<T>() {
+            return Err(Error::ExpectedExternalPtrType(
+                self.as_robj().clone(),
+                std::any::type_name::<T>().into(),
+            ));
+        }
+        let ptr = self.external_ptr_addr::<T>();
+        if ptr.is_null() {
+            return Err(Error::Consumed(self.as_robj().clone()));
+        }
+        single_threaded(|| R_ClearExternalPtr(self.get()));
+        Ok(Box::from_raw(ptr))
+    }
+
     /// Internal function used to implement `#[extendr]` impl
     #[doc(hidden)]
     unsafe fn external_ptr_tag(&self) -> Robj {
@@ -0,0 +1,232 @@
+//! A uniquely owned, mutable R object.
+
+use super::*;
+use crate::ownership;
+
+/// An R object that nothing else refers to, which can be changed safely.
+///
+/// Cloning an [Robj] only adds a reference, and objects passed from R are
+/// usually bound to R variables too, so changing an `Robj` in place, with
+/// [Robj::as_real_slice_mut()] or [Attributes::set_attrib()], can change
+/// values that R code or other rust code can see.
+///
+/// `RobjMut` is copy-on-write. Converting an `Robj` duplicates it unless it
+/// is the only reference to the object, and every change through an
+/// `RobjMut` first checks that this is still true, so changes are never
+/// seen through another reference. [Robj::from()] turns it back into a
+/// shared `Robj` without copying.
+///
+/// The existing mutating methods of `Robj` are unchanged, so code can move
+/// to `RobjMut` one function at a time.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let x = r!([1.0, 2.0, 3.0]);
+///     let mut y = RobjMut::from(x.clone());
+///     y.as_real_slice_mut().unwrap()[0] = 10.0;
+///     y.set_attrib(sym!(units), "cm")?;
+///
+///     // `x` still refers to the original.
+///     assert_eq!(x, r!([1.0, 2.0, 3.0]));
+///     assert_eq!(x.get_attrib(sym!(units)), None);
+///
+///     let y = Robj::from(y);
+///     assert_eq!(y.as_real_slice().unwrap(), [10.0, 2.0, 3.0]);
+///     assert_eq!(y.get_attrib(sym!(units)), Some(r!("cm")));
+/// }
+/// ```
+pub struct RobjMut {
+    robj: Robj,
+}
+
+impl RobjMut {
+    /// True if the object has no other references from rust or from R.
+    ///
+    /// A clone of the shared `Robj` taken through [Deref] is another
+    /// reference, which makes the next change copy the object.
+    pub fn is_unique(&self) -> bool {
+        is_unique(&self.robj)
+    }
+
+    /// Get a read-write slice of the elements, such as `&mut [f64]` for a
+    /// double vector.
+    pub fn as_typed_slice_mut<'a, T>(&'a mut self) -> Option<&'a mut [T]>
+    where
+        Robj: AsTypedSlice<'a, T>,
+    {
+        AsTypedSlice::<T>::as_typed_slice_mut(self.make_unique())
+    }
+
+    /// Get a read-write slice of the elements of an integer vector.
+    pub fn as_integer_slice_mut(&mut self) -> Option<&mut [i32]> {
+        self.as_typed_slice_mut()
+    }
+
+    /// Get a read-write slice of the elements of a logical vector.
+    pub fn as_logical_slice_mut(&mut self) -> Option<&mut [Rbool]> {
+        self.as_typed_slice_mut()
+    }
+
+    /// Get a read-write slice of the elements of a double vector.
+    pub fn as_real_slice_mut(&mut self) -> Option<&mut [f64]> {
+        self.as_typed_slice_mut()
+    }
+
+    /// Get a read-write slice of the elements of a complex vector.
+    pub fn as_complex_slice_mut(&mut self) -> Option<&mut [c64]> {
+        self.as_typed_slice_mut()
+    }
+
+    /// Get a read-write slice of the bytes of a raw vector.
+    pub fn as_raw_slice_mut(&mut self) -> Option<&mut [u8]> {
+        self.as_typed_slice_mut()
+    }
+
+    /// Set an attribute, or remove it if `value` is `NULL`.
+    pub fn set_attrib<N, V>(&mut self, name: N, value: V) -> Result<&mut Self>
+    where
+        N: Into<Robj>,
+        V: Into<Robj>,
+    {
+        self.make_unique().set_attrib(name, value)?;
+        Ok(self)
+    }
+
+    /// Set the names attribute from a string iterator.
+    pub fn set_names<T>(&mut self, names: T) -> Result<&mut Self>
+    where
+        T: IntoIterator,
+        T::IntoIter: ExactSizeIterator,
+        T::Item: ToVectorValue + AsRef<str>,
+    {
+        self.make_unique().set_names(names)?;
+        Ok(self)
+    }
+
+    /// Set the class attribute from a string iterator.
+    pub fn set_class<T>(&mut self, class: T) -> Result<&mut Self>
+    where
+        T: IntoIterator,
+        T::IntoIter: ExactSizeIterator,
+        T::Item: ToVectorValue + AsRef<str>,
+    {
+        self.make_unique().set_class(class)?;
+        Ok(self)
+    }
+
+    // Duplicate the object if anything else refers to it.
+    fn make_unique(&mut self) -> &mut Robj {
+        self.robj.make_unique();
+        &mut self.robj
+    }
+}
+
+// Only this `Robj` handle refers to the object and R has not bound it to a
+// variable or put it in another object. The preservation vector of the
+// `ownership` module holds one reference, so R's count is one, not zero.
+fn is_unique(robj: &Robj) -> bool {
+    unsafe {
+        let sexp = robj.get();
+        sexp != R_NilValue && ownership::ref_count(sexp) <= 1 && MAYBE_SHARED(sexp) == 0
+    }
+}
+
+impl From<Robj> for RobjMut {
+    /// Take ownership of an object, duplicating it if it is shared.
+    fn from(robj: Robj) -> Self {
+        let mut res = RobjMut { robj };
+        res.make_unique();
+        res
+    }
+}
+
+impl From<&Robj> for RobjMut {
+    /// Make a mutable copy of an object.
+    fn from(robj: &Robj) -> Self {
+        RobjMut {
+            robj: robj.duplicate(),
+        }
+    }
+}
+
+impl From<RobjMut> for Robj {
+    /// Share the object, without copying it.
+    fn from(robj: RobjMut) -> Self {
+        robj.robj
+    }
+}
+
+impl Clone for RobjMut {
+    /// Copy the object, unlike `Robj::clone()` which adds a reference.
+    fn clone(&self) -> Self {
+        RobjMut::from(&self.robj)
+    }
+}
+
+impl Deref for RobjMut {
+    type Target = Robj;
+
+    /// Read the object with the methods of `Robj`.
+    fn deref(&self) -> &Robj {
+        &self.robj
+    }
+}
+
+impl PartialEq<Robj> for RobjMut {
+    fn eq(&self, other: &Robj) -> bool {
+        self.robj == *other
+    }
+}
+
+impl PartialEq for RobjMut {
+    fn eq(&self, other: &RobjMut) -> bool {
+        self.robj == other.robj
+    }
+}
+
+impl std::fmt::Debug for RobjMut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.robj, f)
+    }
+}
+
+impl Robj {
+    /// Make a uniquely owned, mutable version of this object, see [RobjMut].
+    /// The object is duplicated unless this is its only reference.
+    pub fn into_mut(self) -> RobjMut {
+        RobjMut::from(self)
+    }
+
+    /// True if an R variable, another R object or another `Robj` refers to
+    /// this object, so that changing it in place would be seen elsewhere.
+    pub fn is_shared(&self) -> bool {
+        !is_unique(self)
+    }
+
+    /// Replace this object with a copy if it is shared, so that it can be
+    /// changed in place with methods like [Robj::as_real_slice_mut()].
+    /// Returns true if the object was copied.
+    ///
+    /// This is what R does before changing a vector, as in `x[1] <- 0`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut x = eval_string("x <- c(1, 2); x")?;
+    ///     assert!(x.make_unique());
+    ///     x.as_real_slice_mut().unwrap().iter_mut().for_each(|x| *x *= 10.0);
+    ///     assert_eq!(x, r!([10.0, 20.0]));
+    ///     assert_eq!(eval_string("x")?, r!([1.0, 2.0]));
+    ///
+    ///     // The copy is now unique.
+    ///     assert!(!x.make_unique());
+    /// }
+    /// ```
+    pub fn make_unique(&mut self) -> bool {
+        if is_unique(self) {
+            false
+        } else {
+            *self = self.duplicate();
+            true
+        }
+    }
+}
@@ -358,3 +358,45 @@ fn input_iterator_test() {
         */
     }
 }
+
+#[test]
+fn std_collections_test() {
+    use std::collections::{BTreeSet, HashSet, VecDeque};
+    test! {
+        let deque: VecDeque<i32> = [1, 2, 3].into_iter().collect();
+        assert_eq!(Robj::from(&deque), r!([1, 2, 3]));
+        assert_eq!(<VecDeque<i32>>::try_from(r!([1, 2, 3]))?, deque);
+        assert_eq!(<VecDeque<String>>::try_from(r!(["a", "b"]))?, ["a", "b"]);
+
+        let set: BTreeSet<i32> = [3, 1, 2].into_iter().collect();
+        assert_eq!(Robj::from(&set), r!([1, 2, 3]));
+        assert_eq!(<BTreeSet<i32>>::try_from(r!([2, 1, 2, 3]))?, set);
+
+        let set: HashSet<String> = ["a".to_string()].into_iter().collect();
+        assert_eq!(Robj::from(set.clone()), r!("a"));
+        assert_eq!(<HashSet<String>>::try_from(r!(["a", "a"]))?, set);
+
+        assert!(r!([1, 1]).try_as_unique_btree_set::<i32>().is_err());
+        assert!(r!(["a", "b"]).try_as_unique_hash_set::<String>().is_ok());
+    }
+}
+
+#[test]
+fn char_test() {
+    test! {
+        assert_eq!(Robj::from('a'), r!("a"));
+        assert_eq!(Robj::from('é'), r!("é"));
+        assert_eq!(Robj::from(vec!['a', 'b']), r!(["a", "b"]));
+        assert_eq!(Robj::from([Some('a'), None]), r!([Some("a"), None]));
+
+        assert_eq!(char::try_from(r!("x")), Ok('x'));
+        assert_eq!(char::try_from(r!("xy")), Err(Error::ExpectedChar(r!("xy"))));
+        assert_eq!(char::try_from(r!("")), Err(Error::ExpectedChar(r!(""))));
+        assert_eq!(char::try_from(r!(NA_STRING)), Err(Error::MustNotBeNA(r!(NA_STRING))));
+        assert_eq!(<Option<char>>::try_from(r!(NA_STRING)), Ok(None));
+
+        assert_eq!(<Vec<char>>::try_from(r!(["a", "b"])), Ok(vec!['a', 'b']));
+        assert!(<Vec<char>>::try_from(r!(["a", "bc"])).is_err());
+        assert_eq!(<char>::from_robj(&r!("xy")), Err("Input must be a single character."));
+    }
+}
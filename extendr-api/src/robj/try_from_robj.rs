@@ -1,6 +1,8 @@
 //! Conversions to Robj
 
 use super::*;
+use crate as extendr_api;
+use crate::scalar::Scalar;
 
 macro_rules! impl_try_from_scalar_integer {
     ($t:ty) => {
@@ -21,6 +23,11 @@ macro_rules! impl_try_from_scalar_integer {
                     return Err(Error::MustNotBeNA(robj.clone()));
                 }
 
+                // A raw scalar is a byte, which has no NA.
+                if let Some(v) = robj.as_raw_slice() {
+                    return Self::try_from(v[0]).map_err(|_| Error::OutOfLimits(robj.clone()));
+                }
+
                 // If the conversion is int-to-int, check the limits. This
                 // needs to be done by `TryFrom` because the conversion by `as`
                 // is problematic when converting a negative value to unsigned
@@ -149,16 +156,44 @@ impl TryFrom<&Robj> for String {
     }
 }
 
+/// Get the only character of `s`, a string from `robj`.
+///
+/// A character is a single unicode code point, so some accented letters
+/// and emoji that display as one character are rejected.
+fn single_char(robj: &Robj, s: &str) -> Result<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(Error::ExpectedChar(robj.clone())),
+    }
+}
+
+impl TryFrom<&Robj> for char {
+    type Error = Error;
+
+    /// Convert a scalar STRSXP object holding a one character string into a char.
+    /// NAs, empty strings and longer strings are not allowed.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        single_char(robj, <&str>::try_from(robj)?)
+    }
+}
+
 impl TryFrom<&Robj> for Vec<i32> {
     type Error = Error;
 
     /// Convert an INTSXP object into a vector of i32 (integer).
+    /// A REALSXP object of whole numbers is also accepted, see [crate::cast].
     /// Note: Unless you plan to store the result, use a slice instead.
     /// Use `value.is_na()` to detect NA values.
     fn try_from(robj: &Robj) -> Result<Self> {
         if let Some(v) = robj.as_typed_slice() {
             // TODO: check NAs
             Ok(Vec::from(v))
+        } else if let Ok(doubles) = Doubles::try_from(robj) {
+            let (integers, _) =
+                crate::cast::doubles_to_integers(&doubles, &crate::cast::CastPolicy::strict())
+                    .map_err(|_| Error::ExpectedInteger(robj.clone()))?;
+            Ok(integers.iter().map(|value| value.inner()).collect())
         } else {
             Err(Error::ExpectedInteger(robj.clone()))
         }
@@ -169,12 +204,16 @@ impl TryFrom<&Robj> for Vec<f64> {
     type Error = Error;
 
     /// Convert a REALSXP object into a vector of f64 (double precision floating point).
+    /// An INTSXP object is also accepted, see [crate::cast].
     /// Note: Unless you plan to store the result, use a slice instead.
     /// Use `value.is_na()` to detect NA values.
     fn try_from(robj: &Robj) -> Result<Self> {
         if let Some(v) = robj.as_typed_slice() {
             // TODO: check NAs
             Ok(Vec::from(v))
+        } else if let Ok(integers) = Integers::try_from(robj) {
+            let doubles = crate::cast::integers_to_doubles(&integers);
+            Ok(doubles.iter().map(|value| value.inner()).collect())
         } else {
             Err(Error::ExpectedReal(robj.clone()))
         }
@@ -272,6 +311,26 @@ impl TryFrom<&Robj> for Vec<String> {
     }
 }
 
+impl TryFrom<&Robj> for Vec<char> {
+    type Error = Error;
+
+    /// Convert a STRSXP object whose elements are one character strings into a vector of `char`s.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if let Some(iter) = robj.as_str_iter() {
+            iter.map(|s| {
+                if s.is_na() {
+                    Err(Error::MustNotBeNA(robj.clone()))
+                } else {
+                    single_char(robj, s)
+                }
+            })
+            .collect()
+        } else {
+            Err(Error::ExpectedString(robj.clone()))
+        }
+    }
+}
+
 impl TryFrom<&Robj> for &[i32] {
     type Error = Error;
 
@@ -384,6 +443,166 @@ impl TryFrom<&Robj> for Rcplx {
     }
 }
 
+impl TryFrom<&Robj> for c64 {
+    type Error = Error;
+
+    /// Convert a complex or numeric scalar to a complex number.
+    /// `NA` is an error, use `Option<c64>` to accept it.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let value = Rcplx::try_from(robj)?;
+        if value.is_na() {
+            Err(Error::MustNotBeNA(robj.clone()))
+        } else {
+            Ok(value.inner())
+        }
+    }
+}
+
+impl TryFrom<&Robj> for Vec<c64> {
+    type Error = Error;
+
+    /// Convert a CPLXSXP object into a vector of complex numbers.
+    /// Note: Unless you plan to store the result, use a slice instead.
+    /// Use `value.is_na()` to detect NA values.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        robj.try_as_complex_vector()
+    }
+}
+
+impl TryFrom<&Robj> for &[c64] {
+    type Error = Error;
+
+    /// Convert a CPLXSXP object into a slice of complex numbers.
+    /// Use `value.is_na()` to detect NA values.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        robj.try_as_complex_slice()
+    }
+}
+
+/// Apply R's `path.expand()` to a character vector and convert it to UTF-8.
+///
+/// On Windows, `path.expand()` may give 8.3 short names (e.g.
+/// `C:/Users/RUNNER~1`), which are expanded with `normalizePath()`.
+fn expand_r_paths(robj: &Robj) -> Result<Vec<PathBuf>> {
+    let expanded = call!("enc2utf8", call!("path.expand", robj)?)?;
+    let paths = expanded
+        .as_str_iter()
+        .ok_or_else(|| Error::ExpectedString(robj.clone()))?;
+    paths
+        .map(|path| {
+            if cfg!(windows) {
+                expand_short_names(std::path::Path::new(path))
+            } else {
+                Ok(PathBuf::from(path))
+            }
+        })
+        .collect()
+}
+
+// `normalizePath()` only knows the long names of files that exist, so the
+// longest existing part of the path is expanded if it has a short name, and
+// the rest is kept as it is.
+fn expand_short_names(path: &std::path::Path) -> Result<PathBuf> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists());
+    let existing = match existing {
+        Some(existing) if existing.iter().any(is_short_name) => existing,
+        _ => return Ok(path.to_path_buf()),
+    };
+    let existing_str = existing
+        .to_str()
+        .ok_or_else(|| Error::Other(format!("{:?} is not valid UTF-8", existing)))?;
+    let long = call!(
+        "normalizePath",
+        existing_str,
+        winslash = "/",
+        mustWork = false
+    )?;
+    let long = PathBuf::from(<&str>::try_from(&long)?);
+    // `existing` is an ancestor of `path`, so this can't fail.
+    let rest = path.strip_prefix(existing).unwrap();
+    if rest.as_os_str().is_empty() {
+        Ok(long)
+    } else {
+        Ok(long.join(rest))
+    }
+}
+
+// An 8.3 short name has a `~` followed by digits, as in `PROGRA~1` or `LONGNA~2.TXT`.
+fn is_short_name(name: &std::ffi::OsStr) -> bool {
+    let name = match name.to_str() {
+        Some(name) => name,
+        None => return false,
+    };
+    match name.split_once('~') {
+        Some((stem, tail)) => {
+            let digits = tail.split('.').next().unwrap_or("");
+            !stem.is_empty() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+impl TryFrom<&Robj> for PathBuf {
+    type Error = Error;
+
+    /// Convert a scalar STRSXP object into a path, with `path.expand()` semantics.
+    /// NAs are not allowed.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use std::path::PathBuf;
+    /// test! {
+    ///     let path: PathBuf = r!("data/file.csv").try_into()?;
+    ///     assert_eq!(path, PathBuf::from("data/file.csv"));
+    ///     let home: PathBuf = r!("~").try_into()?;
+    ///     assert!(!home.starts_with("~"));
+    ///     assert!(PathBuf::try_from(r!(NA_STRING)).is_err());
+    /// }
+    /// ```
+    fn try_from(robj: &Robj) -> Result<Self> {
+        // Checks for a non-NA string scalar.
+        <&str>::try_from(robj)?;
+        Ok(expand_r_paths(robj)?.remove(0))
+    }
+}
+
+impl TryFrom<&Robj> for Vec<PathBuf> {
+    type Error = Error;
+
+    /// Convert a STRSXP object into a vector of paths, with `path.expand()` semantics.
+    /// NAs are not allowed.
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let mut strings = robj
+            .as_str_iter()
+            .ok_or_else(|| Error::ExpectedString(robj.clone()))?;
+        if strings.any(|s| s.is_na()) {
+            return Err(Error::MustNotBeNA(robj.clone()));
+        }
+        expand_r_paths(robj)
+    }
+}
+
+// Collections are built from the vector conversions above.
+// Sets silently drop repeated values, see [Robj::try_as_unique_hash_set()] for a checked version.
+macro_rules! impl_try_from_robj_collection {
+    ($collection:ident: $($t:ty)*) => {
+        $(
+            impl TryFrom<&Robj> for $collection<$t> {
+                type Error = Error;
+
+                fn try_from(robj: &Robj) -> Result<Self> {
+                    Ok(<Vec<$t>>::try_from(robj)?.into_iter().collect())
+                }
+            }
+        )*
+    }
+}
+
+impl_try_from_robj_collection!(VecDeque: Rint Rfloat Rbool Rcplx c64 u8 i32 f64 String);
+impl_try_from_robj_collection!(HashSet: u8 i32 String);
+impl_try_from_robj_collection!(BTreeSet: u8 i32 String);
+
 // Convert TryFrom<&Robj> into TryFrom<Robj>. Sadly, we are unable to make a blanket
 // conversion using GetSexp with the current version of Rust.
 macro_rules! impl_try_from_robj {
@@ -425,8 +644,14 @@ impl_try_from_robj!(
     i8 i16 i32 i64 isize
     bool
     Rint Rfloat Rbool Rcplx
-    f32 f64
-    Vec::<Rint> Vec::<Rfloat> Vec::<Rbool> Vec::<Rcplx> Vec::<u8> Vec::<i32> Vec::<f64>
-    &[Rint] &[Rfloat] &[Rbool] &[Rcplx] &[u8] &[i32] &[f64]
+    f32 f64 c64
+    Vec::<Rint> Vec::<Rfloat> Vec::<Rbool> Vec::<Rcplx> Vec::<u8> Vec::<i32> Vec::<f64> Vec::<c64>
+    &[Rint] &[Rfloat] &[Rbool] &[Rcplx] &[u8] &[i32] &[f64] &[c64]
     &str String
+    char Vec::<char>
+    PathBuf Vec::<PathBuf>
+    VecDeque::<Rint> VecDeque::<Rfloat> VecDeque::<Rbool> VecDeque::<Rcplx> VecDeque::<c64>
+    VecDeque::<u8> VecDeque::<i32> VecDeque::<f64> VecDeque::<String>
+    HashSet::<u8> HashSet::<i32> HashSet::<String>
+    BTreeSet::<u8> BTreeSet::<i32> BTreeSet::<String>
 );
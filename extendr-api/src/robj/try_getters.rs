@@ -0,0 +1,280 @@
+//! Non-panicking getters for Robj.
+//!
+//! Each `try_` method returns an [Error] describing why the value could not be
+//! read, and never panics. They check the same things as the `Option` returning
+//! getters, which stay cheap type checks, and only make the error on failure.
+
+use super::*;
+
+impl Robj {
+    /// Get the length of a vector, pairlist or environment, or an error for other objects.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert_eq!(r!([1, 2, 3]).try_len(), Ok(3));
+    ///     assert!(sym!(x).try_len().is_err());
+    /// }
+    /// ```
+    pub fn try_len(&self) -> Result<usize> {
+        if self.is_vector() || self.is_pairlist() || self.is_environment() || self.is_null() {
+            Ok(self.len())
+        } else {
+            Err(Error::ExpectedVector(self.clone()))
+        }
+    }
+
+    fn try_typed_slice<'a, T>(&self, err: fn(Robj) -> Error) -> Result<&'a [T]>
+    where
+        Robj: AsTypedSlice<'a, T>,
+    {
+        self.as_typed_slice().ok_or_else(|| err(self.clone()))
+    }
+
+    fn try_typed_slice_mut<'a, T>(&mut self, err: fn(Robj) -> Error) -> Result<&'a mut [T]>
+    where
+        Robj: AsTypedSlice<'a, T>,
+    {
+        let robj = self.clone();
+        self.as_typed_slice_mut().ok_or_else(|| err(robj))
+    }
+
+    fn try_scalar<T: Copy>(&self, slice: &[T]) -> Result<T> {
+        match slice.len() {
+            0 => Err(Error::ExpectedNonZeroLength(self.clone())),
+            1 => Ok(slice[0]),
+            _ => Err(Error::ExpectedScalar(self.clone())),
+        }
+    }
+
+    /// Get a read-only reference to the content of an integer vector.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert_eq!(r!([1, 2, 3]).try_as_integer_slice()?, [1, 2, 3]);
+    ///     assert!(r!(1.0).try_as_integer_slice().is_err());
+    /// }
+    /// ```
+    pub fn try_as_integer_slice<'a>(&self) -> Result<&'a [i32]> {
+        self.try_typed_slice(Error::ExpectedInteger)
+    }
+
+    /// Get a `Vec<i32>` copied from the object.
+    pub fn try_as_integer_vector(&self) -> Result<Vec<i32>> {
+        self.try_as_integer_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-only reference to the content of a logical vector.
+    pub fn try_as_logical_slice(&self) -> Result<&[Rbool]> {
+        self.try_typed_slice(Error::ExpectedLogical)
+    }
+
+    /// Get a `Vec<Rbool>` copied from the object.
+    pub fn try_as_logical_vector(&self) -> Result<Vec<Rbool>> {
+        self.try_as_logical_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-only reference to the content of a double vector.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert_eq!(r!([1.0, 2.0]).try_as_real_slice()?, [1.0, 2.0]);
+    ///     assert_eq!(r!("a").try_as_real_slice(), Err(Error::ExpectedReal(r!("a"))));
+    /// }
+    /// ```
+    pub fn try_as_real_slice(&self) -> Result<&[f64]> {
+        self.try_typed_slice(Error::ExpectedReal)
+    }
+
+    /// Get a `Vec<f64>` copied from the object.
+    pub fn try_as_real_vector(&self) -> Result<Vec<f64>> {
+        self.try_as_real_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-only reference to the content of a complex vector.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let robj = r!([c64::new(1.0, 2.0), c64::new(0.0, -1.0)]);
+    ///     assert_eq!(robj.try_as_complex_slice()?, [c64::new(1.0, 2.0), c64::new(0.0, -1.0)]);
+    ///     assert!(r!(1.0).try_as_complex_slice().is_err());
+    /// }
+    /// ```
+    pub fn try_as_complex_slice<'a>(&self) -> Result<&'a [c64]> {
+        self.try_typed_slice(Error::ExpectedComplex)
+    }
+
+    /// Get a `Vec<c64>` copied from the object.
+    pub fn try_as_complex_vector(&self) -> Result<Vec<c64>> {
+        self.try_as_complex_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-only reference to the content of a raw vector.
+    pub fn try_as_raw_slice(&self) -> Result<&[u8]> {
+        self.try_typed_slice(Error::ExpectedRaw)
+    }
+
+    /// Get a `Vec<u8>` copied from a raw vector.
+    pub fn try_as_raw_vector(&self) -> Result<Vec<u8>> {
+        self.try_as_raw_slice().map(|value| value.to_vec())
+    }
+
+    /// Get a read-write reference to the content of an integer vector.
+    pub fn try_as_integer_slice_mut(&mut self) -> Result<&mut [i32]> {
+        self.try_typed_slice_mut(Error::ExpectedInteger)
+    }
+
+    /// Get a read-write reference to the content of a logical vector.
+    pub fn try_as_logical_slice_mut(&mut self) -> Result<&mut [Rbool]> {
+        self.try_typed_slice_mut(Error::ExpectedLogical)
+    }
+
+    /// Get a read-write reference to the content of a double vector.
+    pub fn try_as_real_slice_mut(&mut self) -> Result<&mut [f64]> {
+        self.try_typed_slice_mut(Error::ExpectedReal)
+    }
+
+    /// Get a read-write reference to the content of a complex vector.
+    pub fn try_as_complex_slice_mut(&mut self) -> Result<&mut [c64]> {
+        self.try_typed_slice_mut(Error::ExpectedComplex)
+    }
+
+    /// Get a read-write reference to the content of a raw vector.
+    pub fn try_as_raw_slice_mut(&mut self) -> Result<&mut [u8]> {
+        self.try_typed_slice_mut(Error::ExpectedRaw)
+    }
+
+    /// Get a vector of owned strings.
+    pub fn try_as_string_vector(&self) -> Result<Vec<String>> {
+        self.try_as_str_vector()
+            .map(|v| v.into_iter().map(str::to_string).collect())
+    }
+
+    /// Get a vector of string references.
+    pub fn try_as_str_vector(&self) -> Result<Vec<&str>> {
+        self.as_str_iter()
+            .map(|iter| iter.collect())
+            .ok_or_else(|| Error::ExpectedString(self.clone()))
+    }
+
+    /// Get a read-only reference to a scalar string.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert_eq!(r!("xyz").try_as_str(), Ok("xyz"));
+    ///     assert_eq!(r!(["a", "b"]).try_as_str(), Err(Error::ExpectedScalar(r!(["a", "b"]))));
+    ///     assert_eq!(r!(1).try_as_str(), Err(Error::ExpectedString(r!(1))));
+    /// }
+    /// ```
+    pub fn try_as_str<'a>(&self) -> Result<&'a str> {
+        if self.sexptype() != STRSXP {
+            return Err(Error::ExpectedString(self.clone()));
+        }
+        match self.len() {
+            0 => Err(Error::ExpectedNonZeroLength(self.clone())),
+            1 => unsafe { Ok(to_str(R_CHAR(STRING_ELT(self.get(), 0)) as *const u8)) },
+            _ => Err(Error::ExpectedScalar(self.clone())),
+        }
+    }
+
+    /// Get a scalar integer. `NA` is an error.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert_eq!(r!(1).try_as_integer(), Ok(1));
+    ///     assert_eq!(r!(NA_INTEGER).try_as_integer(), Err(Error::MustNotBeNA(r!(NA_INTEGER))));
+    /// }
+    /// ```
+    pub fn try_as_integer(&self) -> Result<i32> {
+        let value = self.try_scalar(self.try_as_integer_slice()?)?;
+        if value.is_na() {
+            Err(Error::MustNotBeNA(self.clone()))
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Get a scalar real. `NA` is an error.
+    pub fn try_as_real(&self) -> Result<f64> {
+        let value = self.try_scalar(self.try_as_real_slice()?)?;
+        if value.is_na() {
+            Err(Error::MustNotBeNA(self.clone()))
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Get a scalar complex number. `NA` is an error.
+    pub fn try_as_complex(&self) -> Result<c64> {
+        let value = self.try_scalar(self.try_as_complex_slice()?)?;
+        if value.is_na() {
+            Err(Error::MustNotBeNA(self.clone()))
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Get a scalar rust boolean. `NA` is an error.
+    pub fn try_as_bool(&self) -> Result<bool> {
+        let value = self.try_as_logical()?;
+        if value.is_na() {
+            Err(Error::MustNotBeNA(self.clone()))
+        } else {
+            Ok(value.is_true())
+        }
+    }
+
+    /// Get a scalar boolean as a tri-boolean [Rbool] value.
+    pub fn try_as_logical(&self) -> Result<Rbool> {
+        self.try_scalar(self.try_as_logical_slice()?)
+    }
+
+    /// Convert a vector to a [HashSet], failing if it has repeated values.
+    ///
+    /// `HashSet::try_from()` drops repeated values instead.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use std::collections::HashSet;
+    /// test! {
+    ///     let set: HashSet<i32> = r!([1, 2, 3]).try_as_unique_hash_set()?;
+    ///     assert_eq!(set.len(), 3);
+    ///     assert!(r!([1, 2, 2]).try_as_unique_hash_set::<i32>().is_err());
+    /// }
+    /// ```
+    pub fn try_as_unique_hash_set<T>(&self) -> Result<HashSet<T>>
+    where
+        T: Eq + std::hash::Hash,
+        Vec<T>: for<'a> TryFrom<&'a Robj, Error = Error>,
+    {
+        let values = Vec::<T>::try_from(self)?;
+        let len = values.len();
+        let set: HashSet<T> = values.into_iter().collect();
+        self.check_unique(len, set.len())?;
+        Ok(set)
+    }
+
+    /// Convert a vector to a [BTreeSet], failing if it has repeated values.
+    ///
+    /// `BTreeSet::try_from()` drops repeated values instead.
+    pub fn try_as_unique_btree_set<T>(&self) -> Result<BTreeSet<T>>
+    where
+        T: Ord,
+        Vec<T>: for<'a> TryFrom<&'a Robj, Error = Error>,
+    {
+        let values = Vec::<T>::try_from(self)?;
+        let len = values.len();
+        let set: BTreeSet<T> = values.into_iter().collect();
+        self.check_unique(len, set.len())?;
+        Ok(set)
+    }
+
+    fn check_unique(&self, len: usize, unique_len: usize) -> Result<()> {
+        if len == unique_len {
+            Ok(())
+        } else {
+            Err(Error::Other(format!(
+                "expected unique values, found {} repeated",
+                len - unique_len
+            )))
+        }
+    }
+}
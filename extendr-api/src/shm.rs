@@ -0,0 +1,373 @@
+//! Share vectors between R processes through POSIX shared memory. Experimental.
+//!
+//! [share] copies the data of an atomic vector, and its attributes, into a
+//! named shared memory segment. [open] maps the segment in another process
+//! and returns an ALTREP vector that reads the shared memory directly,
+//! so parallel workers can use large inputs without unserializing a copy.
+//!
+//! The mapping is private, so a process that modifies its vector gets its
+//! own copy of the changed pages and other processes are not affected.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::shm;
+//! test! {
+//!     let x = R!("c(a = 1, b = 2, c = 3)")?;
+//!     let segment = shm::share("extendr_shm_doctest", &x)?;
+//!
+//!     // Usually in another process, given the name.
+//!     let y = shm::open(segment.name())?;
+//!     assert_eq!(y, x);
+//! }
+//! ```
+use crate::scalar::{Rfloat, Rint};
+use crate::*;
+use std::ffi::CString;
+use std::rc::Rc;
+
+const MAGIC: [u8; 8] = *b"EXTRSHM1";
+
+// The start of a segment. The data follows the header and the
+// serialized attributes follow the data.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Header {
+    magic: [u8; 8],
+    sexptype: u32,
+    elt_size: u32,
+    len: u64,
+    attributes_len: u64,
+}
+
+const HEADER_SIZE: usize = std::mem::size_of::<Header>();
+
+fn os_error(what: &str, name: &str) -> Error {
+    Error::Other(format!(
+        "{} '{}': {}",
+        what,
+        name,
+        std::io::Error::last_os_error()
+    ))
+}
+
+fn segment_name(name: &str) -> Result<CString> {
+    let name = if name.starts_with('/') {
+        name.to_string()
+    } else {
+        format!("/{}", name)
+    };
+    CString::new(name).map_err(|e| Error::Other(e.to_string()))
+}
+
+fn elt_size(rtype: Rtype) -> Option<usize> {
+    match rtype {
+        Rtype::Logicals | Rtype::Integers => Some(4),
+        Rtype::Doubles => Some(8),
+        Rtype::Complexes => Some(16),
+        Rtype::Raw => Some(1),
+        _ => None,
+    }
+}
+
+/// A shared memory segment made by [share].
+///
+/// The name is removed when this is dropped. Processes that have already
+/// opened the segment keep their vectors.
+#[derive(Debug)]
+pub struct SharedSegment {
+    name: String,
+    size: usize,
+}
+
+impl SharedSegment {
+    /// The name to pass to [open] in other processes.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The size of the segment in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Drop for SharedSegment {
+    fn drop(&mut self) {
+        if let Ok(name) = segment_name(&self.name) {
+            unsafe { libc::shm_unlink(name.as_ptr()) };
+        }
+    }
+}
+
+/// Copy a logical, integer, double, complex or raw vector into a new shared
+/// memory segment called `name`.
+///
+/// Attributes, such as names and class, are serialized with the data.
+pub fn share(name: &str, robj: &Robj) -> Result<SharedSegment> {
+    let elt_size = elt_size(robj.rtype()).ok_or_else(|| Error::ExpectedVector(robj.clone()))?;
+    let attributes = if unsafe { ATTRIB(robj.get()) != R_NilValue } {
+        Raw::try_from(eval_string_with_params(
+            "serialize(attributes(param.0), NULL)",
+            &[robj],
+        )?)?
+    } else {
+        Raw::new(0)
+    };
+    let attributes = attributes.as_slice();
+    let data_len = robj.len() * elt_size;
+    let size = HEADER_SIZE + data_len + attributes.len();
+    let header = Header {
+        magic: MAGIC,
+        sexptype: robj.sexptype(),
+        elt_size: elt_size as u32,
+        len: robj.len() as u64,
+        attributes_len: attributes.len() as u64,
+    };
+
+    let cname = segment_name(name)?;
+    unsafe {
+        let fd = libc::shm_open(
+            cname.as_ptr(),
+            libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+            0o600,
+        );
+        if fd < 0 {
+            return Err(os_error("can't create shared memory", name));
+        }
+        let segment = SharedSegment {
+            name: name.to_string(),
+            size,
+        };
+        // A zero sized mapping is an error, so there is always a header.
+        if libc::ftruncate(fd, size as libc::off_t) != 0 {
+            libc::close(fd);
+            return Err(os_error("can't size shared memory", name));
+        }
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        );
+        libc::close(fd);
+        if ptr == libc::MAP_FAILED {
+            return Err(os_error("can't map shared memory", name));
+        }
+        let ptr = ptr as *mut u8;
+        std::ptr::write_unaligned(ptr as *mut Header, header);
+        if data_len != 0 {
+            std::ptr::copy_nonoverlapping(
+                DATAPTR(robj.get()) as *const u8,
+                ptr.add(HEADER_SIZE),
+                data_len,
+            );
+        }
+        std::ptr::copy_nonoverlapping(
+            attributes.as_ptr(),
+            ptr.add(HEADER_SIZE + data_len),
+            attributes.len(),
+        );
+        libc::munmap(ptr as *mut libc::c_void, size);
+        Ok(segment)
+    }
+}
+
+/// Map the shared memory segment `name`, made by [share], as an R vector.
+///
+/// The vector is read only, R copies it before any modification.
+pub fn open(name: &str) -> Result<Robj> {
+    let cname = segment_name(name)?;
+    let mapping = unsafe {
+        let fd = libc::shm_open(cname.as_ptr(), libc::O_RDONLY, 0);
+        if fd < 0 {
+            return Err(os_error("can't open shared memory", name));
+        }
+        let mut stat: libc::stat = std::mem::zeroed();
+        if libc::fstat(fd, &mut stat) != 0 {
+            libc::close(fd);
+            return Err(os_error("can't read shared memory", name));
+        }
+        let size = stat.st_size as usize;
+        // A private mapping can be written without affecting other processes.
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE,
+            fd,
+            0,
+        );
+        libc::close(fd);
+        if ptr == libc::MAP_FAILED {
+            return Err(os_error("can't map shared memory", name));
+        }
+        Mapping {
+            ptr: ptr as *mut u8,
+            size,
+        }
+    };
+
+    if mapping.size < HEADER_SIZE {
+        return Err(Error::Other(format!("'{}' is not a shared R vector", name)));
+    }
+    let header = mapping.header();
+    let rtype = sxp_to_rtype(header.sexptype as i32);
+    let not_shared = || Error::Other(format!("'{}' is not a shared R vector", name));
+    let data_len = (header.len as usize)
+        .checked_mul(header.elt_size as usize)
+        .ok_or_else(not_shared)?;
+    let total_size = data_len
+        .checked_add(HEADER_SIZE)
+        .and_then(|size| size.checked_add(header.attributes_len as usize))
+        .ok_or_else(not_shared)?;
+    if header.magic != MAGIC
+        || elt_size(rtype) != Some(header.elt_size as usize)
+        || total_size > mapping.size
+    {
+        return Err(not_shared());
+    }
+    let attributes = Raw::from_bytes(unsafe {
+        std::slice::from_raw_parts(
+            mapping.ptr.add(HEADER_SIZE + data_len),
+            header.attributes_len as usize,
+        )
+    });
+
+    let state = SharedVector {
+        mapping: Rc::new(mapping),
+        len: header.len as usize,
+    };
+    let class_name = "extendr_shm";
+    let class = match rtype {
+        Rtype::Logicals => Altrep::make_altlogical_class::<SharedVector>(class_name, "extendr"),
+        Rtype::Integers => Altrep::make_altinteger_class::<SharedVector>(class_name, "extendr"),
+        Rtype::Doubles => Altrep::make_altreal_class::<SharedVector>(class_name, "extendr"),
+        Rtype::Complexes => Altrep::make_altcomplex_class::<SharedVector>(class_name, "extendr"),
+        _ => Altrep::make_altraw_class::<SharedVector>(class_name, "extendr"),
+    };
+    let mut res: Robj = Altrep::from_state_and_class(state, class, false).into();
+
+    if attributes.len() != 0 {
+        let attributes: List =
+            eval_string_with_params("unserialize(param.0)", &[&attributes.into()])?.try_into()?;
+        for (name, value) in attributes.iter() {
+            res.set_attrib(Symbol::from_string(name), value)?;
+        }
+    }
+    Ok(res)
+}
+
+// A private mapping of a segment, unmapped when the last vector using it is freed.
+#[derive(Debug)]
+struct Mapping {
+    ptr: *mut u8,
+    size: usize,
+}
+
+impl Mapping {
+    fn header(&self) -> Header {
+        unsafe { std::ptr::read_unaligned(self.ptr as *const Header) }
+    }
+
+    fn data(&self) -> *mut u8 {
+        unsafe { self.ptr.add(HEADER_SIZE) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.size) };
+    }
+}
+
+// The ALTREP state of a vector in shared memory.
+#[derive(Debug, Clone)]
+struct SharedVector {
+    mapping: Rc<Mapping>,
+    len: usize,
+}
+
+impl SharedVector {
+    fn read<T: Copy>(&self, index: usize) -> T {
+        assert!(index < self.len);
+        unsafe { *(self.mapping.data() as *const T).add(index) }
+    }
+
+    fn read_region<T: Copy>(&self, index: usize, data: &mut [T]) -> usize {
+        if index >= self.len {
+            return 0;
+        }
+        let n = data.len().min(self.len - index);
+        unsafe {
+            let src = (self.mapping.data() as *const T).add(index);
+            std::ptr::copy_nonoverlapping(src, data.as_mut_ptr(), n);
+        }
+        n
+    }
+}
+
+impl AltrepImpl for SharedVector {
+    fn length(&self) -> usize {
+        self.len
+    }
+
+    fn dataptr(x: SEXP, _writeable: bool) -> *mut u8 {
+        Altrep::get_state::<SharedVector>(x).mapping.data()
+    }
+
+    fn dataptr_or_null(x: SEXP) -> *const u8 {
+        Altrep::get_state::<SharedVector>(x).mapping.data()
+    }
+}
+
+impl AltLogicalImpl for SharedVector {
+    fn elt(&self, index: usize) -> Rbool {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [Rbool]) -> usize {
+        self.read_region(index, data)
+    }
+}
+
+impl AltIntegerImpl for SharedVector {
+    fn elt(&self, index: usize) -> Rint {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [Rint]) -> usize {
+        self.read_region(index, data)
+    }
+}
+
+impl AltRealImpl for SharedVector {
+    fn elt(&self, index: usize) -> Rfloat {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [Rfloat]) -> usize {
+        self.read_region(index, data)
+    }
+}
+
+impl AltComplexImpl for SharedVector {
+    fn elt(&self, index: usize) -> Rcplx {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [Rcplx]) -> usize {
+        self.read_region(index, data)
+    }
+}
+
+impl AltRawImpl for SharedVector {
+    fn elt(&self, index: usize) -> u8 {
+        self.read(index)
+    }
+
+    fn get_region(&self, index: usize, data: &mut [u8]) -> usize {
+        self.read_region(index, data)
+    }
+}
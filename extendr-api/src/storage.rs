@@ -0,0 +1,121 @@
+//! Objects whose state is stored in an R environment.
+//!
+//! By default `#[extendr]` impl blocks wrap the rust value in an external
+//! pointer, which is fast but opaque to R and lost by `saveRDS()`.
+//! With `#[extendr(storage = "environment")]` on both the struct and the impl
+//! block, the fields are stored as R values in an environment instead:
+//!
+//! ```ignore
+//! #[extendr(storage = "environment")]
+//! struct Person {
+//!     name: String,
+//!     age: i32,
+//! }
+//!
+//! #[extendr(storage = "environment")]
+//! impl Person {
+//!     fn new(name: String) -> Self {
+//!         Self { name, age: 0 }
+//!     }
+//!     fn birthday(&mut self) {
+//!         self.age += 1;
+//!     }
+//! }
+//! ```
+//!
+//! From R, `p$age` reads a field and `p$age <- 10L` sets it. The object can
+//! be saved and restored with `saveRDS()`/`readRDS()`.
+//!
+//! Each method call converts the fields to a rust value, and methods taking
+//! `&mut self` write them back, so this is slower than an external pointer.
+//! Field types need to be `Clone` and convert to and from [Robj].
+use crate::*;
+
+/// Store and load a value as the fields of an environment.
+///
+/// This is implemented by `#[extendr(storage = "environment")]` on a struct,
+/// but can also be written by hand.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::storage::{from_env_object, to_env_object, EnvStorage};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// impl EnvStorage for Point {
+///     const FIELDS: &'static [&'static str] = &["x", "y"];
+///
+///     fn store(&self, env: &Environment) -> Result<()> {
+///         env.set_local(sym!(x), self.x);
+///         env.set_local(sym!(y), self.y);
+///         Ok(())
+///     }
+///
+///     fn load(env: &Environment) -> Result<Self> {
+///         Ok(Point {
+///             x: env.local(sym!(x))?.try_into()?,
+///             y: env.local(sym!(y))?.try_into()?,
+///         })
+///     }
+/// }
+///
+/// test! {
+///     let robj = to_env_object(&Point { x: 1.0, y: 2.0 }, "Point")?;
+///     assert!(robj.is_environment());
+///     assert_eq!(robj.dollar("y")?, r!(2.0));
+///     assert_eq!(from_env_object::<Point>(&robj, "Point")?, Point { x: 1.0, y: 2.0 });
+/// }
+/// ```
+pub trait EnvStorage: Sized {
+    /// The names of the fields stored in the environment.
+    const FIELDS: &'static [&'static str];
+
+    /// Write the fields into `env`.
+    fn store(&self, env: &Environment) -> Result<()>;
+
+    /// Read the fields from `env`.
+    fn load(env: &Environment) -> Result<Self>;
+}
+
+/// Make a new environment of class `class` holding the fields of `value`.
+pub fn to_env_object<T: EnvStorage>(value: &T, class: &str) -> Result<Robj> {
+    let env = new_env(empty_env(), true, T::FIELDS.len() as i32);
+    value.store(&env)?;
+    let mut robj: Robj = env.into();
+    robj.set_class([class])?;
+    Ok(robj)
+}
+
+fn storage_env(robj: &Robj, class: &str) -> Result<Environment> {
+    if !robj.is_environment() {
+        return Err(Error::ExpectedEnvironment(robj.clone()));
+    }
+    if !robj.inherits(class) {
+        return Err(Error::Other(format!("expected {}", class)));
+    }
+    robj.clone().try_into()
+}
+
+/// Read a value from an environment made by [to_env_object].
+pub fn from_env_object<T: EnvStorage>(robj: &Robj, class: &str) -> Result<T> {
+    T::load(&storage_env(robj, class)?)
+}
+
+/// Write the fields of `value` back to an environment made by [to_env_object].
+pub fn update_env_object<T: EnvStorage>(value: &T, robj: &Robj, class: &str) -> Result<()> {
+    value.store(&storage_env(robj, class)?)
+}
+
+/// Get a field from a storage environment, as used by the generated code.
+#[doc(hidden)]
+pub fn load_field(env: &Environment, name: &str) -> Result<Robj> {
+    let value = env.local(Symbol::from_string(name))?;
+    if value.is_unbound_value() {
+        Err(Error::Other(format!("field '{}' is missing", name)))
+    } else {
+        Ok(value)
+    }
+}
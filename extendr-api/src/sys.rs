@@ -0,0 +1,138 @@
+//! Environment variables, locale and time zone, as seen by R.
+//!
+//! These go through R's `Sys.*` functions, so a variable set here is visible
+//! to both R code and rust code (through [std::env]), and locale changes are
+//! made the same way as `Sys.setlocale()`.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::sys::{sys_getenv, sys_setenv, sys_unsetenv};
+//! test! {
+//!     sys_setenv("EXTENDR_SYS_DOC", "yes")?;
+//!     assert_eq!(sys_getenv("EXTENDR_SYS_DOC")?, Some("yes".to_string()));
+//!     assert_eq!(std::env::var("EXTENDR_SYS_DOC").unwrap(), "yes");
+//!
+//!     sys_unsetenv("EXTENDR_SYS_DOC")?;
+//!     assert_eq!(sys_getenv("EXTENDR_SYS_DOC")?, None);
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+
+/// Get an environment variable, like `Sys.getenv(name, unset = NA)`.
+///
+/// Returns `None` if the variable is not set.
+pub fn sys_getenv(name: &str) -> Result<Option<String>> {
+    let value = call!("Sys.getenv", name, unset = NA_STRING)?;
+    let value: Rstr = Strings::try_from(value)?.elt(0);
+    if value.is_na() {
+        Ok(None)
+    } else {
+        Ok(Some(value.as_str().to_string()))
+    }
+}
+
+/// Set an environment variable, like `Sys.setenv(name = value)`.
+pub fn sys_setenv(name: &str, value: &str) -> Result<()> {
+    let setenv: Function = global_function(Symbol::from_string("Sys.setenv"))?.try_into()?;
+    let ok = setenv.call(Pairlist::from_pairs([(name, r!(value))]))?;
+    check_success(ok, "Sys.setenv", name)
+}
+
+/// Remove an environment variable, like `Sys.unsetenv(name)`.
+pub fn sys_unsetenv(name: &str) -> Result<()> {
+    let ok = call!("Sys.unsetenv", name)?;
+    check_success(ok, "Sys.unsetenv", name)
+}
+
+fn check_success(ok: Robj, function: &str, name: &str) -> Result<()> {
+    if ok.try_as_bool()? {
+        Ok(())
+    } else {
+        Err(Error::Other(format!(
+            "{}() failed for '{}'",
+            function, name
+        )))
+    }
+}
+
+/// A locale category, as used by `Sys.getlocale()` and `Sys.setlocale()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocaleCategory {
+    All,
+    Collate,
+    Ctype,
+    Monetary,
+    Numeric,
+    Time,
+    Messages,
+}
+
+impl LocaleCategory {
+    /// The name used by R, for example `"LC_COLLATE"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LocaleCategory::All => "LC_ALL",
+            LocaleCategory::Collate => "LC_COLLATE",
+            LocaleCategory::Ctype => "LC_CTYPE",
+            LocaleCategory::Monetary => "LC_MONETARY",
+            LocaleCategory::Numeric => "LC_NUMERIC",
+            LocaleCategory::Time => "LC_TIME",
+            LocaleCategory::Messages => "LC_MESSAGES",
+        }
+    }
+}
+
+/// Get the locale for a category, like `Sys.getlocale(category)`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::sys::{sys_getlocale, LocaleCategory};
+/// test! {
+///     let collate = sys_getlocale(LocaleCategory::Collate)?;
+///     assert_eq!(collate, R!("Sys.getlocale('LC_COLLATE')")?.as_str().unwrap());
+/// }
+/// ```
+pub fn sys_getlocale(category: LocaleCategory) -> Result<String> {
+    let locale = call!("Sys.getlocale", category.as_str())?;
+    Ok(locale.try_as_str()?.to_string())
+}
+
+/// Set the locale for a category, like `Sys.setlocale(category, locale)`.
+///
+/// Returns the new locale string, or an error if R could not set it.
+/// An empty `locale` sets the default from the environment.
+pub fn sys_setlocale(category: LocaleCategory, locale: &str) -> Result<String> {
+    let res = call!(
+        "suppressWarnings",
+        lang!("Sys.setlocale", category.as_str(), locale)
+    )?;
+    match res.try_as_str()? {
+        "" => Err(Error::Other(format!(
+            "Could not set {} to '{}'",
+            category.as_str(),
+            locale
+        ))),
+        res => Ok(res.to_string()),
+    }
+}
+
+/// Get the current time zone, using the same logic as `Sys.timezone()`.
+///
+/// Returns `None` if R can not determine the time zone.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::sys::sys_timezone;
+/// test! {
+///     if let Some(tz) = sys_timezone()? {
+///         assert!(!tz.is_empty());
+///     }
+/// }
+/// ```
+pub fn sys_timezone() -> Result<Option<String>> {
+    let tz: Rstr = Strings::try_from(call!("Sys.timezone")?)?.elt(0);
+    if tz.is_na() {
+        Ok(None)
+    } else {
+        Ok(Some(tz.as_str().to_string()))
+    }
+}
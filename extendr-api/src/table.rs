@@ -0,0 +1,180 @@
+//! Count values like base R's `table()`.
+//!
+//! [table()] counts the values of one vector and [cross_table()] the pairs
+//! of values of two, giving the same `table` objects as `table(x)` and
+//! `table(x, y)`, with `NA` left out. The values are counted with a rust
+//! hash map, which is much faster than `table()` on long character
+//! vectors and factors.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::table::{cross_table, table};
+//! test! {
+//!     let x = r!(["b", "a", "b", "c"]);
+//!     assert_eq!(table(&x)?, R!("table(c('b', 'a', 'b', 'c'))")?);
+//!
+//!     let y = r!([1, 2, 1, 1]);
+//!     let counts = cross_table(&x, &y)?;
+//!     assert_eq!(counts, R!("table(c('b', 'a', 'b', 'c'), c(1, 2, 1, 1))")?);
+//!     assert_eq!(counts.as_integer_slice().unwrap(), [0, 2, 1, 1, 0, 0]);
+//! }
+//! ```
+//!
+//! The levels are those of a factor, with unused levels counted as zero,
+//! or the sorted distinct values of other vectors, as `factor()` gives.
+//! Strings are sorted in byte order, like `sort(method = "radix")`, which
+//! may differ from `table()` in locales that sort strings differently.
+use crate as extendr_api;
+use crate::factor::Categorical;
+use crate::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Count the values of a vector, like `table(x)`.
+///
+/// `x` can be a factor or a logical, integer, double or character vector.
+pub fn table(x: &Robj) -> Result<Robj> {
+    let (codes, levels) = categorize(x)?;
+    let mut counts = vec![0; levels.len()];
+    for code in codes.into_iter().flatten() {
+        counts[code as usize] += 1;
+    }
+    make_table(counts, vec![levels])
+}
+
+/// Count the pairs of values of two vectors of the same length, like `table(x, y)`.
+///
+/// The result is a matrix with a row for each level of `x` and a column
+/// for each level of `y`. Pairs with an `NA` are not counted.
+pub fn cross_table(x: &Robj, y: &Robj) -> Result<Robj> {
+    if x.len() != y.len() {
+        return Err(Error::Other(format!(
+            "cross_table: vectors have lengths {} and {}, not the same",
+            x.len(),
+            y.len()
+        )));
+    }
+    let (x_codes, x_levels) = categorize(x)?;
+    let (y_codes, y_levels) = categorize(y)?;
+    let nrow = x_levels.len();
+    let mut counts = vec![0; nrow * y_levels.len()];
+    for (cx, cy) in x_codes.into_iter().zip(y_codes) {
+        if let (Some(cx), Some(cy)) = (cx, cy) {
+            counts[cx as usize + nrow * cy as usize] += 1;
+        }
+    }
+    make_table(counts, vec![x_levels, y_levels])
+}
+
+// The zero-based codes and the levels of a vector, as `factor(x)` would give them.
+fn categorize(x: &Robj) -> Result<(Vec<Option<u32>>, Vec<String>)> {
+    if x.is_factor() {
+        return Ok(Categorical::try_from(x)?.into_parts());
+    }
+    match x.rtype() {
+        Rtype::Strings => {
+            let values = x.as_str_iter().unwrap().map(|s| (!s.is_na()).then_some(s));
+            let (codes, levels) = sorted_codes(values, |a: &&str, b: &&str| a.cmp(b));
+            Ok((codes, levels.into_iter().map(|s| s.to_string()).collect()))
+        }
+        Rtype::Integers => {
+            let values = x
+                .as_integer_slice()
+                .unwrap()
+                .iter()
+                .map(|&i| (!i.is_na()).then_some(i));
+            let (codes, levels) = sorted_codes(values, |a: &i32, b: &i32| a.cmp(b));
+            Ok((codes, levels.into_iter().map(|i| i.to_string()).collect()))
+        }
+        Rtype::Logicals => {
+            let values = x
+                .as_logical_slice()
+                .unwrap()
+                .iter()
+                .map(|b| (!b.is_na()).then_some(b.is_true()));
+            let (codes, levels) = sorted_codes(values, |a: &bool, b: &bool| a.cmp(b));
+            Ok((
+                codes,
+                levels
+                    .into_iter()
+                    .map(|b| if b { "TRUE" } else { "FALSE" }.to_string())
+                    .collect(),
+            ))
+        }
+        Rtype::Doubles => {
+            // Hash the bits, with -0 as 0. NaN, like NA, has no level.
+            let values = x.as_real_slice().unwrap().iter().map(|&d| {
+                if d.is_nan() {
+                    None
+                } else {
+                    Some((d + 0.0).to_bits())
+                }
+            });
+            let (codes, levels) = sorted_codes(values, |a: &u64, b: &u64| {
+                f64::from_bits(*a).partial_cmp(&f64::from_bits(*b)).unwrap()
+            });
+            // Write the levels as R does.
+            let levels: Vec<f64> = levels.into_iter().map(f64::from_bits).collect();
+            let labels = call!("as.character", levels)?;
+            Ok((
+                codes,
+                labels
+                    .as_str_iter()
+                    .unwrap()
+                    .map(|s| s.to_string())
+                    .collect(),
+            ))
+        }
+        _ => Err(Error::Other(format!(
+            "can't tabulate an R object of type {:?}",
+            x.rtype()
+        ))),
+    }
+}
+
+// Zero-based codes of the sorted distinct values, and the values.
+fn sorted_codes<T, I, C>(values: I, cmp: C) -> (Vec<Option<u32>>, Vec<T>)
+where
+    T: Copy + Eq + Hash,
+    I: Iterator<Item = Option<T>>,
+    C: Fn(&T, &T) -> std::cmp::Ordering,
+{
+    let mut index: HashMap<T, u32> = HashMap::new();
+    let mut distinct = Vec::new();
+    let codes: Vec<Option<u32>> = values
+        .map(|value| {
+            value.map(|value| {
+                *index.entry(value).or_insert_with(|| {
+                    distinct.push(value);
+                    distinct.len() as u32 - 1
+                })
+            })
+        })
+        .collect();
+
+    // new_code[old_code] is the code of the value in sorted order.
+    let mut order: Vec<usize> = (0..distinct.len()).collect();
+    order.sort_unstable_by(|&a, &b| cmp(&distinct[a], &distinct[b]));
+    let mut new_code = vec![0; distinct.len()];
+    for (i, &old) in order.iter().enumerate() {
+        new_code[old] = i as u32;
+    }
+    let codes = codes
+        .into_iter()
+        .map(|code| code.map(|code| new_code[code as usize]))
+        .collect();
+    let levels = order.into_iter().map(|i| distinct[i]).collect();
+    (codes, levels)
+}
+
+fn make_table(counts: Vec<i32>, levels: Vec<Vec<String>>) -> Result<Robj> {
+    let dim: Vec<i32> = levels.iter().map(|l| l.len() as i32).collect();
+    let names = vec![""; levels.len()];
+    let mut dimnames: Robj = List::from_values(levels).into();
+    dimnames.set_names(names)?;
+    let mut res = Robj::from(counts);
+    res.set_attrib(dim_symbol(), dim)?;
+    res.set_attrib(dimnames_symbol(), dimnames)?;
+    res.set_class(["table"])?;
+    Ok(res)
+}
@@ -0,0 +1,112 @@
+//! Tokenize character vectors and make n-grams, as a base for text processing.
+//!
+//! [tokenize] splits each string of a character vector into tokens, giving a
+//! list of character vectors like `strsplit()`, and [ngrams] joins runs of
+//! tokens. `NA` strings give `NA` elements and names are kept.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::text::{ngrams, tokenize, Tokenizer};
+//! test! {
+//!     let text = Strings::from_values(["The cat sat.", "On the mat!"]);
+//!     let tokens = tokenize(&text, &Tokenizer::Words)?;
+//!     assert_eq!(tokens.elt(0)?, r!(["The", "cat", "sat"]));
+//!
+//!     let bigrams = ngrams(&tokens, 2, " ")?;
+//!     assert_eq!(bigrams.elt(1)?, r!(["On the", "the mat"]));
+//! }
+//! ```
+use crate::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How to split strings into tokens.
+#[derive(Debug, Clone)]
+pub enum Tokenizer {
+    /// Split at runs of Unicode white space.
+    Whitespace,
+    /// Words, as found by the Unicode word boundary rules (UAX #29).
+    /// Punctuation and white space are dropped.
+    Words,
+    /// The matches of a regular expression, see [Tokenizer::regex].
+    Regex(regex::Regex),
+}
+
+impl Tokenizer {
+    /// Make a tokenizer giving the matches of `pattern`, for example `r"\w+"`.
+    pub fn regex(pattern: &str) -> Result<Tokenizer> {
+        let regex = regex::Regex::new(pattern).map_err(|e| Error::Other(e.to_string()))?;
+        Ok(Tokenizer::Regex(regex))
+    }
+
+    /// Split one string into tokens.
+    /// ```
+    /// use extendr_api::text::Tokenizer;
+    /// let tokenizer = Tokenizer::regex("[0-9]+").unwrap();
+    /// assert_eq!(tokenizer.tokens("a1b22c333"), ["1", "22", "333"]);
+    /// assert_eq!(Tokenizer::Whitespace.tokens(" a  b\tc "), ["a", "b", "c"]);
+    /// ```
+    pub fn tokens<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        match self {
+            Tokenizer::Whitespace => text.split_whitespace().collect(),
+            Tokenizer::Words => text.unicode_words().collect(),
+            Tokenizer::Regex(regex) => regex.find_iter(text).map(|m| m.as_str()).collect(),
+        }
+    }
+}
+
+/// Split each element of `x` into tokens, giving a list of character vectors.
+///
+/// Strings are read without copying when possible, see [Strings::str_views].
+pub fn tokenize(x: &Strings, tokenizer: &Tokenizer) -> Result<List> {
+    let views = x.str_views();
+    let mut res = List::from_values(views.iter().map(|text| {
+        let text: &str = text;
+        if text.is_na() {
+            Strings::from_values([<&str>::na()])
+        } else {
+            Strings::from_values(tokenizer.tokens(text))
+        }
+    }));
+    copy_list_names(&mut res, x.as_robj())?;
+    Ok(res)
+}
+
+/// Make the n-grams of each character vector in `tokens`, joining `n` tokens with `sep`.
+///
+/// Vectors with fewer than `n` tokens give no n-grams and `NA` stays `NA`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::text::ngrams;
+/// test! {
+///     let tokens = List::try_from(list!(a = ["x", "y", "z"], b = "x"))?;
+///     let trigrams = ngrams(&tokens, 3, "_")?;
+///     assert_eq!(trigrams, List::try_from(list!(a = "x_y_z", b = Strings::new(0)))?);
+/// }
+/// ```
+pub fn ngrams(tokens: &List, n: usize, sep: &str) -> Result<List> {
+    if n == 0 {
+        return Err(Error::Other("n must be at least 1".to_string()));
+    }
+    let ngrams = tokens
+        .values()
+        .map(|value| {
+            let strings = Strings::try_from(value)?;
+            if strings.len() == 1 && strings.elt(0).is_na() {
+                return Ok(strings);
+            }
+            let views = strings.str_views();
+            let joined = views.windows(n).map(|window| window.join(sep));
+            Ok(Strings::from_values(joined.collect::<Vec<_>>()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut res = List::from_values(ngrams);
+    copy_list_names(&mut res, tokens.as_robj())?;
+    Ok(res)
+}
+
+fn copy_list_names(to: &mut List, from: &Robj) -> Result<()> {
+    if let Some(names) = from.get_attrib(names_symbol()) {
+        to.set_attrib(names_symbol(), names)?;
+    }
+    Ok(())
+}
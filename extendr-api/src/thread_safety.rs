@@ -1,7 +1,10 @@
 //! Provide limited protection for multithreaded access to the R API.
 
+use crate as extendr_api;
 use crate::*;
+use std::any::Any;
 use std::cell::Cell;
+use std::os::raw;
 use std::sync::Mutex;
 
 /// A global lock, that should represent the global lock on the R-API.
@@ -72,6 +75,194 @@ pub fn throw_r_error<S: AsRef<str>>(s: S) -> ! {
     };
 }
 
+/// An R error, interrupt or other jump out of R code, unwinding through rust frames.
+///
+/// [unwind_protect] stops such a jump before it reaches the rust code that
+/// called R, and panics with an `RUnwind` as the payload, so the rust frames
+/// between the R code and the `#[extendr]` function that R called drop their
+/// values. The wrapper of that function then resumes the jump in R with
+/// [RUnwind::resume]. With R calling rust calling R several times over, the
+/// jump passes through each layer of rust frames in turn.
+#[derive(Debug)]
+pub struct RUnwind {
+    // The continuation from `R_MakeUnwindCont`, protected until resumed or dropped.
+    token: Robj,
+}
+
+// The token is only used by the thread that called R.
+unsafe impl Send for RUnwind {}
+
+impl RUnwind {
+    /// Continue the jump in R, as if rust had not been in the way.
+    ///
+    /// Only call this from the `#[extendr]` wrapper, after the rust frames
+    /// below it have been unwound, with nothing left to drop in the frame
+    /// that calls it.
+    pub fn resume(self) -> ! {
+        unsafe {
+            // Keep the token on the protection stack, which the jump resets,
+            // so that it is released whether the jump ends in R or in an
+            // outer `unwind_protect`.
+            let token = Rf_protect(self.token.get());
+            drop(self);
+            R_ContinueUnwind(token)
+        }
+    }
+
+    /// Resume `payload` if it is an `RUnwind`, or return the payload of any
+    /// other panic. Used by `#[extendr]` wrappers.
+    #[doc(hidden)]
+    pub fn resume_or_return(payload: Box<dyn Any + Send>) -> Box<dyn Any + Send> {
+        match payload.downcast::<RUnwind>() {
+            // The box is freed here, as nothing after the jump drops it.
+            Ok(r_unwind) => RUnwind::resume(*r_unwind),
+            Err(payload) => payload,
+        }
+    }
+}
+
+extern "C" {
+    // From Rinternals.h.
+    pub(crate) fn R_tryCatch(
+        body: Option<unsafe extern "C" fn(*mut raw::c_void) -> SEXP>,
+        bdata: *mut raw::c_void,
+        conds: SEXP,
+        handler: Option<unsafe extern "C" fn(SEXP, *mut raw::c_void) -> SEXP>,
+        hdata: *mut raw::c_void,
+        finally: Option<unsafe extern "C" fn(*mut raw::c_void)>,
+        fdata: *mut raw::c_void,
+    ) -> SEXP;
+}
+
+// The class of the condition that stops a jump in `unwind_protect`.
+const UNWIND_CLASS: &str = "extendr_unwind";
+
+/// Run `f`, which calls R, so that a jump out of R unwinds the rust stack.
+///
+/// Without this, an R error in `f` jumps straight over the rust frames that
+/// called it, without running destructors. With it, the error is turned into
+/// a panic with an [RUnwind] payload, which the `#[extendr]` wrapper turns
+/// back into the R error. Use [catch_r_error] to handle the error in rust
+/// instead.
+///
+/// The jump still passes over the frames of `f` itself, so `f` should only
+/// call R, with nothing to drop.
+/// ```
+/// use extendr_api::prelude::*;
+/// use libR_sys::*;
+/// test! {
+///     let call = lang!("stop", "boom");
+///     let res = std::panic::catch_unwind(|| {
+///         unwind_protect(|| unsafe { Rf_eval(call.get(), R_GlobalEnv) })
+///     });
+///     assert!(res.unwrap_err().is::<RUnwind>());
+/// }
+/// ```
+pub fn unwind_protect<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    struct Data<F, R> {
+        f: Option<F>,
+        value: Option<R>,
+        panic: Option<Box<dyn Any + Send>>,
+        jumped: bool,
+        cont: SEXP,
+    }
+
+    // Call `f`, keeping its panics from unwinding through R.
+    unsafe extern "C" fn do_call<F, R>(data: *mut raw::c_void) -> SEXP
+    where
+        F: FnOnce() -> R,
+    {
+        let data = &mut *(data as *mut Data<F, R>);
+        let f = data.f.take().unwrap();
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+            Ok(value) => data.value = Some(value),
+            Err(payload) => data.panic = Some(payload),
+        }
+        R_NilValue
+    }
+
+    // On a jump, R_UnwindProtect continues it in R once this returns. So
+    // record it and jump to the handler of R_tryCatch in `do_protect`
+    // instead, which returns to rust.
+    unsafe extern "C" fn do_cleanup(jumped: *mut raw::c_void, jump: Rboolean) {
+        if jump != 0 {
+            *(jumped as *mut bool) = true;
+            Rf_eval(unwind_signal(), R_BaseEnv);
+        }
+    }
+
+    // Nothing in these frames needs dropping when `do_cleanup` jumps out of them.
+    unsafe extern "C" fn do_protect<F, R>(data: *mut raw::c_void) -> SEXP
+    where
+        F: FnOnce() -> R,
+    {
+        let fields = data as *mut Data<F, R>;
+        let jumped = std::ptr::addr_of_mut!((*fields).jumped);
+        let fun = std::mem::transmute(do_call::<F, R> as *const ());
+        let cleanfun = std::mem::transmute(do_cleanup as *const ());
+        R_UnwindProtect(
+            fun,
+            data,
+            cleanfun,
+            jumped as *mut raw::c_void,
+            (*fields).cont,
+        )
+    }
+
+    unsafe extern "C" fn do_handle(_cond: SEXP, _data: *mut raw::c_void) -> SEXP {
+        R_NilValue
+    }
+
+    let mut data = Data {
+        f: Some(f),
+        value: None,
+        panic: None,
+        jumped: false,
+        cont: unsafe { R_NilValue },
+    };
+    // The panics are raised here, outside `single_threaded`, so that they
+    // do not poison the lock.
+    let token = single_threaded(|| unsafe {
+        let token = Robj::from_sexp(R_MakeUnwindCont());
+        let conds = Robj::from(UNWIND_CLASS);
+        data.cont = token.get();
+        R_tryCatch(
+            Some(do_protect::<F, R>),
+            &mut data as *mut Data<F, R> as *mut raw::c_void,
+            conds.get(),
+            Some(do_handle),
+            std::ptr::null_mut(),
+            None,
+            std::ptr::null_mut(),
+        );
+        token
+    });
+    if data.jumped {
+        std::panic::resume_unwind(Box::new(RUnwind { token }));
+    }
+    if let Some(payload) = data.panic {
+        std::panic::resume_unwind(payload);
+    }
+    data.value.unwrap()
+}
+
+// `stop(cond)` with a condition of class `UNWIND_CLASS`, which only the
+// handler in `unwind_protect` catches. It is left on the protection stack,
+// which the jump of `stop()` resets.
+unsafe fn unwind_signal() -> SEXP {
+    let mut cond: Robj = List::from_names_and_values(
+        ["message", "call"],
+        [r!("extendr unwinding rust frames"), r!(())],
+    )
+    .unwrap()
+    .into();
+    cond.set_class([UNWIND_CLASS, "condition"]).unwrap();
+    Rf_protect(lang!("stop", cond).get())
+}
+
 /// Wrap an R function such as `Rf_findFunction` and convert errors and panics into results.
 /// ```ignore
 /// use extendr_api::prelude::*;
@@ -88,42 +279,10 @@ where
     F: FnOnce() -> SEXP + Copy,
     F: std::panic::UnwindSafe,
 {
-    use std::os::raw;
-
-    unsafe extern "C" fn do_call<F>(data: *mut raw::c_void) -> SEXP
-    where
-        F: FnOnce() -> SEXP + Copy,
-    {
-        let data = data as *const ();
-        let f: &F = &*(data as *const F);
-        f()
-    }
-
-    unsafe extern "C" fn do_cleanup(_: *mut raw::c_void, jump: Rboolean) {
-        if jump != 0 {
-            panic!("R has thrown an error.");
-        }
-    }
-
-    single_threaded(|| unsafe {
-        let fun_ptr = do_call::<F> as *const ();
-        let clean_ptr = do_cleanup as *const ();
-        let x = false;
-        let fun = std::mem::transmute(fun_ptr);
-        let cleanfun = std::mem::transmute(clean_ptr);
-        let data = &f as *const _ as _;
-        let cleandata = &x as *const _ as _;
-        let cont = R_MakeUnwindCont();
-        Rf_protect(cont);
-
-        // Note that catch_unwind does not work for 32 bit windows targets.
-        let res = match std::panic::catch_unwind(|| {
-            R_UnwindProtect(fun, data, cleanfun, cleandata, cont)
-        }) {
-            Ok(res) => Ok(res),
-            Err(_) => Err("Error in protected R code".into()),
-        };
-        Rf_unprotect(1);
-        res
+    // Note that catch_unwind does not work for 32 bit windows targets.
+    // Dropping the payload releases the continuation of an RUnwind.
+    single_threaded(|| match std::panic::catch_unwind(|| unwind_protect(f)) {
+        Ok(res) => Ok(res),
+        Err(_) => Err("Error in protected R code".into()),
     })
 }
@@ -0,0 +1,492 @@
+//! Thread pool sizing that respects R's options.
+//!
+//! CRAN asks packages not to use more than two cores during checks.
+//! [max_threads()] combines the usual R settings into a single number
+//! and [ThreadLimit] lets you lower it for a scope.
+//!
+//! [scope()] runs scoped worker threads that can hand R work back to the
+//! calling thread, and [SendableSlice] lets them read R vectors directly.
+//! With the `rayon` feature, [par_chunks()] maps and reduces chunks of a
+//! vector in parallel.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::threads::{max_threads, ThreadLimit};
+//! test! {
+//!     let limit = ThreadLimit::new(1);
+//!     assert_eq!(max_threads(), 1);
+//!     drop(limit);
+//!     assert!(max_threads() >= 1);
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The current scoped limit set by [ThreadLimit]. Zero means no limit.
+static THREAD_LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+/// The ids and limits of the live [ThreadLimit] guards. [THREAD_LIMIT] is the
+/// smallest of them, so the guards can be dropped in any order.
+static LIMITS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+static NEXT_LIMIT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The rayon pools used by [ThreadLimit::install], one per number of threads.
+#[cfg(feature = "rayon")]
+static POOLS: once_cell::sync::Lazy<
+    Mutex<std::collections::HashMap<usize, std::sync::Arc<rayon::ThreadPool>>>,
+> = once_cell::sync::Lazy::new(Default::default);
+
+/// Number of threads used when CRAN's `_R_CHECK_LIMIT_CORES_` is set.
+pub const CRAN_MAX_THREADS: usize = 2;
+
+fn positive_count(robj: &Robj) -> Option<usize> {
+    let value = if let Some(v) = robj.as_integer() {
+        v as f64
+    } else {
+        robj.as_real()?
+    };
+    if value >= 1.0 {
+        Some(value as usize)
+    } else {
+        None
+    }
+}
+
+fn env_count(name: &str) -> Option<usize> {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n >= 1)
+}
+
+/// Return true if `_R_CHECK_LIMIT_CORES_` is set, as it is by `R CMD check --as-cran`.
+pub fn cran_limit_cores() -> bool {
+    match std::env::var("_R_CHECK_LIMIT_CORES_") {
+        Ok(v) => !v.is_empty() && !v.eq_ignore_ascii_case("false"),
+        Err(_) => false,
+    }
+}
+
+/// Get `getOption("Ncpus")` if it is set to a positive number.
+///
+/// This must be called from the R thread.
+pub fn r_ncpus() -> Option<usize> {
+    call!("getOption", "Ncpus")
+        .ok()
+        .and_then(|robj| positive_count(&robj))
+}
+
+/// The maximum number of threads a package should use.
+///
+/// In order of precedence:
+/// - `2` if `_R_CHECK_LIMIT_CORES_` is set (CRAN checks).
+/// - `getOption("Ncpus")`.
+/// - The `RAYON_NUM_THREADS` or `OMP_THREAD_LIMIT` environment variables.
+/// - The number of available cores.
+///
+/// The result is further capped by any active [ThreadLimit] and is always at least one.
+///
+/// This must be called from the R thread.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::threads::max_threads;
+/// test! {
+///     assert!(max_threads() >= 1);
+/// }
+/// ```
+pub fn max_threads() -> usize {
+    let threads = if cran_limit_cores() {
+        CRAN_MAX_THREADS
+    } else {
+        r_ncpus()
+            .or_else(|| env_count("RAYON_NUM_THREADS"))
+            .or_else(|| env_count("OMP_THREAD_LIMIT"))
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+    };
+    match THREAD_LIMIT.load(Ordering::SeqCst) {
+        0 => threads.max(1),
+        limit => threads.min(limit).max(1),
+    }
+}
+
+/// Temporarily limit the number of threads reported by [max_threads()].
+///
+/// The limit is lifted when the guard is dropped, so guards can be nested.
+/// They may also be dropped in any order, the limit is always that of the
+/// most restrictive guard that is still alive.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::threads::{max_threads, ThreadLimit};
+/// test! {
+///     let outer = ThreadLimit::new(2);
+///     {
+///         let _inner = ThreadLimit::new(1);
+///         assert_eq!(max_threads(), 1);
+///     }
+///     assert!(max_threads() <= 2);
+///
+///     // Dropping the outer guard first keeps the inner limit.
+///     let inner = ThreadLimit::new(1);
+///     drop(outer);
+///     assert_eq!(max_threads(), 1);
+///     drop(inner);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ThreadLimit {
+    id: usize,
+    limit: usize,
+}
+
+impl ThreadLimit {
+    /// Limit the number of threads to `limit` (at least one) until the guard is dropped.
+    /// A nested guard can not raise the limit set by an enclosing one.
+    pub fn new(limit: usize) -> Self {
+        let mut limits = LIMITS.lock().unwrap_or_else(|e| e.into_inner());
+        let limit = match THREAD_LIMIT.load(Ordering::SeqCst) {
+            0 => limit.max(1),
+            previous => limit.min(previous).max(1),
+        };
+        let id = NEXT_LIMIT_ID.fetch_add(1, Ordering::SeqCst);
+        limits.push((id, limit));
+        THREAD_LIMIT.store(limit, Ordering::SeqCst);
+        Self { id, limit }
+    }
+
+    /// Limit the number of threads to [max_threads()] until the guard is dropped.
+    pub fn from_r_options() -> Self {
+        Self::new(max_threads())
+    }
+
+    /// The limit set by this guard.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Run `f` in a rayon thread pool with at most [ThreadLimit::limit] threads.
+    ///
+    /// The closure must not call R. The pool is made on first use and
+    /// reused by later calls with the same limit.
+    #[cfg(feature = "rayon")]
+    pub fn install<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        let pool = {
+            let mut pools = POOLS.lock().unwrap_or_else(|e| e.into_inner());
+            match pools.get(&self.limit) {
+                Some(pool) => pool.clone(),
+                None => {
+                    let pool = rayon::ThreadPoolBuilder::new()
+                        .num_threads(self.limit)
+                        .build()
+                        .map_err(|e| Error::Other(e.to_string()))?;
+                    let pool = std::sync::Arc::new(pool);
+                    pools.insert(self.limit, pool.clone());
+                    pool
+                }
+            }
+        };
+        Ok(pool.install(f))
+    }
+}
+
+impl Drop for ThreadLimit {
+    fn drop(&mut self) {
+        let mut limits = LIMITS.lock().unwrap_or_else(|e| e.into_inner());
+        limits.retain(|&(id, _)| id != self.id);
+        let limit = limits.iter().map(|&(_, limit)| limit).min().unwrap_or(0);
+        THREAD_LIMIT.store(limit, Ordering::SeqCst);
+    }
+}
+
+/// A closure sent from a worker thread to be run on the R thread.
+type RJob<'env> = Box<dyn FnOnce() + Send + 'env>;
+
+/// Run worker threads that can call back into R.
+///
+/// Threads spawned with [Scope::spawn] get an [RContext]. Closures passed to
+/// [RContext::run] are sent to the thread that called `scope` and run there
+/// while it waits in [ScopedJoinHandle::join], [Scope::sync] or at the end
+/// of the scope. The worker blocks until its closure has run.
+///
+/// All threads are joined before `scope` returns. As [Robj] is not `Send`,
+/// R objects can not leave the R thread; convert them to rust values first.
+///
+/// This must be called from the R thread.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::threads::scope;
+/// test! {
+///     let total = scope(|s| {
+///         let handles: Vec<_> = (1..=4)
+///             .map(|i| {
+///                 s.spawn(move |r| {
+///                     r.run(move || call!("*", i, 2).unwrap().as_integer().unwrap())
+///                         .unwrap()
+///                 })
+///             })
+///             .collect();
+///         handles.into_iter().map(|h| h.join().unwrap()).sum::<i32>()
+///     });
+///     assert_eq!(total, 20);
+/// }
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&Scope<'scope, 'env>) -> T,
+{
+    std::thread::scope(|inner| {
+        let (sender, receiver) = channel();
+        let scope = Scope {
+            inner,
+            sender,
+            receiver,
+        };
+        let result = f(&scope);
+
+        // Serve the workers that are still running until they have all
+        // dropped their senders.
+        let Scope {
+            sender, receiver, ..
+        } = scope;
+        drop(sender);
+        for job in receiver {
+            single_threaded(job);
+        }
+        result
+    })
+}
+
+/// A scope for spawning threads, see [scope()].
+///
+/// The scope can only be used from the R thread.
+pub struct Scope<'scope, 'env: 'scope> {
+    inner: &'scope std::thread::Scope<'scope, 'env>,
+    sender: Sender<RJob<'env>>,
+    receiver: Receiver<RJob<'env>>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawn a worker thread. The closure gets an [RContext] for sending work to the R thread.
+    pub fn spawn<F, T>(&self, f: F) -> ScopedJoinHandle<'_, 'scope, 'env, T>
+    where
+        F: FnOnce(RContext<'env>) -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let context = RContext {
+            sender: self.sender.clone(),
+        };
+        ScopedJoinHandle {
+            scope: self,
+            handle: self.inner.spawn(move || f(context)),
+        }
+    }
+
+    /// Run the R closures that workers are waiting on.
+    /// Returns the number of closures run.
+    pub fn sync(&self) -> usize {
+        let mut count = 0;
+        while let Ok(job) = self.receiver.try_recv() {
+            single_threaded(job);
+            count += 1;
+        }
+        count
+    }
+
+    fn wait(&self, timeout: Duration) {
+        if let Ok(job) = self.receiver.recv_timeout(timeout) {
+            single_threaded(job);
+        }
+    }
+}
+
+/// A handle for joining a thread spawned by [Scope::spawn].
+pub struct ScopedJoinHandle<'a, 'scope, 'env, T> {
+    scope: &'a Scope<'scope, 'env>,
+    handle: std::thread::ScopedJoinHandle<'scope, T>,
+}
+
+impl<'a, 'scope, 'env, T> ScopedJoinHandle<'a, 'scope, 'env, T> {
+    /// Wait for the thread to finish, running its R closures meanwhile.
+    ///
+    /// Returns an error if the thread panicked.
+    pub fn join(self) -> Result<T> {
+        while !self.handle.is_finished() {
+            self.scope.wait(Duration::from_millis(1));
+        }
+        self.handle
+            .join()
+            .map_err(|_| Error::Other("Worker thread panicked".into()))
+    }
+
+    /// Return true if the thread has finished.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+/// Sends closures from a worker thread to the R thread, see [scope()].
+pub struct RContext<'env> {
+    sender: Sender<RJob<'env>>,
+}
+
+impl<'env> RContext<'env> {
+    /// Run `f` on the R thread and wait for the result.
+    ///
+    /// Returns an error if `f` panics or the scope has stopped serving requests.
+    pub fn run<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce() -> R + Send + 'env,
+        R: Send + 'env,
+    {
+        let (reply, result) = sync_channel(1);
+        let job: RJob<'env> = Box::new(move || {
+            let _ = reply.send(catch_unwind(AssertUnwindSafe(f)));
+        });
+        self.sender
+            .send(job)
+            .map_err(|_| Error::Other("R thread is no longer accepting work".into()))?;
+        match result.recv() {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err(Error::Other("R closure panicked".into())),
+            Err(_) => Err(Error::Other("R thread is no longer accepting work".into())),
+        }
+    }
+}
+
+/// A read-only slice of an R vector that worker threads can share without copying.
+///
+/// Made on the R thread from an `Robj` it borrows, so the vector stays
+/// protected from the garbage collector while the slice lives. The vector is
+/// marked as shared, so R copies it rather than modifying it in place, and
+/// ALTREP vectors are expanded before the slice is made, so reading the
+/// slice never calls R. The slice is `Send` and `Sync` and dereferences to
+/// `&[T]`, for example for rayon's `par_iter()`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::threads::SendableSlice;
+/// test! {
+///     let robj = R!("as.double(1:1000)")?;
+///     let slice = SendableSlice::<f64>::new(&robj)?;
+///     let (low, high) = slice.split_at(500);
+///     let total = std::thread::scope(|s| {
+///         let a = s.spawn(|| low.iter().sum::<f64>());
+///         let b = s.spawn(|| high.iter().sum::<f64>());
+///         a.join().unwrap() + b.join().unwrap()
+///     });
+///     assert_eq!(total, 500500.0);
+///     assert!(SendableSlice::<i32>::new(&robj).is_err());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SendableSlice<'a, T> {
+    data: &'a [T],
+}
+
+impl<'a, T> SendableSlice<'a, T> {
+    /// Make a slice of a vector of type `T`, such as `f64`, `i32`, [Rfloat] or `u8`.
+    pub fn new(robj: &'a Robj) -> Result<Self>
+    where
+        Robj: AsTypedSlice<'a, T>,
+    {
+        let data = single_threaded(|| unsafe {
+            MARK_NOT_MUTABLE(robj.get());
+            robj.as_typed_slice()
+        });
+        data.map(|data| SendableSlice { data })
+            .ok_or_else(|| Error::TypeMismatch(robj.clone()))
+    }
+
+    /// The slice, with the lifetime of the `Robj`.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.data
+    }
+}
+
+impl<'a, T> Deref for SendableSlice<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.data
+    }
+}
+
+/// Split a vector of type `T` into chunks of `chunk_size` elements for
+/// [ParChunks::map_reduce].
+///
+/// The chunks are read through a [SendableSlice], so the workers never call R.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::threads::par_chunks;
+/// test! {
+///     let robj = R!("as.double(1:100000)")?;
+///     let total = par_chunks::<f64>(&robj, 1000)?
+///         .map_reduce(|chunk| chunk.iter().sum::<f64>(), |a, b| a + b)?;
+///     assert_eq!(total, r!(5000050000.0));
+/// }
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_chunks<'a, T>(robj: &'a Robj, chunk_size: usize) -> Result<ParChunks<'a, T>>
+where
+    Robj: AsTypedSlice<'a, T>,
+{
+    if chunk_size == 0 {
+        return Err(Error::Other("chunk size must be positive".to_string()));
+    }
+    Ok(ParChunks {
+        slice: SendableSlice::new(robj)?,
+        chunk_size,
+    })
+}
+
+/// The chunks of a vector made by [par_chunks()].
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, Copy)]
+pub struct ParChunks<'a, T> {
+    slice: SendableSlice<'a, T>,
+    chunk_size: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync> ParChunks<'a, T> {
+    /// Apply `map` to each chunk and combine the results with `reduce`, in a
+    /// rayon pool of at most [max_threads()] threads, and convert the result
+    /// to R.
+    ///
+    /// `reduce` must be associative, as the order of the reductions varies.
+    /// An empty vector gives `map(&[])`.
+    pub fn map_reduce<A, M, F>(&self, map: M, reduce: F) -> Result<Robj>
+    where
+        A: Send + Into<Robj>,
+        M: Fn(&[T]) -> A + Sync + Send,
+        F: Fn(A, A) -> A + Sync + Send,
+    {
+        Ok(self.map_reduce_value(map, reduce)?.into())
+    }
+
+    /// Like [ParChunks::map_reduce], but return the rust result.
+    pub fn map_reduce_value<A, M, F>(&self, map: M, reduce: F) -> Result<A>
+    where
+        A: Send,
+        M: Fn(&[T]) -> A + Sync + Send,
+        F: Fn(A, A) -> A + Sync + Send,
+    {
+        use rayon::prelude::*;
+        let data = self.slice.as_slice();
+        let chunk_size = self.chunk_size;
+        ThreadLimit::from_r_options().install(|| {
+            data.par_chunks(chunk_size)
+                .map(&map)
+                .reduce_with(&reduce)
+                .unwrap_or_else(|| map(&[]))
+        })
+    }
+}
@@ -0,0 +1,123 @@
+//! Quosures, for tidyverse-style arguments.
+//!
+//! A quosure is an expression bundled with the environment it was written in,
+//! as made by `rlang::quo()` and `rlang::enquo()`. Functions taking unquoted
+//! column names capture them with `enquo()` in their R wrapper and pass the
+//! quosure to rust, which can evaluate it against a data frame with
+//! [Quosure::eval_tidy].
+//!
+//! Quosures are made with the same representation as rlang, a formula of class
+//! `c("quosure", "formula")`, so rlang is only needed to evaluate them with
+//! its full data mask.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::tidyeval::Quosure;
+//! test! {
+//!     let env = Environment::new_with_parent(global_env());
+//!     env.set_local(sym!(y), 10.0);
+//!     let quo = Quosure::new(R!("quote(x + y)")?, env)?;
+//!     assert!(quo.as_robj().inherits("quosure"));
+//!
+//!     // `x` is a column of the data, `y` comes from the environment of the quosure.
+//!     let data = list!(x = 1.0);
+//!     assert_eq!(quo.eval_tidy(Some(&data))?, r!(11.0));
+//! }
+//! ```
+use crate::*;
+
+/// An expression with its environment, see the [module documentation](self).
+#[derive(Debug, PartialEq, Clone)]
+pub struct Quosure {
+    robj: Robj,
+}
+
+impl Quosure {
+    /// Make a quosure from an expression and an environment, like `rlang::new_quosure()`.
+    pub fn new<E: Into<Robj>>(expr: E, env: Environment) -> Result<Quosure> {
+        let expr = expr.into();
+        let mut robj = single_threaded(|| unsafe {
+            Robj::from_sexp(Rf_lang2(Symbol::from_string("~").get(), expr.get()))
+        });
+        robj.set_attrib(dot_environment(), env)?;
+        robj.set_class(["quosure", "formula"])?;
+        Ok(Quosure { robj })
+    }
+
+    /// The expression, like `rlang::quo_get_expr()`.
+    pub fn expr(&self) -> Robj {
+        unsafe { Robj::from_sexp(CADR(self.robj.get())) }
+    }
+
+    /// The environment, like `rlang::quo_get_env()`.
+    pub fn env(&self) -> Option<Environment> {
+        self.robj
+            .get_attrib(dot_environment())
+            .and_then(|env| env.as_environment())
+    }
+
+    /// The quosure as an R object.
+    pub fn as_robj(&self) -> &Robj {
+        &self.robj
+    }
+
+    /// Evaluate the quosure with `data`, a data frame or list, masking its environment.
+    ///
+    /// With rlang installed this is `rlang::eval_tidy()`, which supports the `.data`
+    /// and `.env` pronouns and nested quosures. Otherwise the expression is
+    /// evaluated with `eval(expr, data, env)`.
+    pub fn eval_tidy(&self, data: Option<&Robj>) -> Result<Robj> {
+        let quo = self.robj.clone();
+        let data = data.cloned().unwrap_or_else(|| ().into());
+        if has_rlang()? {
+            eval_string_with_params("rlang::eval_tidy(param.0, param.1)", &[&quo, &data])
+        } else {
+            let env: Robj = match self.env() {
+                Some(env) => env.into(),
+                None => global_env().into(),
+            };
+            eval_string_with_params(
+                "eval(param.0, param.1, param.2)",
+                &[&self.expr(), &data, &env],
+            )
+        }
+    }
+}
+
+impl TryFrom<&Robj> for Quosure {
+    type Error = Error;
+
+    /// Accept a quosure made by rlang or by [Quosure::new].
+    fn try_from(robj: &Robj) -> Result<Self> {
+        if robj.is_language() && robj.inherits("quosure") && robj.len() == 2 {
+            Ok(Quosure { robj: robj.clone() })
+        } else {
+            Err(Error::Other(format!(
+                "Expected a quosure got {:?}",
+                robj.rtype()
+            )))
+        }
+    }
+}
+
+impl TryFrom<Robj> for Quosure {
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Quosure::try_from(&robj)
+    }
+}
+
+impl From<Quosure> for Robj {
+    fn from(quo: Quosure) -> Self {
+        quo.robj
+    }
+}
+
+fn dot_environment() -> Symbol {
+    Symbol::from_string(".Environment")
+}
+
+fn has_rlang() -> Result<bool> {
+    Ok(crate::packages::require_namespace("rlang"))
+}
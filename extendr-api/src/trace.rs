@@ -0,0 +1,189 @@
+//! An opt-in log of the conversions made by `#[extendr]` wrappers.
+//!
+//! When tracing is on, every argument converted from R and every value
+//! returned to R is logged with its R type and length, the rust type, and
+//! whether the conversion copies the data. This helps to find large copies,
+//! such as a `Vec<f64>` argument that could be a `&[f64]` or `Doubles`.
+//!
+//! Turn tracing on from rust with [trace_conversions], or by setting the
+//! environment variable `EXTENDR_TRACE_CONVERSIONS` before the first call,
+//! to `console` to log to the R console or to a file name to append to a file.
+//! From R, `options(extendr.log_level = "trace")` logs to the console, see
+//! [crate::options].
+//!
+//! A line of the log looks like this:
+//!
+//! ```text
+//! extendr: mean_of(x): double[100000] -> alloc::vec::Vec<f64> (copy)
+//! extendr: mean_of() -> f64 -> double[1] (scalar)
+//! ```
+use crate::*;
+use once_cell::sync::Lazy;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// Where to write the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceTarget {
+    /// Print to the R console.
+    Console,
+    /// Append to a file.
+    File(PathBuf),
+}
+
+const UNKNOWN: u8 = 0;
+const OFF: u8 = 1;
+const ON: u8 = 2;
+
+// UNKNOWN until the environment variable has been read.
+static STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+static TARGET: Lazy<Mutex<Option<TraceTarget>>> = Lazy::new(|| Mutex::new(None));
+
+/// Turn tracing on with a target, or off with `None`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::trace::{is_tracing, trace_conversions, TraceTarget};
+/// test! {
+///     trace_conversions(Some(TraceTarget::Console));
+///     assert!(is_tracing());
+///     trace_conversions(None);
+///     assert!(!is_tracing());
+/// }
+/// ```
+pub fn trace_conversions(target: Option<TraceTarget>) {
+    let state = if target.is_some() { ON } else { OFF };
+    *TARGET.lock().unwrap_or_else(|e| e.into_inner()) = target;
+    STATE.store(state, Ordering::Release);
+}
+
+/// Return true if conversions are being logged.
+pub fn is_tracing() -> bool {
+    match STATE.load(Ordering::Acquire) {
+        ON => true,
+        OFF => false,
+        _ => {
+            let target = match std::env::var("EXTENDR_TRACE_CONVERSIONS") {
+                Ok(value) if value == "console" => Some(TraceTarget::Console),
+                Ok(value) if !value.is_empty() => Some(TraceTarget::File(value.into())),
+                _ => None,
+            };
+            let on = target.is_some();
+            trace_conversions(target);
+            on
+        }
+    }
+}
+
+/// Whether converting to or from the rust type `type_name` copies the data.
+fn conversion_kind(type_name: &str) -> &'static str {
+    const SCALARS: [&str; 14] = [
+        "bool", "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize", "f32",
+        "f64", "()",
+    ];
+    let inner = type_name
+        .strip_prefix("core::option::Option<")
+        .and_then(|t| t.strip_suffix('>'))
+        .unwrap_or(type_name);
+    if SCALARS.contains(&inner) || inner.starts_with("extendr_api::scalar::") {
+        "scalar"
+    } else if type_name.starts_with('&')
+        || type_name.starts_with("extendr_api::")
+        || type_name.contains("ArrayView")
+    {
+        "view"
+    } else {
+        "copy"
+    }
+}
+
+fn describe(robj: &Robj) -> String {
+    let rtype = match robj.rtype() {
+        Rtype::Null => "NULL",
+        Rtype::Logicals => "logical",
+        Rtype::Integers => "integer",
+        Rtype::Doubles => "double",
+        Rtype::Complexes => "complex",
+        Rtype::Strings => "character",
+        Rtype::Raw => "raw",
+        Rtype::List => "list",
+        Rtype::Environment => "environment",
+        Rtype::Function => "function",
+        Rtype::ExternalPtr => "externalptr",
+        _ => "other",
+    };
+    format!("{}[{}]", rtype, robj.len())
+}
+
+// The target set by trace_conversions(), or the console with
+// `options(extendr.log_level = "trace")`.
+fn current_target() -> Option<TraceTarget> {
+    if is_tracing() {
+        TARGET.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    } else if options::extendr_options().map_or(false, |o| o.logs(options::LogLevel::Trace)) {
+        Some(TraceTarget::Console)
+    } else {
+        None
+    }
+}
+
+fn write_line(target: TraceTarget, line: String) {
+    match target {
+        TraceTarget::Console => {
+            print_r_output(line);
+            print_r_output("\n");
+        }
+        TraceTarget::File(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path);
+            if let Ok(mut file) = file {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+}
+
+/// Log the conversion of argument `arg` of `function` to `T`. Used by `#[extendr]` wrappers.
+#[doc(hidden)]
+pub fn trace_arg<T: ?Sized>(function: &str, arg: &str, robj: &Robj) {
+    if let Some(target) = current_target() {
+        let type_name = std::any::type_name::<T>();
+        write_line(
+            target,
+            format!(
+                "extendr: {}({}): {} -> {} ({})",
+                function,
+                arg,
+                describe(robj),
+                type_name,
+                conversion_kind(type_name)
+            ),
+        );
+    }
+}
+
+/// Convert the value returned by `function` to an `Robj` and log the conversion.
+/// Used by `#[extendr]` wrappers.
+#[doc(hidden)]
+pub fn trace_return<T: Into<Robj>>(function: &str, value: T) -> Robj {
+    let target = match current_target() {
+        Some(target) => target,
+        None => return value.into(),
+    };
+    let type_name = std::any::type_name::<T>();
+    let robj = value.into();
+    write_line(
+        target,
+        format!(
+            "extendr: {}() -> {} -> {} ({})",
+            function,
+            type_name,
+            describe(&robj),
+            conversion_kind(type_name)
+        ),
+    );
+    robj
+}
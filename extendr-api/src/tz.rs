@@ -0,0 +1,163 @@
+//! Time zones as R sees them.
+//!
+//! R resolves time zones with its own copy of the tz database on Windows
+//! and macOS, and with the system's elsewhere, so offsets computed in rust
+//! from another copy can disagree with R around changes of the rules.
+//! These functions ask R, so that rust code gets the same zone names,
+//! offsets and abbreviations as R does on every platform.
+//!
+//! Times are `POSIXct` values: seconds since 1970-01-01 UTC. Date-times
+//! keep their time zone in the `"tzone"` attribute, where `""` or a missing
+//! attribute means the local time zone, see [tzone] and [resolve_timezone].
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::tz;
+//! test! {
+//!     assert!(tz::is_valid_timezone("Europe/London")?);
+//!     assert!(!tz::is_valid_timezone("Mars/Olympus_Mons")?);
+//!
+//!     // 2021-07-01 12:00:00 UTC, during British Summer Time.
+//!     let time = 1625140800.0;
+//!     assert_eq!(tz::utc_offset("Europe/London", time)?, 3600);
+//!     assert_eq!(tz::abbreviation("Europe/London", time)?, "BST");
+//!     assert!(tz::is_dst("Europe/London", time)?);
+//!     assert_eq!(tz::utc_offset("UTC", time)?, 0);
+//! }
+//! ```
+use crate as extendr_api;
+use crate::*;
+use std::path::PathBuf;
+
+/// The names of the time zones R knows, like `OlsonNames()`.
+pub fn timezone_names() -> Result<Vec<String>> {
+    call!("OlsonNames")?.try_as_string_vector()
+}
+
+/// True if R knows the time zone `tz`. `"UTC"` and `"GMT"` are always valid.
+pub fn is_valid_timezone(tz: &str) -> Result<bool> {
+    Ok(matches!(tz, "UTC" | "GMT") || timezone_names()?.iter().any(|name| name == tz))
+}
+
+/// The local time zone: the `TZ` environment variable if it is set,
+/// otherwise `Sys.timezone()`.
+pub fn local_timezone() -> Result<String> {
+    let tz = eval_string(
+        r#"local({ tz <- Sys.getenv("TZ"); if (nzchar(tz)) tz else Sys.timezone() })"#,
+    )?;
+    match tz.as_str() {
+        Some(tz) if !tz.is_na() => Ok(tz.to_string()),
+        _ => Err(Error::Other("the local time zone is not known".into())),
+    }
+}
+
+/// The time zone that R uses for `tz`: the local time zone for `""`,
+/// as in the `"tzone"` attribute and the `tz` arguments of base R.
+pub fn resolve_timezone(tz: &str) -> Result<String> {
+    if tz.is_empty() {
+        local_timezone()
+    } else {
+        Ok(tz.to_string())
+    }
+}
+
+/// The time zone of a date-time, from its `"tzone"` attribute.
+///
+/// `None` if there is no attribute, which R reads as the local time zone.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::tz;
+/// test! {
+///     let mut time = r!(0.0);
+///     assert_eq!(tz::tzone(&time), None);
+///     tz::set_tzone(&mut time, "Asia/Tokyo")?;
+///     assert_eq!(tz::tzone(&time).as_deref(), Some("Asia/Tokyo"));
+/// }
+/// ```
+pub fn tzone(robj: &Robj) -> Option<String> {
+    let tzone = robj.get_attrib(sym!(tzone))?;
+    tzone.as_str_iter()?.next().map(String::from)
+}
+
+/// Set the `"tzone"` attribute of a date-time, which changes how it is
+/// shown but not the time.
+pub fn set_tzone(robj: &mut Robj, tz: &str) -> Result<()> {
+    robj.set_attrib(sym!(tzone), tz)?;
+    Ok(())
+}
+
+/// The offset from UTC in seconds of the time zone `tz` at `time`.
+pub fn utc_offset(tz: &str, time: f64) -> Result<i32> {
+    Ok(utc_offsets(tz, &[time])?[0])
+}
+
+/// The offsets from UTC in seconds of the time zone `tz` at each time,
+/// as R computes them for `as.POSIXlt(time, tz)`.
+///
+/// Errors if R doesn't know the time zone, where R would use UTC with a
+/// warning.
+pub fn utc_offsets(tz: &str, times: &[f64]) -> Result<Vec<i32>> {
+    let lt = posixlt(tz, times)?;
+    // `gmtoff` is `NA` on some platforms, so fall back to the difference
+    // between the local time read as UTC and the time.
+    let offsets = eval_string_with_params(
+        r#"
+        off <- param.0$gmtoff
+        if (is.null(off) || anyNA(off)) {
+            local <- as.POSIXct(format(param.0, "%Y-%m-%d %H:%M:%S"), tz = "UTC")
+            off <- round(as.numeric(local) - floor(param.1))
+        }
+        as.integer(off)
+        "#,
+        &[&lt, &r!(times)],
+    )?;
+    Ok(offsets
+        .as_integer_slice()
+        .map(|offsets| offsets.to_vec())
+        .unwrap_or_default())
+}
+
+/// The abbreviation of the time zone `tz` at `time`, like `"BST"`.
+pub fn abbreviation(tz: &str, time: f64) -> Result<String> {
+    let lt = posixlt(tz, &[time])?;
+    let abbreviation = call!("format", lt, "%Z")?;
+    Ok(abbreviation.as_str().unwrap_or_default().to_string())
+}
+
+/// True if daylight saving time is in force in the time zone `tz` at `time`.
+pub fn is_dst(tz: &str, time: f64) -> Result<bool> {
+    let lt = posixlt(tz, &[time])?;
+    let isdst = lt.dollar("isdst")?;
+    Ok(isdst.as_integer().map_or(false, |isdst| isdst > 0))
+}
+
+/// The directory of the tz database files that R uses, if there is one.
+///
+/// This is the `TZDIR` environment variable if it is set, R's own copy in
+/// `R.home("share")`, which is used on Windows, or the system's copy.
+/// Rust code that reads zone files from here agrees with R.
+pub fn tzdata_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("TZDIR").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    let share = call!("R.home", "share").ok()?;
+    let r_dir = PathBuf::from(share.as_str()?).join("zoneinfo");
+    let candidates = [
+        r_dir,
+        PathBuf::from("/usr/share/zoneinfo"),
+        PathBuf::from("/usr/lib/zoneinfo"),
+        PathBuf::from("/usr/share/lib/zoneinfo"),
+    ];
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+fn posixlt(tz: &str, times: &[f64]) -> Result<Robj> {
+    let tz = resolve_timezone(tz)?;
+    if !is_valid_timezone(&tz)? {
+        return Err(Error::Other(format!("unknown time zone '{}'", tz)));
+    }
+    eval_string_with_params(
+        "as.POSIXlt(.POSIXct(param.0, tz = param.1))",
+        &[&r!(times), &r!(tz)],
+    )
+}
@@ -0,0 +1,158 @@
+//! Record and `list_of` vectors compatible with the vctrs package.
+//!
+//! These build the same objects as `vctrs::new_rcrd()` and `vctrs::new_list_of()`
+//! without calling into vctrs, so packages can return tidyverse-compatible
+//! vectors from rust. Only the package using the objects needs vctrs installed.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::vctrs::{new_rcrd, validate_rcrd};
+//! test! {
+//!     let x = new_rcrd([("x", r!([1.0, 2.0])), ("y", r!([3.0, 4.0]))], &["point"])?;
+//!     assert!(x.inherits("point"));
+//!     assert!(x.inherits("vctrs_rcrd"));
+//!     assert_eq!(validate_rcrd(&x), Ok(()));
+//! }
+//! ```
+use crate::*;
+
+/// Make a record vector, like `vctrs::new_rcrd(fields, class = class)`.
+///
+/// A record is a list of named fields of the same length. Each element of
+/// the record is one value from each field. `class` is the subclass, which is
+/// followed by `vctrs_rcrd` and `vctrs_vctr`.
+pub fn new_rcrd<I, N, V>(fields: I, class: &[&str]) -> Result<Robj>
+where
+    I: IntoIterator<Item = (N, V)>,
+    N: AsRef<str>,
+    V: Into<Robj>,
+{
+    let fields: Vec<(N, Robj)> = fields.into_iter().map(|(n, v)| (n, v.into())).collect();
+    let mut res = List::from_names_and_values(
+        fields.iter().map(|(n, _)| n.as_ref()).collect::<Vec<_>>(),
+        fields.into_iter().map(|(_, v)| v),
+    )?
+    .into_robj();
+    validate_rcrd_fields(&res)?;
+    res.set_class(with_base_classes(class, &["vctrs_rcrd", "vctrs_vctr"]))?;
+    Ok(res)
+}
+
+/// Check that `x` is a valid record vector.
+pub fn validate_rcrd(x: &Robj) -> Result<()> {
+    if !x.inherits("vctrs_rcrd") {
+        return Err(Error::Other("expected a vctrs_rcrd".to_string()));
+    }
+    validate_rcrd_fields(x)
+}
+
+fn validate_rcrd_fields(x: &Robj) -> Result<()> {
+    let list = List::try_from(x)?;
+    if list.len() == 0 {
+        return Err(Error::Other(
+            "a record needs at least one field".to_string(),
+        ));
+    }
+    let names: Vec<&str> = match x.names() {
+        Some(names) => names.collect(),
+        None => return Err(Error::Other("record fields must be named".to_string())),
+    };
+    for (i, name) in names.iter().enumerate() {
+        if name.is_empty() || name.is_na() {
+            return Err(Error::Other("record fields must be named".to_string()));
+        }
+        if names[..i].contains(name) {
+            return Err(Error::Other(format!("duplicate record field '{}'", name)));
+        }
+    }
+    let len = list.elt(0)?.len();
+    for (name, field) in names.iter().zip(list.values()) {
+        if !field.is_vector() || field.get_attrib(dim_symbol()).is_some() {
+            return Err(Error::Other(format!(
+                "record field '{}' must be a vector",
+                name
+            )));
+        }
+        if field.len() != len {
+            return Err(Error::Other(format!(
+                "record fields must have the same length, '{}' has length {} not {}",
+                name,
+                field.len(),
+                len
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Make a list of vectors of the same type, like `vctrs::new_list_of(values, ptype, class = class)`.
+///
+/// `ptype` is a zero length prototype of the elements, for example `Integers::new(0)`.
+/// Each element must be `NULL` or a vector of the same type and class as `ptype`.
+/// `class` is the subclass, which is followed by `vctrs_list_of`, `vctrs_vctr` and `list`.
+/// ```
+/// use extendr_api::prelude::*;
+/// use extendr_api::vctrs::new_list_of;
+/// test! {
+///     let x = new_list_of([r!([1, 2]), r!(()), r!(3)], Integers::new(0), &[])?;
+///     assert!(x.inherits("vctrs_list_of"));
+///     assert_eq!(x.get_attrib(sym!(ptype)), Some(Integers::new(0).into()));
+///
+///     assert!(new_list_of([r!(1), r!("a")], Integers::new(0), &[]).is_err());
+/// }
+/// ```
+pub fn new_list_of<I, V, P>(values: I, ptype: P, class: &[&str]) -> Result<Robj>
+where
+    I: IntoIterator<Item = V>,
+    V: Into<Robj>,
+    P: Into<Robj>,
+{
+    let ptype = ptype.into();
+    if ptype.len() != 0 {
+        return Err(Error::Other("ptype must have length zero".to_string()));
+    }
+    let mut res: Robj = values.into_iter().map(Into::into).collect::<List>().into();
+    validate_list_of_elements(&res, &ptype)?;
+    res.set_attrib(ptype_symbol(), ptype)?;
+    res.set_class(with_base_classes(
+        class,
+        &["vctrs_list_of", "vctrs_vctr", "list"],
+    ))?;
+    Ok(res)
+}
+
+/// Check that `x` is a valid `list_of` vector.
+pub fn validate_list_of(x: &Robj) -> Result<()> {
+    if !x.inherits("vctrs_list_of") {
+        return Err(Error::Other("expected a vctrs_list_of".to_string()));
+    }
+    let ptype = x
+        .get_attrib(ptype_symbol())
+        .ok_or_else(|| Error::Other("a list_of needs a ptype attribute".to_string()))?;
+    validate_list_of_elements(x, &ptype)
+}
+
+fn validate_list_of_elements(x: &Robj, ptype: &Robj) -> Result<()> {
+    let list = List::try_from(x)?;
+    let ptype_class = ptype.get_attrib(class_symbol());
+    for (i, value) in list.values().enumerate() {
+        if value.is_null() {
+            continue;
+        }
+        if value.rtype() != ptype.rtype() || value.get_attrib(class_symbol()) != ptype_class {
+            return Err(Error::Other(format!(
+                "element {} of a list_of must have the same type as the ptype",
+                i + 1
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn with_base_classes<'a>(class: &[&'a str], base: &[&'a str]) -> Vec<&'a str> {
+    class.iter().chain(base).copied().collect()
+}
+
+fn ptype_symbol() -> Symbol {
+    Symbol::from_string("ptype")
+}
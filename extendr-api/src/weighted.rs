@@ -0,0 +1,204 @@
+//! Weighted means, variances and quantiles with R's handling of `NA`.
+//!
+//! [weighted_mean()] follows `stats::weighted.mean()`: elements with zero
+//! weight are ignored, even if they are `NA`, negative weights are allowed
+//! and `na_rm` removes `NA` values of `x` but not of the weights.
+//! [weighted_var()] and [weighted_quantile()] follow `cov.wt()` and
+//! `Hmisc::wtd.var()` / `Hmisc::wtd.quantile()`, for which weights must not
+//! be negative, and `na_rm` removes the pairs where either is `NA`.
+//!
+//! ```
+//! use extendr_api::prelude::*;
+//! use extendr_api::weighted::{weighted_mean, weighted_quantile, weighted_var, VarianceWeights};
+//! test! {
+//!     let x = [1.0, 2.0, 3.0, f64::na()];
+//!     let w = [3.0, 1.0, 0.0, 1.0];
+//!     assert!(weighted_mean(&x, &w, false)?.is_na());
+//!     assert_eq!(weighted_mean(&x, &w, true)?, 1.25);
+//!
+//!     // Frequency weights count repeated observations: 1, 1, 1, 2.
+//!     assert_eq!(weighted_var(&x, &w, VarianceWeights::Frequency, true)?, 0.25);
+//!     assert_eq!(weighted_quantile(&x, &w, &[0.5, 1.0], true)?, [1.0, 2.0]);
+//! }
+//! ```
+use crate::*;
+
+/// The weighted mean of `x`, like `weighted.mean(x, w, na.rm)`.
+///
+/// The result is `NA` if a weight, or an element with a non-zero weight,
+/// is `NA`, and `NaN` if there are no elements or the weights sum to zero.
+/// Like `is.na()`, `na_rm` treats `NaN` as missing.
+pub fn weighted_mean(x: &[f64], w: &[f64], na_rm: bool) -> Result<f64> {
+    check_lengths(x, w)?;
+    let mut sum = 0.0;
+    let mut sum_w = 0.0;
+    for (&x, &w) in x.iter().zip(w) {
+        if na_rm && x.is_nan() {
+            continue;
+        }
+        if w.is_nan() || (x.is_na() && w != 0.0) {
+            return Ok(f64::na());
+        }
+        if w != 0.0 {
+            sum += x * w;
+        }
+        sum_w += w;
+    }
+    Ok(sum / sum_w)
+}
+
+/// The meaning of the weights of [weighted_var()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarianceWeights {
+    /// The number of times each value was seen, as in
+    /// `Hmisc::wtd.var(method = "unbiased")`. The variance is `NA` if
+    /// the weights add up to one or less.
+    Frequency,
+    /// Relative weights, normalized to add up to one, with the unbiased
+    /// estimate of `cov.wt(method = "unbiased")`.
+    Reliability,
+    /// Relative weights with the maximum likelihood estimate of
+    /// `cov.wt(method = "ML")`.
+    ML,
+}
+
+/// The weighted variance of `x`.
+///
+/// The result is `NA` if there are `NA` values and `na_rm` is false.
+/// Negative weights are an error.
+pub fn weighted_var(x: &[f64], w: &[f64], weights: VarianceWeights, na_rm: bool) -> Result<f64> {
+    let (x, w) = match complete_pairs(x, w, na_rm)? {
+        Some(pairs) => pairs,
+        None => return Ok(f64::na()),
+    };
+    let sum_w: f64 = w.iter().sum();
+    let center = x.iter().zip(&w).map(|(x, w)| x * w).sum::<f64>() / sum_w;
+    let sum_sq = x
+        .iter()
+        .zip(&w)
+        .map(|(x, w)| w * (x - center) * (x - center))
+        .sum::<f64>();
+    Ok(match weights {
+        VarianceWeights::Frequency if sum_w <= 1.0 => f64::na(),
+        VarianceWeights::Frequency => sum_sq / (sum_w - 1.0),
+        VarianceWeights::Reliability => {
+            let sum_w2 = w.iter().map(|w| (w / sum_w) * (w / sum_w)).sum::<f64>();
+            sum_sq / sum_w / (1.0 - sum_w2)
+        }
+        VarianceWeights::ML => sum_sq / sum_w,
+    })
+}
+
+/// Weighted quantiles of `x` at the probabilities `probs`, like
+/// `Hmisc::wtd.quantile(x, w, probs, normwt = FALSE)`.
+///
+/// The weights are frequency weights: with whole number weights the
+/// quantiles are those of `quantile(rep(x, w), probs)`. It is an error if
+/// there are `NA` values and `na_rm` is false, if a weight is negative,
+/// if a probability is outside `[0, 1]` or if no value has a positive
+/// weight.
+pub fn weighted_quantile(x: &[f64], w: &[f64], probs: &[f64], na_rm: bool) -> Result<Vec<f64>> {
+    if let Some(p) = probs.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+        return Err(Error::OutOfRange(r!(*p)));
+    }
+    let (x, w) = match complete_pairs(x, w, na_rm)? {
+        Some(pairs) => pairs,
+        None => {
+            return Err(Error::Other(
+                "missing values are not allowed if na_rm is false".to_string(),
+            ))
+        }
+    };
+
+    // The distinct values with positive weight, sorted, and the running
+    // total of their weights, as Hmisc::wtd.table() gives them.
+    let mut pairs: Vec<(f64, f64)> = x.into_iter().zip(w).filter(|(_, w)| *w > 0.0).collect();
+    if pairs.is_empty() {
+        return Err(Error::Other(
+            "weighted quantiles need a value with a positive weight".to_string(),
+        ));
+    }
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let mut values: Vec<f64> = Vec::new();
+    let mut cum_w: Vec<f64> = Vec::new();
+    let mut total = 0.0;
+    for (x, w) in pairs {
+        total += w;
+        if values.last() == Some(&x) {
+            *cum_w.last_mut().unwrap() = total;
+        } else {
+            values.push(x);
+            cum_w.push(total);
+        }
+    }
+
+    Ok(probs
+        .iter()
+        .map(|p| {
+            let order = 1.0 + (total - 1.0) * p;
+            let low = order.floor().max(1.0);
+            let high = (low + 1.0).min(total);
+            let frac = order % 1.0;
+            let q_low = step_value(&cum_w, &values, low);
+            let q_high = step_value(&cum_w, &values, high);
+            (1.0 - frac) * q_low + frac * q_high
+        })
+        .collect())
+}
+
+// approx(cum_w, values, xout = v, method = "constant", f = 1, rule = 2).
+fn step_value(cum_w: &[f64], values: &[f64], v: f64) -> f64 {
+    let (mut i, mut j) = (0, cum_w.len() - 1);
+    if v <= cum_w[i] {
+        return values[i];
+    }
+    if v >= cum_w[j] {
+        return values[j];
+    }
+    while i < j - 1 {
+        let mid = (i + j) / 2;
+        if v < cum_w[mid] {
+            j = mid;
+        } else {
+            i = mid;
+        }
+    }
+    if v == cum_w[i] {
+        values[i]
+    } else {
+        values[j]
+    }
+}
+
+fn check_lengths(x: &[f64], w: &[f64]) -> Result<()> {
+    if x.len() == w.len() {
+        Ok(())
+    } else {
+        Err(Error::Other(format!(
+            "values and weights have lengths {} and {}, not the same",
+            x.len(),
+            w.len()
+        )))
+    }
+}
+
+// The pairs without NA, or None if there is an NA and na_rm is false.
+fn complete_pairs(x: &[f64], w: &[f64], na_rm: bool) -> Result<Option<(Vec<f64>, Vec<f64>)>> {
+    check_lengths(x, w)?;
+    let mut xs = Vec::with_capacity(x.len());
+    let mut ws = Vec::with_capacity(w.len());
+    for (&x, &w) in x.iter().zip(w) {
+        if x.is_nan() || w.is_nan() {
+            if na_rm {
+                continue;
+            }
+            return Ok(None);
+        }
+        if w < 0.0 {
+            return Err(Error::Other("weights must not be negative".to_string()));
+        }
+        xs.push(x);
+        ws.push(w);
+    }
+    Ok(Some((xs, ws)))
+}
@@ -1,6 +1,18 @@
 use prelude::{Rbool, Rcplx, Rfloat, Rint, Scalar};
 
 use super::*;
+use once_cell::sync::Lazy;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Mutex;
+
+// The class made for each state type, so that vectors share one class.
+// Classes are preserved, so the SEXPs stay valid.
+static ALTREP_CLASSES: Lazy<Mutex<HashMap<TypeId, usize>>> = Lazy::new(Default::default);
+
+// The library registering classes, set by the module init function.
+static ALTREP_DLL_INFO: AtomicPtr<DllInfo> = AtomicPtr::new(std::ptr::null_mut());
 
 macro_rules! make_from_iterator {
     ($fn_name : ident, $make_class : ident, $impl : ident, $scalar_type : ident, $prim_type : ty) => {
@@ -33,7 +45,9 @@ macro_rules! make_from_iterator {
                 }
             }
 
-            let class = Altrep::$make_class::<Iter>(std::any::type_name::<Iter>(), "extendr");
+            let class = Altrep::cached_class::<Iter>(|| {
+                Altrep::$make_class::<Iter>(std::any::type_name::<Iter>(), "extendr")
+            });
             let robj: Robj = Altrep::from_state_and_class(iter, class, false).into();
             Altrep { robj }
         }
@@ -180,6 +194,57 @@ fn manifest(x: SEXP) -> SEXP {
     })
 }
 
+/// An ALTREP class that [extendr_module!](crate::extendr_module) registers
+/// when the package is loaded, with `altrep Type;`. Make vectors of the
+/// class with [Altrep::from_state()].
+///
+/// Implement [AltrepImpl] and the trait for the type of vector, such as
+/// [AltRealImpl], then make the class in `make_class`.
+/// ```
+/// use extendr_api::prelude::*;
+///
+/// #[derive(Debug, Clone)]
+/// struct Squares {
+///     len: usize,
+/// }
+///
+/// impl AltrepImpl for Squares {
+///     fn length(&self) -> usize {
+///         self.len
+///     }
+/// }
+///
+/// impl AltRealImpl for Squares {
+///     fn elt(&self, index: usize) -> Rfloat {
+///         ((index * index) as f64).into()
+///     }
+/// }
+///
+/// impl AltrepClass for Squares {
+///     fn make_class(name: &str, base: &str) -> Robj {
+///         Altrep::make_altreal_class::<Self>(name, base)
+///     }
+/// }
+///
+/// test! {
+///     // A billion elements, computed when R asks for them.
+///     let x = Altrep::from_state(Squares { len: 1_000_000_000 });
+///     assert_eq!(x.len(), 1_000_000_000);
+///     let x = Doubles::try_from(Robj::from(x))?;
+///     assert_eq!(x.elt(30_000), Rfloat::from(900_000_000.0));
+///     assert!(!Altrep::try_from(Robj::from(x))?.is_manifest());
+/// }
+/// ```
+pub trait AltrepClass: AltrepImpl + 'static {
+    /// The name of the class, which must be unique in the package.
+    fn class_name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Make the class with one of the `Altrep::make_alt*_class` functions.
+    fn make_class(name: &str, base: &str) -> Robj;
+}
+
 pub trait AltIntegerImpl: AltrepImpl {
     fn tot_min_max_nas(&self) -> (i64, i32, i32, usize, usize) {
         let len = self.length();
@@ -465,6 +530,19 @@ pub trait AltListImpl {
     fn set_elt(&mut self, _index: usize, _value: Robj) {}
 }
 
+/// With `strict-checks`, raise an R error if an ALTREP element callback
+/// is called with an index outside of `0..length(x)`.
+#[cfg(feature = "strict-checks")]
+unsafe fn check_altrep_index(x: SEXP, i: R_xlen_t) {
+    let len = Rf_xlength(x);
+    if i < 0 || i >= len {
+        throw_r_error(format!(
+            "strict-checks: ALTREP index {} out of bounds for length {}",
+            i, len
+        ));
+    }
+}
+
 impl Altrep {
     /// Safely implement R_altrep_data1, R_altrep_data2.
     /// When implementing Altrep classes, this gets the metadata.
@@ -527,6 +605,59 @@ impl Altrep {
         })
     }
 
+    /// Make a vector of a class registered with `altrep` in
+    /// [extendr_module!](crate::extendr_module).
+    /// The elements are computed by `state` when R asks for them.
+    ///
+    /// The class is made on first use if the module has not registered it,
+    /// for example in tests.
+    pub fn from_state<StateType: AltrepClass>(state: StateType) -> Altrep {
+        let class = Altrep::cached_class::<StateType>(|| {
+            StateType::make_class(StateType::class_name(), "extendr")
+        });
+        Altrep::from_state_and_class(state, class, false)
+    }
+
+    /// Register the class of `StateType` for the package `package`.
+    /// This is called by the init function that
+    /// [extendr_module!](crate::extendr_module) generates, so that R can
+    /// find the class when it reads a saved vector.
+    ///
+    /// # Safety
+    ///
+    /// `dll_info` must be null or the `DllInfo` that R passed to the init function.
+    pub unsafe fn register_class<StateType: AltrepClass>(dll_info: *mut DllInfo, package: &str) {
+        ALTREP_DLL_INFO.store(dll_info, Ordering::Relaxed);
+        let class = StateType::make_class(StateType::class_name(), package);
+        single_threaded(|| R_PreserveObject(class.get()));
+        ALTREP_CLASSES
+            .lock()
+            .unwrap()
+            .insert(TypeId::of::<StateType>(), class.get() as usize);
+    }
+
+    // The class of `StateType`, made by `make_class` the first time.
+    fn cached_class<StateType: 'static>(make_class: impl FnOnce() -> Robj) -> Robj {
+        let key = TypeId::of::<StateType>();
+        let cached = ALTREP_CLASSES.lock().unwrap().get(&key).copied();
+        let sexp = match cached {
+            Some(sexp) => sexp,
+            None => {
+                // Make the class without holding the lock, as it calls R.
+                let class = make_class();
+                unsafe {
+                    single_threaded(|| R_PreserveObject(class.get()));
+                    *ALTREP_CLASSES
+                        .lock()
+                        .unwrap()
+                        .entry(key)
+                        .or_insert(class.get() as usize)
+                }
+            }
+        };
+        unsafe { Robj::from_sexp(sexp as SEXP) }
+    }
+
     /// Return true if the ALTREP object has been manifested (copied into memory).
     pub fn is_manifest(&self) -> bool {
         unsafe { !DATAPTR_OR_NULL(self.get()).is_null() }
@@ -650,30 +781,25 @@ impl Altrep {
         unsafe {
             let csname = std::ffi::CString::new(name).unwrap();
             let csbase = std::ffi::CString::new(base).unwrap();
+            let dll_info = ALTREP_DLL_INFO.load(Ordering::Relaxed);
 
             let class_ptr = match ty {
                 Rtype::Integers => {
-                    R_make_altinteger_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
-                }
-                Rtype::Doubles => {
-                    R_make_altreal_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
+                    R_make_altinteger_class(csname.as_ptr(), csbase.as_ptr(), dll_info)
                 }
+                Rtype::Doubles => R_make_altreal_class(csname.as_ptr(), csbase.as_ptr(), dll_info),
                 Rtype::Logicals => {
-                    R_make_altlogical_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
-                }
-                Rtype::Raw => {
-                    R_make_altraw_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
+                    R_make_altlogical_class(csname.as_ptr(), csbase.as_ptr(), dll_info)
                 }
+                Rtype::Raw => R_make_altraw_class(csname.as_ptr(), csbase.as_ptr(), dll_info),
                 Rtype::Complexes => {
-                    R_make_altcomplex_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
+                    R_make_altcomplex_class(csname.as_ptr(), csbase.as_ptr(), dll_info)
                 }
                 Rtype::Strings => {
-                    R_make_altstring_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
+                    R_make_altstring_class(csname.as_ptr(), csbase.as_ptr(), dll_info)
                 }
                 #[cfg(use_r_altlist)]
-                Rtype::List => {
-                    R_make_altlist_class(csname.as_ptr(), csbase.as_ptr(), std::ptr::null_mut())
-                }
+                Rtype::List => R_make_altlist_class(csname.as_ptr(), csbase.as_ptr(), dll_info),
                 _ => panic!("expected Altvec compatible type"),
             };
 
@@ -716,6 +842,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> c_int {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state::<StateType>(x).elt(i as usize).inner() as c_int
             }
 
@@ -790,6 +918,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> f64 {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state::<StateType>(x).elt(i as usize).inner()
             }
 
@@ -861,6 +991,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> c_int {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state::<StateType>(x).elt(i as usize).inner() as c_int
             }
 
@@ -918,6 +1050,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> Rbyte {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state::<StateType>(x).elt(i as usize) as Rbyte
             }
 
@@ -953,6 +1087,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> Rcomplex {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 std::mem::transmute(Altrep::get_state::<StateType>(x).elt(i as usize))
             }
 
@@ -989,6 +1125,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> SEXP {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state::<StateType>(x).elt(i as usize).get()
             }
 
@@ -997,6 +1135,8 @@ impl Altrep {
                 i: R_xlen_t,
                 v: SEXP,
             ) {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state_mut::<StateType>(x)
                     .set_elt(i as usize, Robj::from_sexp(v).try_into().unwrap())
             }
@@ -1037,6 +1177,8 @@ impl Altrep {
                 x: SEXP,
                 i: R_xlen_t,
             ) -> SEXP {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state::<StateType>(x).elt(i as usize).get()
             }
 
@@ -1045,6 +1187,8 @@ impl Altrep {
                 i: R_xlen_t,
                 v: SEXP,
             ) {
+                #[cfg(feature = "strict-checks")]
+                check_altrep_index(x, i);
                 Altrep::get_state_mut::<StateType>(x)
                     .set_elt(i as usize, Robj::from_sexp(v).try_into().unwrap())
             }
@@ -1,9 +1,14 @@
 use super::*;
+use crate::scalar::{Rfloat, Rint, Scalar};
 
 pub trait IntoDataFrameRow<T> {
     fn into_dataframe(self) -> Result<Dataframe<T>>;
 }
 
+/// A wrapper for `data.frame` objects, with typed access to columns and rows.
+///
+/// `T` is the row type of frames made with `#[derive(IntoDataFrameRow)]`, or `()`.
+/// Use [DataframeBuilder] to make a data frame from columns.
 #[derive(PartialEq, Clone)]
 pub struct Dataframe<T> {
     pub(crate) robj: Robj,
@@ -37,8 +42,555 @@ impl<T> Dataframe<T> {
     pub fn try_from_values<I: IntoDataFrameRow<T>>(iter: I) -> Result<Self> {
         iter.into_dataframe()
     }
+
+    fn columns(&self) -> List {
+        List {
+            robj: self.robj.clone(),
+        }
+    }
+
+    /// The number of columns.
+    pub fn ncol(&self) -> usize {
+        self.robj.len()
+    }
+
+    /// The number of rows, given by the row names.
+    ///
+    /// Without row names, this is the length of the first column.
+    pub fn nrow(&self) -> usize {
+        // Read the attribute directly, as `get_attrib()` expands the compact
+        // row names `c(NA, -nrow)` to `1:nrow`.
+        let attrib = unsafe { Robj::from_sexp(ATTRIB(self.robj.get())) };
+        let row_names = attrib.as_pairlist().and_then(|attrib| {
+            attrib
+                .iter()
+                .find(|(name, _)| *name == "row.names")
+                .map(|(_, value)| value)
+        });
+        match row_names {
+            Some(row_names) => match row_names.as_integer_slice() {
+                Some(&[first, n]) if first.is_na() => n.unsigned_abs() as usize,
+                _ => row_names.len(),
+            },
+            None => self
+                .columns()
+                .values()
+                .next()
+                .map_or(0, |column| column.len()),
+        }
+    }
+
+    /// The names of the columns.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.robj
+            .names()
+            .map_or_else(Vec::new, |names| names.collect())
+    }
+
+    /// Get the column called `name` as a wrapper such as [Doubles] or [Strings].
+    ///
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let df: Dataframe<()> = R!("data.frame(x = c(1.5, 2.5), y = c('a', NA))")?.try_into()?;
+    ///     assert_eq!((df.nrow(), df.ncol()), (2, 2));
+    ///     assert_eq!(df.column_names(), ["x", "y"]);
+    ///
+    ///     let x: Doubles = df.column("x")?;
+    ///     assert_eq!(x.elt(1), 2.5);
+    ///     assert!(df.column::<Integers>("x").is_err());
+    ///     assert!(df.column::<Doubles>("z").is_err());
+    /// }
+    /// ```
+    pub fn column<C>(&self, name: &str) -> Result<C>
+    where
+        C: TryFrom<Robj, Error = Error>,
+    {
+        let index = self
+            .column_names()
+            .iter()
+            .position(|n| *n == name)
+            .ok_or_else(|| Error::NotFound(r!(name)))?;
+        self.column_at(index)
+    }
+
+    /// Get the column at zero-based `index` as a wrapper such as [Doubles] or [Strings].
+    pub fn column_at<C>(&self, index: usize) -> Result<C>
+    where
+        C: TryFrom<Robj, Error = Error>,
+    {
+        if index >= self.ncol() {
+            return Err(Error::OutOfRange(r!(index as i32)));
+        }
+        self.columns().elt(index)?.try_into()
+    }
+
+    /// Get the column called `name` as a slice of `i32`, `f64`, `Rint`, `Rfloat`,
+    /// `Rbool` or `u8`, without copying.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let df: Dataframe<()> = R!("data.frame(x = 1:3, y = c(0.5, 1.5, NA))")?.try_into()?;
+    ///     assert_eq!(df.column_slice::<i32>("x")?, [1, 2, 3]);
+    ///     assert_eq!(df.column_slice::<Rfloat>("y")?[2], Rfloat::na());
+    ///     assert!(df.column_slice::<f64>("x").is_err());
+    /// }
+    /// ```
+    pub fn column_slice<'a, E>(&'a self, name: &str) -> Result<&'a [E]>
+    where
+        Robj: AsTypedSlice<'a, E>,
+    {
+        let column: Robj = self.column(name)?;
+        column
+            .as_typed_slice()
+            .ok_or_else(|| Error::TypeMismatch(column.clone()))
+    }
+
+    /// Iterate over the rows as tuples, or structs with `#[derive(FromDataframeRow)]`.
+    ///
+    /// Each row is an `Err` if a value can't be converted, for example an `NA`
+    /// read as `f64` rather than `Option<f64>` or [Rfloat].
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let df: Dataframe<()> = R!("data.frame(x = c(1.5, 2.5), y = c('a', NA))")?.try_into()?;
+    ///     let rows = df.rows::<(f64, Option<String>)>()?.collect::<Result<Vec<_>>>()?;
+    ///     assert_eq!(rows, [(1.5, Some("a".to_string())), (2.5, None)]);
+    ///     assert!(df.rows::<(f64, String)>()?.nth(1).unwrap().is_err());
+    /// }
+    /// ```
+    pub fn rows<R: FromDataframeRow>(&self) -> Result<RowIter<R>> {
+        self.row_view(RowIndices::Range(0..self.nrow())).rows()
+    }
+
+    /// A view of the rows in `range`, without copying the data.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let df: Dataframe<()> = R!("data.frame(x = 1:5)")?.try_into()?;
+    ///     let view = df.slice_rows(1..3)?;
+    ///     let x = view.rows::<(i32,)>()?.collect::<Result<Vec<_>>>()?;
+    ///     assert_eq!(x, [(2,), (3,)]);
+    ///     assert_eq!(view.to_dataframe()?.column::<Integers>("x")?, Integers::from_values([2, 3]));
+    /// }
+    /// ```
+    pub fn slice_rows(&self, range: std::ops::Range<usize>) -> Result<RowView<T>> {
+        if range.start > range.end || range.end > self.nrow() {
+            return Err(Error::OutOfRange(r!(range.end as i32)));
+        }
+        Ok(self.row_view(RowIndices::Range(range)))
+    }
+
+    /// A view of the rows at zero-based `indices`, in that order, without copying the data.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let df: Dataframe<()> = R!("data.frame(x = 1:5)")?.try_into()?;
+    ///     let view = df.take_rows(&[4, 0])?;
+    ///     assert_eq!(view.len(), 2);
+    ///     assert_eq!(view.row_index(0), 4);
+    ///     assert!(df.take_rows(&[5]).is_err());
+    /// }
+    /// ```
+    pub fn take_rows(&self, indices: &[usize]) -> Result<RowView<T>> {
+        let nrow = self.nrow();
+        if let Some(&index) = indices.iter().find(|&&i| i >= nrow) {
+            return Err(Error::OutOfRange(r!(index as i32)));
+        }
+        Ok(self.row_view(RowIndices::Indices(indices.to_vec())))
+    }
+
+    fn row_view(&self, rows: RowIndices) -> RowView<T> {
+        RowView { df: self, rows }
+    }
+}
+
+/// Build a [Dataframe] column by column.
+///
+/// The columns must have the same length. Row names are the compact
+/// `c(NA, -nrow)` form R uses for automatic row names.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let df = DataframeBuilder::new()
+///         .column("x", [1.5, 2.5])
+///         .column_from_iter("y", ["a", "b"].iter().map(|s| s.to_uppercase()))
+///         .build()?;
+///     assert_eq!(df.as_robj(), &R!("data.frame(x = c(1.5, 2.5), y = c('A', 'B'))")?);
+///     assert_eq!(df.nrow(), 2);
+///
+///     let ragged = DataframeBuilder::new().column("x", [1, 2]).column("y", [1]);
+///     assert!(ragged.build().is_err());
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DataframeBuilder {
+    names: Vec<String>,
+    columns: Vec<Robj>,
+}
+
+impl DataframeBuilder {
+    /// Make a builder with no columns.
+    pub fn new() -> Self {
+        DataframeBuilder::default()
+    }
+
+    /// Add a column, or replace the column with the same name.
+    pub fn column<V: Into<Robj>>(mut self, name: &str, values: V) -> Self {
+        let values = values.into();
+        match self.names.iter().position(|n| n == name) {
+            Some(index) => self.columns[index] = values,
+            None => {
+                self.names.push(name.to_string());
+                self.columns.push(values);
+            }
+        }
+        self
+    }
+
+    /// Add a list column, with `NULL` for `None`, like the nested columns
+    /// of tidyr. Each element can be a vector of any length.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let df = DataframeBuilder::new()
+    ///         .column("id", [1, 2, 3])
+    ///         .list_column("scores", vec![Some(vec![1.5, 2.5]), None, Some(vec![])])
+    ///         .build()?;
+    ///     let scores: List = df.column("scores")?;
+    ///     assert_eq!(scores.elt(0)?, r!([1.5, 2.5]));
+    ///     assert!(scores.elt(1)?.is_null());
+    ///     assert_eq!(df.nrow(), 3);
+    /// }
+    /// ```
+    pub fn list_column<I, V>(self, name: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = Option<V>>,
+        I::IntoIter: ExactSizeIterator,
+        V: Into<Robj>,
+    {
+        let values = values
+            .into_iter()
+            .map(|value| value.map_or_else(|| Robj::from(()), Into::into));
+        self.column(name, List::from_values(values))
+    }
+
+    /// Add a column collected from an iterator of numbers, booleans or strings.
+    pub fn column_from_iter<I>(self, name: &str, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToVectorValue,
+    {
+        self.column(name, values.into_iter().collect_robj())
+    }
+
+    /// The number of columns added so far.
+    pub fn ncol(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Make the data frame. Columns of different lengths are an error.
+    pub fn build(self) -> Result<Dataframe<()>> {
+        let nrow = self.columns.first().map_or(0, |column| column.len());
+        let ragged = self
+            .names
+            .iter()
+            .zip(&self.columns)
+            .find(|(_, column)| column.len() != nrow);
+        if let Some((name, column)) = ragged {
+            // A list column has one element per row, so a missing value
+            // must be a `NULL` element rather than a shorter list.
+            let hint = if column.is_list() {
+                ", use None for rows without a value"
+            } else {
+                ""
+            };
+            return Err(Error::Other(format!(
+                "data frame columns must have the same length, '{}' has length {} not {}{}",
+                name,
+                column.len(),
+                nrow,
+                hint
+            )));
+        }
+        let mut robj: Robj = List::from_names_and_values(self.names, self.columns)?.into();
+        robj.set_attrib(row_names_symbol(), [i32::na(), -(nrow as i32)])?;
+        robj.set_class(["data.frame"])?;
+        robj.try_into()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum RowIndices {
+    Range(std::ops::Range<usize>),
+    Indices(Vec<usize>),
 }
 
+impl RowIndices {
+    fn len(&self) -> usize {
+        match self {
+            RowIndices::Range(range) => range.len(),
+            RowIndices::Indices(indices) => indices.len(),
+        }
+    }
+
+    fn get(&self, i: usize) -> usize {
+        match self {
+            RowIndices::Range(range) => range.start + i,
+            RowIndices::Indices(indices) => indices[i],
+        }
+    }
+}
+
+/// Some of the rows of a [Dataframe], made by [Dataframe::slice_rows] or [Dataframe::take_rows].
+///
+/// The view holds row indices and reads the columns of the data frame when iterated.
+#[derive(Debug, Clone)]
+pub struct RowView<'a, T> {
+    df: &'a Dataframe<T>,
+    rows: RowIndices,
+}
+
+impl<'a, T> RowView<'a, T> {
+    /// The number of rows in the view.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Return true if the view has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The zero-based index in the data frame of row `i` of the view.
+    pub fn row_index(&self, i: usize) -> usize {
+        self.rows.get(i)
+    }
+
+    /// Iterate over the rows of the view, see [Dataframe::rows].
+    pub fn rows<R: FromDataframeRow>(&self) -> Result<RowIter<R>> {
+        Ok(RowIter {
+            columns: R::select_columns(&self.df.columns())?,
+            rows: self.rows.clone(),
+            i: 0,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    /// Copy the rows of the view into a new data frame, like `df[rows, , drop = FALSE]`.
+    pub fn to_dataframe(&self) -> Result<Dataframe<T>> {
+        let rows: Vec<i32> = (0..self.len())
+            .map(|i| self.row_index(i) as i32 + 1)
+            .collect();
+        eval_string_with_params(
+            "param.0[param.1, , drop = FALSE]",
+            &[&self.df.robj, &Robj::from(rows)],
+        )?
+        .try_into()
+    }
+}
+
+/// An iterator over rows of a data frame, made by [Dataframe::rows] or [RowView::rows].
+pub struct RowIter<R> {
+    columns: Vec<Robj>,
+    rows: RowIndices,
+    i: usize,
+    marker: std::marker::PhantomData<R>,
+}
+
+impl<R: FromDataframeRow> Iterator for RowIter<R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i < self.rows.len() {
+            let row = self.rows.get(self.i);
+            self.i += 1;
+            Some(R::from_row(&self.columns, row))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.rows.len() - self.i;
+        (len, Some(len))
+    }
+}
+
+impl<R: FromDataframeRow> ExactSizeIterator for RowIter<R> {}
+
+/// A type that can be read from a row of a data frame.
+///
+/// This is implemented for tuples of up to eight [FromColumnElt] types,
+/// which read the first columns in order. Use `#[derive(FromDataframeRow)]`
+/// to read columns by name into a struct.
+pub trait FromDataframeRow: Sized {
+    /// Choose the columns to read from `columns`, in the order [FromDataframeRow::from_row] expects.
+    fn select_columns(columns: &List) -> Result<Vec<Robj>>;
+
+    /// Read row `row` from the selected columns.
+    fn from_row(columns: &[Robj], row: usize) -> Result<Self>;
+}
+
+macro_rules! impl_from_dataframe_row_for_tuple {
+    ($n: expr; $($t: ident $i: tt),+) => {
+        impl<$($t: FromColumnElt),+> FromDataframeRow for ($($t,)+) {
+            fn select_columns(columns: &List) -> Result<Vec<Robj>> {
+                if columns.len() < $n {
+                    return Err(Error::Other(format!(
+                        "Expected at least {} columns, got {}",
+                        $n,
+                        columns.len()
+                    )));
+                }
+                Ok(columns.values().take($n).collect())
+            }
+
+            fn from_row(columns: &[Robj], row: usize) -> Result<Self> {
+                Ok(($($t::from_column_elt(&columns[$i], row)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_dataframe_row_for_tuple!(1; A 0);
+impl_from_dataframe_row_for_tuple!(2; A 0, B 1);
+impl_from_dataframe_row_for_tuple!(3; A 0, B 1, C 2);
+impl_from_dataframe_row_for_tuple!(4; A 0, B 1, C 2, D 3);
+impl_from_dataframe_row_for_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+impl_from_dataframe_row_for_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_from_dataframe_row_for_tuple!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_from_dataframe_row_for_tuple!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// A type that can be read from one element of a data frame column.
+///
+/// Numbers are read from integer or double columns and strings from
+/// character columns or factors, which give the label. The R scalar
+/// types and `Option` accept `NA`; other types give an error for `NA`.
+pub trait FromColumnElt: Sized {
+    /// Read element `row` of `column`.
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self>;
+}
+
+impl FromColumnElt for Rfloat {
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        if let Some(values) = column.as_real_slice() {
+            Ok(values[row].into())
+        } else if let Some(values) = column.as_integer_slice() {
+            Ok(Rint::from(values[row]).into())
+        } else {
+            Err(Error::ExpectedNumeric(column.clone()))
+        }
+    }
+}
+
+impl FromColumnElt for Rint {
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        match column.as_integer_slice() {
+            Some(values) => Ok(values[row].into()),
+            None => Err(Error::ExpectedInteger(column.clone())),
+        }
+    }
+}
+
+impl FromColumnElt for Rbool {
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        match column.as_logical_slice() {
+            Some(values) => Ok(values[row]),
+            None => Err(Error::ExpectedLogical(column.clone())),
+        }
+    }
+}
+
+impl FromColumnElt for Rstr {
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        if column.is_factor() {
+            let code = Rint::from_column_elt(column, row)?;
+            if code.is_na() {
+                return Ok(Rstr::na());
+            }
+            let levels = column
+                .get_attrib(levels_symbol())
+                .ok_or_else(|| Error::ExpectedString(column.clone()))?;
+            let levels = Strings::try_from(levels)?;
+            // Factor codes count from one.
+            match usize::try_from(code.inner()) {
+                Ok(code) if (1..=levels.len()).contains(&code) => Ok(levels.elt(code - 1)),
+                _ => Err(Error::OutOfRange(column.clone())),
+            }
+        } else {
+            Ok(Strings::try_from(column)?.elt(row))
+        }
+    }
+}
+
+impl FromColumnElt for Robj {
+    /// Read an element of a list column, which is `NULL` for a missing value.
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        match column.as_list() {
+            Some(values) => values.elt(row),
+            None => Err(Error::ExpectedList(column.clone())),
+        }
+    }
+}
+
+impl<T> FromColumnElt for Vec<T>
+where
+    for<'a> Vec<T>: TryFrom<&'a Robj, Error = Error>,
+{
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        match Option::<Vec<T>>::from_column_elt(column, row)? {
+            Some(value) => Ok(value),
+            None => Err(Error::MustNotBeNA(column.clone())),
+        }
+    }
+}
+
+impl<T> FromColumnElt for Option<Vec<T>>
+where
+    for<'a> Vec<T>: TryFrom<&'a Robj, Error = Error>,
+{
+    fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+        let value = Robj::from_column_elt(column, row)?;
+        if value.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(Vec::<T>::try_from(&value)?))
+        }
+    }
+}
+
+macro_rules! impl_from_column_elt {
+    ($t: ty, $scalar: ty, $value: expr) => {
+        impl FromColumnElt for $t {
+            fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+                let value = <$scalar>::from_column_elt(column, row)?;
+                if value.is_na() {
+                    Err(Error::MustNotBeNA(column.clone()))
+                } else {
+                    Ok($value(value))
+                }
+            }
+        }
+
+        impl FromColumnElt for Option<$t> {
+            fn from_column_elt(column: &Robj, row: usize) -> Result<Self> {
+                let value = <$scalar>::from_column_elt(column, row)?;
+                if value.is_na() {
+                    Ok(None)
+                } else {
+                    Ok(Some($value(value)))
+                }
+            }
+        }
+    };
+}
+
+impl_from_column_elt!(f64, Rfloat, |v: Rfloat| v.inner());
+impl_from_column_elt!(i32, Rint, |v: Rint| v.inner());
+impl_from_column_elt!(bool, Rbool, |v: Rbool| v.is_true());
+impl_from_column_elt!(String, Rstr, |v: Rstr| v.as_str().to_string());
+
 impl<T> Attributes for Dataframe<T> {}
 
 impl<T> std::fmt::Debug for Dataframe<T>
@@ -0,0 +1,128 @@
+use super::*;
+use crate::robj::Attributes;
+
+/// The `...` arguments of a call, in order and with their names.
+///
+/// An `#[extendr]` function with an argument of type `Dots` gets a `...`
+/// formal argument in its R wrapper, so it can be called with any number
+/// of arguments:
+///
+/// ```ignore
+/// #[extendr]
+/// fn my_sum(values: Dots, na_rm: bool) -> f64 {
+///     values
+///         .values()
+///         .filter_map(|value| value.as_real())
+///         .filter(|x| !(na_rm && x.is_nan()))
+///         .sum()
+/// }
+/// ```
+///
+/// is called from R as `my_sum(1, 2, 3, na_rm = TRUE)`. The arguments are
+/// evaluated by the wrapper, which passes `list(...)`.
+#[derive(PartialEq, Clone)]
+pub struct Dots {
+    pub(crate) robj: Robj,
+}
+
+impl Dots {
+    /// Return an iterator over the names and values of the arguments.
+    /// Unnamed arguments have the name `""`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let dots = Dots::try_from(list!(1, b = 2))?;
+    ///     let args: Vec<_> = dots.iter().collect();
+    ///     assert_eq!(args, vec![("", r!(1)), ("b", r!(2))]);
+    /// }
+    /// ```
+    pub fn iter(&self) -> NamedListIter {
+        self.as_list().iter()
+    }
+
+    /// Return an iterator over the values of the arguments.
+    pub fn values(&self) -> ListIter {
+        self.as_list().values()
+    }
+
+    /// Return an iterator over the values of the unnamed arguments.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let dots = Dots::try_from(list!(1, b = 2, 3))?;
+    ///     assert_eq!(dots.unnamed().collect::<Vec<_>>(), vec![r!(1), r!(3)]);
+    /// }
+    /// ```
+    pub fn unnamed(&self) -> impl Iterator<Item = Robj> {
+        self.iter()
+            .filter(|(name, _)| name.is_empty())
+            .map(|(_, value)| value)
+    }
+
+    /// Return an iterator over the names and values of the named arguments.
+    pub fn named(&self) -> impl Iterator<Item = (&'static str, Robj)> {
+        self.iter().filter(|(name, _)| !name.is_empty())
+    }
+
+    /// Get the first argument with this name.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let dots = Dots::try_from(list!(1, b = 2))?;
+    ///     assert_eq!(dots.get("b"), Some(r!(2)));
+    ///     assert_eq!(dots.get("c"), None);
+    /// }
+    /// ```
+    pub fn get(&self, name: &str) -> Option<Robj> {
+        self.iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The arguments as a list.
+    pub fn as_list(&self) -> List {
+        List {
+            robj: self.robj.clone(),
+        }
+    }
+}
+
+impl Default for Dots {
+    fn default() -> Self {
+        Dots {
+            robj: List::default().into(),
+        }
+    }
+}
+
+impl From<List> for Dots {
+    fn from(list: List) -> Self {
+        Dots { robj: list.robj }
+    }
+}
+
+impl From<Dots> for List {
+    fn from(dots: Dots) -> Self {
+        List { robj: dots.robj }
+    }
+}
+
+impl Attributes for Dots {}
+
+impl Deref for Dots {
+    type Target = [Robj];
+
+    /// The values of the arguments, as a slice.
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            let data = DATAPTR(self.robj.get()) as *const Robj;
+            std::slice::from_raw_parts(data, self.robj.len())
+        }
+    }
+}
+
+impl std::fmt::Debug for Dots {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Dots").field(&self.as_list()).finish()
+    }
+}
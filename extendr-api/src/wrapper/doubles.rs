@@ -48,11 +48,40 @@ impl Doubles {
     pub fn no_na(&self) -> Rbool {
         unsafe { REAL_NO_NA(self.get()).into() }
     }
+
+    /// The sortedness of the vector, as known to R.
+    pub fn sortedness(&self) -> Sortedness {
+        Sortedness::from_code(unsafe { REAL_IS_SORTED(self.get()) })
+    }
+
+    /// Record that the vector is sorted, so that R can skip work in
+    /// `sort()`, `order()` and `match()`.
+    ///
+    /// The result shares the data of this vector. Marking a vector that is
+    /// not sorted as sorted gives wrong results in R.
+    pub fn mark_sorted(&self, sortedness: Sortedness) -> Result<Doubles> {
+        let no_na = unsafe { REAL_NO_NA(self.get()) } != 0;
+        wrap_meta(self.as_robj(), sortedness.code(), no_na)?.try_into()
+    }
+
+    /// Record that the vector has no `NA`s, so that R can skip checks for them.
+    ///
+    /// The result shares the data of this vector. Marking a vector that has
+    /// `NA`s gives wrong results in R.
+    pub fn mark_no_na(&self) -> Result<Doubles> {
+        let sorted = unsafe { REAL_IS_SORTED(self.get()) };
+        wrap_meta(self.as_robj(), sorted, true)?.try_into()
+    }
 }
 
 // TODO: this should be a trait.
 impl Doubles {
     pub fn set_elt(&mut self, index: usize, val: Rfloat) {
+        #[cfg(feature = "strict-checks")]
+        {
+            crate::robj::check_sexptype(unsafe { self.get() }, &[REALSXP], "SET_REAL_ELT");
+            crate::robj::check_index(index, self.len(), "SET_REAL_ELT");
+        }
         single_threaded(|| unsafe {
             SET_REAL_ELT(self.get_mut(), index as R_xlen_t, val.inner());
         })
@@ -181,6 +181,129 @@ impl Environment {
             Err(Error::NotFound(key))
         }
     }
+
+    /// Create a new, empty environment whose parent is this one.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let child = global_env().new_child();
+    ///     assert_eq!(child.parent(), Some(global_env()));
+    /// }
+    /// ```
+    pub fn new_child(&self) -> Environment {
+        Environment::new_with_parent(self.clone())
+    }
+
+    /// Get a variable from an environment or its ancestors, like `get()` in R.
+    ///
+    /// Promises, such as lazy-loaded package data, are evaluated.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let parent = Environment::new_with_parent(global_env());
+    ///     parent.set("x", 1)?;
+    ///     let child = parent.new_child();
+    ///     assert_eq!(child.get("x")?, r!(1));
+    ///     assert!(child.get("not_a_variable_anywhere").is_err());
+    ///     assert!(global_env().get("iris")?.is_frame());
+    /// }
+    /// ```
+    pub fn get<K: Into<Symbol>>(&self, name: K) -> Result<Robj> {
+        let name = name.into();
+        let (sym, env) = unsafe { (name.get(), self.get()) };
+        match catch_r_error(move || unsafe { Rf_findVar(sym, env) }) {
+            Ok(value) if unsafe { value != R_UnboundValue } => {
+                Robj::from_sexp(value).eval_promise()
+            }
+            _ => Err(Error::NotFound(name.into())),
+        }
+    }
+
+    /// Set a variable in this environment, like `assign()` in R.
+    ///
+    /// Fails if the environment is locked and the variable is new,
+    /// or if the binding is locked.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let env = Environment::new_with_parent(global_env());
+    ///     env.set("x", "fred")?;
+    ///     assert_eq!(env.get("x")?, r!("fred"));
+    ///
+    ///     R!("lockEnvironment({{&env}})")?;
+    ///     assert!(env.set("y", 1).is_err());
+    /// }
+    /// ```
+    pub fn set<K: Into<Symbol>, V: Into<Robj>>(&self, name: K, value: V) -> Result<()> {
+        let name = name.into();
+        let value = value.into();
+        let (sym, val, env) = unsafe { (name.get(), value.get(), self.get()) };
+        catch_r_error(move || unsafe {
+            Rf_defineVar(sym, val, env);
+            R_NilValue
+        })
+        .map(|_| ())
+        .map_err(|_| Error::Other(format!("cannot assign '{}'", name.as_str())))
+    }
+
+    /// Remove a variable from this environment, but not its ancestors,
+    /// returning its value, like `rm()` in R.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let env = Environment::new_with_parent(global_env());
+    ///     env.set("x", 1)?;
+    ///     assert_eq!(env.remove("x")?, r!(1));
+    ///     assert!(!env.exists_local("x"));
+    ///     assert!(env.remove("x").is_err());
+    /// }
+    /// ```
+    pub fn remove<K: Into<Symbol>>(&self, name: K) -> Result<Robj> {
+        use crate as extendr_api;
+        let name = name.into();
+        let value = self.local(name.clone())?;
+        if value.is_unbound_value() {
+            return Err(Error::NotFound(name.into()));
+        }
+        lang!("rm", list = name.as_str(), envir = self.clone()).eval_with_env(&base_env())?;
+        Ok(value)
+    }
+
+    /// Return true if a variable is bound in this environment or its ancestors,
+    /// like `exists()` in R.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let env = Environment::new_with_parent(global_env());
+    ///     assert!(env.exists("sum"));
+    ///     assert!(!env.exists_local("sum"));
+    /// }
+    /// ```
+    pub fn exists<K: Into<Symbol>>(&self, name: K) -> bool {
+        let name = name.into();
+        unsafe { Rf_findVar(name.get(), self.get()) != R_UnboundValue }
+    }
+
+    /// Return true if a variable is bound in this environment, ignoring its ancestors.
+    pub fn exists_local<K: Into<Symbol>>(&self, name: K) -> bool {
+        let name = name.into();
+        unsafe { Rf_findVarInFrame3(self.get(), name.get(), 0) != R_UnboundValue }
+    }
+
+    /// Iterate over the parent, grandparent and so on, ending with [empty_env()].
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let env = global_env().new_child();
+    ///     let ancestors = env.ancestors().collect::<Vec<_>>();
+    ///     assert_eq!(ancestors[0], global_env());
+    ///     assert_eq!(ancestors.last(), Some(&empty_env()));
+    ///     assert!(ancestors.contains(&base_env()));
+    /// }
+    /// ```
+    pub fn ancestors(&self) -> impl Iterator<Item = Environment> {
+        std::iter::successors(self.parent(), |env| env.parent())
+    }
 }
 
 /// Iterator over the names and values of an environment
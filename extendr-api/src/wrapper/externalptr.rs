@@ -103,7 +103,10 @@ impl<T: Any + Debug> ExternalPtr<T> {
 
                     // Convert the pointer to a box and drop it implictly.
                     // This frees up the memory we have used and calls the "T::drop" method if there is one.
-                    drop(Box::from_raw(ptr));
+                    // The pointer is null if the value was taken with `take()`.
+                    if !ptr.is_null() {
+                        drop(Box::from_raw(ptr));
+                    }
 
                     // Now set the pointer in ExternalPTR to C `NULL`
                     R_ClearExternalPtr(x);
@@ -135,21 +138,53 @@ impl<T: Any + Debug> ExternalPtr<T> {
 
     /// Get the "address" field of an external pointer.
     /// Normally, we will use Deref to do this.
-    pub fn addr<'a>(&self) -> &'a T {
+    ///
+    /// Panics if the value has been taken with [ExternalPtr::take].
+    pub fn addr(&self) -> &T {
         unsafe {
             let ptr = R_ExternalPtrAddr(self.robj.get()) as *const T;
-            &*ptr as &'a T
+            assert!(!ptr.is_null(), "external pointer already consumed");
+            &*ptr as &T
         }
     }
 
     /// Get the "address" field of an external pointer as a mutable reference.
     /// Normally, we will use DerefMut to do this.
+    ///
+    /// Panics if the value has been taken with [ExternalPtr::take].
     pub fn addr_mut(&mut self) -> &mut T {
         unsafe {
             let ptr = R_ExternalPtrAddr(self.robj.get()) as *mut T;
+            assert!(!ptr.is_null(), "external pointer already consumed");
             &mut *ptr as &mut T
         }
     }
+
+    /// Move the value out of the external pointer.
+    ///
+    /// The R object stays alive, but its pointer is cleared, so any later
+    /// `take()` or conversion by an `#[extendr]` method returns
+    /// [Error::Consumed] rather than reading freed memory.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let extptr = ExternalPtr::new(String::from("done"));
+    ///     let robj: Robj = extptr.clone().into();
+    ///     assert_eq!(extptr.take()?, "done");
+    ///
+    ///     let again: ExternalPtr<String> = (&robj).try_into()?;
+    ///     assert!(again.is_consumed());
+    ///     assert_eq!(again.take(), Err(Error::Consumed(robj)));
+    /// }
+    /// ```
+    pub fn take(self) -> Result<T> {
+        unsafe { self.robj.take_external_ptr::<T>().map(|boxed| *boxed) }
+    }
+
+    /// Return true if the value has been moved out with [ExternalPtr::take].
+    pub fn is_consumed(&self) -> bool {
+        unsafe { R_ExternalPtrAddr(self.robj.get()).is_null() }
+    }
 }
 
 impl<T: Any + Debug> TryFrom<&Robj> for ExternalPtr<T> {
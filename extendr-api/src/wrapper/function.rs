@@ -66,6 +66,39 @@ impl Function {
         })
     }
 
+    /// Call the function with positional arguments followed by named ones,
+    /// like `f(a, b, name = value)`. Names that are not valid rust
+    /// identifiers, like `na.rm`, can be used.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let paste = R!("paste")?.as_function().unwrap();
+    ///     let res = paste.call_with([r!("a"), r!("b")], [("sep", "-")])?;
+    ///     assert_eq!(res, r!("a-b"));
+    ///
+    ///     let mean = R!("mean")?.as_function().unwrap();
+    ///     let x = r!([1.0, f64::NAN, 3.0]);
+    ///     assert_eq!(mean.call_with([x], [("na.rm", true)])?, r!(2.0));
+    /// }
+    /// ```
+    pub fn call_with<P, N, K, V>(&self, positional: P, named: N) -> Result<Robj>
+    where
+        P: IntoIterator,
+        P::Item: Into<Robj>,
+        N: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<Robj>,
+    {
+        let positional = positional
+            .into_iter()
+            .map(|value| (String::new(), value.into()));
+        let named = named
+            .into_iter()
+            .map(|(name, value)| (name.as_ref().to_string(), value.into()));
+        let pairs: Vec<(String, Robj)> = positional.chain(named).collect();
+        self.call(Pairlist::from_pairs(pairs))
+    }
+
     /// Get the formal arguments of the function or None if it is a primitive.
     pub fn formals(&self) -> Option<Pairlist> {
         unsafe {
@@ -107,6 +140,368 @@ impl Function {
     }
 }
 
+/// The numbers of arguments a function accepts, see [Function::arity].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arity {
+    /// Arguments without defaults.
+    pub required: usize,
+    /// Arguments with defaults, not counting `...`.
+    pub optional: usize,
+    /// True if the function takes `...`.
+    pub dots: bool,
+}
+
+impl Arity {
+    /// Return true if the function can be called with `n` arguments by position.
+    ///
+    /// Arguments without defaults may be left out, if the function doesn't
+    /// use them, so this is a check of intent rather than a guarantee.
+    pub fn accepts(&self, n: usize) -> bool {
+        n >= self.required && (self.dots || n <= self.required + self.optional)
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let max = self.required + self.optional;
+        match (self.dots, self.optional) {
+            (true, _) => write!(f, "{} or more arguments", self.required),
+            (false, 0) => write!(f, "{} argument{}", max, plural(max)),
+            (false, _) => write!(f, "{} to {} arguments", self.required, max),
+        }
+    }
+}
+
+impl Function {
+    /// The names of the arguments, including `...`, of a closure or a primitive.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let f: Function = R!("function(x, y = 2, ...) x")?.try_into()?;
+    ///     assert_eq!(f.formal_names(), vec!["x", "y", "..."]);
+    ///     let sum: Function = R!("sum")?.try_into()?;
+    ///     assert_eq!(sum.formal_names(), vec!["...", "na.rm"]);
+    /// }
+    /// ```
+    pub fn formal_names(&self) -> Vec<String> {
+        self.formal_pairs()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Count the arguments of a closure or a primitive, for checking callbacks
+    /// before calling them.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let f: Function = R!("function(x, y = 2, ...) x")?.try_into()?;
+    ///     let arity = f.arity();
+    ///     assert_eq!((arity.required, arity.optional, arity.dots), (1, 1, true));
+    ///     assert!(arity.accepts(5) && !arity.accepts(0));
+    ///
+    ///     let g: Function = R!("function(a, b) a + b")?.try_into()?;
+    ///     assert_eq!(g.arity().to_string(), "2 arguments");
+    ///     assert!(g.check_arity(2).is_ok());
+    ///     assert_eq!(
+    ///         g.check_arity(1).unwrap_err().to_string(),
+    ///         "the function must accept 1 argument, but it takes 2 arguments"
+    ///     );
+    /// }
+    /// ```
+    pub fn arity(&self) -> Arity {
+        let mut arity = Arity {
+            required: 0,
+            optional: 0,
+            dots: false,
+        };
+        for (name, default) in self.formal_pairs() {
+            if name == "..." {
+                arity.dots = true;
+            } else if default.is_missing_arg() {
+                arity.required += 1;
+            } else {
+                arity.optional += 1;
+            }
+        }
+        arity
+    }
+
+    /// Return an error describing the problem if the function can't be called
+    /// with `n` arguments, see [Arity::accepts].
+    pub fn check_arity(&self, n: usize) -> Result<()> {
+        let arity = self.arity();
+        if arity.accepts(n) {
+            Ok(())
+        } else {
+            Err(Error::Other(format!(
+                "the function must accept {} argument{}, but it takes {}",
+                n,
+                if n == 1 { "" } else { "s" },
+                arity
+            )))
+        }
+    }
+
+    // The formals of a closure, or of the closure `args()` gives for a primitive.
+    fn formal_pairs(&self) -> Vec<(String, Robj)> {
+        use crate as extendr_api;
+        let formals = match self.formals() {
+            Some(formals) => Some(formals),
+            None => call!("args", self.clone())
+                .ok()
+                .and_then(|closure| Function::try_from(closure).ok())
+                .and_then(|closure| closure.formals()),
+        };
+        formals
+            .map(|formals| {
+                formals
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A Rust closure that can be called from R.
+type RustClosure = Box<dyn Fn(List) -> Result<Robj>>;
+
+/// Native entry point for closures created by [Function::from_closure].
+/// `closure` is an external pointer to a `RustClosure`, `args` the list of
+/// arguments given in R.
+unsafe extern "C" fn call_rust_closure(closure: SEXP, args: SEXP) -> SEXP {
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let ptr = R_ExternalPtrAddr(closure) as *const RustClosure;
+        if ptr.is_null() {
+            return Err(Error::Other(
+                "this Rust closure is no longer available".to_string(),
+            ));
+        }
+        let args: List = Robj::from_sexp(args).try_into()?;
+        (*ptr)(args)
+    }));
+
+    // Make sure that nothing needs dropping before we call Rf_error.
+    let err_string = match result {
+        Ok(Ok(robj)) => return robj.get(),
+        Ok(Err(err)) => err.to_string(),
+        Err(payload) => {
+            // An R error or other jump from R code called by the closure is resumed.
+            drop(RUnwind::resume_or_return(payload));
+            "Rust closure panicked".to_string()
+        }
+    };
+    throw_r_error(err_string)
+}
+
+unsafe extern "C" fn finalize_rust_closure(closure: SEXP) {
+    let ptr = R_ExternalPtrAddr(closure) as *mut RustClosure;
+    if !ptr.is_null() {
+        R_ClearExternalPtr(closure);
+        drop(Box::from_raw(ptr));
+    }
+}
+
+impl Function {
+    /// Make an R function that calls a Rust closure.
+    ///
+    /// The R function takes `...`; the arguments are passed to the closure as a `List`.
+    /// Errors returned by the closure, and panics, become R errors.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let add = Function::from_closure(|args: List| {
+    ///         let a: f64 = args.elt(0)?.try_into()?;
+    ///         let b: f64 = args.elt(1)?.try_into()?;
+    ///         Ok(r!(a + b))
+    ///     })?;
+    ///     assert_eq!(add.call(pairlist!(1.0, 2.0))?, r!(3.0));
+    ///     assert!(add.call(pairlist!(1.0)).is_err());
+    ///
+    ///     // Conditions signalled by R code that the closure calls reach R's handlers.
+    ///     let fail: Robj = Function::from_closure(|_| {
+    ///         let stop = lang!("stop", "boom");
+    ///         Ok(extendr_api::conditions::signalling_eval(&stop, &global_env()))
+    ///     })?
+    ///     .into();
+    ///     let res = R!("tryCatch({{fail}}(), error = function(e) conditionMessage(e))")?;
+    ///     assert_eq!(res, r!("boom"));
+    /// }
+    /// ```
+    pub fn from_closure<F>(f: F) -> Result<Function>
+    where
+        F: Fn(List) -> Result<Robj> + 'static,
+    {
+        let closure: RustClosure = Box::new(f);
+        let (function, _) = unsafe { Self::from_boxed_closure(closure)? };
+        Ok(function)
+    }
+
+    /// Make an R function that calls a Rust closure with typed arguments.
+    ///
+    /// Each argument is converted with `TryFrom<Robj>` and the value returned
+    /// is converted to an `Robj`. The function can be passed to R's higher
+    /// order functions such as `lapply()`, `Map()` and `optim()`. Closures of
+    /// up to five arguments are supported, see [IntoRClosure].
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let scale = 2.0;
+    ///     let double = Function::from_fn(move |x: f64| Ok(x * scale))?;
+    ///     let res = call!("vapply", r!([1.0, 2.0]), &double, 0.0)?;
+    ///     assert_eq!(res, r!([2.0, 4.0]));
+    ///
+    ///     let label = Function::from_fn(|name: String, n: i32| Ok(format!("{}{}", name, n)))?;
+    ///     let res = call!("mapply", label, r!(["a", "b"]), r!([1, 2]), "USE.NAMES" = false)?;
+    ///     assert_eq!(res, r!(["a1", "b2"]));
+    ///
+    ///     // Conversion errors name the argument.
+    ///     let err = double.call(pairlist!("x")).unwrap_err();
+    ///     assert!(err.to_string().contains("argument 1"));
+    /// }
+    /// ```
+    pub fn from_fn<F, Args>(f: F) -> Result<Function>
+    where
+        F: IntoRClosure<Args>,
+    {
+        let (function, _) = unsafe { Self::from_boxed_closure(f.into_closure())? };
+        Ok(function)
+    }
+
+    /// Run `body` with an R function that calls `f`, which may borrow local data.
+    ///
+    /// The R function is invalidated when `body` returns; calling it after that
+    /// gives an R error instead of undefined behaviour.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let offset = 10.0;
+    ///     let res = Function::with_closure(
+    ///         |args: List| Ok(r!(f64::try_from(args.elt(0)?)? + offset)),
+    ///         |func| func.call(pairlist!(1.0)),
+    ///     )?;
+    ///     assert_eq!(res, r!(11.0));
+    /// }
+    /// ```
+    pub fn with_closure<'a, F, B, R>(f: F, body: B) -> Result<R>
+    where
+        F: Fn(List) -> Result<Robj> + 'a,
+        B: FnOnce(&Function) -> Result<R>,
+    {
+        struct Invalidate(Robj);
+
+        impl Drop for Invalidate {
+            fn drop(&mut self) {
+                unsafe { finalize_rust_closure(self.0.get()) }
+            }
+        }
+
+        let closure: Box<dyn Fn(List) -> Result<Robj> + 'a> = Box::new(f);
+        // Safety: the closure is dropped by `Invalidate` before `'a` ends and
+        // any later call from R sees a null pointer.
+        let closure: RustClosure = unsafe { std::mem::transmute(closure) };
+        let (function, closure_ptr) = unsafe { Self::from_boxed_closure(closure)? };
+        let _invalidate = Invalidate(closure_ptr);
+        body(&function)
+    }
+
+    /// Returns the function and the external pointer that owns the closure.
+    unsafe fn from_boxed_closure(closure: RustClosure) -> Result<(Function, Robj)> {
+        single_threaded(|| {
+            let ptr = Box::into_raw(Box::new(closure));
+            let closure_ptr = Robj::from_sexp(R_MakeExternalPtr(
+                ptr as *mut std::os::raw::c_void,
+                R_NilValue,
+                R_NilValue,
+            ));
+            R_RegisterCFinalizerEx(closure_ptr.get(), Some(finalize_rust_closure), 1);
+
+            // .Call() accepts an external pointer tagged "native symbol".
+            let entry: unsafe extern "C" fn(SEXP, SEXP) -> SEXP = call_rust_closure;
+            let native = Robj::from_sexp(R_MakeExternalPtrFn(
+                Some(std::mem::transmute(entry)),
+                make_symbol("native symbol"),
+                R_NilValue,
+            ));
+
+            let env = new_env(base_env(), false, 2);
+            env.set_local(Symbol::from_string(".extendr_native"), native);
+            env.set_local(Symbol::from_string(".extendr_closure"), closure_ptr.clone());
+            let function =
+                parse("function(...) .Call(.extendr_native, .extendr_closure, list(...))")?
+                    .eval_with_env(&env)?
+                    .try_into()?;
+            Ok((function, closure_ptr))
+        })
+    }
+}
+
+/// Rust closures that [Function::from_fn] can make into R functions.
+///
+/// This is implemented for closures of up to five arguments that implement
+/// `TryFrom<Robj>`, returning a `Result` of a value that converts to an `Robj`.
+pub trait IntoRClosure<Args> {
+    /// The number of arguments the closure takes.
+    fn arity() -> usize;
+
+    /// Box the closure as one taking its arguments as a list.
+    fn into_closure(self) -> Box<dyn Fn(List) -> Result<Robj>>;
+}
+
+fn closure_arg<T>(value: Robj, position: usize) -> Result<T>
+where
+    T: TryFrom<Robj>,
+    T::Error: Into<Error>,
+{
+    T::try_from(value).map_err(|err| Error::Other(format!("argument {}: {}", position, err.into())))
+}
+
+macro_rules! impl_into_r_closure {
+    ($($arg: ident $value: ident),*) => {
+        impl<Func, Ret, $($arg),*> IntoRClosure<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Result<Ret> + 'static,
+            Ret: Into<Robj>,
+            $($arg: TryFrom<Robj>, <$arg as TryFrom<Robj>>::Error: Into<Error>,)*
+        {
+            fn arity() -> usize {
+                [$(stringify!($arg)),*].len()
+            }
+
+            fn into_closure(self) -> Box<dyn Fn(List) -> Result<Robj>> {
+                Box::new(move |args: List| {
+                    let arity = <Self as IntoRClosure<($($arg,)*)>>::arity();
+                    if args.len() != arity {
+                        return Err(Error::Other(format!(
+                            "the function takes {} arguments, but {} were given",
+                            arity,
+                            args.len()
+                        )));
+                    }
+                    #[allow(unused_mut, unused_variables)]
+                    let mut values = args.values().enumerate();
+                    $(
+                        let $value = values
+                            .next()
+                            .map(|(i, value)| closure_arg::<$arg>(value, i + 1))
+                            .unwrap()?;
+                    )*
+                    Ok(self($($value),*)?.into())
+                })
+            }
+        }
+    };
+}
+
+impl_into_r_closure!();
+impl_into_r_closure!(A a);
+impl_into_r_closure!(A a, B b);
+impl_into_r_closure!(A a, B b, C c);
+impl_into_r_closure!(A a, B b, C c, D d);
+impl_into_r_closure!(A a, B b, C c, D d, E e);
+
 impl std::fmt::Debug for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.deparse().unwrap())
@@ -48,11 +48,56 @@ impl Integers {
     pub fn no_na(&self) -> Rbool {
         unsafe { INTEGER_NO_NA(self.get()).into() }
     }
+
+    /// The sortedness of the vector, as known to R.
+    pub fn sortedness(&self) -> Sortedness {
+        Sortedness::from_code(unsafe { INTEGER_IS_SORTED(self.get()) })
+    }
+
+    /// Record that the vector is sorted, so that R can skip work in
+    /// `sort()`, `order()` and `match()`.
+    ///
+    /// The result shares the data of this vector. Marking a vector that is
+    /// not sorted as sorted gives wrong results in R.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let x = Integers::from_values([1, 2, 3]).mark_sorted(Sortedness::Increasing)?;
+    ///     assert_eq!(x.sortedness(), Sortedness::Increasing);
+    ///     assert_eq!(x, Integers::from_values([1, 2, 3]));
+    /// }
+    /// ```
+    pub fn mark_sorted(&self, sortedness: Sortedness) -> Result<Integers> {
+        let no_na = unsafe { INTEGER_NO_NA(self.get()) } != 0;
+        wrap_meta(self.as_robj(), sortedness.code(), no_na)?.try_into()
+    }
+
+    /// Record that the vector has no `NA`s, so that R can skip checks for them.
+    ///
+    /// The result shares the data of this vector. Marking a vector that has
+    /// `NA`s gives wrong results in R.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let x = Integers::from_values([3, 1, 2]).mark_no_na()?;
+    ///     assert_eq!(x.no_na(), TRUE);
+    ///     assert_eq!(R!("anyNA({{x}})")?, r!(false));
+    /// }
+    /// ```
+    pub fn mark_no_na(&self) -> Result<Integers> {
+        let sorted = unsafe { INTEGER_IS_SORTED(self.get()) };
+        wrap_meta(self.as_robj(), sorted, true)?.try_into()
+    }
 }
 
 // TODO: this should be a trait.
 impl Integers {
     pub fn set_elt(&mut self, index: usize, val: Rint) {
+        #[cfg(feature = "strict-checks")]
+        {
+            crate::robj::check_sexptype(unsafe { self.get() }, &[INTSXP], "SET_INTEGER_ELT");
+            crate::robj::check_index(index, self.len(), "SET_INTEGER_ELT");
+        }
         single_threaded(|| unsafe {
             SET_INTEGER_ELT(self.get(), index as R_xlen_t, val.inner());
         })
@@ -44,6 +44,11 @@ impl Logicals {
 // TODO: this should be a trait.
 impl Logicals {
     pub fn set_elt(&mut self, index: usize, val: Rbool) {
+        #[cfg(feature = "strict-checks")]
+        {
+            crate::robj::check_sexptype(unsafe { self.get() }, &[LGLSXP], "SET_INTEGER_ELT");
+            crate::robj::check_index(index, self.len(), "SET_INTEGER_ELT");
+        }
         single_threaded(|| unsafe {
             SET_INTEGER_ELT(self.get_mut(), index as R_xlen_t, val.inner());
         })
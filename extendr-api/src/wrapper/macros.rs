@@ -96,6 +96,8 @@ macro_rules! gen_vector_wrapper_impl {
                     if(index >= self.len()) {
                         <$scalar_type>::na()
                     } else {
+                        #[cfg(feature = "strict-checks")]
+                        crate::robj::check_sexptype(unsafe { self.get() }, &[$sexp], stringify!([<$r_prefix _ELT>]));
                         unsafe { [<$r_prefix _ELT>](self.get(), index as R_xlen_t).into() }
                     }
                 }
@@ -116,6 +118,85 @@ macro_rules! gen_vector_wrapper_impl {
                     self.as_robj_mut().as_typed_slice_mut().unwrap().iter_mut()
                 }
             }
+
+            paste::paste!{
+                #[doc = "Copy `src`, which must have the same length, into the vector."]
+                #[doc = "Returns `Error::Aliased` if `src` shares memory with the vector,"]
+                #[doc = "as it does when R passes the same vector as two arguments."]
+                #[doc = "```"]
+                #[doc = "use extendr_api::prelude::*;"]
+                #[doc = "test! {"]
+                #[doc = "   let mut vec = " $type "::new(2);"]
+                #[doc = "   let src = " $type "::new(2);"]
+                #[doc = "   vec.copy_from_slice(&src)?;"]
+                #[doc = "   let alias = vec.clone();"]
+                #[doc = "   assert!(vec.copy_from_slice(&alias).is_err());"]
+                #[doc = "}"]
+                #[doc = "```"]
+                pub fn copy_from_slice(&mut self, src: &[$scalar_type]) -> Result<()> {
+                    if src.len() != self.len() {
+                        return Err(Error::Other(format!(
+                            "can't copy {} elements into a vector of length {}",
+                            src.len(),
+                            self.len()
+                        )));
+                    }
+                    if self.robj.overlaps_slice(src) {
+                        return Err(Error::Aliased(self.robj.clone()));
+                    }
+                    self.as_robj_mut().as_typed_slice_mut().unwrap().copy_from_slice(src);
+                    Ok(())
+                }
+            }
+
+            paste::paste!{
+                #[doc = "Apply `f` to each element, keeping the names, dim, dimnames and other attributes."]
+                #[doc = "```"]
+                #[doc = "use extendr_api::prelude::*;"]
+                #[doc = "test! {"]
+                #[doc = "   let mut robj: Robj = " $type "::new(2).into();"]
+                #[doc = "   robj.set_names([\"a\", \"b\"])?;"]
+                #[doc = "   let vec = " $type "::try_from(robj)?;"]
+                #[doc = "   let res = vec.map_preserving_names(|x| x);"]
+                #[doc = "   assert_eq!(res.as_robj().names().unwrap().collect::<Vec<_>>(), [\"a\", \"b\"]);"]
+                #[doc = "}"]
+                #[doc = "```"]
+                pub fn map_preserving_names<F>(&self, f: F) -> Self
+                where
+                    F: FnMut($scalar_type) -> $scalar_type,
+                {
+                    let mut res: $type = self.iter().map(f).collect();
+                    crate::wrapper::copy_all_attributes(&mut res.robj, &self.robj);
+                    res
+                }
+            }
+
+            paste::paste!{
+                #[doc = "Combine two " $doc_name " vectors of the same length elementwise, keeping the attributes of `self`."]
+                pub fn zip_map_preserving_names<F>(&self, other: &$type, mut f: F) -> Result<Self>
+                where
+                    F: FnMut($scalar_type, $scalar_type) -> $scalar_type,
+                {
+                    crate::wrapper::check_same_length(&self.robj, &other.robj)?;
+                    let mut res: $type = self.iter().zip(other.iter()).map(|(a, b)| f(a, b)).collect();
+                    crate::wrapper::copy_all_attributes(&mut res.robj, &self.robj);
+                    Ok(res)
+                }
+            }
+
+            paste::paste!{
+                #[doc = "Apply `f` to each element to make a vector of another type, keeping the names, dim and dimnames."]
+                #[doc = "Other attributes, such as the class, are dropped as they may not apply to the new type."]
+                pub fn map_into_preserving_names<T, U, F>(&self, f: F) -> Result<T>
+                where
+                    F: FnMut($scalar_type) -> U,
+                    T: FromIterator<U> + GetSexp,
+                {
+                    let mut res: T = self.iter().map(f).collect();
+                    crate::wrapper::copy_names_and_dims(res.as_robj_mut(), &self.robj)?;
+                    Ok(res)
+                }
+            }
         }
 
         impl FromIterator<$scalar_type> for $type {
@@ -50,7 +50,7 @@ trait Offset<D> {
 impl<T> Offset<[usize; 1]> for RArray<T, [usize; 1]> {
     /// Get the offset into the array for a given index.
     fn offset(&self, index: [usize; 1]) -> usize {
-        if index[0] - BASE > self.dim[0] {
+        if index[0] - BASE >= self.dim[0] {
             panic!("array index: row overflow");
         }
         index[0] - BASE
@@ -60,10 +60,10 @@ impl<T> Offset<[usize; 1]> for RArray<T, [usize; 1]> {
 impl<T> Offset<[usize; 2]> for RArray<T, [usize; 2]> {
     /// Get the offset into the array for a given index.
     fn offset(&self, index: [usize; 2]) -> usize {
-        if index[0] - BASE > self.dim[0] {
+        if index[0] - BASE >= self.dim[0] {
             panic!("matrix index: row overflow");
         }
-        if index[1] - BASE > self.dim[1] {
+        if index[1] - BASE >= self.dim[1] {
             panic!("matrix index: column overflow");
         }
         (index[0] - BASE) + self.dim[0] * (index[1] - BASE)
@@ -73,13 +73,13 @@ impl<T> Offset<[usize; 2]> for RArray<T, [usize; 2]> {
 impl<T> Offset<[usize; 3]> for RArray<T, [usize; 3]> {
     /// Get the offset into the array for a given index.
     fn offset(&self, index: [usize; 3]) -> usize {
-        if index[0] - BASE > self.dim[0] {
+        if index[0] - BASE >= self.dim[0] {
             panic!("RMatrix3D index: row overflow");
         }
-        if index[1] - BASE > self.dim[1] {
+        if index[1] - BASE >= self.dim[1] {
             panic!("RMatrix3D index: column overflow");
         }
-        if index[2] - BASE > self.dim[2] {
+        if index[2] - BASE >= self.dim[2] {
             panic!("RMatrix3D index: submatrix overflow");
         }
         (index[0] - BASE) + self.dim[0] * (index[1] - BASE + self.dim[1] * (index[2] - BASE))
@@ -163,8 +163,395 @@ where
     pub fn ncols(&self) -> usize {
         self.dim[1]
     }
+
+    /// Make a new matrix that is the transpose of this one, like `t()`.
+    ///
+    /// The dimnames are swapped.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::new_matrix(2, 3, |r, c| (r * 3 + c) as f64);
+    ///     let transposed = matrix.transpose()?;
+    ///     assert_eq!((transposed.nrows(), transposed.ncols()), (3, 2));
+    ///     assert_eq!(transposed[[2, 1]], matrix[[1, 2]]);
+    /// }
+    /// ```
+    pub fn transpose(&self) -> Result<Self>
+    where
+        T: Copy,
+    {
+        let mut res = RMatrix::new_matrix(self.ncols(), self.nrows(), |r, c| self[[c, r]]);
+        if let Some(dimnames) = transposed_dimnames(&self.robj)? {
+            res.robj
+                .set_attrib(wrapper::symbol::dimnames_symbol(), dimnames)?;
+        }
+        Ok(res)
+    }
+}
+
+impl<T> RArray<T, [usize; 2]> {
+    /// Give the matrix new dimensions with the same number of elements,
+    /// keeping the data in column-major order, like setting `dim()`.
+    ///
+    /// The data is not copied and the dimnames are dropped.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::new_matrix(2, 3, |r, c| (r + 2 * c) as f64);
+    ///     let reshaped = matrix.reshape(3, 2)?;
+    ///     assert_eq!(reshaped.column_view(1).as_slice(), Some(&[3., 4., 5.][..]));
+    ///     assert!(reshaped.reshape(4, 2).is_err());
+    /// }
+    /// ```
+    pub fn reshape(mut self, nrows: usize, ncols: usize) -> Result<Self> {
+        let len = self.data().len();
+        if nrows.checked_mul(ncols) != Some(len) {
+            return Err(Error::Other(format!(
+                "can't reshape {} elements to {} x {}",
+                len, nrows, ncols
+            )));
+        }
+        let dim = [nrows, ncols];
+        self.robj.set_attrib(wrapper::symbol::dim_symbol(), dim)?;
+        self.robj
+            .set_attrib(wrapper::symbol::dimnames_symbol(), ())?;
+        self.dim = dim;
+        Ok(self)
+    }
+
+    /// Transpose a square matrix without allocating.
+    ///
+    /// The matrix is transposed in blocks to make good use of the cache.
+    /// The dimnames are swapped. Fails if the matrix is not square.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut matrix = RMatrix::new_matrix(3, 3, |r, c| (r * 3 + c) as i32);
+    ///     matrix.transpose_in_place()?;
+    ///     assert_eq!(matrix.as_integer_slice().unwrap(), &[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    ///
+    ///     let mut rect = RMatrix::new_matrix(2, 3, |_, _| 0.0);
+    ///     assert!(rect.transpose_in_place().is_err());
+    /// }
+    /// ```
+    pub fn transpose_in_place(&mut self) -> Result<()> {
+        const BLOCK: usize = 32;
+        let [nrows, ncols] = self.dim;
+        if nrows != ncols {
+            return Err(Error::Other(format!(
+                "can't transpose a {} x {} matrix in place",
+                nrows, ncols
+            )));
+        }
+        let n = nrows;
+        let data = unsafe { std::slice::from_raw_parts_mut(self.data, n * n) };
+        for block_col in (0..n).step_by(BLOCK) {
+            for block_row in (block_col..n).step_by(BLOCK) {
+                for col in block_col..(block_col + BLOCK).min(n) {
+                    // On the diagonal block, only swap below the diagonal.
+                    let first_row = block_row.max(col + 1);
+                    for row in first_row..(block_row + BLOCK).min(n) {
+                        data.swap(row + n * col, col + n * row);
+                    }
+                }
+            }
+        }
+        if let Some(dimnames) = transposed_dimnames(&self.robj)? {
+            self.robj
+                .set_attrib(wrapper::symbol::dimnames_symbol(), dimnames)?;
+        }
+        Ok(())
+    }
+}
+
+// The dimnames of a matrix with the rows and columns swapped.
+fn transposed_dimnames(robj: &Robj) -> Result<Option<Robj>> {
+    match robj.get_attrib(wrapper::symbol::dimnames_symbol()) {
+        Some(dimnames) if !dimnames.is_null() => {
+            let dimnames = List::try_from(dimnames)?;
+            let mut values: Vec<Robj> = dimnames.values().collect();
+            values.reverse();
+            let mut res = List::from_values(values);
+            if let Some(names) = dimnames.names() {
+                let mut names: Vec<&str> = names.collect();
+                names.reverse();
+                res.set_names(names)?;
+            }
+            Ok(Some(res.into()))
+        }
+        _ => Ok(None),
+    }
+}
+
+impl<T> RArray<T, [usize; 2]> {
+    /// A view of column `col`, which is contiguous and so also a slice.
+    ///
+    /// Panics if `col` is out of bounds.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::new_matrix(3, 2, |r, c| (r + 10 * c) as f64);
+    ///     let column = matrix.column_view(1);
+    ///     assert_eq!(column.as_slice(), Some(&[10., 11., 12.][..]));
+    ///     assert_eq!(column.copied().sum::<f64>(), 33.);
+    /// }
+    /// ```
+    pub fn column_view(&self, col: usize) -> StridedView<T> {
+        let [nrows, ncols] = self.dim;
+        assert!(col < ncols, "matrix index: column overflow");
+        StridedView {
+            data: self.data(),
+            start: col * nrows,
+            stride: 1,
+            len: nrows,
+        }
+    }
+
+    /// A view of row `row`, whose elements are `nrows` apart.
+    ///
+    /// Panics if `row` is out of bounds.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::new_matrix(3, 2, |r, c| (r + 10 * c) as f64);
+    ///     let row = matrix.row_view(2);
+    ///     assert_eq!(row.len(), 2);
+    ///     assert_eq!(row[1], 12.);
+    ///     assert_eq!(row.as_slice(), None);
+    ///     assert_eq!(row.copied().collect::<Vec<_>>(), [2., 12.]);
+    /// }
+    /// ```
+    pub fn row_view(&self, row: usize) -> StridedView<T> {
+        let [nrows, ncols] = self.dim;
+        assert!(row < nrows, "matrix index: row overflow");
+        StridedView {
+            data: self.data(),
+            start: row,
+            stride: nrows,
+            len: ncols,
+        }
+    }
+
+    /// Iterate over views of the columns.
+    pub fn column_iter(&self) -> impl Iterator<Item = StridedView<T>> + '_ {
+        (0..self.dim[1]).map(move |col| self.column_view(col))
+    }
+
+    /// Iterate over views of the rows.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::new_matrix(2, 3, |r, c| (r * 3 + c) as i32);
+    ///     let row_sums: Vec<i32> = matrix.row_iter().map(|row| row.sum()).collect();
+    ///     assert_eq!(row_sums, [3, 12]);
+    /// }
+    /// ```
+    pub fn row_iter(&self) -> impl Iterator<Item = StridedView<T>> + '_ {
+        (0..self.dim[0]).map(move |row| self.row_view(row))
+    }
+
+    /// The element at `row`, `col`, or `None` if either is out of bounds.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::new_matrix(2, 3, |r, c| (r * 3 + c) as i32);
+    ///     assert_eq!(matrix.get(1, 2), Some(&5));
+    ///     assert_eq!(matrix[(1, 2)], 5);
+    ///     assert_eq!(matrix.get(2, 0), None);
+    /// }
+    /// ```
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        let [nrows, ncols] = self.dim;
+        if row < nrows && col < ncols {
+            self.data().get(row + nrows * col)
+        } else {
+            None
+        }
+    }
+
+    /// Copy the rows into vectors.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix: RMatrix<f64> = R!("matrix(1:6 + 0, nrow = 2)")?.try_into()?;
+    ///     assert_eq!(matrix.to_rows(), [[1., 3., 5.], [2., 4., 6.]]);
+    ///     assert_eq!(matrix.to_columns(), [[1., 2.], [3., 4.], [5., 6.]]);
+    /// }
+    /// ```
+    pub fn to_rows(&self) -> Vec<Vec<T>>
+    where
+        T: Clone,
+    {
+        self.row_iter().map(|row| row.cloned().collect()).collect()
+    }
+
+    /// Copy the columns into vectors.
+    pub fn to_columns(&self) -> Vec<Vec<T>>
+    where
+        T: Clone,
+    {
+        let [nrows, ncols] = self.dim;
+        let data = self.data();
+        (0..ncols)
+            .map(|c| data[c * nrows..(c + 1) * nrows].to_vec())
+            .collect()
+    }
+}
+
+impl<'a, T: ToVectorValue + Clone + 'a> RMatrix<T>
+where
+    Robj: AsTypedSlice<'a, T>,
+{
+    /// Make a matrix from rows of the same length.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let matrix = RMatrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]])?;
+    ///     assert_eq!(r!(matrix), R!("matrix(1:6, nrow = 2, byrow = TRUE)")?);
+    ///     assert!(RMatrix::from_rows(&[vec![1, 2], vec![3]]).is_err());
+    /// }
+    /// ```
+    pub fn from_rows(rows: &[Vec<T>]) -> Result<Self> {
+        let ncols = rows.first().map_or(0, |row| row.len());
+        if let Some(row) = rows.iter().find(|row| row.len() != ncols) {
+            return Err(Error::Other(format!(
+                "matrix rows must have the same length, expected {} not {}",
+                ncols,
+                row.len()
+            )));
+        }
+        Ok(RMatrix::new_matrix(rows.len(), ncols, |r, c| {
+            rows[r][c].clone()
+        }))
+    }
+
+    /// Make a matrix from columns of the same length.
+    pub fn from_columns(columns: &[Vec<T>]) -> Result<Self> {
+        let nrows = columns.first().map_or(0, |col| col.len());
+        if let Some(col) = columns.iter().find(|col| col.len() != nrows) {
+            return Err(Error::Other(format!(
+                "matrix columns must have the same length, expected {} not {}",
+                nrows,
+                col.len()
+            )));
+        }
+        Ok(RMatrix::new_matrix(nrows, columns.len(), |r, c| {
+            columns[c][r].clone()
+        }))
+    }
+}
+
+impl<'a, T: ToVectorValue + Clone + 'a> TryFrom<Vec<Vec<T>>> for RMatrix<T>
+where
+    Robj: AsTypedSlice<'a, T>,
+{
+    type Error = Error;
+
+    /// Make a matrix from a vector of rows, see [RMatrix::from_rows].
+    fn try_from(rows: Vec<Vec<T>>) -> Result<Self> {
+        RMatrix::from_rows(&rows)
+    }
+}
+
+impl<T: Clone> From<RMatrix<T>> for Vec<Vec<T>> {
+    /// Copy the rows of a matrix, see [RMatrix::to_rows].
+    fn from(matrix: RMatrix<T>) -> Self {
+        matrix.to_rows()
+    }
+}
+
+/// A view of elements a fixed stride apart in an array, such as a row or column of a matrix.
+///
+/// The view is also an iterator over its elements, which consumes the view.
+#[derive(Debug)]
+pub struct StridedView<'a, T> {
+    data: &'a [T],
+    start: usize,
+    stride: usize,
+    len: usize,
+}
+
+impl<'a, T> Clone for StridedView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for StridedView<'a, T> {}
+
+impl<'a, T> StridedView<'a, T> {
+    /// Get element `i` of the view.
+    pub fn get(&self, i: usize) -> Option<&'a T> {
+        if i < self.len {
+            self.data.get(self.start + i * self.stride)
+        } else {
+            None
+        }
+    }
+
+    /// The elements as a slice if they are contiguous.
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        if self.len == 0 {
+            Some(&[])
+        } else if self.stride == 1 || self.len == 1 {
+            Some(&self.data[self.start..self.start + self.len])
+        } else {
+            None
+        }
+    }
+
+    /// The distance between elements in the underlying data.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
 }
 
+impl<'a, T> Index<usize> for StridedView<'a, T> {
+    type Output = T;
+
+    /// Panics if out of bounds.
+    fn index(&self, i: usize) -> &T {
+        self.get(i).expect("strided view index out of bounds")
+    }
+}
+
+impl<'a, T> Iterator for StridedView<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.get(0)?;
+        self.start += self.stride;
+        self.len -= 1;
+        Some(res)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.len {
+            self.len = 0;
+            return None;
+        }
+        self.start += n * self.stride;
+        self.len -= n;
+        self.next()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for StridedView<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.data.get(self.start + self.len * self.stride)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for StridedView<'a, T> {}
+
 impl<'a, T: ToVectorValue + 'a> RMatrix3D<T>
 where
     Robj: AsTypedSlice<'a, T>,
@@ -251,6 +638,17 @@ where
     }
 }
 
+impl<'a, T: 'a> TryFrom<&Robj> for RMatrix<T>
+where
+    Robj: AsTypedSlice<'a, T>,
+{
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        robj.clone().try_into()
+    }
+}
+
 impl<'a, T: 'a> TryFrom<Robj> for RMatrix3D<T>
 where
     Robj: AsTypedSlice<'a, T>,
@@ -353,6 +751,26 @@ impl<T> IndexMut<[usize; 2]> for RArray<T, [usize; 2]> {
     }
 }
 
+impl<T> Index<(usize, usize)> for RArray<T, [usize; 2]> {
+    type Output = T;
+
+    /// Zero-based indexing by `(row, column)`.
+    ///
+    /// Panics if out of bounds.
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self[[row, col]]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for RArray<T, [usize; 2]> {
+    /// Zero-based mutable indexing by `(row, column)`.
+    ///
+    /// Panics if out of bounds.
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        &mut self[[row, col]]
+    }
+}
+
 impl<T, D> Deref for RArray<T, D> {
     type Target = Robj;
 
@@ -428,4 +846,25 @@ mod tests {
 
         }
     }
+
+    #[test]
+    fn test_rows_and_columns() {
+        test! {
+            let rows = vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]];
+            let mut matrix: RMatrix<f64> = rows.try_into()?;
+            assert_eq!((matrix.nrows(), matrix.ncols()), (3, 2));
+            assert_eq!(matrix[(2, 0)], 5.0);
+            matrix[(2, 0)] = 7.0;
+            assert_eq!(matrix.get(2, 0), Some(&7.0));
+            assert_eq!(matrix.get(0, 2), None);
+            assert_eq!(matrix.to_columns(), [vec![1.0, 3.0, 7.0], vec![2.0, 4.0, 6.0]]);
+
+            let columns = RMatrix::from_columns(&matrix.to_columns())?;
+            assert_eq!(Vec::<Vec<f64>>::from(columns), matrix.to_rows());
+
+            let empty = RMatrix::<i32>::from_rows(&[])?;
+            assert_eq!((empty.nrows(), empty.ncols()), (0, 0));
+            assert!(empty.to_rows().is_empty() && empty.to_columns().is_empty());
+        }
+    }
 }
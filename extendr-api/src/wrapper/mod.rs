@@ -8,6 +8,7 @@ use libR_sys::*;
 pub mod altrep;
 pub mod complexes;
 pub mod dataframe;
+pub mod dots;
 pub mod doubles;
 pub mod environment;
 pub mod expr;
@@ -19,43 +20,53 @@ pub mod list;
 pub mod logicals;
 mod macros;
 pub mod matrix;
+pub mod namespace;
 pub mod nullable;
 pub mod pairlist;
 pub mod primitive;
 pub mod promise;
+pub mod r6;
 pub mod raw;
 pub mod rstr;
 pub mod s4;
 pub mod strings;
 pub mod symbol;
+pub mod typed_function;
 
 pub use self::rstr::Rstr;
 #[cfg(use_r_altlist)]
 pub use altrep::AltListImpl;
 pub use altrep::{
     AltComplexImpl, AltIntegerImpl, AltLogicalImpl, AltRawImpl, AltRealImpl, AltStringImpl, Altrep,
-    AltrepImpl,
+    AltrepClass, AltrepImpl,
 };
 pub use complexes::Complexes;
-pub use dataframe::{Dataframe, IntoDataFrameRow};
+pub use dataframe::{
+    Dataframe, DataframeBuilder, FromColumnElt, FromDataframeRow, IntoDataFrameRow, RowIter,
+    RowView,
+};
+pub use dots::Dots;
 pub use doubles::Doubles;
 pub use environment::{EnvIter, Environment};
 pub use expr::Expressions;
 pub use externalptr::ExternalPtr;
-pub use function::Function;
+pub use function::{Arity, Function, IntoRClosure};
 pub use integers::Integers;
 pub use lang::Language;
 pub use list::{FromList, List, ListIter};
 pub use logicals::Logicals;
-pub use matrix::{MatrixConversions, RArray, RColumn, RMatrix, RMatrix3D};
+pub use matrix::{MatrixConversions, RArray, RColumn, RMatrix, RMatrix3D, StridedView};
+pub use namespace::{double_colon, triple_colon, Namespace};
 pub use nullable::Nullable;
 pub use pairlist::{Pairlist, PairlistIter};
 pub use primitive::Primitive;
 pub use promise::Promise;
+pub use r6::R6;
 pub use raw::Raw;
-pub use s4::S4;
-pub use strings::Strings;
+pub use s4::{S4DispatchContext, S4};
+pub use strings::{StrViews, Strings};
 pub use symbol::Symbol;
+pub use typed_function::{IntoArgs, TypedFunction};
 
 pub(crate) fn make_symbol(name: &str) -> SEXP {
     let name = CString::new(name).unwrap();
@@ -79,6 +90,88 @@ where
     })
 }
 
+/// Copy all the attributes of `from` to `to`, as R's elementwise arithmetic does.
+pub(crate) fn copy_all_attributes(to: &mut Robj, from: &Robj) {
+    single_threaded(|| unsafe { DUPLICATE_ATTRIB(to.get_mut(), from.get()) });
+}
+
+/// Copy only the names, dim and dimnames of `from` to `to`.
+pub(crate) fn copy_names_and_dims(to: &mut Robj, from: &Robj) -> Result<()> {
+    for name in [names_symbol(), dim_symbol(), dimnames_symbol()] {
+        if let Some(value) = from.get_attrib(name.clone()) {
+            to.set_attrib(name, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// The sortedness of an integer, double or character vector, as known to R.
+///
+/// R uses this to skip work in `sort()`, `order()`, `match()` and
+/// `anyNA()`. It is set with [Integers::mark_sorted] and similar methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sortedness {
+    /// Nothing is known.
+    Unknown,
+    /// Known not to be sorted.
+    Unsorted,
+    /// Increasing, with any `NA`s last.
+    Increasing,
+    /// Decreasing, with any `NA`s last.
+    Decreasing,
+    /// Increasing, with any `NA`s first.
+    IncreasingNaFirst,
+    /// Decreasing, with any `NA`s first.
+    DecreasingNaFirst,
+}
+
+impl Sortedness {
+    /// The code R uses for this sortedness.
+    pub fn code(self) -> i32 {
+        match self {
+            Sortedness::Unknown => i32::MIN,
+            Sortedness::Unsorted => 0,
+            Sortedness::Increasing => 1,
+            Sortedness::Decreasing => -1,
+            Sortedness::IncreasingNaFirst => 2,
+            Sortedness::DecreasingNaFirst => -2,
+        }
+    }
+
+    /// The sortedness for an R code.
+    pub fn from_code(code: i32) -> Sortedness {
+        match code {
+            0 => Sortedness::Unsorted,
+            1 => Sortedness::Increasing,
+            -1 => Sortedness::Decreasing,
+            2 => Sortedness::IncreasingNaFirst,
+            -2 => Sortedness::DecreasingNaFirst,
+            _ => Sortedness::Unknown,
+        }
+    }
+}
+
+/// Wrap a vector in R's metadata ALTREP class, which records the
+/// sortedness and absence of `NA`s without copying the data.
+pub(crate) fn wrap_meta(robj: &Robj, sorted: i32, no_na: bool) -> Result<Robj> {
+    eval_string_with_params(
+        ".Internal(wrap_meta(param.0, param.1, param.2))",
+        &[robj, &Robj::from(sorted), &Robj::from(no_na as i32)],
+    )
+}
+
+pub(crate) fn check_same_length(a: &Robj, b: &Robj) -> Result<()> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(Error::Other(format!(
+            "Vectors must have the same length, got {} and {}",
+            a.len(),
+            b.len()
+        )))
+    }
+}
+
 macro_rules! make_conversions {
     ($typename: ident, $errname: ident, $isfunc: ident, $errstr: expr) => {
         impl<'a> FromRobj<'a> for $typename {
@@ -194,8 +287,17 @@ make_conversions!(
     "Not an Environment"
 );
 
+make_conversions!(
+    Namespace,
+    ExpectedEnvironment,
+    is_namespace_env,
+    "Not a namespace"
+);
+
 make_conversions!(List, ExpectedList, is_list, "Not a List");
 
+make_conversions!(Dots, ExpectedList, is_list, "Not a list of arguments");
+
 make_conversions!(
     Expressions,
     ExpectedExpression,
@@ -225,6 +327,8 @@ make_conversions!(Altrep, ExpectedAltrep, is_altrep, "Not an Altrep type");
 
 make_conversions!(S4, ExpectedS4, is_s4, "Not a S4 type");
 
+make_conversions!(R6, ExpectedR6, is_r6, "Not an R6 object");
+
 make_conversions!(Integers, ExpectedInteger, is_integer, "Not an integer type");
 make_conversions!(Logicals, ExpectedLogical, is_logical, "Not a logical type");
 make_conversions!(Doubles, ExpectedReal, is_real, "Not a floating point type");
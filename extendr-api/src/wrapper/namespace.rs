@@ -0,0 +1,176 @@
+use super::*;
+use crate as extendr_api;
+use std::cell::RefCell;
+
+thread_local! {
+    // Namespaces loaded through `Namespace::load`. A namespace can be unloaded
+    // or loaded again, so a cached one is only used while it is the one in the
+    // namespace registry.
+    static NAMESPACE_CACHE: RefCell<HashMap<String, Namespace>> = RefCell::new(HashMap::new());
+}
+
+/// Wrapper for package namespaces.
+///
+/// Use this to call functions from a specific package without relying
+/// on the search order of the global environment.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let stats = Namespace::load("stats")?;
+///     assert_eq!(stats.name()?, "stats");
+///     let qnorm = stats.get("qnorm")?;
+///     assert_eq!(qnorm.is_function(), true);
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct Namespace {
+    pub(crate) robj: Robj,
+}
+
+impl Namespace {
+    /// Load a namespace, equivalent to `loadNamespace(name)`.
+    ///
+    /// Namespaces are cached, so repeated calls are cheap. A namespace that
+    /// has been unloaded or loaded again since is loaded afresh.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let base = Namespace::load("base")?;
+    ///     assert_eq!(base, Namespace::load("base")?);
+    ///     assert!(Namespace::load("not_a_package").is_err());
+    ///
+    ///     let tools = Namespace::load("tools")?;
+    ///     R!("unloadNamespace('tools')")?;
+    ///     let reloaded = Namespace::load("tools")?;
+    ///     assert!(reloaded != tools);
+    ///     let registered: Environment = namespace_registry().local(sym!(tools))?.try_into()?;
+    ///     assert_eq!(reloaded.as_environment(), registered);
+    /// }
+    /// ```
+    pub fn load(name: &str) -> Result<Namespace> {
+        if let Some(ns) = NAMESPACE_CACHE.with(|cache| cache.borrow().get(name).cloned()) {
+            let registered = namespace_registry().local(Symbol::from_string(name));
+            if registered.as_ref() == Ok(&ns.robj) {
+                return Ok(ns);
+            }
+        }
+        let ns: Namespace = single_threaded(|| {
+            if name == "base" {
+                Ok(base_namespace().robj)
+            } else {
+                call!("loadNamespace", name)
+            }
+        })
+        .map_err(|_| Error::NamespaceNotFound(r!(name)))?
+        .try_into()?;
+        NAMESPACE_CACHE.with(|cache| cache.borrow_mut().insert(name.to_string(), ns.clone()));
+        Ok(ns)
+    }
+
+    /// Forget all cached namespaces.
+    pub fn clear_cache() {
+        NAMESPACE_CACHE.with(|cache| cache.borrow_mut().clear());
+    }
+
+    /// Get the name of this namespace.
+    pub fn name(&self) -> Result<String> {
+        let name = call!("getNamespaceName", self.robj.clone())?;
+        name.as_str()
+            .map(|s| s.to_string())
+            .ok_or(Error::ExpectedString(name))
+    }
+
+    /// Get an exported object, the equivalent of `pkg::name`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let stats = Namespace::load("stats")?;
+    ///     let qnorm = stats.get("qnorm")?;
+    ///     assert_eq!(qnorm.call(pairlist!(0.5))?, r!(0.0));
+    ///     assert!(stats.get("not_a_function").is_err());
+    /// }
+    /// ```
+    pub fn get(&self, name: &str) -> Result<Robj> {
+        if self.robj.get() == unsafe { R_BaseNamespace } {
+            return self.get_internal(name);
+        }
+        call!("getExportedValue", self.robj.clone(), name)
+            .map_err(|_| Error::NotFound(r!(Symbol::from_string(name))))
+    }
+
+    /// Get any object in the namespace, exported or not,
+    /// the equivalent of `pkg:::name`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let stats = Namespace::load("stats")?;
+    ///     assert_eq!(stats.get_internal("qnorm")?.is_function(), true);
+    /// }
+    /// ```
+    pub fn get_internal(&self, name: &str) -> Result<Robj> {
+        let env: Environment = self.robj.clone().try_into()?;
+        let value = env.local(Symbol::from_string(name))?;
+        if value.is_unbound_value() {
+            return Err(Error::NotFound(r!(Symbol::from_string(name))));
+        }
+        value.eval_promise()
+    }
+
+    /// Get an exported function, the equivalent of `pkg::name`.
+    pub fn function(&self, name: &str) -> Result<Function> {
+        self.get(name)?.try_into()
+    }
+
+    /// Get the names of the exported objects.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let stats = Namespace::load("stats")?;
+    ///     assert!(stats.exports()?.iter().any(|s| s == "qnorm"));
+    /// }
+    /// ```
+    pub fn exports(&self) -> Result<Vec<String>> {
+        let exports = call!("getNamespaceExports", self.robj.clone())?;
+        exports
+            .as_string_vector()
+            .ok_or(Error::ExpectedString(exports))
+    }
+
+    /// View the namespace as an environment.
+    pub fn as_environment(&self) -> Environment {
+        Environment {
+            robj: self.robj.clone(),
+        }
+    }
+}
+
+/// Look up `pkg::name`, loading the namespace if needed.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     assert_eq!(double_colon("stats", "qnorm")?.is_function(), true);
+/// }
+/// ```
+pub fn double_colon(package: &str, name: &str) -> Result<Robj> {
+    Namespace::load(package)?.get(name)
+}
+
+/// Look up `pkg:::name`, loading the namespace if needed.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     assert_eq!(triple_colon("stats", "qnorm")?.is_function(), true);
+/// }
+/// ```
+pub fn triple_colon(package: &str, name: &str) -> Result<Robj> {
+    Namespace::load(package)?.get_internal(name)
+}
+
+impl std::fmt::Debug for Namespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Ok(name) => write!(f, "<namespace:{}>", name),
+            Err(_) => write!(f, "<namespace>"),
+        }
+    }
+}
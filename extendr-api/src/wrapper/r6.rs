@@ -0,0 +1,118 @@
+//! R6 object support.
+//!
+//! An R6 object is an environment holding its public fields and methods, with
+//! the class attribute `c(<class>, ..., "R6")`. Methods are closures whose
+//! enclosing environment binds `self` and `private`, so they can be called
+//! directly from rust.
+
+use super::*;
+
+/// A wrapper for R6 objects, for calling their methods and reading their fields.
+///
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     // Equivalent to an object of `R6Class("Counter", public = list(...))`.
+///     let counter = R!(r#"local({
+///         self <- new.env()
+///         self$count <- 0
+///         self$add <- function(n = 1) { self$count <- self$count + n; invisible(self) }
+///         class(self) <- c("Counter", "R6")
+///         self
+///     })"#)?;
+///     let counter = R6::try_from(counter)?;
+///     counter.call_method("add", pairlist!(n = 2))?;
+///     counter.call_method("add", pairlist!())?;
+///     assert_eq!(counter.get_field("count")?, r!(3));
+///     assert_eq!(counter.class_name(), Some("Counter"));
+/// }
+/// ```
+#[derive(PartialEq, Clone)]
+pub struct R6 {
+    pub(crate) robj: Robj,
+}
+
+impl R6 {
+    /// The most specific class of the object, such as `"Counter"`.
+    pub fn class_name(&self) -> Option<&str> {
+        self.robj.class()?.next()
+    }
+
+    /// The environment holding the public fields and methods.
+    pub fn environment(&self) -> Environment {
+        Environment {
+            robj: self.robj.clone(),
+        }
+    }
+
+    /// The environment holding the private fields and methods, if there are any.
+    pub fn private(&self) -> Option<Environment> {
+        let enclos = self
+            .environment()
+            .local(Symbol::from_string(".__enclos_env__"))
+            .ok()?;
+        enclos
+            .as_environment()?
+            .local(sym!(private))
+            .ok()?
+            .as_environment()
+    }
+
+    /// Return true if the object has a public field or method called `name`.
+    pub fn has_field(&self, name: &str) -> bool {
+        self.environment()
+            .local(Symbol::from_string(name))
+            .map_or(false, |value| !value.is_unbound_value())
+    }
+
+    /// Get a public field, or a method as a function.
+    ///
+    /// Active bindings are evaluated.
+    pub fn get_field(&self, name: &str) -> Result<Robj> {
+        let value = self.environment().local(Symbol::from_string(name))?;
+        if value.is_unbound_value() {
+            Err(Error::NotFound(r!(name)))
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Set a public field, as `obj$name <- value` does.
+    ///
+    /// Like R, this fails if the object is locked and has no field `name`,
+    /// or if `name` is a method.
+    pub fn set_field<V: Into<Robj>>(&self, name: &str, value: V) -> Result<()> {
+        use crate as extendr_api;
+        let env = self.robj.clone();
+        let value = value.into();
+        R!("assign({{name}}, {{value}}, envir = {{env}})")?;
+        Ok(())
+    }
+
+    /// Call the public method `name` with `args`, as `obj$name(...)` does.
+    pub fn call_method(&self, name: &str, args: Pairlist) -> Result<Robj> {
+        let method = self.get_field(name)?;
+        let method = method
+            .as_function()
+            .ok_or_else(|| Error::ExpectedFunction(method.clone()))?;
+        method.call(args)
+    }
+
+    /// The names of the public fields and methods.
+    pub fn field_names(&self) -> Vec<String> {
+        self.environment()
+            .names()
+            .filter(|name| *name != ".__enclos_env__")
+            .map(|name| name.to_string())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for R6 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("R6")
+            .field("class", &self.class_name())
+            .field("fields", &self.field_names())
+            .finish()
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::iter::FromIterator;
 
 /// Wrapper for creating raw (byte) objects.
 ///
@@ -53,6 +54,67 @@ impl Raw {
     pub fn as_slice(&self) -> &[u8] {
         self.robj.as_typed_slice().unwrap()
     }
+
+    /// Get a mutable slice of bytes from the Raw object, without copying.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut bytes = Raw::new(3);
+    ///     bytes.as_slice_mut()[1] = 0xff;
+    ///     assert_eq!(bytes.as_slice(), &[0, 0xff, 0]);
+    /// }
+    /// ```
+    pub fn as_slice_mut(&mut self) -> &mut [u8] {
+        self.robj.as_typed_slice_mut().unwrap()
+    }
+}
+
+impl Deref for Raw {
+    type Target = [u8];
+
+    /// Treat Raw as if it is a slice, like `Vec<u8>`.
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for Raw {
+    /// Treat Raw as if it is a mutable slice, like `Vec<u8>`.
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_slice_mut()
+    }
+}
+
+impl From<&[u8]> for Raw {
+    fn from(bytes: &[u8]) -> Self {
+        Raw::from_bytes(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Raw {
+    fn from(bytes: Vec<u8>) -> Self {
+        Raw::from_bytes(&bytes)
+    }
+}
+
+impl From<Raw> for Vec<u8> {
+    fn from(bytes: Raw) -> Self {
+        bytes.as_slice().to_vec()
+    }
+}
+
+impl FromIterator<u8> for Raw {
+    /// Collect bytes into a raw vector.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let bytes: Raw = (0..4u8).map(|i| i * 2).collect();
+    ///     assert_eq!(&*bytes, &[0, 2, 4, 6]);
+    /// }
+    /// ```
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        Raw::from(iter.into_iter().collect::<Vec<u8>>())
+    }
 }
 
 impl std::fmt::Debug for Raw {
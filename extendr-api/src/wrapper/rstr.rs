@@ -1,4 +1,5 @@
 use super::*;
+use std::borrow::Cow;
 
 /// Wrapper for creating CHARSXP objects.
 /// These are used only as the contents of a character
@@ -25,6 +26,29 @@ pub(crate) unsafe fn sexp_to_str(sexp: SEXP) -> &'static str {
     }
 }
 
+// Read a CHARSXP as UTF-8, borrowing it when it is valid UTF-8 that is
+// marked as UTF-8 or is ASCII, so needs no translation.
+pub(crate) unsafe fn sexp_to_str_view(sexp: SEXP) -> Cow<'static, str> {
+    if sexp == R_NaString {
+        return Cow::Borrowed(<&str>::na());
+    }
+    let bytes = std::slice::from_raw_parts(R_CHAR(sexp) as *const u8, LENGTH(sexp) as usize);
+    let encoding = Rf_getCharCE(sexp);
+    if encoding == cetype_t_CE_UTF8 || bytes.is_ascii() {
+        if let Ok(s) = std::str::from_utf8(bytes) {
+            return Cow::Borrowed(s);
+        }
+    }
+    if encoding == cetype_t_CE_BYTES {
+        // R refuses to translate strings marked as bytes.
+        return Cow::Owned(String::from_utf8_lossy(bytes).into_owned());
+    }
+    single_threaded(|| {
+        let translated = std::ffi::CStr::from_ptr(Rf_translateCharUTF8(sexp));
+        Cow::Owned(translated.to_string_lossy().into_owned())
+    })
+}
+
 impl Rstr {
     /// Make a character object from a string.
     pub fn from_string(val: &str) -> Self {
@@ -38,6 +62,25 @@ impl Rstr {
     pub fn as_str(&self) -> &str {
         unsafe { sexp_to_str(self.robj.get()) }
     }
+
+    /// Get the string as UTF-8, translating it from its encoding if needed.
+    ///
+    /// Strings marked as UTF-8 and ASCII strings are borrowed without copying.
+    /// Others, such as latin1 strings, are translated into a new `String`.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// use std::borrow::Cow;
+    /// test! {
+    ///     let s = Rstr::from("abc");
+    ///     assert!(matches!(s.as_str_view(), Cow::Borrowed("abc")));
+    ///
+    ///     let latin1 = Strings::try_from(eval_string("iconv('caf\\u00e9', 'UTF-8', 'latin1')")?)?;
+    ///     assert_eq!(latin1.elt(0).as_str_view(), "café");
+    /// }
+    /// ```
+    pub fn as_str_view(&self) -> Cow<str> {
+        unsafe { sexp_to_str_view(self.robj.get()) }
+    }
 }
 
 impl AsRef<str> for Rstr {
@@ -118,11 +118,241 @@ impl S4 {
     }
 }
 
+impl S4 {
+    /// Create a S4 object with values for some of its slots, like `new(name, ...)`.
+    ///
+    /// Example:
+    /// ```
+    /// use extendr_api::prelude::*;
+    ///
+    /// test! {
+    ///     S4::set_class("point", pairlist!(x="numeric", y="numeric"), r!(()))?;
+    ///     let p = S4::new_with_slots("point", pairlist!(x=1.0, y=2.0))?;
+    ///     assert_eq!(p.slot("y")?, r!(2.0));
+    ///     assert!(p.slot("z").is_err());
+    /// }
+    /// ```
+    pub fn new_with_slots(name: &str, slots: Pairlist) -> Result<S4> {
+        use crate as extendr_api;
+        let res = R!(r#"do.call(methods::new, c(list({{name}}), as.list({{slots}})))"#)?;
+        res.try_into()
+    }
+
+    /// Get a named slot, or an error if there is no such slot.
+    pub fn slot<N: Into<Robj>>(&self, name: N) -> Result<Robj> {
+        let name = name.into();
+        unsafe {
+            if R_has_slot(self.get(), name.get()) != 0 {
+                Ok(Robj::from_sexp(R_do_slot(self.get(), name.get())))
+            } else {
+                Err(Error::NotFound(name))
+            }
+        }
+    }
+
+    /// Check if the object is of class `class` or a subclass, like `is(x, class)`.
+    ///
+    /// Example:
+    /// ```
+    /// use extendr_api::prelude::*;
+    ///
+    /// test! {
+    ///     S4::set_class("base_shape", pairlist!(), r!(()))?;
+    ///     S4::set_class("square", pairlist!(side="numeric"), r!("base_shape"))?;
+    ///     let sq = S4::new("square")?;
+    ///     assert!(sq.is("square") && sq.is("base_shape"));
+    ///     assert!(!sq.is("numeric"));
+    /// }
+    /// ```
+    pub fn is(&self, class: &str) -> bool {
+        use crate as extendr_api;
+        let robj = &self.robj;
+        R!(r#"methods::is({{robj}}, {{class}})"#)
+            .ok()
+            .and_then(|res| res.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Check the object with the validity methods of its class and superclasses,
+    /// like `validObject(x)`.
+    pub fn validate(&self) -> Result<()> {
+        use crate as extendr_api;
+        let robj = &self.robj;
+        R!(r#"methods::validObject({{robj}})"#)?;
+        Ok(())
+    }
+
+    /// Set the validity method of a class, like `setValidity(class, method)`.
+    ///
+    /// The method is called with the object and returns `TRUE` if it is valid,
+    /// or strings describing the problems.
+    ///
+    /// Example:
+    /// ```
+    /// use extendr_api::prelude::*;
+    ///
+    /// test! {
+    ///     S4::set_class("positive", pairlist!(x="numeric"), r!(()))?;
+    ///     let check = R!(r#"function(object) if (all(object@x > 0)) TRUE else "x must be positive""#)?;
+    ///     S4::set_validity("positive", check.try_into()?)?;
+    ///     assert!(S4::new_with_slots("positive", pairlist!(x=1.0))?.validate().is_ok());
+    ///     assert!(S4::new_with_slots("positive", pairlist!(x=-1.0)).is_err());
+    /// }
+    /// ```
+    pub fn set_validity(class: &str, method: Function) -> Result<()> {
+        use crate as extendr_api;
+        R!(r#"methods::setValidity({{class}}, {{method}})"#)?;
+        Ok(())
+    }
+
+    /// Create a generic function called `name` with arguments `args`,
+    /// like `setGeneric(name, function(args) standardGeneric(name))`.
+    ///
+    /// The generic, and the methods made by [S4::set_method], are put in the
+    /// global environment.
+    ///
+    /// Example:
+    /// ```
+    /// use extendr_api::prelude::*;
+    ///
+    /// test! {
+    ///     S4::set_class("circle", pairlist!(r="numeric"), r!(()))?;
+    ///     S4::set_generic("area", &["shape", "..."])?;
+    ///     let area = R!("function(shape, ...) pi * shape@r^2")?;
+    ///     S4::set_method("area", &["circle"], area.try_into()?)?;
+    ///     let circle = S4::new_with_slots("circle", pairlist!(r=1.0))?;
+    ///     assert_eq!(call!("area", circle)?, r!(std::f64::consts::PI));
+    /// }
+    /// ```
+    pub fn set_generic(name: &str, args: &[&str]) -> Result<()> {
+        use crate as extendr_api;
+        let args = Strings::from_values(args.iter().copied());
+        R!(r#"local({
+            def <- function() NULL
+            formals(def) <- setNames(rep(list(quote(expr = )), length({{&args}})), {{&args}})
+            body(def) <- call("standardGeneric", {{name}})
+            methods::setGeneric({{name}}, def, where = globalenv())
+        })"#)?;
+        Ok(())
+    }
+
+    /// Define the method of the generic `name` for the classes in `signature`,
+    /// like `setMethod(name, signature, definition)`.
+    ///
+    /// The definition must have the arguments of the generic.
+    pub fn set_method(name: &str, signature: &[&str], definition: Function) -> Result<()> {
+        use crate as extendr_api;
+        let signature = Strings::from_values(signature.iter().copied());
+        R!(r#"methods::setMethod({{name}}, {{signature}}, {{definition}}, where = globalenv())"#)?;
+        Ok(())
+    }
+}
+
+impl S4 {
+    /// Dispatch to the methods of the generic `name`, like `standardGeneric(name)`.
+    ///
+    /// This is for rust functions that are the body of a generic,
+    /// called from its R function, as it uses the arguments of that call.
+    pub fn standard_generic(name: &str) -> Result<Robj> {
+        call_in_frame(
+            "standardGeneric",
+            Pairlist::from_pairs([("", r!(name))]),
+            &current_env(),
+        )
+    }
+}
+
+/// The dispatch state of a S4 method that calls a rust function.
+///
+/// A rust function registered as a method, with `setMethod()` and an R
+/// function that calls it, can inspect the classes used to select it and
+/// call the next method, taking part in the class hierarchy.
+/// ```ignore
+/// #[extendr]
+/// fn describe_b(x: Robj) -> Result<String> {
+///     let context = S4DispatchContext::current().unwrap();
+///     let next: String = context.call_next_method()?.try_into()?;
+///     Ok(format!("B, then {}", next))
+/// }
+/// ```
+/// ```r
+/// setMethod("describe", "B", function(x) describe_b(x))
+/// ```
+#[derive(Debug, Clone)]
+pub struct S4DispatchContext {
+    frame: Environment,
+}
+
+impl S4DispatchContext {
+    /// The context of the S4 method whose R function called the current rust function,
+    /// or `None` if it is not a method.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     assert!(S4DispatchContext::current().is_none());
+    /// }
+    /// ```
+    pub fn current() -> Option<S4DispatchContext> {
+        S4DispatchContext::from_frame(current_env())
+    }
+
+    /// The context of the S4 method evaluating in `frame`, if any.
+    pub fn from_frame(frame: Environment) -> Option<S4DispatchContext> {
+        let defined = frame.local(dot_defined()).ok()?;
+        if defined.is_unbound_value() {
+            None
+        } else {
+            Some(S4DispatchContext { frame })
+        }
+    }
+
+    /// The name of the generic, `.Generic`.
+    pub fn generic(&self) -> Result<String> {
+        let generic = self.frame.local(Symbol::from_string(".Generic"))?;
+        Ok(generic.try_as_str()?.to_string())
+    }
+
+    /// The signature the method was defined for, `.defined`.
+    pub fn defined(&self) -> Result<Robj> {
+        self.frame.local(dot_defined())
+    }
+
+    /// The classes of the arguments that selected the method, `.target`.
+    pub fn target(&self) -> Result<Robj> {
+        self.frame.local(dot_target())
+    }
+
+    /// The method being run, `.Method`.
+    pub fn method(&self) -> Result<Robj> {
+        self.frame.local(dot_method())
+    }
+
+    /// The frame of the method, where its arguments are.
+    pub fn frame(&self) -> &Environment {
+        &self.frame
+    }
+
+    /// Call the next method with the arguments of this method, like `callNextMethod()`.
+    pub fn call_next_method(&self) -> Result<Robj> {
+        call_in_frame("callNextMethod", Pairlist::new(), &self.frame)
+    }
+
+    /// Call the next method with other arguments, like `callNextMethod(...)`.
+    pub fn call_next_method_with(&self, args: Pairlist) -> Result<Robj> {
+        call_in_frame("callNextMethod", args, &self.frame)
+    }
+}
+
+// Call a function from the methods package as if it was called in `frame`,
+// which these functions use to find the arguments and dispatch state.
+fn call_in_frame(name: &str, args: Pairlist, frame: &Environment) -> Result<Robj> {
+    let fun = find_namespace("methods")?.local(Symbol::from_string(name))?;
+    let call = single_threaded(|| unsafe { Robj::from_sexp(Rf_lcons(fun.get(), args.get())) });
+    call.eval_with_env(frame)
+}
+
 // TODO: Think about these functions in the future.
 //
-// Currently, S4 support is not a top priority, but we hope that what we have
-// covered the basics for now.
-//
 // extern "C" {
 //     pub fn R_S4_extends(klass: SEXP, useTable: SEXP) -> SEXP;
 // }
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::convert::From;
 use std::iter::FromIterator;
 
@@ -92,6 +93,69 @@ impl Strings {
         self.as_slice().iter()
     }
 
+    /// Apply `f` to each element, keeping the names, dim, dimnames and other attributes.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut robj = r!(["x", "y"]);
+    ///     robj.set_names(["a", "b"])?;
+    ///     let strings = Strings::try_from(robj)?;
+    ///     let res = strings.map_preserving_names(|s| format!("{}!", s).into());
+    ///     assert_eq!(res.as_robj(), &r!(["x!", "y!"]).set_names(["a", "b"])?);
+    /// }
+    /// ```
+    pub fn map_preserving_names<F>(&self, mut f: F) -> Self
+    where
+        F: FnMut(&Rstr) -> Rstr,
+    {
+        let mut res = Strings::new(self.len());
+        for (i, s) in self.iter().enumerate() {
+            res.set_elt(i, f(s));
+        }
+        copy_all_attributes(&mut res.robj, &self.robj);
+        res
+    }
+
+    /// Apply `f` to each element to make a vector of another type, keeping the names, dim and dimnames.
+    /// Other attributes, such as the class, are dropped as they may not apply to the new type.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let mut robj = r!(["x", "yy"]);
+    ///     robj.set_names(["a", "b"])?;
+    ///     let strings = Strings::try_from(robj)?;
+    ///     let res: Integers = strings.map_into_preserving_names(|s| Rint::from(s.len() as i32))?;
+    ///     assert_eq!(res.as_robj(), &r!([1, 2]).set_names(["a", "b"])?);
+    /// }
+    /// ```
+    pub fn map_into_preserving_names<T, U, F>(&self, f: F) -> Result<T>
+    where
+        F: FnMut(&Rstr) -> U,
+        T: FromIterator<U> + GetSexp,
+    {
+        let mut res: T = self.iter().map(f).collect();
+        copy_names_and_dims(res.as_robj_mut(), &self.robj)?;
+        Ok(res)
+    }
+
+    /// Get all the elements as UTF-8 string slices, see [Rstr::as_str_view].
+    ///
+    /// For character vectors of ASCII or UTF-8 strings, which is most of them,
+    /// no strings are copied. [StrViews::is_zero_copy] reports if any needed translation.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let strings = Strings::from_values(["a", "b"]);
+    ///     let views = strings.str_views();
+    ///     assert!(views.is_zero_copy());
+    ///     assert_eq!(views.join(""), "ab");
+    /// }
+    /// ```
+    pub fn str_views(&self) -> StrViews<'_> {
+        let views = self.iter().map(|s| s.as_str_view()).collect();
+        StrViews { views }
+    }
+
     /// Return `TRUE` if the vector is sorted, `FALSE` if not, or `NA_BOOL` if unknown.
     pub fn is_sorted(&self) -> Rbool {
         unsafe { STRING_IS_SORTED(self.get()).into() }
@@ -101,6 +165,61 @@ impl Strings {
     pub fn no_na(&self) -> Rbool {
         unsafe { STRING_NO_NA(self.get()).into() }
     }
+
+    /// The sortedness of the vector, as known to R.
+    pub fn sortedness(&self) -> Sortedness {
+        Sortedness::from_code(unsafe { STRING_IS_SORTED(self.get()) })
+    }
+
+    /// Record that the vector is sorted, so that R can skip work in
+    /// `sort()`, `order()` and `match()`.
+    ///
+    /// The result shares the data of this vector. Marking a vector that is
+    /// not sorted as sorted gives wrong results in R.
+    pub fn mark_sorted(&self, sortedness: Sortedness) -> Result<Strings> {
+        let no_na = unsafe { STRING_NO_NA(self.get()) } != 0;
+        wrap_meta(self.as_robj(), sortedness.code(), no_na)?.try_into()
+    }
+
+    /// Record that the vector has no `NA`s, so that R can skip checks for them.
+    ///
+    /// The result shares the data of this vector. Marking a vector that has
+    /// `NA`s gives wrong results in R.
+    pub fn mark_no_na(&self) -> Result<Strings> {
+        let sorted = unsafe { STRING_IS_SORTED(self.get()) };
+        wrap_meta(self.as_robj(), sorted, true)?.try_into()
+    }
+}
+
+/// The elements of a character vector as UTF-8, made by [Strings::str_views].
+///
+/// `NA` elements are the special NA string, see [CanBeNA].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrViews<'a> {
+    views: Vec<Cow<'a, str>>,
+}
+
+impl<'a> StrViews<'a> {
+    /// Return true if every element was borrowed from R without translation.
+    pub fn is_zero_copy(&self) -> bool {
+        self.translated() == 0
+    }
+
+    /// The number of elements that were translated into new strings.
+    pub fn translated(&self) -> usize {
+        self.views
+            .iter()
+            .filter(|v| matches!(v, Cow::Owned(_)))
+            .count()
+    }
+}
+
+impl<'a> Deref for StrViews<'a> {
+    type Target = [Cow<'a, str>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.views
+    }
 }
 
 impl<T: AsRef<str>> FromIterator<T> for Strings {
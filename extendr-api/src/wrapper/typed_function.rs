@@ -0,0 +1,191 @@
+use super::*;
+use std::marker::PhantomData;
+
+/// Argument lists that can be passed to a [TypedFunction].
+///
+/// This is implemented for tuples of up to eight values that convert into `Robj`.
+pub trait IntoArgs {
+    /// Convert the arguments into an unnamed pairlist.
+    fn into_args(self) -> Pairlist;
+}
+
+impl IntoArgs for () {
+    fn into_args(self) -> Pairlist {
+        Pairlist::new()
+    }
+}
+
+impl IntoArgs for Pairlist {
+    fn into_args(self) -> Pairlist {
+        self
+    }
+}
+
+macro_rules! impl_into_args {
+    ($($name: ident),+) => {
+        impl<$($name: Into<Robj>),+> IntoArgs for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_args(self) -> Pairlist {
+                let ($($name,)+) = self;
+                Pairlist::from_pairs([$(("", $name.into())),+])
+            }
+        }
+    };
+}
+
+impl_into_args!(A);
+impl_into_args!(A, B);
+impl_into_args!(A, B, C);
+impl_into_args!(A, B, C, D);
+impl_into_args!(A, B, C, D, E);
+impl_into_args!(A, B, C, D, E, F);
+impl_into_args!(A, B, C, D, E, F, G);
+impl_into_args!(A, B, C, D, E, F, G, H);
+
+/// An R function with typed arguments and return value.
+///
+/// Arguments are given as a tuple and converted with `Into<Robj>`,
+/// the result is converted with `TryFrom<Robj>`.
+/// ```
+/// use extendr_api::prelude::*;
+/// test! {
+///     let add: TypedFunction<(f64, f64), f64> = R!("function(a, b) a + b")?.try_into()?;
+///     assert_eq!(add.call((1.0, 2.0))?, 3.0);
+///
+///     // A failing conversion of the result is an error.
+///     let paste: TypedFunction<(f64,), f64> = R!("function(a) paste(a)")?.try_into()?;
+///     assert!(paste.call((1.0,)).is_err());
+/// }
+/// ```
+pub struct TypedFunction<Args, Ret> {
+    function: Function,
+    marker: PhantomData<fn(Args) -> Ret>,
+}
+
+impl<Args, Ret> TypedFunction<Args, Ret>
+where
+    Args: IntoArgs,
+    Ret: TryFrom<Robj>,
+    <Ret as TryFrom<Robj>>::Error: Into<Error>,
+{
+    /// Wrap an R function.
+    pub fn new(function: Function) -> Self {
+        Self {
+            function,
+            marker: PhantomData,
+        }
+    }
+
+    /// Call the function, converting the arguments and the result.
+    pub fn call(&self, args: Args) -> Result<Ret> {
+        let res = self.function.call(args.into_args())?;
+        Ret::try_from(res).map_err(|e| e.into())
+    }
+
+    /// Get a Rust closure that calls this function.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     let square: TypedFunction<(i32,), i32> = R!("function(x) x * x")?.try_into()?;
+    ///     let f = square.as_fn();
+    ///     let squares = (1..4).map(|x| f((x,))).collect::<Result<Vec<_>>>()?;
+    ///     assert_eq!(squares, vec![1, 4, 9]);
+    /// }
+    /// ```
+    pub fn as_fn(&self) -> impl Fn(Args) -> Result<Ret> + '_ {
+        move |args| self.call(args)
+    }
+
+    /// The underlying R function.
+    pub fn function(&self) -> &Function {
+        &self.function
+    }
+}
+
+impl<A, Ret> TypedFunction<(A,), Ret>
+where
+    A: Into<Robj>,
+    Ret: TryFrom<Robj>,
+    <Ret as TryFrom<Robj>>::Error: Into<Error>,
+{
+    /// Get a closure of one argument, for use with Rust algorithms
+    /// that accept `impl Fn(f64) -> f64` and the like.
+    ///
+    /// Panics if the R function fails or returns the wrong type.
+    /// Inside an `#[extendr]` function the panic becomes an R error.
+    /// ```
+    /// use extendr_api::prelude::*;
+    /// test! {
+    ///     fn apply_twice(f: impl Fn(f64) -> f64, x: f64) -> f64 {
+    ///         f(f(x))
+    ///     }
+    ///     let double: TypedFunction<(f64,), f64> = R!("function(x) 2 * x")?.try_into()?;
+    ///     assert_eq!(apply_twice(double.as_unary_fn(), 1.5), 6.0);
+    /// }
+    /// ```
+    pub fn as_unary_fn(&self) -> impl Fn(A) -> Ret + '_ {
+        move |a| match self.call((a,)) {
+            Ok(res) => res,
+            Err(e) => panic!("R function call failed: {}", e),
+        }
+    }
+}
+
+impl<Args, Ret> Clone for TypedFunction<Args, Ret> {
+    fn clone(&self) -> Self {
+        Self {
+            function: self.function.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Args, Ret> From<Function> for TypedFunction<Args, Ret>
+where
+    Args: IntoArgs,
+    Ret: TryFrom<Robj>,
+    <Ret as TryFrom<Robj>>::Error: Into<Error>,
+{
+    fn from(function: Function) -> Self {
+        Self::new(function)
+    }
+}
+
+impl<Args, Ret> TryFrom<&Robj> for TypedFunction<Args, Ret>
+where
+    Args: IntoArgs,
+    Ret: TryFrom<Robj>,
+    <Ret as TryFrom<Robj>>::Error: Into<Error>,
+{
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        let function: Function = robj.try_into()?;
+        Ok(Self::new(function))
+    }
+}
+
+impl<Args, Ret> TryFrom<Robj> for TypedFunction<Args, Ret>
+where
+    Args: IntoArgs,
+    Ret: TryFrom<Robj>,
+    <Ret as TryFrom<Robj>>::Error: Into<Error>,
+{
+    type Error = Error;
+
+    fn try_from(robj: Robj) -> Result<Self> {
+        Self::try_from(&robj)
+    }
+}
+
+impl<Args, Ret> From<TypedFunction<Args, Ret>> for Robj {
+    fn from(f: TypedFunction<Args, Ret>) -> Self {
+        f.function.robj
+    }
+}
+
+impl<Args, Ret> std::fmt::Debug for TypedFunction<Args, Ret> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TypedFunction({:?})", self.function)
+    }
+}
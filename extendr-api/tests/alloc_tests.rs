@@ -0,0 +1,43 @@
+use extendr_api::alloc::RVec;
+use extendr_api::prelude::*;
+use extendr_api::scalar::{Rbool, Rint};
+
+#[test]
+fn test_rvec() {
+    test! {
+        let mut values = RVec::<i32>::new();
+        assert!(values.is_empty());
+        values.extend(1..=20);
+        assert_eq!(values.len(), 20);
+        assert!(values.capacity() >= 20);
+        assert_eq!(values.pop(), Some(20));
+        values.truncate(3);
+        values.extend_from_slice(&[7, 8]);
+        assert_eq!(values[..], [1, 2, 3, 7, 8]);
+        assert_eq!(format!("{:?}", values), "[1, 2, 3, 7, 8]");
+        assert_eq!(values.clone().into_robj(), r!([1, 2, 3, 7, 8]));
+        values.clear();
+        assert_eq!(values.pop(), None);
+        assert_eq!(Robj::from(values), Robj::from(Vec::<i32>::new()));
+
+        let ints: RVec<Rint> = [Rint::from(1), Rint::na()].into_iter().collect();
+        assert_eq!(Robj::from(ints), R!("c(1L, NA)")?);
+        let bools = RVec::from(&[Rbool::from(true), Rbool::from(false)][..]);
+        assert_eq!(Robj::from(bools), r!([true, false]));
+        let bytes: RVec<u8> = b"abc".iter().copied().collect();
+        assert_eq!(Robj::from(bytes), R!("charToRaw('abc')")?);
+    }
+}
+
+#[test]
+fn test_rvec_gc() {
+    test! {
+        // The buffer is an R vector, so R reports the memory it uses.
+        let used = || R!("sum(gc(full = TRUE)[, 2])").unwrap().as_real().unwrap();
+        let before = used();
+        let buffer = RVec::<f64>::with_capacity(10_000_000);
+        assert!(used() - before >= 70.0);
+        drop(buffer);
+        assert!(used() - before < 10.0);
+    }
+}
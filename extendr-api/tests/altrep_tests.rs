@@ -280,3 +280,55 @@ fn test_altlist() {
         assert!(li.inherits("VecUsize"));
     })
 }
+
+#[derive(Debug, Clone)]
+struct Evens {
+    len: usize,
+}
+
+impl AltrepImpl for Evens {
+    fn length(&self) -> usize {
+        self.len
+    }
+}
+
+impl AltIntegerImpl for Evens {
+    fn elt(&self, index: usize) -> Rint {
+        Rint::from(2 * index as i32)
+    }
+}
+
+impl AltrepClass for Evens {
+    fn make_class(name: &str, base: &str) -> Robj {
+        Altrep::make_altinteger_class::<Self>(name, base)
+    }
+}
+
+#[extendr]
+fn evens(len: i32) -> Robj {
+    Altrep::from_state(Evens { len: len as usize }).into()
+}
+
+extendr_module! {
+    mod altrep_module;
+    fn evens;
+    altrep Evens;
+}
+
+#[test]
+fn test_altrep_class_registration() {
+    test! {
+        unsafe { register_altrep_module_altrep_classes(std::ptr::null_mut(), "altrep_module") };
+
+        let x = Altrep::from_state(Evens { len: 5 });
+        let y = Altrep::from_state(Evens { len: 3 });
+        assert_eq!(x.class(), y.class());
+        assert!(!x.is_manifest());
+        assert_eq!(x.as_robj().as_integer_slice().unwrap(), [0, 2, 4, 6, 8]);
+        assert!(x.is_manifest());
+
+        let z = unsafe { Robj::from_sexp(wrap__evens(r!(4).get())) };
+        assert!(z.is_altrep());
+        assert_eq!(R!("as.integer(sum({{&z}}))")?, r!(12));
+    }
+}
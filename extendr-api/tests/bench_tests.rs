@@ -0,0 +1,37 @@
+use extendr_api::prelude::*;
+
+extendr_bench_export! {
+    /// The sum of squares of `x`.
+    fn sum_squares(x: &[f64]) -> f64 {
+        x.iter().map(|x| x * x).sum()
+    }
+}
+
+extendr_module! {
+    mod benched;
+    fn sum_squares;
+    fn sum_squares_bench;
+}
+
+#[test]
+fn test_bench_companion() {
+    test! {
+        let x = r!([1.0, 2.0, 3.0]);
+        let sum = unsafe { Robj::from_sexp(wrap__sum_squares(x.get())) };
+        assert_eq!(sum, r!(14.0));
+
+        let times = unsafe { Robj::from_sexp(wrap__sum_squares_bench(x.get(), r!(3).get())) };
+        assert!(times.inherits("data.frame"));
+        assert_eq!(times.dollar("expression")?, r!(["sum_squares"; 3]));
+        assert_eq!(times.dollar("iteration")?, r!([1, 2, 3]));
+        assert_eq!(times.dollar("time")?.len(), 3);
+    }
+}
+
+#[test]
+fn test_bench_wrappers() {
+    test! {
+        let wrappers = get_benched_metadata().make_r_wrappers(true, "benched")?;
+        assert!(wrappers.contains("sum_squares_bench <- function(x, times = 100L)"));
+    }
+}
@@ -0,0 +1,66 @@
+use extendr_api::binning::*;
+use extendr_api::prelude::*;
+
+#[test]
+fn test_cut() {
+    test! {
+        let x = [-1.0, 0.0, 0.25, 1.0, 1e-3, 9.99, 10.0, f64::NAN];
+        let r_x = r!(x);
+        for right in [true, false] {
+            for include_lowest in [true, false] {
+                let cut = Cut::new(&[10.0, 0.0, 1.0, 2.5])?
+                    .with_right(right)
+                    .with_include_lowest(include_lowest);
+                let expected = R!("cut({{&r_x}}, c(0, 1, 2.5, 10), right = {{right}}, include.lowest = {{include_lowest}})")?;
+                assert_eq!(cut.factor(&x)?, expected);
+            }
+        }
+
+        // More digits are used until the labels differ.
+        let cut = Cut::new(&[1.0, 1.0001, 1.0002])?;
+        assert_eq!(cut.labels(), R!("levels(cut(1, c(1, 1.0001, 1.0002)))")?.as_str_vector().unwrap());
+        let cut = Cut::new(&[1e-6, 2e5, f64::INFINITY])?;
+        assert_eq!(cut.labels(), ["(1e-06,2e+05]", "(2e+05,Inf]"]);
+
+        let cut = Cut::new(&[0.0, 1.0, 2.0])?.with_labels(["low", "high"])?.with_ordered(true);
+        assert_eq!(cut.factor(&[0.5, 1.5])?, R!("cut(c(0.5, 1.5), 0:2, labels = c('low', 'high'), ordered_result = TRUE)")?);
+
+        assert!(Cut::new(&[1.0]).is_err());
+        assert!(Cut::new(&[0.0, 1.0, 1.0]).is_err());
+        assert!(Cut::new(&[0.0, f64::NAN]).is_err());
+        assert!(Cut::new(&[0.0, 1.0])?.with_labels(["a", "b"]).is_err());
+    }
+}
+
+#[test]
+fn test_histogram() {
+    test! {
+        let x: Vec<f64> = (0..200).map(|i| ((i * 37) % 101) as f64 / 7.0 - 3.0).collect();
+        let r_x = r!(x.clone());
+        let hist = Histogram::new(&x)?;
+        let expected = R!("hist({{&r_x}}, plot = FALSE)")?;
+        assert_eq!(r!(hist.breaks.clone()), expected.dollar("breaks")?);
+        assert_eq!(r!(hist.counts.clone()), expected.dollar("counts")?);
+        assert_eq!(r!(hist.mids.clone()), expected.dollar("mids")?);
+        assert!(hist.equidist);
+
+        let breaks = [-3.0, 0.0, 0.5, 5.0, 12.0];
+        let hist = Histogram::with_breaks(&x, &breaks)?;
+        let expected = R!("hist({{&r_x}}, c(-3, 0, 0.5, 5, 12), plot = FALSE)")?;
+        assert_eq!(r!(hist.counts.clone()), expected.dollar("counts")?);
+        assert_eq!(r!(hist.density.clone()), expected.dollar("density")?);
+
+        let hist = Cut::new(&breaks)?.with_right(false).with_include_lowest(true).histogram(&x)?;
+        let expected = R!("hist({{&r_x}}, c(-3, 0, 0.5, 5, 12), right = FALSE, plot = FALSE)")?;
+        assert_eq!(r!(hist.counts), expected.dollar("counts")?);
+
+        // Values that are not finite are ignored.
+        let hist = Histogram::new(&[1.0, f64::NAN, f64::INFINITY, 2.0])?;
+        assert_eq!(hist.counts.iter().sum::<i32>(), 2);
+        assert!(Histogram::new(&[f64::NAN]).is_err());
+        assert!(Histogram::with_breaks(&[20.0], &breaks).is_err());
+
+        assert_eq!(r!(pretty(0.0, 1.0, 5)), R!("pretty(c(0, 1))")?);
+        assert_eq!(r!(pretty(-0.37, 1234.5, 8)), R!("pretty(c(-0.37, 1234.5), 8)")?);
+    }
+}
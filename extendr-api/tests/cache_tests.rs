@@ -0,0 +1,95 @@
+use extendr_api::cache::{self, cached, Cached};
+use extendr_api::prelude::*;
+use std::cell::Cell;
+
+thread_local! {
+    static PARSES: Cell<usize> = Cell::new(0);
+}
+
+#[derive(Debug)]
+struct Total(f64);
+
+impl TryFrom<&Robj> for Total {
+    type Error = Error;
+
+    fn try_from(robj: &Robj) -> Result<Self> {
+        PARSES.with(|parses| parses.set(parses.get() + 1));
+        let values: Vec<f64> = robj.try_into()?;
+        Ok(Total(values.iter().sum()))
+    }
+}
+
+#[extendr(use_try_from = true)]
+fn total(x: Cached<Total>) -> f64 {
+    x.0
+}
+
+#[test]
+fn test_cached_argument() {
+    test! {
+        cache::clear();
+        let x = r!([1.0, 2.0]);
+        let first = unsafe { Robj::from_sexp(wrap__total(x.get())) };
+        let second = unsafe { Robj::from_sexp(wrap__total(x.get())) };
+        assert_eq!(first, r!(3.0));
+        assert_eq!(second, r!(3.0));
+        assert_eq!(PARSES.with(|parses| parses.get()), 1);
+        assert_eq!(cache::len(), 1);
+
+        // Errors are not saved.
+        assert!(Cached::<Total>::try_from(&r!("a")).is_err());
+        assert_eq!(cache::len(), 1);
+    }
+}
+
+#[test]
+fn test_changed_objects_are_converted_again() {
+    test! {
+        cache::clear();
+        let sum = |x: &Robj| -> Result<f64> { Ok(x.as_real_slice().unwrap().iter().sum()) };
+
+        let x = R!("x <- c(1, 2); x")?;
+        assert_eq!(*cached(&x, sum)?, 3.0);
+
+        // R copies the cached object before changing it.
+        eval_string("x[1] <- 10")?;
+        let changed = eval_string("x")?;
+        assert_eq!(*cached(&changed, sum)?, 12.0);
+        assert_eq!(*cached(&x, sum)?, 3.0);
+        assert_eq!(cache::len(), 2);
+
+        // The same object can be cached as several types.
+        let len = |x: &Robj| -> Result<usize> { Ok(x.len()) };
+        assert_eq!(*cached(&x, len)?, 2);
+        assert_eq!(cache::len(), 3);
+    }
+}
+
+#[test]
+fn test_cache_capacity() {
+    test! {
+        cache::clear();
+        cache::set_capacity(2);
+        let calls = Cell::new(0);
+        let convert = |x: &Robj| -> Result<i32> {
+            calls.set(calls.get() + 1);
+            Ok(x.as_integer().unwrap())
+        };
+        let (a, b, c) = (r!(1), r!(2), r!(3));
+        cached(&a, convert)?;
+        cached(&b, convert)?;
+        cached(&a, convert)?;
+        cached(&c, convert)?;
+        assert_eq!(calls.get(), 3);
+        assert_eq!(cache::len(), 2);
+
+        // `b` was the least recently used.
+        cached(&a, convert)?;
+        cached(&b, convert)?;
+        assert_eq!(calls.get(), 4);
+
+        cache::set_capacity(0);
+        assert_eq!(cache::len(), 0);
+        cache::set_capacity(cache::DEFAULT_CAPACITY);
+    }
+}
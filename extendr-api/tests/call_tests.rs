@@ -1,4 +1,60 @@
 use extendr_api::prelude::*;
+use libR_sys::{R_MakeExternalPtrFn, R_NilValue, SEXP};
+use std::cell::Cell;
+
+thread_local! {
+    static DROPPED: Cell<usize> = Cell::new(0);
+}
+
+// Counts the rust frames that are dropped.
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+fn dropped() -> usize {
+    DROPPED.with(|dropped| dropped.replace(0))
+}
+
+#[extendr]
+fn call_stop(msg: &str) -> Result<Robj> {
+    let _guard = Guard;
+    call!("stop", msg)
+}
+
+#[extendr]
+fn call_function(f: Function) -> Result<Robj> {
+    let _guard = Guard;
+    f.call(pairlist!())
+}
+
+extendr_module! {
+    mod call_tests;
+    fn call_stop;
+    fn call_function;
+}
+
+// Define the R function `name <- function(x) .Call(<wrapper>, x)`, like the
+// R wrappers that extendr generates.
+fn define_r_function(name: &str, wrapper: extern "C" fn(SEXP) -> SEXP) -> Result<()> {
+    let native = unsafe {
+        Robj::from_sexp(R_MakeExternalPtrFn(
+            Some(std::mem::transmute(wrapper)),
+            Symbol::from_string("native symbol").get(),
+            R_NilValue,
+        ))
+    };
+    let native_name = format!(".{}_native", name);
+    global_env().set_local(Symbol::from_string(&native_name), native);
+    eval_string(&format!(
+        "{} <- function(x) .Call({}, x)",
+        name, native_name
+    ))?;
+    Ok(())
+}
 
 #[test]
 fn formula_test() {
@@ -25,3 +81,63 @@ fn special_test() {
         assert_eq!(add.call(pairlist!(1, 2))?, r!(3));
     }
 }
+
+#[test]
+fn named_args_test() {
+    test! {
+        assert_eq!(call!("paste", "a", "b", sep = "-")?, r!("a-b"));
+        assert_eq!(call!("mean", r!([1.0, f64::NAN, 3.0]), "na.rm" = true)?, r!(2.0));
+        assert_eq!(call!("list", r#if = 1)?, list!(r#if = 1));
+        assert_eq!(pairlist!("na.rm" = true), Pairlist::from_pairs(&[("na.rm", true)]));
+
+        let seq = R!("seq")?.as_function().unwrap();
+        assert_eq!(seq.call_with([1.0, 10.0], [("by", 3.0)])?, r!([1.0, 4.0, 7.0, 10.0]));
+    }
+}
+
+#[test]
+fn r_error_test() {
+    test! {
+        let err = R!("stop('boom')").unwrap_err();
+        assert!(matches!(&err, Error::RError(msg) if msg.contains("boom")));
+        assert!(err.to_string().contains("boom"));
+
+        let err = call!("stop", "bang").unwrap_err();
+        assert!(matches!(&err, Error::RError(msg) if msg.contains("bang")));
+
+        let err = lang!("log", "a").eval().unwrap_err();
+        assert!(matches!(err, Error::RError(_)));
+
+        // R is still usable after the errors.
+        assert_eq!(R!("1 + 1")?, r!(2.0));
+    }
+}
+
+#[test]
+fn r_error_in_extendr_function_test() {
+    test! {
+        define_r_function("call_stop", wrap__call_stop)?;
+        define_r_function("call_function", wrap__call_function)?;
+
+        // The error of `stop()` is returned to the rust frames of `call_stop`,
+        // which are dropped before it is raised again in R.
+        let res = R!("tryCatch(call_stop('boom'), error = function(e) conditionMessage(e))")?;
+        assert!(res.as_str().unwrap().contains("boom"));
+        assert_eq!(dropped(), 1);
+
+        // The same for errors of R code called back from rust.
+        let res = R!("tryCatch(
+            call_function(function() stop('bang')),
+            error = function(e) conditionMessage(e)
+        )")?;
+        assert!(res.as_str().unwrap().contains("bang"));
+        assert_eq!(dropped(), 1);
+
+        assert!(R!("call_stop('boom')").is_err());
+        assert_eq!(dropped(), 1);
+
+        // R is still usable after the errors.
+        assert_eq!(R!("call_function(function() 1 + 1)")?, r!(2.0));
+        assert_eq!(dropped(), 1);
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(feature = "chrono")]
+mod chrono_tests {
+    use ::chrono::{Duration, TimeZone};
+    use extendr_api::prelude::*;
+
+    #[extendr(use_try_from = true)]
+    fn days_between(from: NaiveDate, to: Vec<Option<NaiveDate>>) -> Vec<Option<i32>> {
+        to.iter()
+            .map(|to| to.map(|to| (to - from).num_days() as i32))
+            .collect()
+    }
+
+    #[extendr]
+    fn add_hour(time: DateTime<Utc>) -> DateTime<Utc> {
+        time + Duration::hours(1)
+    }
+
+    extendr_module! {
+        mod chrono_tests;
+        fn days_between;
+        fn add_hour;
+    }
+
+    #[test]
+    fn test_dates() {
+        test! {
+            let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+            assert_eq!(Robj::from(date), R!("as.Date('2024-01-01')")?);
+            assert_eq!(NaiveDate::try_from(R!("as.Date('2024-01-01')")?)?, date);
+
+            // Before 1970, stored as integers, and with a fraction of a day.
+            let early = NaiveDate::from_ymd_opt(1969, 12, 31).unwrap();
+            assert_eq!(NaiveDate::try_from(R!("as.Date('1969-12-31')")?)?, early);
+            assert_eq!(NaiveDate::try_from(R!("structure(-1L, class = 'Date')")?)?, early);
+            assert_eq!(NaiveDate::try_from(R!("structure(-0.5, class = 'Date')")?)?, early);
+
+            // Plain numbers and other classes are not dates.
+            assert!(NaiveDate::try_from(r!(19723.0)).is_err());
+            assert!(NaiveDate::try_from(R!("Sys.time()")?).is_err());
+            assert!(NaiveDate::try_from(R!("as.Date(c('2024-01-01', '2024-01-02'))")?).is_err());
+            assert!(NaiveDate::try_from(R!("structure(1e12, class = 'Date')")?).is_err());
+        }
+    }
+
+    #[test]
+    fn test_date_vectors_and_na() {
+        test! {
+            let robj = R!("as.Date(c('2024-01-01', NA, '2024-03-01'))")?;
+            let dates = <Vec<Option<NaiveDate>>>::try_from(&robj)?;
+            assert_eq!(dates, [
+                NaiveDate::from_ymd_opt(2024, 1, 1),
+                None,
+                NaiveDate::from_ymd_opt(2024, 3, 1),
+            ]);
+            assert_eq!(Robj::from(dates), robj);
+
+            // NA is an error unless Option is used.
+            assert!(<Vec<NaiveDate>>::try_from(&robj).is_err());
+            assert!(NaiveDate::try_from(R!("as.Date(NA)")?).is_err());
+            assert_eq!(<Option<NaiveDate>>::try_from(R!("as.Date(NA)")?)?, None);
+            assert_eq!(Robj::from(None::<NaiveDate>), R!("as.Date(NA)")?);
+
+            let from = r!(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+            let res = unsafe { Robj::from_sexp(wrap__days_between(from.get(), robj.get())) };
+            assert_eq!(res, r!([Some(0), None, Some(60)]));
+        }
+    }
+
+    #[test]
+    fn test_datetimes() {
+        test! {
+            let time = Utc.from_utc_datetime(
+                &NaiveDate::from_ymd_opt(2021, 7, 1)
+                    .unwrap()
+                    .and_hms_micro_opt(12, 0, 0, 250_000)
+                    .unwrap(),
+            );
+            let robj = R!("as.POSIXct('2021-07-01 12:00:00.25', tz = 'UTC')")?;
+            assert_eq!(Robj::from(time), robj);
+            assert_eq!(<DateTime<Utc>>::try_from(&robj)?, time);
+
+            // The time zone only changes how R shows the time.
+            let tokyo = R!("as.POSIXct('2021-07-01 21:00:00.25', tz = 'Asia/Tokyo')")?;
+            assert_eq!(<DateTime<Utc>>::try_from(&tokyo)?, time);
+
+            let times = R!("as.POSIXct(c('2021-07-01 12:00:00', NA), tz = 'UTC')")?;
+            let res = <Vec<Option<DateTime<Utc>>>>::try_from(&times)?;
+            assert_eq!(res[1], None);
+            assert_eq!(Robj::from(res), times);
+            assert!(<DateTime<Utc>>::try_from(R!("as.Date('2021-07-01')")?).is_err());
+
+            let res = unsafe { Robj::from_sexp(wrap__add_hour(robj.get())) };
+            assert_eq!(res, R!("{{robj}} + 3600")?);
+        }
+    }
+
+    #[test]
+    fn test_durations() {
+        test! {
+            let robj = R!("as.difftime(1.5, units = 'mins')")?;
+            assert_eq!(Duration::try_from(&robj)?, Duration::seconds(90));
+            assert_eq!(Robj::from(Duration::seconds(90)), R!("as.difftime(90, units = 'secs')")?);
+            assert_eq!(Duration::try_from(R!("as.difftime(-2, units = 'weeks')")?)?, Duration::weeks(-2));
+            assert_eq!(
+                Duration::try_from(R!("as.difftime(0.001, units = 'secs')")?)?,
+                Duration::milliseconds(1)
+            );
+
+            let robj = R!("as.difftime(c(1, NA), units = 'hours')")?;
+            assert_eq!(<Vec<Option<Duration>>>::try_from(&robj)?, [Some(Duration::hours(1)), None]);
+            assert!(<Vec<Duration>>::try_from(&robj).is_err());
+            assert!(Duration::try_from(r!(1.0)).is_err());
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use extendr_api::coerce::{coerce_arg, coerced_eq};
+use extendr_api::prelude::*;
+
+#[derive(Debug, Default)]
+struct Counter {
+    total: i32,
+}
+
+#[extendr(coerce = "lenient")]
+impl Counter {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, by: i32) {
+        self.total += by;
+    }
+
+    fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+#[extendr(coerce = "lenient", use_try_from = true)]
+fn scale(x: Vec<f64>, k: f64) -> Vec<f64> {
+    x.iter().map(|x| x * k).collect()
+}
+
+extendr_module! {
+    mod coerce_tests;
+    impl Counter;
+    fn scale;
+}
+
+#[test]
+fn test_lenient_methods() {
+    test! {
+        unsafe {
+            let counter = Robj::from_sexp(wrap__Counter__new());
+            wrap__Counter__add(counter.get(), r!(2.0).get());
+            wrap__Counter__add(counter.get(), r!(TRUE).get());
+            wrap__Counter__add(counter.get(), r!(3).get());
+            assert_eq!(Robj::from_sexp(wrap__Counter__total(counter.get())), r!(6));
+
+            let res = Robj::from_sexp(wrap__scale(R!("1:3")?.get(), r!(2).get()));
+            assert_eq!(res, r!([2.0, 4.0, 6.0]));
+        }
+    }
+}
+
+#[test]
+fn test_coerce_arg() {
+    test! {
+        let is_integer = |robj: &Robj| robj.as_integer_slice().is_some();
+        assert_eq!(coerce_arg(&r!(2.0), is_integer), Some(r!(2)));
+        // Fractions, factors and other types are not coerced, so the conversion fails as before.
+        assert_eq!(coerce_arg(&r!(2.5), is_integer), None);
+        let f = R!("factor('a')")?;
+        assert_eq!(coerce_arg(&f, |robj: &Robj| robj.is_real()), None);
+        assert_eq!(coerce_arg(&r!("2"), is_integer), None);
+
+        // Logicals are tried as integers first.
+        assert_eq!(coerce_arg(&r!(TRUE), |robj: &Robj| robj.is_integer() || robj.is_real()), Some(r!(1)));
+
+        // Attributes are kept.
+        let m = coerce_arg(&R!("matrix(1:4, 2)")?, |robj: &Robj| robj.is_real());
+        assert_eq!(m, Some(R!("matrix(c(1, 2, 3, 4), 2)")?));
+
+        assert!(coerced_eq(&R!("c(a = TRUE)")?, &R!("c(a = 1)")?));
+        assert!(!coerced_eq(&R!("c(a = 1L)")?, &R!("c(b = 1)")?));
+    }
+}
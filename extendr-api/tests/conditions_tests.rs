@@ -0,0 +1,122 @@
+use extendr_api::conditions::{catch_condition, signalling_eval, Condition, ConditionKind};
+use extendr_api::prelude::*;
+use libR_sys::{R_MakeExternalPtrFn, R_NilValue, SEXP};
+use std::cell::Cell;
+
+thread_local! {
+    static DROPPED: Cell<usize> = Cell::new(0);
+}
+
+// Counts the frames that are unwound.
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+fn dropped() -> usize {
+    DROPPED.with(|dropped| dropped.replace(0))
+}
+
+#[extendr]
+fn signal(kind: &str) -> i32 {
+    let _guard = Guard;
+    match kind {
+        "error" => Condition::error("failed")
+            .class("my_error")
+            .data("code", 3)
+            .throw(),
+        "warning" => warning!("careful with {}", 1),
+        "message" => message!("hello"),
+        _ => (),
+    }
+    1
+}
+
+extendr_module! {
+    mod conditions_tests;
+    fn signal;
+}
+
+// Define the R function `name <- function(x) .Call(<wrapper>, x)`, like the
+// R wrappers that extendr generates.
+fn define_r_function(name: &str, wrapper: extern "C" fn(SEXP) -> SEXP) -> Result<()> {
+    let native = unsafe {
+        Robj::from_sexp(R_MakeExternalPtrFn(
+            Some(std::mem::transmute(wrapper)),
+            Symbol::from_string("native symbol").get(),
+            R_NilValue,
+        ))
+    };
+    let native_name = format!(".{}_native", name);
+    global_env().set_local(Symbol::from_string(&native_name), native);
+    eval_string(&format!(
+        "{} <- function(x) .Call({}, x)",
+        name, native_name
+    ))?;
+    Ok(())
+}
+
+#[test]
+fn test_try_catch_error() {
+    test! {
+        define_r_function("signal", wrap__signal)?;
+        let res = R!("tryCatch(signal('error'), my_error = function(e) e$code)")?;
+        assert_eq!(res, r!(3));
+        assert_eq!(dropped(), 1);
+
+        let res = R!("tryCatch(signal('error'), error = function(e) conditionMessage(e))")?;
+        assert_eq!(res, r!("failed"));
+        assert_eq!(dropped(), 1);
+
+        // Without a handler stop() ends the evaluation.
+        let err = R!("signal('error')").unwrap_err();
+        assert!(matches!(&err, Error::RError(msg) if msg.contains("failed")));
+        assert_eq!(dropped(), 1);
+    }
+}
+
+#[test]
+fn test_try_catch_warning() {
+    test! {
+        define_r_function("signal", wrap__signal)?;
+        let res = R!("tryCatch(signal('warning'), warning = function(w) conditionMessage(w))")?;
+        assert_eq!(res, r!("careful with 1"));
+        assert_eq!(dropped(), 1);
+
+        // A muffled warning lets the function return.
+        let res = R!("withCallingHandlers(signal('warning'), warning = function(w) invokeRestart('muffleWarning'))")?;
+        assert_eq!(res, r!(1));
+        assert_eq!(dropped(), 1);
+
+        let res = R!("tryCatch(signal('message'), message = function(m) conditionMessage(m))")?;
+        assert_eq!(res, r!("hello\n"));
+        assert_eq!(dropped(), 1);
+    }
+}
+
+#[test]
+fn test_catch_condition() {
+    test! {
+        let caught = catch_condition(|| signalling_eval(&lang!("stop", "boom"), &global_env()));
+        let cond = caught.unwrap_err();
+        assert_eq!(cond.kind(), ConditionKind::Error);
+        assert_eq!(cond.message_text(), "boom");
+
+        let caught = catch_condition(|| signalling_eval(&lang!("warning", "careful"), &global_env()));
+        assert_eq!(caught.unwrap_err().kind(), ConditionKind::Warning);
+
+        // A condition thrown by an `#[extendr]` function called from R.
+        define_r_function("signal", wrap__signal)?;
+        let call = lang!("signal", "error");
+        let cond = catch_condition(|| signalling_eval(&call, &global_env())).unwrap_err();
+        assert!(cond.inherits("my_error"));
+        assert_eq!(cond.get("code"), Some(&r!(3)));
+        assert_eq!(dropped(), 1);
+
+        // Nothing is caught from code that returns.
+        assert_eq!(catch_condition(|| signalling_eval(&lang!("+", 1, 2), &global_env())), Ok(r!(3)));
+    }
+}
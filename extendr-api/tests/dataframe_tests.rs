@@ -29,3 +29,102 @@ fn test_derive_into_dataframe() {
         assert_eq!(list[1], r!(["0", "1"]));
     }
 }
+
+#[test]
+fn test_derive_from_dataframe_row() {
+    test! {
+        use extendr_api::prelude::*;
+
+        #[derive(Debug, PartialEq, FromDataframeRow, IntoDataFrameRow)]
+        struct Row {
+            id: i32,
+            #[dataframe(rename = "value.x", na = "default")]
+            value: f64,
+            label: Option<String>,
+        }
+
+        let df: Dataframe<Row> = R!("data.frame(label = c('a', NA), id = 1:2, value.x = c(0.5, NA))")?.try_into()?;
+        let rows = df.rows::<Row>()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(rows, vec![
+            Row { id: 1, value: 0.5, label: Some("a".into()) },
+            Row { id: 2, value: 0.0, label: None },
+        ]);
+
+        // The same columns are written back.
+        let df2 = rows.into_dataframe()?;
+        assert_eq!(df2.column_names(), ["id", "value.x", "label"]);
+        let rows2 = df2.rows::<Row>()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(rows2[0], Row { id: 1, value: 0.5, label: Some("a".into()) });
+
+        let missing: Dataframe<()> = R!("data.frame(id = 1L)")?.try_into()?;
+        assert!(missing.rows::<Row>().is_err());
+    }
+}
+
+#[test]
+fn test_list_columns() {
+    test! {
+        #[derive(Debug, PartialEq, FromDataframeRow, IntoDataFrameRow)]
+        struct Order {
+            id: i32,
+            items: Option<Vec<String>>,
+            prices: Vec<f64>,
+        }
+
+        let orders = vec![
+            Order { id: 1, items: Some(vec!["tea".into(), "cake".into()]), prices: vec![2.5, 3.0] },
+            Order { id: 2, items: None, prices: vec![] },
+        ];
+        let df = orders.into_dataframe()?;
+        assert_eq!(df.column_names(), ["id", "items", "prices"]);
+        assert_eq!(df.nrow(), 2);
+
+        let items: List = df.column("items")?;
+        assert_eq!(items.elt(0)?, r!(["tea", "cake"]));
+        assert!(items.elt(1)?.is_null());
+        let prices: List = df.column("prices")?;
+        assert_eq!(prices.elt(0)?, r!([2.5, 3.0]));
+        assert_eq!(prices.elt(1)?.len(), 0);
+
+        let rows = df.rows::<Order>()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(rows[0].items.as_deref(), Some(&["tea".to_string(), "cake".to_string()][..]));
+        assert_eq!(rows[1], Order { id: 2, items: None, prices: vec![] });
+
+        // NULL is missing, so it can't be read as a Vec.
+        assert!(df.rows::<(i32, Vec<String>)>()?.nth(1).unwrap().is_err());
+
+        let column: Robj = vec![Some(vec![1, 2]), None].into();
+        assert_eq!(column, R!("list(1:2, NULL)")?);
+
+        let ragged = DataframeBuilder::new()
+            .column("id", [1, 2, 3])
+            .list_column("x", [Some(r!(1)), None]);
+        let message = ragged.build().unwrap_err().to_string();
+        assert!(message.contains("'x' has length 2 not 3"));
+        assert!(message.contains("use None"));
+    }
+}
+
+#[test]
+fn test_rows_and_factors() {
+    test! {
+        let no_columns: Dataframe<()> = R!("data.frame(row.names = c('a', 'b', 'c'))")?.try_into()?;
+        assert_eq!(no_columns.nrow(), 3);
+
+        // Compact row names c(NA, -nrow) are read without the columns.
+        let compact: Dataframe<()> = R!("structure(list(x = 1:5), row.names = c(NA, -5L), class = 'data.frame')")?.try_into()?;
+        assert_eq!(compact.nrow(), 5);
+
+        let df: Dataframe<()> = R!("structure(
+            list(f = structure(c(1L, NA, 0L, 3L), levels = c('a', 'b'), class = 'factor')),
+            row.names = c(NA, -4L),
+            class = 'data.frame'
+        )")?.try_into()?;
+        let rows = df.rows::<(Rstr,)>()?.collect::<Vec<_>>();
+        assert_eq!(rows[0].as_ref().unwrap().0.as_str(), "a");
+        assert!(rows[1].as_ref().unwrap().0.is_na());
+        // Codes outside the levels are errors.
+        assert!(rows[2].is_err());
+        assert!(rows[3].is_err());
+    }
+}
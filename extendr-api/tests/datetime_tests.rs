@@ -0,0 +1,81 @@
+use extendr_api::datetime::{DurationUnits, RDate, RDuration, RTimestamp};
+use extendr_api::prelude::*;
+
+#[extendr]
+fn next_day(date: RDate) -> RDate {
+    RDate::from_days(date.days() + 1.0)
+}
+
+#[extendr(use_try_from = true)]
+fn later(time: RTimestamp, by: RDuration) -> RTimestamp {
+    let res = RTimestamp::from_secs(time.secs() + by.as_secs());
+    match time.tz() {
+        Some(tz) => res.with_tz(tz),
+        None => res,
+    }
+}
+
+extendr_module! {
+    mod datetime_tests;
+    fn next_day;
+    fn later;
+}
+
+#[test]
+fn test_dates() {
+    test! {
+        for (y, m, d) in [(1970, 1, 1), (2000, 2, 29), (1900, 3, 1), (1, 1, 1), (2024, 12, 31)] {
+            let date = RDate::from_ymd(y, m, d).unwrap();
+            assert_eq!(date.to_ymd(), Some((y, m, d)));
+            let text = format!("{:04}-{:02}-{:02}", y, m, d);
+            assert_eq!(date.to_string(), text);
+            assert_eq!(Robj::from(date), call!("as.Date", text)?);
+        }
+        assert_eq!(RDate::from_ymd(1900, 2, 29), None);
+        assert_eq!(RDate::from_ymd(2023, 4, 31), None);
+
+        // Dates stored as integers, and NA.
+        let date: RDate = R!("structure(19723L, class = 'Date')")?.try_into()?;
+        assert_eq!(date.days(), 19723.0);
+        let na: RDate = R!("as.Date(NA)")?.try_into()?;
+        assert!(na.is_na());
+        assert_eq!(na.to_string(), "NA");
+
+        assert!(RDate::try_from(R!("as.Date(c('2024-01-01', '2024-01-02'))")?).is_err());
+        assert!(RDate::try_from(R!("Sys.time()")?).is_err());
+    }
+}
+
+#[test]
+fn test_timestamps_and_durations() {
+    test! {
+        let time: RTimestamp = R!("as.POSIXct('2021-07-01 12:00:00', tz = 'Asia/Tokyo')")?.try_into()?;
+        assert_eq!(time.secs(), 1625108400.0);
+        assert_eq!(time.tz(), Some("Asia/Tokyo"));
+        assert_eq!(time.to_date()?, RDate::from_ymd(2021, 7, 1).unwrap());
+        assert_eq!(R!("format({{time}})")?, r!("2021-07-01 12:00:00"));
+
+        let minutes: RDuration = R!("as.difftime(90, units = 'mins')")?.try_into()?;
+        assert_eq!(minutes.units(), DurationUnits::Mins);
+        assert_eq!(minutes.to_units(DurationUnits::Hours).value(), 1.5);
+        assert_eq!(minutes.to_std(), Some(std::time::Duration::from_secs(5400)));
+        assert_eq!(RDuration::from_secs(-1.0).to_std(), None);
+        assert_eq!(Robj::from(minutes), R!("as.difftime(90, units = 'mins')")?);
+    }
+}
+
+#[test]
+fn test_classed_arguments() {
+    test! {
+        unsafe {
+            let date = R!("as.Date('2024-02-28')")?;
+            let res = Robj::from_sexp(wrap__next_day(date.get()));
+            assert_eq!(res, R!("as.Date('2024-02-29')")?);
+
+            let time = R!("as.POSIXct('2021-07-01 12:00:00', tz = 'UTC')")?;
+            let by = R!("as.difftime(2, units = 'hours')")?;
+            let res = Robj::from_sexp(wrap__later(time.get(), by.get()));
+            assert_eq!(res, R!("as.POSIXct('2021-07-01 14:00:00', tz = 'UTC')")?);
+        }
+    }
+}
@@ -52,3 +52,65 @@ fn test_derive_list() {
         assert_eq!(&converted_r_borrow, &converted_r_owned);
     }
 }
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn test_derive_enum() {
+    use extendr_api::prelude::*;
+    use extendr_macros::{IntoRobj, TryFromRobj};
+
+    test! {
+        #[derive(TryFromRobj, IntoRobj, PartialEq, Debug)]
+        enum Expr {
+            Literal(f64),
+            Add { lhs: i32, rhs: i32 },
+            Empty,
+        }
+
+        let cases = [
+            (Expr::Literal(1.5), R!("list(variant = 'Literal', 1.5)").unwrap()),
+            (Expr::Add { lhs: 1, rhs: 2 }, R!("list(variant = 'Add', lhs = 1L, rhs = 2L)").unwrap()),
+            (Expr::Empty, R!("list(variant = 'Empty')").unwrap()),
+        ];
+        for (native_rust, native_r) in cases {
+            // Rust → R
+            assert_eq!(Robj::from(&native_rust), native_r);
+            // R → Rust
+            assert_eq!(Expr::try_from(&native_r).unwrap(), native_rust);
+            assert_eq!(Expr::try_from(Robj::from(native_rust)).unwrap(), Expr::try_from(native_r).unwrap());
+        }
+
+        // An unknown variant is an error
+        assert!(Expr::try_from(R!("list(variant = 'Mul')").unwrap()).is_err());
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn test_derive_list_field_attributes() {
+    use extendr_api::prelude::*;
+    use extendr_macros::{IntoRobj, TryFromRobj};
+
+    test! {
+        #[derive(TryFromRobj, IntoRobj, PartialEq, Debug)]
+        struct Point {
+            #[robj(rename = "x.coord")]
+            x: f64,
+            #[robj(default)]
+            label: String,
+            #[robj(skip)]
+            cache: Vec<f64>,
+        }
+
+        let point: Point = R!("list(x.coord = 1.5)").unwrap().try_into().unwrap();
+        assert_eq!(point, Point { x: 1.5, label: String::new(), cache: vec![] });
+
+        let point = Point { x: 2.0, label: "a".into(), cache: vec![1.0] };
+        let native_r = R!("list(x.coord = 2, label = 'a')").unwrap();
+        assert_eq!(Robj::from(&point), native_r);
+        assert_eq!(Point::try_from(&native_r).unwrap(), Point { cache: vec![], ..point });
+
+        // A missing field without `default` is an error.
+        assert!(Point::try_from(R!("list(label = 'a')").unwrap()).is_err());
+    }
+}
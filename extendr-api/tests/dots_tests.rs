@@ -0,0 +1,46 @@
+use extendr_api::prelude::*;
+
+#[extendr]
+fn my_sum(values: Dots, na_rm: bool) -> f64 {
+    values
+        .values()
+        .filter_map(|value| value.as_real())
+        .filter(|x| !(na_rm && x.is_nan()))
+        .sum()
+}
+
+#[extendr]
+fn arg_names(prefix: &str, args: Dots) -> Vec<String> {
+    args.iter()
+        .map(|(name, _)| format!("{}{}", prefix, name))
+        .collect()
+}
+
+extendr_module! {
+    mod dots;
+    fn my_sum;
+    fn arg_names;
+}
+
+#[test]
+fn test_dots_wrappers() {
+    test! {
+        let wrappers = get_dots_metadata().make_r_wrappers(true, "dots").unwrap();
+        assert!(wrappers.contains("my_sum <- function(..., na_rm) .Call(wrap__my_sum, list(...), na_rm)"));
+        assert!(wrappers.contains("arg_names <- function(prefix, ...) .Call(wrap__arg_names, prefix, list(...))"));
+    }
+}
+
+#[test]
+fn test_dots_arguments() {
+    test! {
+        let values = Dots::try_from(list!(1.0, b = 2.0, f64::NAN))?;
+        assert!(my_sum(values.clone(), false).is_nan());
+        assert_eq!(my_sum(values, true), 3.0);
+
+        let args = Dots::try_from(list!(a = 1, 2, c = 3))?;
+        assert_eq!(arg_names("x", args), vec!["xa", "x", "xc"]);
+        assert_eq!(my_sum(Dots::default(), false), 0.0);
+        assert!(Dots::try_from(r!(1)).is_err());
+    }
+}
@@ -209,3 +209,36 @@ fn test_metadata() {
         }
     );
 }
+
+#[extendr]
+fn test_impl_into(val: impl Into<Doubles>) -> f64 {
+    val.into().iter().map(|x| x.inner()).sum()
+}
+
+#[extendr(use_try_from = true)]
+fn test_impl_as_ref(val: impl AsRef<str>) -> String {
+    val.as_ref().to_uppercase()
+}
+
+#[extendr]
+fn test_generic_where<T>(val: T) -> i32
+where
+    T: AsRef<[i32]>,
+{
+    val.as_ref().len() as i32
+}
+
+#[test]
+fn test_generic_arguments() {
+    unsafe {
+        test! {
+            assert_eq!(Robj::from_sexp(wrap__test_impl_into(r!([1.0, 2.5]).get())), r!(3.5));
+            assert_eq!(Robj::from_sexp(wrap__test_impl_as_ref(r!("abc").get())), r!("ABC"));
+            assert_eq!(Robj::from_sexp(wrap__test_generic_where(r!([1, 2, 3]).get())), r!(3));
+
+            let mut funcs = Vec::new();
+            meta__test_impl_as_ref(&mut funcs);
+            assert_eq!(funcs[0].args[0].arg_type, "str");
+        }
+    }
+}
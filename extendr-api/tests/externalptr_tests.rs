@@ -60,3 +60,63 @@ fn test_externalptr_deref() {
         assert_eq!(extptr.y, 2);
     }
 }
+
+#[derive(Debug, Default)]
+struct Builder {
+    values: Vec<i32>,
+}
+
+#[extendr]
+impl Builder {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, value: i32) {
+        self.values.push(value);
+    }
+
+    fn build(self, scale: i32) -> Vec<i32> {
+        self.values.iter().map(|v| v * scale).collect()
+    }
+}
+
+extendr_module! {
+    mod externalptr_tests;
+    impl Builder;
+}
+
+#[test]
+fn test_consuming_method() {
+    test! {
+        unsafe {
+            let builder = Robj::from_sexp(wrap__Builder__new());
+            wrap__Builder__push(builder.get(), r!(1).get());
+            wrap__Builder__push(builder.get(), r!(2).get());
+            let built = Robj::from_sexp(wrap__Builder__build(builder.get(), r!(10).get()));
+            assert_eq!(built, r!([10, 20]));
+
+            // The R object survives, but the value has gone.
+            assert_eq!(<&Builder>::from_robj(&builder).err(), Some("Builder object already consumed"));
+            assert_eq!(builder.take_external_ptr::<Builder>().map(|_| ()), Err(Error::Consumed(builder.clone())));
+            R!("gc()")?;
+        }
+    }
+}
+
+#[test]
+fn test_externalptr_take() {
+    test! {
+        let extptr = ExternalPtr::new(vec![1, 2, 3]);
+        let robj: Robj = extptr.clone().into();
+        assert!(!extptr.is_consumed());
+        assert_eq!(extptr.take()?, [1, 2, 3]);
+
+        let extptr: ExternalPtr<Vec<i32>> = robj.try_into()?;
+        assert!(extptr.is_consumed());
+        assert!(extptr.take().is_err());
+
+        // The finalizer must not free the value again.
+        R!("gc()")?;
+    }
+}
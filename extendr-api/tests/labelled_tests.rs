@@ -0,0 +1,70 @@
+use extendr_api::labelled::*;
+use extendr_api::prelude::*;
+
+#[test]
+fn test_variable_labels() {
+    test! {
+        let mut x = r!([1, 2, 3]);
+        assert_eq!(var_label(&x), None);
+        set_var_label(&mut x, Some("Age group"))?;
+        assert_eq!(var_label(&x).as_deref(), Some("Age group"));
+        assert_eq!(R!(r#"attr({{&x}}, "label")"#)?, r!("Age group"));
+        set_var_label(&mut x, None)?;
+        assert_eq!(var_label(&x), None);
+    }
+}
+
+#[test]
+fn test_labelled_like_haven() {
+    test! {
+        let labels = r!(["a", "b"]).set_names(["Apple", "Banana"])?;
+        let x = new_labelled(r!(["a", "b", "c"]), labels, Some("Fruit"))?;
+        assert_eq!(
+            x.class().unwrap().collect::<Vec<_>>(),
+            ["haven_labelled", "vctrs_vctr", "character"]
+        );
+        assert_eq!(value_label(&x, &r!("b")).as_deref(), Some("Banana"));
+
+        let zapped = zap_labels(&x)?;
+        assert!(zapped.class().is_none());
+        assert_eq!(value_labels(&zapped), None);
+        assert_eq!(var_label(&zapped).as_deref(), Some("Fruit"));
+
+        let factor = R!("factor(c('x', 'y'))")?;
+        assert_eq!(zap_labels(&factor)?, factor);
+    }
+}
+
+#[test]
+fn test_invalid_labels() {
+    test! {
+        let unnamed = r!([1.0, 2.0]);
+        assert!(new_labelled(r!([1.0]), unnamed, None).is_err());
+
+        let wrong_type = r!([1, 2]).set_names(["a", "b"])?;
+        assert!(new_labelled(r!([1.0]), wrong_type, None).is_err());
+
+        let repeated = r!([1.0, 1.0]).set_names(["a", "b"])?;
+        assert!(new_labelled(r!([1.0]), repeated, None).is_err());
+
+        let labels = r!(["a"]).set_names(["A"])?;
+        assert!(new_labelled_spss(r!(["a"]), labels, None, Some((1.0, 2.0)), None).is_err());
+        assert!(new_labelled(r!([true]), r!([true]), None).is_err());
+    }
+}
+
+#[test]
+fn test_spss_missing_values() {
+    test! {
+        let labels = r!([1, 9]).set_names(["Yes", "Unknown"])?;
+        let x = r!([Some(1), Some(9), None, Some(-2), Some(3)]);
+        let x = new_labelled_spss(x, labels, Some(r!([9])), Some((-5.0, -1.0)), None)?;
+        assert_eq!(na_values(&x), Some(r!([9])));
+        assert_eq!(na_range(&x), Some((-5.0, -1.0)));
+        assert_eq!(is_missing(&x)?, vec![false, true, true, true, false]);
+        assert_eq!(
+            zap_labels(&x)?,
+            r!([Some(1), None, None, None, Some(3)])
+        );
+    }
+}
@@ -15,3 +15,16 @@ fn test_list() {
         assert!(List::from_names_and_values(["a", "b"], [1, 2, 3]).is_err());
     }
 }
+
+#[test]
+fn test_macros_without_prelude() {
+    use extendr_api::macros::{data_frame, r, sym};
+    use extendr_api::robj::Attributes;
+    use extendr_api::wrapper::Symbol;
+
+    test! {
+        assert_eq!(sym!(x), r!(Symbol::from_string("x")));
+        let df = data_frame!(a = r!([1, 2]));
+        assert!(df.inherits("data.frame"));
+    }
+}
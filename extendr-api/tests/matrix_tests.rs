@@ -0,0 +1,46 @@
+use extendr_api::prelude::*;
+
+#[extendr]
+fn matrix_transpose(x: RMatrix<f64>) -> RMatrix<f64> {
+    let rows = x.to_columns();
+    RMatrix::from_rows(&rows).unwrap()
+}
+
+#[extendr(use_try_from = true)]
+fn matrix_row_sums(x: RMatrix<i32>) -> Vec<i32> {
+    x.row_iter().map(|row| row.sum()).collect()
+}
+
+extendr_module! {
+    mod matrix_tests;
+    fn matrix_transpose;
+    fn matrix_row_sums;
+}
+
+#[test]
+fn test_matrix_arguments() {
+    test! {
+        unsafe {
+            let x = R!("matrix(c(1, 2, 3, 4, 5, 6), nrow = 2)")?;
+            let res = Robj::from_sexp(wrap__matrix_transpose(x.get()));
+            assert_eq!(res, R!("t(matrix(c(1, 2, 3, 4, 5, 6), nrow = 2))")?);
+
+            let x = R!("matrix(1:6, nrow = 3)")?;
+            let res = Robj::from_sexp(wrap__matrix_row_sums(x.get()));
+            assert_eq!(res, r!([5, 7, 9]));
+        }
+    }
+}
+
+#[test]
+fn test_matrix_from_rows() {
+    test! {
+        let matrix: RMatrix<i32> = vec![vec![1, 2, 3], vec![4, 5, 6]].try_into()?;
+        assert_eq!(matrix[(1, 0)], 4);
+        assert_eq!(matrix.row_view(1).copied().collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(Vec::<Vec<i32>>::from(matrix), [[1, 2, 3], [4, 5, 6]]);
+
+        let ragged: Result<RMatrix<i32>> = vec![vec![1], vec![2, 3]].try_into();
+        assert!(ragged.is_err());
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(feature = "nalgebra")]
+mod nalgebra_tests {
+    use extendr_api::optional::nalgebra::DimnamedMatrix;
+    use extendr_api::prelude::*;
+
+    #[extendr]
+    fn crossprod(x: DMatrix<f64>) -> DMatrix<f64> {
+        x.transpose() * x
+    }
+
+    extendr_module! {
+        mod nalgebra_tests;
+        fn crossprod;
+    }
+
+    #[test]
+    fn test_matrix_column_major() {
+        test! {
+            let robj = R!("matrix(c(1, 2, 3, 4, 5, 6), nrow = 2)")?;
+            let matrix = DMatrix::<f64>::try_from(&robj)?;
+            assert_eq!(matrix.shape(), (2, 3));
+            assert_eq!(matrix[(0, 1)], 3.0);
+            assert_eq!(matrix[(1, 2)], 6.0);
+            assert_eq!(Robj::from(matrix), robj);
+
+            assert!(DMatrix::<f64>::try_from(R!("c(1, 2)")?).is_err());
+            assert!(DMatrix::<f64>::try_from(R!("matrix(1:4, 2)")?).is_err());
+
+            let res = unsafe { Robj::from_sexp(wrap__crossprod(robj.get())) };
+            assert_eq!(res, R!("crossprod({{robj}})")?);
+        }
+    }
+
+    #[test]
+    fn test_vector() {
+        test! {
+            let vector = DVector::<f64>::try_from(R!("c(1, 2, 3)")?)?;
+            assert_eq!(vector.as_slice(), [1.0, 2.0, 3.0]);
+            assert_eq!(Robj::from(&vector), r!([1.0, 2.0, 3.0]));
+
+            let column = DVector::<f64>::try_from(R!("matrix(c(1, 2), ncol = 1)")?)?;
+            assert_eq!(column.len(), 2);
+            assert!(DVector::<f64>::try_from(R!("matrix(c(1, 2), nrow = 1)")?).is_err());
+        }
+    }
+
+    #[test]
+    fn test_dimnames() {
+        test! {
+            let robj = R!("matrix(c(1, 2, 3, 4), 2, dimnames = list(c('a', 'b'), NULL))")?;
+            let named = DimnamedMatrix::try_from(&robj)?;
+            assert_eq!(Robj::from(named.clone()), robj);
+
+            // Names that no longer fit are dropped.
+            let resized = DimnamedMatrix { matrix: named.matrix.resize(3, 2, 0.0), ..named };
+            let res = Robj::from(resized);
+            assert_eq!(res.nrows(), 3);
+            assert!(res.get_attrib(dimnames_symbol()).is_none());
+        }
+    }
+}
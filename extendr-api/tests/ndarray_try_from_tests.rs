@@ -360,4 +360,65 @@ mod ndarray_try_from_tests {
              assert!(<ArrayView1<Rbool>>::try_from(&robj).is_err());
         }
     }
+    #[test]
+    fn array_3d_column_major() {
+        test! {
+            let robj = R!("array(1:24, c(2, 3, 4))")?;
+            let view = <ArrayView3<i32>>::try_from(&robj)?;
+            assert_eq!(view.dim(), (2, 3, 4));
+            for ((i, j, k), &value) in view.indexed_iter() {
+                // x[i, j, k] is element i + 2 (j - 1) + 6 (k - 1) in R.
+                assert_eq!(value as usize, 1 + i + 2 * j + 6 * k);
+            }
+            assert_eq!(view[[1, 2, 3]], R!("{{robj.clone()}}[2, 3, 4]")?.as_integer().unwrap());
+
+            let dyn_view = <ArrayViewD<i32>>::try_from(&robj)?;
+            assert_eq!(dyn_view.shape(), [2, 3, 4]);
+            assert_eq!(dyn_view.index_axis(Axis(2), 1), view.index_axis(Axis(2), 1));
+
+            // A vector without dim is one-dimensional.
+            let vector = R!("c(1, 2, 3)")?;
+            assert_eq!(<ArrayViewD<f64>>::try_from(&vector)?.shape(), [3]);
+            assert!(<ArrayView3<f64>>::try_from(&vector).is_err());
+        }
+    }
+
+    #[test]
+    fn mutable_views_change_in_place() {
+        test! {
+            let mut robj = R!("matrix(c(1, 2, 3, 4, 5, 6), nrow = 2)")?;
+            {
+                let mut view = <ArrayViewMut2<f64>>::try_from(&mut robj)?;
+                assert_eq!(view.dim(), (2, 3));
+                view[[1, 0]] = 20.0;
+                view.column_mut(2).fill(0.0);
+            }
+            assert_eq!(robj, R!("matrix(c(1, 20, 3, 4, 0, 0), nrow = 2)")?);
+
+            let mut array = R!("array(as.double(1:8), c(2, 2, 2))")?;
+            let expected = R!("array(as.double(1:8), c(2, 2, 2)) * 10")?;
+            <ArrayViewMutD<f64>>::try_from(&mut array)?.map_inplace(|x| *x *= 10.0);
+            assert_eq!(array, expected);
+
+            let mut ints = R!("c(1L, 2L, 3L)")?;
+            <ArrayViewMut1<i32>>::try_from(&mut ints)?[0] = 7;
+            assert_eq!(ints, r!([7, 2, 3]));
+
+            assert!(<ArrayViewMut1<f64>>::try_from(&mut ints).is_err());
+            assert!(<ArrayViewMut2<i32>>::try_from(&mut ints).is_err());
+        }
+    }
+
+    #[test]
+    fn mutable_view_of_a_shared_object() {
+        test! {
+            eval_string("x <- c(1, 2, 3)")?;
+            let mut x = eval_string("x")?;
+            // make_unique() copies the object bound to `x` before it is changed.
+            assert!(x.make_unique());
+            <ArrayViewMut1<f64>>::try_from(&mut x)?.fill(0.0);
+            assert_eq!(x, r!([0.0, 0.0, 0.0]));
+            assert_eq!(eval_string("x")?, r!([1.0, 2.0, 3.0]));
+        }
+    }
 }
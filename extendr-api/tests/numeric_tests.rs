@@ -0,0 +1,83 @@
+use extendr_api::numeric::{format_real, parse_integer, parse_real, NumberFormat};
+use extendr_api::prelude::*;
+
+const STRINGS: &[&str] = &[
+    "1",
+    " 2.5 ",
+    "-3",
+    "+4",
+    ".5",
+    "5.",
+    "1e3",
+    "1E-2",
+    "1e",
+    "1e+",
+    "0x1A",
+    "0X1p4",
+    "-0x.8",
+    "0x",
+    "Inf",
+    "-inf",
+    "infinity",
+    "NaN",
+    "nan",
+    "NA",
+    "NAN",
+    "",
+    "  ",
+    "abc",
+    "1,5",
+    "1.5.2",
+    "1e400",
+    "-1e-400",
+    "\t7\n",
+    "1.9",
+    "-1.9",
+    "2147483647",
+    "2147483648",
+    "-2147483648",
+];
+
+#[test]
+fn test_parse_like_r() {
+    test! {
+        for s in STRINGS {
+            let expected = R!("suppressWarnings(as.numeric({{*s}}))")?;
+            let expected = Rfloat::try_from(expected)?;
+            let warned = R!("tryCatch({ as.numeric({{*s}}); FALSE }, warning = function(w) TRUE)")?;
+            match parse_real(s) {
+                Some(x) => {
+                    assert_eq!(warned, r!(false), "{:?}", s);
+                    assert!(x == expected || (x.is_nan() && expected.is_nan()), "{:?}", s);
+                    assert_eq!(x.is_na(), expected.is_na(), "{:?}", s);
+                }
+                None => assert!(warned == r!(true) && expected.is_na(), "{:?}", s),
+            }
+
+            let expected = Rint::try_from(R!("suppressWarnings(as.integer({{*s}}))")?)?;
+            assert_eq!(parse_integer(s).unwrap_or(Rint::na()), expected, "{:?}", s);
+        }
+    }
+}
+
+#[test]
+fn test_format_like_r() {
+    test! {
+        let values = [
+            1.0, -1.5, 0.1 + 0.2, 1.0 / 3.0, 100000.0, 123456.0, 1234567.0, 0.0001234, 1e-5, 1e15,
+            1e-300, -2.5e100, 123456789.123, 9.9999999, 0.0, -0.0,
+        ];
+        for x in values {
+            let expected = R!("format({{x}}, digits = 7)")?;
+            assert_eq!(format_real(Rfloat::from(x), 7), expected.as_str().unwrap(), "{}", x);
+            let expected = R!("as.character({{x}})")?;
+            assert_eq!(format_real(Rfloat::from(x), 15), expected.as_str().unwrap(), "{}", x);
+        }
+        assert_eq!(format_real(Rfloat::na(), 7), "NA");
+        assert_eq!(format_real(Rfloat::from(f64::NEG_INFINITY), 7), "-Inf");
+
+        let wide = NumberFormat { scipen: 100, ..Default::default() };
+        assert_eq!(wide.format_real(Rfloat::from(1e10)), "10000000000");
+        assert_eq!(wide.format_integer(Rint::na()), "NA");
+    }
+}
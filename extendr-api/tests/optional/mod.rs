@@ -1,2 +1,5 @@
 #[cfg(feature = "either")]
 mod either;
+
+#[cfg(feature = "num-complex")]
+mod num_complex;
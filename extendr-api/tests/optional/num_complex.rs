@@ -0,0 +1,56 @@
+use extendr_api::prelude::*;
+
+#[extendr(use_try_from = true)]
+fn test_conj(x: Vec<c64>) -> Vec<c64> {
+    x.iter().map(|x| x.conj()).collect()
+}
+
+#[extendr(use_try_from = true)]
+fn test_norm(x: c64) -> f64 {
+    x.norm()
+}
+
+#[extendr(use_try_from = true)]
+fn test_option_c64(x: Option<c64>) -> Option<c64> {
+    x.map(|x| x * 2.0)
+}
+
+#[extendr(use_try_from = true)]
+fn test_sum(x: &[c64]) -> c64 {
+    x.iter().sum()
+}
+
+#[test]
+fn test_complex_conversions() {
+    test! {
+        let x = R!("c(1+2i, -3i)")?;
+        assert_eq!(x.as_complex_slice().unwrap(), [c64::new(1.0, 2.0), c64::new(0.0, -3.0)]);
+        assert_eq!(<Vec<c64>>::try_from(&x)?, vec![c64::new(1.0, 2.0), c64::new(0.0, -3.0)]);
+        assert_eq!(r!(&[c64::new(1.0, 2.0), c64::new(0.0, -3.0)][..]), x);
+
+        // Numeric scalars are complex numbers, vectors are not.
+        assert_eq!(c64::try_from(r!(2))?, c64::new(2.0, 0.0));
+        assert!(<&[c64]>::try_from(&r!([1.0, 2.0])).is_err());
+
+        let na = R!("NA_complex_")?;
+        assert!(c64::try_from(&na).is_err());
+        assert_eq!(<Option<c64>>::try_from(&na)?, None);
+        let with_na = r!([Some(c64::new(1.0, 0.0)), None]);
+        assert_eq!(R!("is.na({{with_na}})")?, r!([false, true]));
+    }
+}
+
+#[test]
+fn test_complex_extendr_functions() {
+    unsafe {
+        test! {
+            let x = R!("c(1+2i, 3-4i)")?;
+            assert_eq!(Robj::from_sexp(wrap__test_conj(x.get())), R!("c(1-2i, 3+4i)")?);
+            assert_eq!(Robj::from_sexp(wrap__test_norm(R!("3+4i")?.get())), r!(5.0));
+            assert_eq!(Robj::from_sexp(wrap__test_option_c64(R!("1i")?.get())), R!("2i")?);
+            let na = Robj::from_sexp(wrap__test_option_c64(R!("NA_complex_")?.get()));
+            assert_eq!(<Option<c64>>::try_from(&na)?, None);
+            assert_eq!(Robj::from_sexp(wrap__test_sum(x.get())), R!("4-2i")?);
+        }
+    }
+}
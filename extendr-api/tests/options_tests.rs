@@ -0,0 +1,175 @@
+use extendr_api::coerce::coerce_arg_if_lenient;
+use extendr_api::options::{
+    check_na, extendr_options, panic_message, CallScope, Coercion, ExtendrOptions, LogLevel,
+    NaPolicy, PanicMessage,
+};
+use extendr_api::prelude::*;
+use libR_sys::{R_MakeExternalPtrFn, R_NilValue, SEXP};
+
+#[extendr]
+fn total(x: &[f64]) -> f64 {
+    x.iter().sum()
+}
+
+extendr_module! {
+    mod options_tests;
+    fn total;
+}
+
+// Define the R function `name <- function(x) .Call(<wrapper>, x)`, like the
+// R wrappers that extendr generates.
+fn define_r_function(name: &str, wrapper: extern "C" fn(SEXP) -> SEXP) -> Result<()> {
+    let native = unsafe {
+        Robj::from_sexp(R_MakeExternalPtrFn(
+            Some(std::mem::transmute(wrapper)),
+            Symbol::from_string("native symbol").get(),
+            R_NilValue,
+        ))
+    };
+    let native_name = format!(".{}_native", name);
+    global_env().set_local(Symbol::from_string(&native_name), native);
+    eval_string(&format!(
+        "{} <- function(x) .Call({}, x)",
+        name, native_name
+    ))?;
+    Ok(())
+}
+
+#[test]
+fn test_defaults() {
+    test! {
+        assert_eq!(extendr_options()?, ExtendrOptions::default());
+        let options = ExtendrOptions::default();
+        assert_eq!(options.coerce, Coercion::Strict);
+        assert_eq!(options.na, NaPolicy::Allow);
+        assert_eq!(options.panic, PanicMessage::Generic);
+        assert!(options.logs(LogLevel::Warn));
+        assert!(!options.logs(LogLevel::Info));
+        assert!(!options.logs(LogLevel::Off));
+    }
+}
+
+#[test]
+fn test_read_options() {
+    test! {
+        R!("options(extendr.coerce = 'lenient', extendr.na = 'error', extendr.log_level = 'trace', extendr.panic = 'detailed')")?;
+        let options = extendr_options()?;
+        R!("options(extendr.coerce = NULL, extendr.na = NULL, extendr.log_level = NULL, extendr.panic = NULL)")?;
+        assert_eq!(options, ExtendrOptions {
+            coerce: Coercion::Lenient,
+            na: NaPolicy::Error,
+            log_level: LogLevel::Trace,
+            panic: PanicMessage::Detailed,
+        });
+
+        R!("options(extendr.na = TRUE)")?;
+        let res = extendr_options();
+        R!("options(extendr.na = NULL)")?;
+        assert!(res.is_err());
+    }
+}
+
+#[test]
+fn test_options_are_kept_during_a_call() {
+    test! {
+        let scope = CallScope::enter();
+        assert_eq!(extendr_options()?.coerce, Coercion::Strict);
+        R!("options(extendr.coerce = 'lenient')")?;
+        assert_eq!(extendr_options()?.coerce, Coercion::Strict);
+
+        // A nested call sees the same options.
+        let nested = CallScope::enter();
+        assert_eq!(extendr_options()?.coerce, Coercion::Strict);
+        drop(nested);
+        assert_eq!(extendr_options()?.coerce, Coercion::Strict);
+
+        drop(scope);
+        assert_eq!(extendr_options()?.coerce, Coercion::Lenient);
+        R!("options(extendr.coerce = NULL)")?;
+    }
+}
+
+#[test]
+fn test_lenient_coercion_by_option() {
+    test! {
+        let is_real = |robj: &Robj| robj.is_real();
+        assert_eq!(coerce_arg_if_lenient(&r!(1), is_real), None);
+
+        R!("options(extendr.coerce = 'lenient')")?;
+        let coerced = coerce_arg_if_lenient(&r!(1), is_real);
+        let res = unsafe { Robj::from_sexp(wrap__total(R!("1:4")?.get())) };
+        R!("options(extendr.coerce = NULL)")?;
+        assert_eq!(coerced, Some(r!(1.0)));
+        assert_eq!(res, r!(10.0));
+    }
+}
+
+#[test]
+fn test_na_policy() {
+    test! {
+        let x = R!("c(1, NA)")?;
+        assert!(check_na("total", "x", &x).is_ok());
+
+        R!("options(extendr.na = 'error')")?;
+        let with_na = check_na("total", "x", &x);
+        let without_na = check_na("total", "x", &r!([1.0, 2.0]));
+        // NA is found in each type of vector, as anyNA() finds it.
+        let found: Vec<bool> = ["c(1L, NA)", "c(1, NaN)", "c(TRUE, NA)", "c('a', NA)", "list(1, NA)"]
+            .iter()
+            .map(|code| Ok(check_na("f", "x", &eval_string(code)?).is_err()))
+            .collect::<Result<_>>()?;
+        let not_found: Vec<bool> = ["1:3", "c('a', 'NA')", "list(c(1, NA))", "NULL"]
+            .iter()
+            .map(|code| Ok(check_na("f", "x", &eval_string(code)?).is_err()))
+            .collect::<Result<_>>()?;
+        R!("options(extendr.na = NULL)")?;
+        assert!(found.iter().all(|&found| found));
+        assert!(not_found.iter().all(|&found| !found));
+        assert_eq!(
+            with_na.unwrap_err().to_string(),
+            "argument 'x' of total() must not contain NA"
+        );
+        assert!(without_na.is_ok());
+    }
+}
+
+#[test]
+fn test_panic_message() {
+    test! {
+        let payload: Box<dyn std::any::Any + Send> = Box::new("index out of bounds");
+        assert_eq!(panic_message("f", &*payload), "user function panicked: f");
+
+        R!("options(extendr.panic = 'detailed')")?;
+        let detailed = panic_message("f", &*payload);
+        let formatted: Box<dyn std::any::Any + Send> = Box::new(format!("{} is too big", 3));
+        let detailed_string = panic_message("f", &*formatted);
+        R!("options(extendr.panic = NULL)")?;
+        assert_eq!(detailed, "user function panicked: f: index out of bounds");
+        assert_eq!(detailed_string, "user function panicked: f: 3 is too big");
+    }
+}
+
+#[test]
+fn test_invalid_options_fall_back_to_defaults() {
+    test! {
+        define_r_function("total", wrap__total)?;
+        assert_eq!(ExtendrOptions::default().checks_args(), extendr_api::trace::is_tracing());
+
+        // A bad option is a warning, and the function runs with the defaults.
+        R!("options(extendr.na = 'x')")?;
+        let res = R!("
+            warnings <- character()
+            value <- withCallingHandlers(total(c(1, NA, 2)), warning = function(cond) {
+                warnings <<- c(warnings, conditionMessage(cond))
+                invokeRestart('muffleWarning')
+            })
+            list(value, warnings)
+        ");
+        R!("options(extendr.na = NULL)")?;
+        let res = res?;
+        assert!(res.index(1)?.is_na());
+        let warnings = res.index(2)?;
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings.as_str().unwrap().contains("option extendr.na must be one of"));
+    }
+}
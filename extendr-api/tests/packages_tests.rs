@@ -0,0 +1,48 @@
+use extendr_api::packages::*;
+use extendr_api::prelude::*;
+
+#[test]
+fn test_installed_packages() {
+    test! {
+        let installed = installed_packages()?;
+        assert!(installed.iter().any(|name| name == "stats"));
+        assert!(is_installed("utils"));
+        assert!(!is_installed("not.a.package"));
+    }
+}
+
+#[test]
+fn test_loaded_namespaces() {
+    test! {
+        assert!(require_namespace("tools"));
+        assert!(is_namespace_loaded("tools"));
+        assert!(loaded_namespaces().iter().any(|name| name == "tools"));
+        let mut expected = R!("loadedNamespaces()")?.as_str_vector().unwrap();
+        let mut names = loaded_namespaces();
+        expected.sort_unstable();
+        names.sort_unstable();
+        assert_eq!(names, expected);
+        assert!(!is_namespace_loaded("not.a.package"));
+    }
+}
+
+#[test]
+fn test_package_version() {
+    test! {
+        // Read from the namespace, and from DESCRIPTION for a package that
+        // is not loaded.
+        let stats = package_version("stats")?;
+        let expected = R!("as.character(packageVersion('stats'))")?;
+        assert_eq!(stats.to_string(), expected.as_str().unwrap());
+        assert!(stats >= PackageVersion::parse("3.0.0")?);
+
+        let installed = installed_packages()?;
+        let unloaded = installed.iter().find(|name| !is_namespace_loaded(name));
+        if let Some(name) = unloaded {
+            let version = package_version(name)?;
+            assert!(!version.components().is_empty());
+            assert!(!is_namespace_loaded(name));
+        }
+        assert!(package_version("not.a.package").is_err());
+    }
+}
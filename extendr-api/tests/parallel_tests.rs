@@ -0,0 +1,37 @@
+use extendr_api::prelude::*;
+use extendr_api::scalar::{Rbool, Rint};
+
+#[test]
+fn test_to_owned_vector() {
+    test! {
+        let ints = R!("c(1L, NA, 3L)")?.to_owned_vector::<Rint>()?;
+        assert_eq!(ints[0], Rint::from(1));
+        assert!(ints[1].is_na());
+
+        let bools = R!("c(TRUE, NA)")?.to_owned_vector::<Rbool>()?;
+        assert!(bools[0].is_true() && bools[1].is_na());
+
+        // ALTREP vectors are copied too.
+        assert_eq!(R!("1:5")?.to_owned_vector::<i32>()?, [1, 2, 3, 4, 5]);
+        assert_eq!(r!(Raw::from_bytes(&[1, 2])).to_owned_vector::<u8>()?, [1, 2]);
+        assert!(r!(1.0).to_owned_vector::<Option<String>>().is_err());
+        assert!(r!(NULL).to_owned_vector::<f64>().is_err());
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel() {
+    use extendr_api::parallel::{par_map, run};
+    test! {
+        let robj = R!("as.double(1:100000)")?;
+        let doubled = par_map(&robj, |x: f64| 2.0 * x)?;
+        assert_eq!(doubled, R!("2 * as.double(1:100000)")?);
+
+        let total = run(robj.to_owned_vector::<f64>()?, |x| x.iter().sum::<f64>())?;
+        assert_eq!(total, 5000050000.0);
+
+        assert!(run((), |_| -> i32 { panic!("oops") }).is_err());
+        assert!(par_map(&robj, |x: i32| x).is_err());
+    }
+}
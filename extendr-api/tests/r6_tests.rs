@@ -0,0 +1,68 @@
+use extendr_api::prelude::*;
+
+#[derive(Debug, Clone)]
+struct Counter {
+    count: i32,
+}
+
+/// A counter.
+/// @export
+#[extendr(r6)]
+impl Counter {
+    fn new(start: i32) -> Self {
+        Counter { count: start }
+    }
+
+    fn zero() -> Self {
+        Counter { count: 0 }
+    }
+
+    fn add(&mut self, n: i32) {
+        self.count += n;
+    }
+
+    fn count(&self) -> i32 {
+        self.count
+    }
+
+    fn set_count(&mut self, count: i32) {
+        self.count = count;
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn same(&self, other: &Counter) -> bool {
+        self.count == other.count
+    }
+}
+
+extendr_module! {
+    mod r6_counter;
+    impl Counter;
+}
+
+#[test]
+fn test_r6_wrappers() {
+    test! {
+        let metadata = get_r6_counter_metadata();
+        let wrappers = metadata.make_r_wrappers(true, "counter").unwrap();
+        assert!(wrappers.contains("Counter <- R6::R6Class(\"Counter\","));
+        assert!(wrappers.contains(
+            "initialize = function(start, .ptr = NULL) { if (is.null(.ptr)) .ptr <- .Call(wrap__Counter__new, start); self$.ptr <- .ptr }"
+        ));
+        assert!(wrappers.contains("add = function(n) { .Call(wrap__Counter__add, self$.ptr, n); invisible(self) }"));
+        assert!(wrappers.contains("copy = function() Counter$new(.ptr = .Call(wrap__Counter__copy, self$.ptr))"));
+        assert!(wrappers.contains("same = function(other) .Call(wrap__Counter__same, self$.ptr, other$.ptr)"));
+        assert!(wrappers.contains(
+            "count = function(value) { if (missing(value)) .Call(wrap__Counter__count, self$.ptr) else { .Call(wrap__Counter__set_count, self$.ptr, value); invisible(self) } }"
+        ));
+        assert!(!wrappers.contains("set_count = function"));
+        assert!(wrappers.contains("Counter$zero <- function() Counter$new(.ptr = .Call(wrap__Counter__zero))"));
+
+        let namespace = metadata.make_namespace("counter");
+        assert!(namespace.contains("export(Counter)"));
+        assert!(!namespace.contains("S3method"));
+    }
+}
@@ -0,0 +1,46 @@
+use extendr_api::prelude::*;
+
+#[extendr(use_try_from = true)]
+fn test_xor(bytes: &[u8], key: u8) -> Vec<u8> {
+    bytes.iter().map(|b| b ^ key).collect()
+}
+
+#[extendr(use_try_from = true)]
+fn test_reverse(bytes: Raw) -> Raw {
+    bytes.iter().rev().copied().collect()
+}
+
+#[test]
+fn test_raw_conversions() {
+    test! {
+        let bytes = R!("as.raw(c(1, 2, 255))")?;
+        assert_eq!(bytes.as_raw_slice().unwrap(), [1, 2, 255]);
+        assert_eq!(<Vec<u8>>::try_from(&bytes)?, vec![1, 2, 255]);
+        assert_eq!(r!(vec![1_u8, 2, 255]), bytes);
+        assert_eq!(r!(&[1_u8, 2, 255][..]).rtype(), Rtype::Raw);
+        assert_eq!(Vec::from(Raw::try_from(bytes)?), vec![1, 2, 255]);
+
+        assert_eq!(u8::try_from(R!("as.raw(7)")?)?, 7);
+        assert_eq!(i32::try_from(R!("as.raw(7)")?)?, 7);
+        assert!(<&[u8]>::try_from(&r!([1, 2])).is_err());
+    }
+}
+
+#[test]
+fn test_raw_extendr_functions() {
+    unsafe {
+        test! {
+            let bytes = R!("as.raw(c(0x0f, 0xf0))")?;
+            let key = R!("as.raw(0xff)")?;
+            let res = Robj::from_sexp(wrap__test_xor(bytes.get(), key.get()));
+            assert_eq!(res, R!("as.raw(c(0xf0, 0x0f))")?);
+
+            // Integer keys are accepted too.
+            let res = Robj::from_sexp(wrap__test_xor(bytes.get(), r!(1).get()));
+            assert_eq!(res, R!("as.raw(c(0x0e, 0xf1))")?);
+
+            let res = Robj::from_sexp(wrap__test_reverse(bytes.get()));
+            assert_eq!(res, R!("as.raw(c(0xf0, 0x0f))")?);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use extendr_api::prelude::*;
+
+#[extendr]
+fn returns_unit() {}
+
+#[extendr]
+fn returns_invisible() -> Invisible<i32> {
+    Invisible(1)
+}
+
+#[extendr]
+fn returns_null() -> Null {
+    Null
+}
+
+#[extendr]
+fn returns_value() -> i32 {
+    1
+}
+
+#[extendr]
+fn never_returns() -> NoReturn {
+    throw_r_error("always fails")
+}
+
+extendr_module! {
+    mod returns;
+    fn returns_unit;
+    fn returns_invisible;
+    fn returns_null;
+    fn returns_value;
+    fn never_returns;
+}
+
+#[test]
+fn test_return_wrappers() {
+    test! {
+        let wrappers = get_returns_metadata().make_r_wrappers(true, "returns").unwrap();
+        assert!(wrappers.contains("returns_unit <- function() invisible(.Call(wrap__returns_unit))"));
+        assert!(wrappers.contains("returns_invisible <- function() invisible(.Call(wrap__returns_invisible))"));
+        assert!(wrappers.contains("returns_null <- function() .Call(wrap__returns_null)\n"));
+        assert!(wrappers.contains("returns_value <- function() .Call(wrap__returns_value)\n"));
+        assert!(wrappers.contains("never_returns <- function() invisible(.Call(wrap__never_returns))"));
+    }
+}
+
+#[test]
+fn test_return_values() {
+    unsafe {
+        test! {
+            assert_eq!(Robj::from_sexp(wrap__returns_invisible()), r!(1));
+            assert!(Robj::from_sexp(wrap__returns_null()).is_null());
+        }
+    }
+}
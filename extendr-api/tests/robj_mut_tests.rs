@@ -0,0 +1,75 @@
+use extendr_api::prelude::*;
+
+#[test]
+fn test_shared_objects_are_copied() {
+    test! {
+        let x = eval_string("robj_mut_x <- c(1L, 2L, 3L); robj_mut_x")?;
+        let mut y = x.clone().into_mut();
+        y.as_integer_slice_mut().unwrap()[1] = 20;
+        y.set_names(["a", "b", "c"])?;
+
+        assert_eq!(eval_string("robj_mut_x")?, r!([1, 2, 3]));
+        assert_eq!(x, r!([1, 2, 3]));
+        assert_eq!(y, R!("c(a = 1L, b = 20L, c = 3L)")?);
+    }
+}
+
+#[test]
+fn test_unique_objects_are_not_copied() {
+    test! {
+        let x = r!([1.0, 2.0]);
+        let sexp = unsafe { x.get() };
+        let mut y = RobjMut::from(x);
+        assert!(y.is_unique());
+        y.as_real_slice_mut().unwrap()[0] = 3.0;
+        assert_eq!(unsafe { y.get() }, sexp);
+
+        // A shared reference taken through `Deref` makes the next change copy.
+        let shared: Robj = (*y).clone();
+        assert!(!y.is_unique());
+        y.as_real_slice_mut().unwrap()[1] = 4.0;
+        assert_ne!(unsafe { y.get() }, sexp);
+        assert_eq!(shared, r!([3.0, 2.0]));
+        assert_eq!(Robj::from(y), r!([3.0, 4.0]));
+    }
+}
+
+#[test]
+fn test_clone_copies() {
+    test! {
+        let mut x = r!(["a", "b"]).into_mut();
+        let y = x.clone();
+        x.set_class(["letters"])?;
+        assert!(x.inherits("letters"));
+        assert!(!y.inherits("letters"));
+    }
+}
+
+#[extendr]
+fn scale_in_place(mut x: Robj, k: f64) -> Robj {
+    x.make_unique();
+    if let Some(slice) = x.as_real_slice_mut() {
+        slice.iter_mut().for_each(|x| *x *= k);
+    }
+    x
+}
+
+#[test]
+fn test_make_unique() {
+    test! {
+        let x = r!([1.0, 2.0]);
+        let scaled = unsafe { Robj::from_sexp(wrap__scale_in_place(x.get(), r!(3.0).get())) };
+        assert_eq!(scaled, r!([3.0, 6.0]));
+        assert_eq!(x, r!([1.0, 2.0]));
+
+        let mut fresh = r!([1, 2]);
+        assert!(!fresh.is_shared());
+        assert!(!fresh.make_unique());
+        let alias = fresh.clone();
+        assert!(fresh.is_shared());
+        assert!(fresh.make_unique());
+        fresh.as_integer_slice_mut().unwrap()[0] = 0;
+        assert_eq!(alias, r!([1, 2]));
+        assert_eq!(fresh, r!([0, 2]));
+    }
+}
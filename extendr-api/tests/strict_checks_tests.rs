@@ -0,0 +1,48 @@
+#[cfg(feature = "strict-checks")]
+mod strict_checks_tests {
+    use extendr_api::prelude::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    // Run `f` and return the message of the panic it raises.
+    fn panic_message<F: FnOnce()>(f: F) -> String {
+        let payload = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_set_elt_out_of_bounds() {
+        test! {
+            let mut vec = Doubles::new(3);
+            let msg = panic_message(|| vec.set_elt(3, 1.0.into()));
+            assert!(msg.starts_with("strict-checks: SET_REAL_ELT index 3 out of bounds"));
+
+            // In-bounds writes are unaffected.
+            vec.set_elt(2, 1.0.into());
+            assert_eq!(vec.elt(2), Rfloat::from(1.0));
+        }
+    }
+
+    #[test]
+    fn test_typed_slice_of_wrong_type() {
+        test! {
+            let robj = r!([1, 2, 3]);
+            let msg = panic_message(|| {
+                unsafe { robj.as_typed_slice_raw::<f64>() };
+            });
+            assert!(msg.starts_with("strict-checks: cannot view SEXPTYPE"));
+
+            let robj = sym!(x);
+            let msg = panic_message(|| {
+                unsafe { robj.as_typed_slice_raw::<i32>() };
+            });
+            assert!(msg.ends_with("has no data pointer"));
+
+            // Matching element types are unaffected.
+            let robj = r!([1, 2, 3]);
+            assert_eq!(unsafe { robj.as_typed_slice_raw::<i32>() }, &[1, 2, 3]);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use extendr_api::prelude::*;
+use extendr_api::table::{cross_table, table};
+
+#[test]
+fn test_table() {
+    test! {
+        for code in [
+            "c('x', NA, 'y', 'x')",
+            "c(3L, NA, -1L, 3L, 10L)",
+            "c(TRUE, NA, TRUE)",
+            "c(0.5, -0, 0, NaN, 1e10, NA, 0.5)",
+            "factor(c('lo', 'hi', NA), levels = c('lo', 'mid', 'hi'))",
+            "character(0)",
+        ] {
+            let x = eval_string(code)?;
+            let expected = eval_string(&format!("table({})", code))?;
+            assert_eq!(table(&x)?, expected, "table({})", code);
+        }
+        assert!(table(&r!(list!(1, 2))).is_err());
+    }
+}
+
+#[test]
+fn test_cross_table() {
+    test! {
+        let x = R!("factor(c('a', 'b', 'a', NA, 'b'), levels = c('b', 'a', 'z'))")?;
+        let y = R!("c(TRUE, FALSE, TRUE, TRUE, NA)")?;
+        let expected = R!("table(factor(c('a', 'b', 'a', NA, 'b'), levels = c('b', 'a', 'z')), c(TRUE, FALSE, TRUE, TRUE, NA))")?;
+        assert_eq!(cross_table(&x, &y)?, expected);
+        assert!(cross_table(&x, &r!([1, 2])).is_err());
+    }
+}
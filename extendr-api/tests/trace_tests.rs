@@ -0,0 +1,89 @@
+use extendr_api::prelude::*;
+use extendr_api::trace::{trace_conversions, TraceTarget};
+use libR_sys::{R_MakeExternalPtrFn, R_NilValue, SEXP};
+
+#[extendr]
+fn traced_sum(values: Vec<f64>, view: &[f64]) -> f64 {
+    values.iter().sum::<f64>() + view.iter().sum::<f64>()
+}
+
+#[extendr]
+fn traced_len(x: &[i32]) -> usize {
+    x.len()
+}
+
+extendr_module! {
+    mod trace_tests;
+    fn traced_sum;
+    fn traced_len;
+}
+
+// Define the R function `name <- function(x) .Call(<wrapper>, x)`, like the
+// R wrappers that extendr generates.
+fn define_r_function(name: &str, wrapper: extern "C" fn(SEXP) -> SEXP) -> Result<()> {
+    let native = unsafe {
+        Robj::from_sexp(R_MakeExternalPtrFn(
+            Some(std::mem::transmute(wrapper)),
+            Symbol::from_string("native symbol").get(),
+            R_NilValue,
+        ))
+    };
+    let native_name = format!(".{}_native", name);
+    global_env().set_local(Symbol::from_string(&native_name), native);
+    eval_string(&format!(
+        "{} <- function(x) .Call({}, x)",
+        name, native_name
+    ))?;
+    Ok(())
+}
+
+#[test]
+fn test_trace_conversions() {
+    test! {
+        let path = std::env::temp_dir().join(format!("extendr_trace_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        trace_conversions(Some(TraceTarget::File(path.clone())));
+
+        let sum = unsafe { Robj::from_sexp(wrap__traced_sum(r!([1.0, 2.0]).get(), r!([3.0]).get())) };
+        assert_eq!(sum, r!(6.0));
+        trace_conversions(None);
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("extendr: traced_sum(values): double[2] -> "));
+        assert!(lines[0].ends_with("(copy)"));
+        assert!(lines[1].ends_with("(view)"));
+        assert_eq!(lines[2], "extendr: traced_sum() -> f64 -> double[1] (scalar)");
+
+        // Nothing is logged when tracing is off.
+        unsafe { wrap__traced_sum(r!([1.0]).get(), r!([1.0]).get()) };
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), log);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn test_trace_to_console() {
+    test! {
+        define_r_function("traced_len", wrap__traced_len)?;
+
+        trace_conversions(Some(TraceTarget::Console));
+        let output = R!("capture.output(traced_len(1:3))");
+        trace_conversions(None);
+        assert_eq!(output?, r!([
+            "extendr: traced_len(x): integer[3] -> &[i32] (view)",
+            "extendr: traced_len() -> usize -> double[1] (scalar)",
+            "[1] 3",
+        ]));
+
+        // `options(extendr.log_level = "trace")` also logs to the console.
+        R!("options(extendr.log_level = 'trace')")?;
+        let output = R!("capture.output(traced_len(1:3))");
+        R!("options(extendr.log_level = NULL)")?;
+        assert_eq!(output?.len(), 3);
+
+        let output = R!("capture.output(traced_len(1:3))")?;
+        assert_eq!(output, r!("[1] 3"));
+    }
+}
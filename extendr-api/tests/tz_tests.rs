@@ -0,0 +1,33 @@
+use extendr_api::prelude::*;
+use extendr_api::tz;
+
+#[test]
+fn test_utc_offsets() {
+    test! {
+        // 2021-01-01 and 2021-07-01, 12:00:00 UTC.
+        let times = [1609502400.0, 1625140800.0];
+        assert_eq!(tz::utc_offsets("America/New_York", &times)?, vec![-5 * 3600, -4 * 3600]);
+        assert_eq!(tz::utc_offsets("Asia/Kolkata", &times)?, vec![19800, 19800]);
+        assert_eq!(tz::abbreviation("America/New_York", times[0])?, "EST");
+        assert!(!tz::is_dst("America/New_York", times[0])?);
+    }
+}
+
+#[test]
+fn test_unknown_timezone() {
+    test! {
+        let err = tz::utc_offset("Nowhere/Special", 0.0).unwrap_err();
+        assert_eq!(err.to_string(), "unknown time zone 'Nowhere/Special'");
+        assert!(tz::timezone_names()?.iter().any(|name| name == "Europe/Paris"));
+    }
+}
+
+#[test]
+fn test_tzone_attribute() {
+    test! {
+        let mut time = R!(r#"as.POSIXct("2021-07-01 12:00:00", tz = "Asia/Tokyo")"#)?;
+        assert_eq!(tz::tzone(&time).as_deref(), Some("Asia/Tokyo"));
+        tz::set_tzone(&mut time, "UTC")?;
+        assert_eq!(R!("format({{time}})")?, r!("2021-07-01 03:00:00"));
+    }
+}
@@ -0,0 +1,152 @@
+//! R errors and other jumps out of R code called from rust unwind the rust
+//! frames in between, see `unwind_protect`.
+use extendr_api::prelude::*;
+use libR_sys::{R_GlobalEnv, R_MakeExternalPtrFn, R_NilValue, Rf_eval, SEXP};
+use std::cell::Cell;
+
+thread_local! {
+    static DROPPED: Cell<usize> = Cell::new(0);
+}
+
+// Counts the frames that are unwound.
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+fn dropped() -> usize {
+    DROPPED.with(|dropped| dropped.replace(0))
+}
+
+#[extendr]
+fn eval_guarded(expr: Robj) -> Robj {
+    let _guard = Guard;
+    let expr = unsafe { expr.get() };
+    unsafe { Robj::from_sexp(unwind_protect(|| Rf_eval(expr, R_GlobalEnv))) }
+}
+
+extendr_module! {
+    mod unwind_tests;
+    fn eval_guarded;
+}
+
+// Define the R function `name <- function(x) .Call(<wrapper>, x)`, like the
+// R wrappers that extendr generates.
+fn define_r_function(name: &str, wrapper: extern "C" fn(SEXP) -> SEXP) -> Result<()> {
+    let native = unsafe {
+        Robj::from_sexp(R_MakeExternalPtrFn(
+            Some(std::mem::transmute(wrapper)),
+            Symbol::from_string("native symbol").get(),
+            R_NilValue,
+        ))
+    };
+    let native_name = format!(".{}_native", name);
+    global_env().set_local(Symbol::from_string(&native_name), native);
+    eval_string(&format!(
+        "{} <- function(x) .Call({}, x)",
+        name, native_name
+    ))?;
+    Ok(())
+}
+
+#[test]
+fn test_unwind_protect() {
+    test! {
+        assert_eq!(unwind_protect(|| 42), 42);
+
+        let call = lang!("stop", "boom");
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = Guard;
+            unwind_protect(|| unsafe { Rf_eval(call.get(), R_GlobalEnv) })
+        }));
+        assert!(res.unwrap_err().is::<RUnwind>());
+        assert_eq!(dropped(), 1);
+
+        // R and the lock still work after the jump is dropped.
+        assert_eq!(R!("1 + 1")?, r!(2.0));
+    }
+}
+
+#[test]
+fn test_unwind_protect_panic() {
+    test! {
+        // A panic in `f` is raised again outside R.
+        let res = std::panic::catch_unwind(|| unwind_protect(|| -> i32 { panic!("oops") }));
+        assert_eq!(res.unwrap_err().downcast_ref::<&str>(), Some(&"oops"));
+        assert_eq!(single_threaded(|| 1), 1);
+    }
+}
+
+#[test]
+fn test_catch_r_error() {
+    test! {
+        let call = lang!("stop", "boom");
+        assert!(catch_r_error(|| unsafe { Rf_eval(call.get(), R_GlobalEnv) }).is_err());
+
+        let call = lang!("+", 1, 2);
+        let res = catch_r_error(|| unsafe { Rf_eval(call.get(), R_GlobalEnv) });
+        assert_eq!(unsafe { Robj::from_sexp(res.unwrap()) }, r!(3));
+    }
+}
+
+#[test]
+fn test_errors_through_extendr_functions() {
+    test! {
+        define_r_function("eval_guarded", wrap__eval_guarded)?;
+        assert_eq!(R!("eval_guarded(quote(1 + 1))")?, r!(2.0));
+        assert_eq!(dropped(), 1);
+
+        let res = R!("tryCatch(eval_guarded(quote(stop('boom'))), error = function(e) conditionMessage(e))")?;
+        assert_eq!(res, r!("boom"));
+        assert_eq!(dropped(), 1);
+
+        // Calling handlers see the error before the jump.
+        let res = R!("
+            seen <- NULL
+            tryCatch(
+                withCallingHandlers(eval_guarded(quote(stop('boom'))), error = function(e) seen <<- conditionMessage(e)),
+                error = function(e) NULL
+            )
+            seen
+        ")?;
+        assert_eq!(res, r!("boom"));
+        assert_eq!(dropped(), 1);
+
+        // An error not handled by R is returned by R!.
+        assert!(R!("eval_guarded(quote(stop('boom')))").is_err());
+        assert_eq!(dropped(), 1);
+    }
+}
+
+#[test]
+fn test_exiting_handlers_through_extendr_functions() {
+    test! {
+        define_r_function("eval_guarded", wrap__eval_guarded)?;
+        let res = R!("tryCatch(eval_guarded(quote(warning('careful'))), warning = function(w) conditionMessage(w))")?;
+        assert_eq!(res, r!("careful"));
+        assert_eq!(dropped(), 1);
+
+        let res = R!("withRestarts(eval_guarded(quote(invokeRestart('done', 3))), done = function(x) x)")?;
+        assert_eq!(res, r!(3.0));
+        assert_eq!(dropped(), 1);
+    }
+}
+
+#[test]
+fn test_nested_unwinding() {
+    test! {
+        // R calls rust calls R calls rust, which calls stop().
+        define_r_function("eval_guarded", wrap__eval_guarded)?;
+        let res = R!("
+            tryCatch(
+                eval_guarded(quote(eval_guarded(quote(stop('inner'))))),
+                error = function(e) conditionMessage(e)
+            )
+        ")?;
+        assert_eq!(res, r!("inner"));
+        assert_eq!(dropped(), 2);
+    }
+}
@@ -0,0 +1,168 @@
+use extendr_api::conditions::{catch_condition, Condition, ConditionKind, Warnings};
+use extendr_api::prelude::*;
+use libR_sys::{R_MakeExternalPtrFn, R_NilValue, SEXP};
+
+#[extendr]
+fn parse_numbers(x: Vec<String>) -> Vec<Option<f64>> {
+    let mut warnings = Warnings::new();
+    let res = x
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let number = text.parse().ok();
+            if number.is_none() {
+                warnings.push(format!("element {} is not a number", i + 1));
+            }
+            number
+        })
+        .collect();
+    warnings.defer();
+    res
+}
+
+#[extendr]
+fn fail_after_warning(x: i32) -> Result<i32> {
+    defer_warning!("x is {}", x);
+    Err(Error::Other("failed".into()))
+}
+
+extendr_module! {
+    mod warnings_tests;
+    fn parse_numbers;
+    fn fail_after_warning;
+}
+
+// Define the R function `name <- function(x) .Call(<wrapper>, x)`, like the
+// R wrappers that extendr generates.
+fn define_r_function(name: &str, wrapper: extern "C" fn(SEXP) -> SEXP) -> Result<()> {
+    let native = unsafe {
+        Robj::from_sexp(R_MakeExternalPtrFn(
+            Some(std::mem::transmute(wrapper)),
+            Symbol::from_string("native symbol").get(),
+            R_NilValue,
+        ))
+    };
+    let native_name = format!(".{}_native", name);
+    global_env().set_local(Symbol::from_string(&native_name), native);
+    eval_string(&format!(
+        "{} <- function(x) .Call({}, x)",
+        name, native_name
+    ))?;
+    Ok(())
+}
+
+// Call `code`, collecting the calls and messages of its warnings in order.
+const COLLECT_WARNINGS: &str = "function(code) {
+    warnings <- character()
+    value <- withCallingHandlers(code, warning = function(cond) {
+        call <- paste(deparse(conditionCall(cond)), collapse = '')
+        warnings <<- c(warnings, paste0(call, ': ', conditionMessage(cond)))
+        invokeRestart('muffleWarning')
+    })
+    list(value, warnings)
+}";
+
+#[test]
+fn test_warnings_collector() {
+    test! {
+        let mut warnings = Warnings::new();
+        assert!(warnings.is_empty());
+        warnings.push("first");
+        warnings.push_condition(Condition::message("second").class("myWarning"));
+        assert_eq!(warnings.len(), 2);
+
+        let conditions: Vec<_> = warnings.iter().collect();
+        assert_eq!(conditions[0].message_text(), "first");
+        assert_eq!(conditions[1].kind(), ConditionKind::Warning);
+        assert!(conditions[1].inherits("myWarning"));
+        assert!(conditions[1].inherits("warning"));
+    }
+}
+
+#[test]
+fn test_condition_call() {
+    test! {
+        let with_call = Condition::warning("careful").call(lang!("f", 1.0)).to_robj();
+        assert_eq!(R!("deparse(conditionCall({{with_call}}))")?, r!("f(1)"));
+
+        let without_call = Condition::warning("careful").to_robj();
+        assert_eq!(R!("conditionCall({{without_call}})")?, r!(()));
+    }
+}
+
+#[test]
+fn test_defer_outside_a_call() {
+    test! {
+        // Without a call to defer them to, the warnings are signalled now.
+        let caught = catch_condition(|| {
+            let mut warnings = Warnings::new();
+            warnings.push("now");
+            warnings.defer();
+        });
+        let cond = caught.unwrap_err();
+        assert_eq!(cond.kind(), ConditionKind::Warning);
+        assert_eq!(cond.message_text(), "now");
+    }
+}
+
+#[test]
+fn test_warnings_after_return() {
+    test! {
+        define_r_function("parse_numbers", wrap__parse_numbers)?;
+        let collect = eval_string(COLLECT_WARNINGS)?;
+        let res = R!("{{collect}}(parse_numbers(c('1', 'a', '2', 'b')))")?;
+        assert_eq!(res.index(1)?, r!([Some(1.0), None, Some(2.0), None]));
+        assert_eq!(
+            res.index(2)?,
+            r!([
+                r#"parse_numbers(c("1", "a", "2", "b")): element 2 is not a number"#,
+                r#"parse_numbers(c("1", "a", "2", "b")): element 4 is not a number"#,
+            ])
+        );
+
+        // Without warnings nothing is signalled.
+        let res = R!("{{collect}}(parse_numbers('1'))")?;
+        assert_eq!(res.index(1)?, r!(1.0));
+        assert_eq!(res.index(2)?, R!("character()")?);
+    }
+}
+
+#[test]
+fn test_warnings_before_an_error() {
+    test! {
+        define_r_function("fail_after_warning", wrap__fail_after_warning)?;
+        let res = R!("
+            warnings <- character()
+            error <- tryCatch(
+                withCallingHandlers(fail_after_warning(3L), warning = function(cond) {
+                    warnings <<- c(warnings, conditionMessage(cond))
+                    invokeRestart('muffleWarning')
+                }),
+                error = function(cond) conditionMessage(cond)
+            )
+            c(warnings, error)
+        ")?;
+        assert_eq!(res, r!(["x is 3", "failed"]));
+    }
+}
+
+#[test]
+fn test_exiting_warning_handlers() {
+    test! {
+        define_r_function("parse_numbers", wrap__parse_numbers)?;
+        let res = R!("tryCatch(parse_numbers(c('a', 'b')), warning = function(w) conditionMessage(w))")?;
+        assert_eq!(res, r!("element 1 is not a number"));
+
+        // With options(warn = 2) the first warning is an error.
+        R!("old_options <- options(warn = 2)")?;
+        let caught = R!("tryCatch(parse_numbers(c('1', 'a')), error = function(e) conditionMessage(e))");
+        let uncaught = R!("parse_numbers(c('1', 'a'))");
+        R!("options(old_options)")?;
+        assert!(caught?.as_str().unwrap().contains("element 2 is not a number"));
+        assert!(uncaught.is_err());
+
+        // R still works after the jumps.
+        let res = R!("parse_numbers('2')")?;
+        assert_eq!(res, r!(2.0));
+    }
+}
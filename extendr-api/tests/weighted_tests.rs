@@ -0,0 +1,61 @@
+use extendr_api::prelude::*;
+use extendr_api::weighted::*;
+
+#[test]
+fn test_weighted_mean() {
+    test! {
+        let x = [1.5, f64::na(), 4.0, -2.0, 7.25];
+        let r_x = r!(x);
+        for w in [[1.0, 0.0, 2.0, 0.5, 3.0], [1.0, 2.0, -1.0, 0.5, 0.0], [1.0, 0.0, f64::na(), 1.0, 1.0]] {
+            let r_w = r!(w);
+            for na_rm in [false, true] {
+                let expected = R!("weighted.mean({{&r_x}}, {{&r_w}}, na.rm = {{na_rm}})")?;
+                assert_eq!(r!(weighted_mean(&x, &w, na_rm)?), expected);
+            }
+        }
+        assert!(weighted_mean(&[], &[], false)?.is_nan());
+        assert!(weighted_mean(&[1.0], &[1.0, 2.0], false).is_err());
+    }
+}
+
+#[test]
+fn test_weighted_var() {
+    test! {
+        let x = [2.0, 3.5, -1.0, 8.0, f64::na()];
+        let w = [1.0, 4.0, 2.0, 3.0, 1.0];
+        let cov = |method: &str| -> Result<f64> {
+            let res = call!("cov.wt", R!("cbind(c(2, 3.5, -1, 8))")?, r!([1.0, 4.0, 2.0, 3.0]), method = method)?;
+            Ok(res.dollar("cov")?.as_real_slice().unwrap()[0])
+        };
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12 * b.abs();
+
+        assert!(weighted_var(&x, &w, VarianceWeights::ML, false)?.is_na());
+        assert!(close(weighted_var(&x, &w, VarianceWeights::Reliability, true)?, cov("unbiased")?));
+        assert!(close(weighted_var(&x, &w, VarianceWeights::ML, true)?, cov("ML")?));
+        let expected = R!("var(rep(c(2, 3.5, -1, 8), c(1, 4, 2, 3)))")?.as_real().unwrap();
+        assert!(close(weighted_var(&x, &w, VarianceWeights::Frequency, true)?, expected));
+
+        assert!(weighted_var(&[1.0], &[1.0], VarianceWeights::Frequency, false)?.is_na());
+        assert!(weighted_var(&[1.0, 2.0], &[1.0, -1.0], VarianceWeights::ML, false).is_err());
+    }
+}
+
+#[test]
+fn test_weighted_quantile() {
+    test! {
+        let x = [5.0, 1.0, 3.0, 1.0, 9.0, f64::NAN, 4.0];
+        let w = [2.0, 1.0, 3.0, 2.0, 0.0, 1.0, 1.0];
+        let probs = [0.0, 0.1, 0.25, 0.5, 0.9, 1.0];
+        let expected = R!("quantile(rep(c(5, 1, 3, 1, 9, 4), c(2, 1, 3, 2, 0, 1)), c(0, 0.1, 0.25, 0.5, 0.9, 1), names = FALSE)")?;
+        let res = weighted_quantile(&x, &w, &probs, true)?;
+        for (a, b) in res.iter().zip(expected.as_real_slice().unwrap()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+
+        assert_eq!(weighted_quantile(&[2.0], &[0.5], &[0.0, 1.0], false)?, [2.0, 2.0]);
+        assert!(weighted_quantile(&x, &w, &probs, false).is_err());
+        assert!(weighted_quantile(&[1.0], &[1.0], &[1.5], false).is_err());
+        assert!(weighted_quantile(&[1.0], &[0.0], &[0.5], false).is_err());
+        assert!(weighted_quantile(&[1.0], &[-1.0], &[0.5], false).is_err());
+    }
+}
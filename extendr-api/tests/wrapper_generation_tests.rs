@@ -0,0 +1,115 @@
+use extendr_api::prelude::*;
+
+/// Add two numbers.
+/// @param a The first number.
+#[extendr(export)]
+fn add_numbers(a: f64, b: i32) -> f64 {
+    a + b as f64
+}
+
+#[extendr(export)]
+fn untitled(x: &str) -> String {
+    x.to_string()
+}
+
+#[extendr]
+fn internal() {}
+
+/// Scale a number.
+///
+/// # Arguments
+///
+/// * `x` - The number.
+/// * `factor` - The scale,
+///   which may be negative.
+///
+/// # Returns
+///
+/// The scaled number.
+///
+/// # Examples
+///
+/// ```
+/// # let y = 1;
+/// assert_eq!(scale(2.0, 3.0), 6.0);
+/// ```
+#[extendr(export)]
+fn scale(x: f64, factor: f64) -> f64 {
+    x * factor
+}
+
+#[derive(Debug, Clone)]
+struct Account {
+    balance: f64,
+}
+
+/// A bank account.
+/// @export
+#[extendr]
+impl Account {
+    /// Open an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The opening balance.
+    fn new(balance: f64) -> Self {
+        Account { balance }
+    }
+
+    /// The balance of the account.
+    fn balance(&self) -> f64 {
+        self.balance
+    }
+
+    fn close(&mut self) {
+        self.balance = 0.0;
+    }
+}
+
+extendr_module! {
+    mod generation;
+    fn add_numbers;
+    fn untitled;
+    fn internal;
+    fn scale;
+    impl Account;
+}
+
+#[test]
+fn test_roxygen_skeletons() {
+    test! {
+        let wrappers = get_generation_metadata().make_r_wrappers(true, "generation").unwrap();
+        assert!(wrappers.contains(
+            "#' Add two numbers.\n#' @param a The first number.\n#' @param b `i32`\n#' @return `f64`\n#' @export\nadd_numbers <- function(a, b)"
+        ));
+        assert!(wrappers.contains("#' untitled\n#' @param x `str`\n#' @return `String`\n#' @export\nuntitled <- function(x)"));
+        assert!(wrappers.contains("internal <- function() invisible(.Call(wrap__internal))"));
+    }
+}
+
+#[test]
+fn test_roxygen_from_rust_docs() {
+    test! {
+        let wrappers = get_generation_metadata().make_r_wrappers(true, "generation").unwrap();
+        assert!(wrappers.contains(
+            "#' Scale a number.\n#'\n#' @param x The number.\n#' @param factor The scale,\n#'   which may be negative.\n#' @return The scaled number.\n#' @export\nscale <- function(x, factor)"
+        ));
+        assert!(!wrappers.contains("assert_eq!"));
+        assert!(wrappers.contains(
+            "#' A bank account.\n#' @section Methods:\n#' \\describe{\n#' \\item{\\code{new(balance)}}{Open an account.}\n#' \\item{\\code{balance()}}{The balance of the account.}\n#' }\n#' @export\nAccount <- new.env"
+        ));
+    }
+}
+
+#[test]
+fn test_write_package_files() {
+    test! {
+        let dir = std::env::temp_dir().join("extendr_generation_package");
+        get_generation_metadata().write_package_files(&dir, "generation", true).unwrap();
+        let wrappers = std::fs::read_to_string(dir.join("R").join("extendr-wrappers.R")).unwrap();
+        assert!(wrappers.contains("add_numbers <- function(a, b) .Call(wrap__add_numbers, a, b)"));
+        let namespace = std::fs::read_to_string(dir.join("NAMESPACE")).unwrap();
+        assert!(namespace.contains("export(add_numbers)"));
+        assert!(!namespace.contains("export(internal)"));
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Only call this from `main()` if you want to run stand-alone.
 //!
-//! Its principal use is for testing.
+//! Its principal use is for testing. The [repl] module gives command-line
+//! programs an R prompt, and [script] runs R scripts as `Rscript` does.
 //!
 //! See [Rembedded.c](https://github.com/wch/r-source/blob/trunk/src/unix/Rembedded.c).
 //!
@@ -20,6 +21,9 @@ macro_rules! cstr_mut {
     };
 }
 
+pub mod repl;
+pub mod script;
+
 static START_R: Once = Once::new();
 
 pub fn start_r() {
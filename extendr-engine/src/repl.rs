@@ -0,0 +1,224 @@
+//! An R prompt for command-line programs.
+//!
+//! [run_repl] reads R code from the terminal and evaluates it in the global
+//! environment of the embedded R, printing visible results as R does. The
+//! program shares the global environment, so it can set variables before
+//! the prompt starts and read them after it ends.
+//!
+//! ```no_run
+//! use extendr_engine::repl::{run_repl, Intercept, ReplConfig};
+//!
+//! let config = ReplConfig::new()
+//!     .prompt(|| "myapp> ".to_string())
+//!     .history_file(".myapp_history")
+//!     .intercept(|line| match line.trim() {
+//!         ":quit" => Intercept::Quit,
+//!         ":help" => {
+//!             println!("Type R code, or :quit to leave.");
+//!             Intercept::Handled
+//!         }
+//!         _ => Intercept::Eval,
+//!     });
+//! run_repl(config).unwrap();
+//! ```
+
+use crate::start_r;
+use libR_sys::*;
+use std::io::{BufRead, Write};
+use std::os::raw;
+use std::path::PathBuf;
+
+/// What to do with a line, as decided by [ReplConfig::intercept].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intercept {
+    /// Evaluate the line as R code.
+    Eval,
+    /// Skip the line, the program has handled it.
+    Handled,
+    /// Leave the prompt.
+    Quit,
+}
+
+/// The settings of [run_repl].
+pub struct ReplConfig<'a> {
+    prompt: Box<dyn FnMut() -> String + 'a>,
+    continuation: String,
+    history_file: Option<PathBuf>,
+    intercept: Box<dyn FnMut(&str) -> Intercept + 'a>,
+}
+
+impl<'a> Default for ReplConfig<'a> {
+    fn default() -> Self {
+        ReplConfig::new()
+    }
+}
+
+impl<'a> ReplConfig<'a> {
+    /// The prompts of R, `> ` and `+ `, no history file and no interception.
+    pub fn new() -> Self {
+        ReplConfig {
+            prompt: Box::new(|| "> ".to_string()),
+            continuation: "+ ".to_string(),
+            history_file: None,
+            intercept: Box::new(|_| Intercept::Eval),
+        }
+    }
+
+    /// Make the prompt shown before each command.
+    pub fn prompt<F: FnMut() -> String + 'a>(mut self, prompt: F) -> Self {
+        self.prompt = Box::new(prompt);
+        self
+    }
+
+    /// Set the prompt shown while a command is incomplete.
+    pub fn continuation<S: Into<String>>(mut self, continuation: S) -> Self {
+        self.continuation = continuation.into();
+        self
+    }
+
+    /// Append each command to a file, like `.Rhistory`.
+    pub fn history_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// Decide what to do with each line that starts a command, before R sees it.
+    pub fn intercept<F: FnMut(&str) -> Intercept + 'a>(mut self, intercept: F) -> Self {
+        self.intercept = Box::new(intercept);
+        self
+    }
+}
+
+/// Run an R prompt on the terminal until end of input or [Intercept::Quit].
+///
+/// Starts R if needed. Errors in R code are printed and the prompt goes on.
+pub fn run_repl(config: ReplConfig) -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    run_repl_with(config, stdin.lock(), std::io::stdout())
+}
+
+/// Run an R prompt reading lines from `input` and writing prompts to `output`.
+///
+/// R prints results and errors to its own console as usual.
+pub fn run_repl_with<R: BufRead, W: Write>(
+    mut config: ReplConfig,
+    mut input: R,
+    mut output: W,
+) -> std::io::Result<()> {
+    start_r();
+    let mut history = match &config.history_file {
+        Some(path) => Some(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?,
+        ),
+        None => None,
+    };
+    let mut command = String::new();
+    loop {
+        if command.is_empty() {
+            write!(output, "{}", (config.prompt)())?;
+        } else {
+            write!(output, "{}", config.continuation)?;
+        }
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let line = line.trim_end_matches(&['\n', '\r'][..]);
+        if command.is_empty() {
+            match (config.intercept)(line) {
+                Intercept::Eval => (),
+                Intercept::Handled => continue,
+                Intercept::Quit => return Ok(()),
+            }
+        } else {
+            command.push('\n');
+        }
+        command.push_str(line);
+
+        match unsafe { parse_and_eval(&command) } {
+            Parsed::Incomplete => continue,
+            Parsed::Complete | Parsed::Error => {
+                if let (Some(history), false) = (&mut history, command.trim().is_empty()) {
+                    writeln!(history, "{}", command)?;
+                }
+                command.clear();
+            }
+        }
+    }
+}
+
+enum Parsed {
+    Complete,
+    Incomplete,
+    Error,
+}
+
+/// Parse `code` and, if it is complete, evaluate it and print visible results.
+unsafe fn parse_and_eval(code: &str) -> Parsed {
+    let code = match std::ffi::CString::new(code) {
+        Ok(code) => code,
+        Err(_) => return Parsed::Error,
+    };
+    let text = Rf_protect(Rf_mkString(code.as_ptr()));
+    let mut status = 0_u32;
+    let exprs = Rf_protect(R_ParseVector(text, -1, &mut status as _, R_NilValue));
+    let parsed = match status {
+        // PARSE_OK
+        1 => {
+            for i in 0..Rf_xlength(exprs) {
+                eval_and_print(VECTOR_ELT(exprs, i));
+            }
+            Parsed::Complete
+        }
+        // PARSE_INCOMPLETE
+        2 => Parsed::Incomplete,
+        _ => {
+            REprintf(cstr_mut!("Error: syntax error\n"));
+            Parsed::Error
+        }
+    };
+    Rf_unprotect(2);
+    parsed
+}
+
+/// Evaluate `expr` in the global environment and print the result if it is visible.
+unsafe fn eval_and_print(expr: SEXP) {
+    let call = Rf_protect(Rf_lang2(Rf_install(cstr_mut!("withVisible")), expr));
+    let mut error: raw::c_int = 0;
+    let res = Rf_protect(R_tryEval(call, R_GlobalEnv, &mut error));
+    if error == 0 && *LOGICAL(VECTOR_ELT(res, 1)) != 0 {
+        // print() dispatches on the class and reports its own errors.
+        let print = Rf_protect(Rf_lang2(Rf_install(cstr_mut!("print")), VECTOR_ELT(res, 0)));
+        R_tryEval(print, R_GlobalEnv, &mut error);
+        Rf_unprotect(1);
+    }
+    Rf_unprotect(2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repl() {
+        let mut seen = Vec::new();
+        let input = "x <- 1 +\n  2\n:skip\nx\n:quit\nnot reached\n".as_bytes();
+        let mut output = Vec::new();
+        let config = ReplConfig::new().intercept(|line| {
+            seen.push(line.to_string());
+            match line {
+                ":skip" => Intercept::Handled,
+                ":quit" => Intercept::Quit,
+                _ => Intercept::Eval,
+            }
+        });
+        run_repl_with(config, input, &mut output).unwrap();
+        assert_eq!(seen, ["x <- 1 +", ":skip", "x", ":quit"]);
+        assert_eq!(String::from_utf8(output).unwrap(), "> + > > > ");
+    }
+}
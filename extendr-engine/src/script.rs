@@ -0,0 +1,148 @@
+//! Run R scripts from rust, as `Rscript` does.
+//!
+//! ```no_run
+//! use extendr_engine::script::run_script;
+//!
+//! // Like `Rscript analysis.R data.csv`.
+//! let status = match run_script("analysis.R", &["data.csv"]) {
+//!     Ok(()) => 0,
+//!     Err(e) => {
+//!         eprintln!("{}", e);
+//!         e.exit_code()
+//!     }
+//! };
+//! std::process::exit(status);
+//! ```
+
+use crate::start_r;
+use libR_sys::*;
+use std::ffi::CString;
+use std::os::raw;
+use std::path::Path;
+
+extern "C" {
+    // From R_ext/RStartup.h. R copies the strings.
+    fn R_set_command_line_arguments(argc: raw::c_int, argv: *mut *mut raw::c_char);
+}
+
+/// An error in an R script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// The message of the error, as from `geterrmessage()`.
+    pub message: String,
+}
+
+impl ScriptError {
+    /// The exit status `Rscript` gives for an error, 1.
+    pub fn exit_code(&self) -> i32 {
+        1
+    }
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message.trim_end())
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Run the script at `path` in the global environment, with `commandArgs()` as `Rscript` sets it.
+///
+/// `commandArgs(trailingOnly = TRUE)` gives `args`. Starts R if needed.
+/// An error in the script is printed by R and returned, with the rest of
+/// the script skipped. Note that `quit()` in the script ends the program.
+pub fn run_script<P: AsRef<Path>, S: AsRef<str>>(path: P, args: &[S]) -> Result<(), ScriptError> {
+    start_r();
+    let path = path.as_ref();
+    let mut command_line = vec![
+        "R".to_string(),
+        "--no-echo".to_string(),
+        "--no-restore".to_string(),
+        format!("--file={}", path.display()),
+        "--args".to_string(),
+    ];
+    command_line.extend(args.iter().map(|arg| arg.as_ref().to_string()));
+    set_command_args(&command_line)?;
+    unsafe { source(path, R_GlobalEnv) }
+}
+
+/// Run the script at `path` in the environment `env`, like `source(path, local = env)`.
+///
+/// Starts R if needed.
+///
+/// # Safety
+///
+/// `env` must be an R environment that is protected from garbage collection.
+pub unsafe fn source<P: AsRef<Path>>(path: P, env: SEXP) -> Result<(), ScriptError> {
+    start_r();
+    let path = c_string(&path.as_ref().to_string_lossy())?;
+    let path = Rf_protect(Rf_mkString(path.as_ptr()));
+    let call = Rf_protect(Rf_lang3(Rf_install(cstr_mut!("source")), path, env));
+    SET_TAG(CDDR(call), Rf_install(cstr_mut!("local")));
+    let mut error: raw::c_int = 0;
+    R_tryEval(call, R_GlobalEnv, &mut error);
+    Rf_unprotect(2);
+    if error == 0 {
+        Ok(())
+    } else {
+        Err(ScriptError {
+            message: last_error_message(),
+        })
+    }
+}
+
+fn set_command_args(command_line: &[String]) -> Result<(), ScriptError> {
+    let args = command_line
+        .iter()
+        .map(|arg| c_string(arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut argv: Vec<*mut raw::c_char> = args.iter().map(|arg| arg.as_ptr() as *mut _).collect();
+    unsafe { R_set_command_line_arguments(argv.len() as raw::c_int, argv.as_mut_ptr()) };
+    Ok(())
+}
+
+fn c_string(s: &str) -> Result<CString, ScriptError> {
+    CString::new(s).map_err(|_| ScriptError {
+        message: format!("nul character in '{}'", s.replace('\0', "\\0")),
+    })
+}
+
+unsafe fn last_error_message() -> String {
+    let call = Rf_protect(Rf_lang1(Rf_install(cstr_mut!("geterrmessage"))));
+    let mut error: raw::c_int = 0;
+    let message = R_tryEval(call, R_BaseEnv, &mut error);
+    let res = if error == 0 && TYPEOF(message) == STRSXP as _ && Rf_xlength(message) > 0 {
+        let chars = R_CHAR(STRING_ELT(message, 0));
+        std::ffi::CStr::from_ptr(chars)
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        "unknown error".to_string()
+    };
+    Rf_unprotect(1);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_script() {
+        let dir = std::env::temp_dir();
+        let good = dir.join("extendr_engine_good.R");
+        std::fs::write(
+            &good,
+            "args <- commandArgs(trailingOnly = TRUE)\nstopifnot(identical(args, c('a', 'b c')))\n",
+        )
+        .unwrap();
+        assert_eq!(run_script(&good, &["a", "b c"]), Ok(()));
+
+        let bad = dir.join("extendr_engine_bad.R");
+        std::fs::write(&bad, "stop('oops')\n").unwrap();
+        let err = run_script(&bad, &[] as &[&str]).unwrap_err();
+        assert!(err.message.contains("oops"));
+        assert_eq!(err.exit_code(), 1);
+    }
+}
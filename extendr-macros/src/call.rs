@@ -1,7 +1,9 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse::ParseStream, punctuated::Punctuated, Token};
-use syn::{parse_macro_input, parse_quote, Expr, ExprAssign, ExprPath, LitStr};
+use syn::{parse_macro_input, parse_quote, Expr, LitStr};
+
+use crate::pairs::name_and_value;
 
 #[derive(Debug)]
 struct Call {
@@ -36,15 +38,8 @@ pub fn call(item: TokenStream) -> TokenStream {
         .pairs
         .iter()
         .map(|e| {
-            if let Expr::Assign(ExprAssign { left, right, .. }) = e {
-                if let Expr::Path(ExprPath { path, .. }) = &**left {
-                    if let Some(ident) = path.get_ident() {
-                        let s = ident.to_string();
-                        return parse_quote!( (#s, extendr_api::Robj::from(#right)) );
-                    }
-                }
-            }
-            parse_quote!( ("", extendr_api::Robj::from(#e)) )
+            let (name, value) = name_and_value(e);
+            parse_quote!( (#name, extendr_api::Robj::from(#value)) )
         })
         .collect::<Vec<Expr>>();
 
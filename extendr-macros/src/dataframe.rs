@@ -1,12 +1,62 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput};
+use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Field, Lit};
+
+/// What to do with an `NA` read into a field that is not an `Option`.
+#[derive(PartialEq)]
+enum NaPolicy {
+    Error,
+    Default,
+}
+
+/// Options from `#[dataframe(rename = "name", na = "error" | "default")]` on a field.
+struct FieldOptions {
+    column: String,
+    na: NaPolicy,
+}
+
+fn field_options(field: &Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions {
+        column: field.ident.as_ref().unwrap().to_string(),
+        na: NaPolicy::Error,
+    };
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("dataframe"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let value = match meta.value()?.parse()? {
+                Lit::Str(litstr) => litstr.value(),
+                _ => return Err(meta.error("expected a string")),
+            };
+            if meta.path.is_ident("rename") {
+                options.column = value;
+            } else if meta.path.is_ident("na") {
+                options.na = match value.as_str() {
+                    "error" => NaPolicy::Error,
+                    "default" => NaPolicy::Default,
+                    _ => return Err(meta.error("expected na = \"error\" or na = \"default\"")),
+                };
+            } else {
+                return Err(meta.error("expected #[dataframe(rename = \"name\", na = \"policy\")]"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
 
 fn parse_struct(input: &DeriveInput, datastruct: &DataStruct) -> TokenStream {
     let structname = &input.ident;
     let mut a = Vec::new();
+    let mut columns = Vec::new();
     for f in &datastruct.fields {
         a.push(f.ident.clone());
+        match field_options(f) {
+            Ok(options) => columns.push(options.column),
+            Err(e) => return e.to_compile_error().into(),
+        }
     }
     quote! {
         impl IntoDataFrameRow<#structname> for Vec<#structname>
@@ -16,11 +66,11 @@ fn parse_struct(input: &DeriveInput, datastruct: &DataStruct) -> TokenStream {
                 for val in self {
                     #(#a.push(val.#a);)*
                 }
-                let caller = eval_string("data.frame")?;
-                let res = caller.call(Pairlist::from_pairs(&[
-                    #((stringify!(#a), extendr_api::robj::Robj::from(#a))),*
-                ]))?;
-                res.try_into()
+                // Build the columns directly, as `data.frame()` would split list columns.
+                let res = extendr_api::DataframeBuilder::new()
+                    #(.column(#columns, extendr_api::robj::Robj::from(#a)))*
+                    .build()?;
+                extendr_api::GetSexp::as_robj(&res).try_into()
             }
         }
 
@@ -34,11 +84,10 @@ fn parse_struct(input: &DeriveInput, datastruct: &DataStruct) -> TokenStream {
                 for val in self.0 {
                     #(#a.push(val.#a);)*
                 }
-                let caller = eval_string("data.frame")?;
-                let res = caller.call(Pairlist::from_pairs(&[
-                    #((stringify!(#a), extendr_api::robj::Robj::from(#a))),*
-                ]))?;
-                res.try_into()
+                let res = extendr_api::DataframeBuilder::new()
+                    #(.column(#columns, extendr_api::robj::Robj::from(#a)))*
+                    .build()?;
+                extendr_api::GetSexp::as_robj(&res).try_into()
             }
         }
     }
@@ -53,3 +102,54 @@ pub fn derive_into_dataframe(item: TokenStream) -> TokenStream {
         _ => quote!(compile_error("IntoDataFrameRow expected a struct.")).into(),
     }
 }
+
+fn parse_struct_rows(input: &DeriveInput, datastruct: &DataStruct) -> TokenStream {
+    let structname = &input.ident;
+    let mut columns = Vec::new();
+    let mut reads = Vec::new();
+    for (i, f) in datastruct.fields.iter().enumerate() {
+        let options = match field_options(f) {
+            Ok(options) => options,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let ident = &f.ident;
+        let ty = &f.ty;
+        let read = if options.na == NaPolicy::Default {
+            quote!(<Option<#ty> as extendr_api::FromColumnElt>::from_column_elt(&columns[#i], row)?.unwrap_or_default())
+        } else {
+            quote!(<#ty as extendr_api::FromColumnElt>::from_column_elt(&columns[#i], row)?)
+        };
+        columns.push(options.column);
+        reads.push(quote!(#ident: #read));
+    }
+    quote! {
+        impl extendr_api::FromDataframeRow for #structname {
+            fn select_columns(columns: &extendr_api::List) -> extendr_api::Result<Vec<extendr_api::Robj>> {
+                let names: Vec<&str> = extendr_api::Attributes::names(columns).map(|names| names.collect()).unwrap_or_default();
+                [#(#columns),*]
+                    .iter()
+                    .map(|column| match names.iter().position(|name| name == column) {
+                        Some(i) => columns.elt(i),
+                        None => Err(extendr_api::Error::NotFound(extendr_api::Robj::from(*column))),
+                    })
+                    .collect()
+            }
+
+            fn from_row(columns: &[extendr_api::Robj], row: usize) -> extendr_api::Result<Self> {
+                Ok(#structname {
+                    #(#reads,)*
+                })
+            }
+        }
+    }
+    .into()
+}
+
+pub fn derive_from_dataframe_row(item: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(item as DeriveInput);
+
+    match &input.data {
+        Data::Struct(datastruct) => parse_struct_rows(&input, datastruct),
+        _ => quote!(compile_error!("FromDataframeRow expected a struct.")).into(),
+    }
+}
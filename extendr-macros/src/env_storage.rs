@@ -0,0 +1,63 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Fields, ItemStruct};
+
+/// Implement `EnvStorage` for a struct marked `#[extendr(storage = "environment")]`.
+///
+/// Each named field is stored as a variable of the same name in the environment.
+pub fn env_storage(item_struct: ItemStruct) -> TokenStream {
+    if !item_struct.generics.params.is_empty() {
+        return quote! { compile_error!("generic structs can not use environment storage"); }
+            .into();
+    }
+
+    let fields = match &item_struct.fields {
+        Fields::Named(fields) => &fields.named,
+        _ => {
+            return quote! { compile_error!("environment storage needs a struct with named fields"); }
+                .into()
+        }
+    };
+
+    let struct_name = &item_struct.ident;
+    let mut field_strs = Vec::<String>::with_capacity(fields.len());
+    let mut store = Vec::<TokenStream2>::with_capacity(fields.len());
+    let mut load = Vec::<TokenStream2>::with_capacity(fields.len());
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_ty = &field.ty;
+        let field_str = field_name.to_string();
+        store.push(quote!(
+            env.set_local(
+                extendr_api::Symbol::from_string(#field_str),
+                extendr_api::Robj::from(self.#field_name.clone()),
+            );
+        ));
+        load.push(quote!(
+            #field_name: <#field_ty as std::convert::TryFrom<extendr_api::Robj>>::try_from(
+                extendr_api::storage::load_field(env, #field_str)?
+            )?
+        ));
+        field_strs.push(field_str);
+    }
+
+    TokenStream::from(quote!(
+        #item_struct
+
+        impl extendr_api::storage::EnvStorage for #struct_name {
+            const FIELDS: &'static [&'static str] = &[#(#field_strs),*];
+
+            fn store(&self, env: &extendr_api::Environment) -> extendr_api::Result<()> {
+                #(#store)*
+                Ok(())
+            }
+
+            fn load(env: &extendr_api::Environment) -> extendr_api::Result<Self> {
+                Ok(#struct_name {
+                    #(#load),*
+                })
+            }
+        }
+    ))
+}
@@ -23,10 +23,36 @@ impl wrappers::ExtendrOptions {
     /// - `use_try_from = bool` which uses `TryFrom<Robj>` for argument conversions.
     /// - `r_name = "name"` which specifies the name of the wrapper on the R-side.
     /// - `use_rng = bool` ensures the RNG-state is pulled and pushed
+    /// - `storage = "environment"` stores the fields of a struct in an R environment
+    ///   instead of an external pointer. Use it on both the struct and its impl block.
+    /// - `serialize` on an impl block keeps a serde copy of the object's state so that
+    ///   it survives `saveRDS()`. Needs the `serde` feature.
+    /// - `r6` on an impl block makes the R wrapper an R6 class, with `$new()`,
+    ///   methods and active bindings for getter and setter pairs.
+    /// - `coerce = "lenient"` converts arguments of the wrong numeric type, such
+    ///   as an integer vector for a `f64` or a whole double for an `i32`, as R
+    ///   functions would. `coerce = "strict"` rejects them. Without either, the R
+    ///   option `extendr.coerce` decides when the function is called, and is strict
+    ///   by default. On an impl block it applies to all of the methods.
     ///
     pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::parse::Result<()> {
         fn help_message() -> ! {
-            panic!("expected #[extendr(use_try_from = bool, r_name = \"name\", mod_name = \"r_mod_name\", use_rng = bool)]");
+            panic!("expected #[extendr(use_try_from = bool, r_name = \"name\", mod_name = \"r_mod_name\", use_rng = bool, storage = \"environment\", serialize, r6, export = bool, coerce = \"lenient\")]");
+        }
+
+        if meta.path.is_ident("serialize") {
+            self.serialize = true;
+            return Ok(());
+        }
+
+        if meta.path.is_ident("r6") {
+            self.r6 = true;
+            return Ok(());
+        }
+
+        if meta.path.is_ident("export") && !meta.input.peek(syn::Token![=]) {
+            self.export = Some(true);
+            return Ok(());
         }
 
         let value = match meta.value() {
@@ -55,6 +81,13 @@ impl wrappers::ExtendrOptions {
             } else {
                 help_message();
             }
+        } else if meta.path.is_ident("export") {
+            if let Ok(LitBool { value, .. }) = value.parse() {
+                self.export = Some(value);
+                Ok(())
+            } else {
+                help_message();
+            }
         } else if meta.path.is_ident("use_rng") {
             if let Ok(LitBool { value, .. }) = value.parse() {
                 self.use_rng = value;
@@ -62,6 +95,30 @@ impl wrappers::ExtendrOptions {
             } else {
                 help_message();
             }
+        } else if meta.path.is_ident("coerce") {
+            match value.parse() {
+                Ok(Lit::Str(litstr)) if litstr.value() == "lenient" => {
+                    self.coerce_lenient = Some(true);
+                    Ok(())
+                }
+                Ok(Lit::Str(litstr)) if litstr.value() == "strict" => {
+                    self.coerce_lenient = Some(false);
+                    Ok(())
+                }
+                _ => help_message(),
+            }
+        } else if meta.path.is_ident("storage") {
+            match value.parse() {
+                Ok(Lit::Str(litstr)) if litstr.value() == "environment" => {
+                    self.env_storage = true;
+                    Ok(())
+                }
+                Ok(Lit::Str(litstr)) if litstr.value() == "external_pointer" => {
+                    self.env_storage = false;
+                    Ok(())
+                }
+                _ => help_message(),
+            }
         } else {
             help_message();
         }
@@ -1,7 +1,7 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{ItemFn, ItemImpl};
+use syn::{parse_quote, ItemFn, ItemImpl};
 
 use crate::wrappers;
 
@@ -36,7 +36,23 @@ use crate::wrappers;
 ///     fn aux_func;
 /// }
 /// ```
-pub fn extendr_impl(mut item_impl: ItemImpl) -> TokenStream {
+///
+/// A method taking `self` by value, such as a `build(self)` that finalizes a
+/// builder, moves the object out of its external pointer and clears it, so
+/// later calls from R fail with an "object already consumed" error. With
+/// environment storage the method gets a copy and the object is unchanged.
+///
+/// With `#[extendr(storage = "environment")]` the object is an R environment
+/// holding the fields (see `extendr_api::storage`) rather than an external pointer.
+///
+/// With `#[extendr(serialize)]` the external pointer also holds the serialized
+/// state of the object so it can be restored after `saveRDS()` (see `extendr_api::persist`).
+///
+/// With `#[extendr(r6)]` the generated R wrapper is an R6 class, so the package
+/// needs to import R6. `Person$new()` calls `new()`, the object holds the
+/// external pointer in `$.ptr` and `name(&self)` with `set_name(&mut self, ..)`
+/// become the active binding `$name`.
+pub fn extendr_impl(mut item_impl: ItemImpl, impl_opts: &wrappers::ExtendrOptions) -> TokenStream {
     // Only `impl name { }` allowed
     if item_impl.defaultness.is_some() {
         return quote! { compile_error!("default not allowed in #[extendr] impl"); }.into();
@@ -62,12 +78,28 @@ pub fn extendr_impl(mut item_impl: ItemImpl) -> TokenStream {
         return quote! { compile_error!("where clause not allowed in #[extendr] impl"); }.into();
     }
 
-    let opts = wrappers::ExtendrOptions::default();
+    if impl_opts.env_storage && impl_opts.serialize {
+        return quote! { compile_error!("serialize is not needed with environment storage"); }
+            .into();
+    }
+
+    if impl_opts.env_storage && impl_opts.r6 {
+        return quote! { compile_error!("r6 classes hold an external pointer, not environment storage"); }
+            .into();
+    }
+
+    let opts = wrappers::ExtendrOptions {
+        env_storage: impl_opts.env_storage,
+        serialize: impl_opts.serialize,
+        coerce_lenient: impl_opts.coerce_lenient,
+        ..Default::default()
+    };
     let self_ty = item_impl.self_ty.as_ref();
     let self_ty_name = wrappers::type_name(self_ty);
     let prefix = format!("{}__", self_ty_name);
     let mut method_meta_names = Vec::new();
-    let doc_string = wrappers::get_doc_string(&item_impl.attrs);
+    let doc_string =
+        wrappers::with_export_tag(wrappers::get_doc_string(&item_impl.attrs), impl_opts.export);
 
     // Generate wrappers for methods.
     // eg.
@@ -82,6 +114,20 @@ pub fn extendr_impl(mut item_impl: ItemImpl) -> TokenStream {
     // }
     // ```
     let mut wrappers: Vec<ItemFn> = Vec::new();
+    if opts.serialize {
+        item_impl.items.push(parse_quote! {
+            /// Get the state of the object as an R list.
+            fn serialize(&self) -> extendr_api::Result<extendr_api::Robj> {
+                extendr_api::serializer::to_robj(self)
+            }
+        });
+        item_impl.items.push(parse_quote! {
+            /// Make an object from the output of `$serialize()`.
+            fn deserialize(state: extendr_api::Robj) -> extendr_api::Result<Self> {
+                extendr_api::deserializer::from_robj(&state)
+            }
+        });
+    }
     for impl_item in &mut item_impl.items {
         if let syn::ImplItem::Fn(ref mut method) = impl_item {
             method_meta_names.push(format_ident!(
@@ -105,74 +151,180 @@ pub fn extendr_impl(mut item_impl: ItemImpl) -> TokenStream {
 
     let finalizer_name = format_ident!("__finalize__{}", self_ty_name);
 
-    let expanded = TokenStream::from(quote! {
-        // The impl itself copied from the source.
-        #item_impl
+    let conversions = if opts.env_storage {
+        quote! {
+            // Input conversion function for this type.
+            impl<'a> extendr_api::FromRobj<'a> for #self_ty {
+                fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                    extendr_api::storage::from_env_object(robj, #self_ty_name)
+                        .map_err(|_| concat!("expected ", #self_ty_name))
+                }
+            }
 
-        // Function wrappers
-        #( #wrappers )*
+            // Output conversion function for this type.
+            impl From<#self_ty> for Robj {
+                fn from(value: #self_ty) -> Self {
+                    extendr_api::storage::to_env_object(&value, #self_ty_name).unwrap()
+                }
+            }
 
-        // Input conversion function for this type.
-        impl<'a> extendr_api::FromRobj<'a> for &#self_ty {
-            fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
-                if robj.check_external_ptr_type::<#self_ty>() {
-                    #[allow(clippy::transmute_ptr_to_ref)]
-                    Ok(unsafe { std::mem::transmute(robj.external_ptr_addr::<#self_ty>()) })
-                } else {
-                    Err(concat!("expected ", #self_ty_name))
+            // Output conversion function for this type.
+            impl<'a> From<&'a #self_ty> for Robj {
+                fn from(value: &'a #self_ty) -> Self {
+                    extendr_api::storage::to_env_object(value, #self_ty_name).unwrap()
                 }
             }
         }
+    } else if opts.serialize {
+        quote! {
+            // Input conversion function for this type.
+            impl<'a> extendr_api::FromRobj<'a> for &#self_ty {
+                fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                    if robj.check_external_ptr_type::<#self_ty>() {
+                        // Rebuilds the object if it was loaded with readRDS().
+                        match unsafe { extendr_api::persist::restore::<#self_ty>(robj, Some(#finalizer_name)) } {
+                            Ok(ptr) => Ok(unsafe { &*ptr }),
+                            Err(_) => Err(concat!("expected ", #self_ty_name)),
+                        }
+                    } else {
+                        Err(concat!("expected ", #self_ty_name))
+                    }
+                }
+            }
 
-        // Input conversion function for a reference to this type.
-        impl<'a> extendr_api::FromRobj<'a> for &mut #self_ty {
-            fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
-                if robj.check_external_ptr_type::<#self_ty>() {
-                    #[allow(clippy::transmute_ptr_to_ref)]
-                    Ok(unsafe { std::mem::transmute(robj.external_ptr_addr::<#self_ty>()) })
-                } else {
-                    Err(concat!("expected ", #self_ty_name))
+            // Input conversion function for a reference to this type.
+            impl<'a> extendr_api::FromRobj<'a> for &mut #self_ty {
+                fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                    if robj.check_external_ptr_type::<#self_ty>() {
+                        match unsafe { extendr_api::persist::restore::<#self_ty>(robj, Some(#finalizer_name)) } {
+                            Ok(ptr) => Ok(unsafe { &mut *ptr }),
+                            Err(_) => Err(concat!("expected ", #self_ty_name)),
+                        }
+                    } else {
+                        Err(concat!("expected ", #self_ty_name))
+                    }
                 }
             }
-        }
 
-        // Output conversion function for this type.
-        impl From<#self_ty> for Robj {
-            fn from(value: #self_ty) -> Self {
-                unsafe {
-                    let ptr = Box::into_raw(Box::new(value));
-                    let mut res = Robj::make_external_ptr(ptr, Robj::from(()));
-                    res.set_attrib(class_symbol(), #self_ty_name).unwrap();
-                    res.register_c_finalizer(Some(#finalizer_name));
-                    res
+            // Output conversion function for this type.
+            impl From<#self_ty> for Robj {
+                fn from(value: #self_ty) -> Self {
+                    unsafe {
+                        extendr_api::persist::make_persistent_ptr(value, #self_ty_name, Some(#finalizer_name))
+                            .unwrap()
+                    }
                 }
             }
-        }
 
-        // Output conversion function for this type.
-        impl<'a> From<&'a #self_ty> for Robj {
-            fn from(value: &'a #self_ty) -> Self {
+            // Output conversion function for a reference to this type.
+            impl<'a> From<&'a #self_ty> for Robj {
+                fn from(value: &'a #self_ty) -> Self {
+                    extendr_api::persist::make_persistent_state(value, #self_ty_name).unwrap()
+                }
+            }
+
+            // Function to free memory for this type.
+            extern "C" fn #finalizer_name (sexp: extendr_api::SEXP) {
                 unsafe {
-                    let ptr = Box::into_raw(Box::new(value));
-                    let mut res = Robj::make_external_ptr(ptr, Robj::from(()));
-                    res.set_attrib(class_symbol(), #self_ty_name).unwrap();
-                    res.register_c_finalizer(Some(#finalizer_name));
-                    res
+                    let robj = extendr_api::robj::Robj::from_sexp(sexp);
+                    if robj.check_external_ptr_type::<#self_ty>() {
+                        let ptr = robj.external_ptr_addr::<#self_ty>();
+                        if !ptr.is_null() {
+                            drop(Box::from_raw(ptr));
+                        }
+                    }
                 }
             }
         }
+    } else {
+        quote! {
+            // Input conversion function for this type.
+            impl<'a> extendr_api::FromRobj<'a> for &#self_ty {
+                fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                    if robj.check_external_ptr_type::<#self_ty>() {
+                        let ptr = unsafe { robj.external_ptr_addr::<#self_ty>() };
+                        if ptr.is_null() {
+                            // Taken by a method with `self` by value.
+                            return Err(concat!(#self_ty_name, " object already consumed"));
+                        }
+                        #[allow(clippy::transmute_ptr_to_ref)]
+                        Ok(unsafe { std::mem::transmute(ptr) })
+                    } else {
+                        Err(concat!("expected ", #self_ty_name))
+                    }
+                }
+            }
+
+            // Input conversion function for a reference to this type.
+            impl<'a> extendr_api::FromRobj<'a> for &mut #self_ty {
+                fn from_robj(robj: &'a Robj) -> std::result::Result<Self, &'static str> {
+                    if robj.check_external_ptr_type::<#self_ty>() {
+                        let ptr = unsafe { robj.external_ptr_addr::<#self_ty>() };
+                        if ptr.is_null() {
+                            // Taken by a method with `self` by value.
+                            return Err(concat!(#self_ty_name, " object already consumed"));
+                        }
+                        #[allow(clippy::transmute_ptr_to_ref)]
+                        Ok(unsafe { std::mem::transmute(ptr) })
+                    } else {
+                        Err(concat!("expected ", #self_ty_name))
+                    }
+                }
+            }
+
+            // Output conversion function for this type.
+            impl From<#self_ty> for Robj {
+                fn from(value: #self_ty) -> Self {
+                    unsafe {
+                        let ptr = Box::into_raw(Box::new(value));
+                        let mut res = Robj::make_external_ptr(ptr, Robj::from(()));
+                        res.set_attrib(class_symbol(), #self_ty_name).unwrap();
+                        res.register_c_finalizer(Some(#finalizer_name));
+                        res
+                    }
+                }
+            }
+
+            // Output conversion function for this type.
+            impl<'a> From<&'a #self_ty> for Robj {
+                fn from(value: &'a #self_ty) -> Self {
+                    unsafe {
+                        let ptr = Box::into_raw(Box::new(value));
+                        let mut res = Robj::make_external_ptr(ptr, Robj::from(()));
+                        res.set_attrib(class_symbol(), #self_ty_name).unwrap();
+                        res.register_c_finalizer(Some(#finalizer_name));
+                        res
+                    }
+                }
+            }
 
-        // Function to free memory for this type.
-        extern "C" fn #finalizer_name (sexp: extendr_api::SEXP) {
-            unsafe {
-                let robj = extendr_api::robj::Robj::from_sexp(sexp);
-                if robj.check_external_ptr_type::<#self_ty>() {
-                    //eprintln!("finalize {}", #self_ty_name);
-                    let ptr = robj.external_ptr_addr::<#self_ty>();
-                    drop(Box::from_raw(ptr));
+            // Function to free memory for this type.
+            extern "C" fn #finalizer_name (sexp: extendr_api::SEXP) {
+                unsafe {
+                    let robj = extendr_api::robj::Robj::from_sexp(sexp);
+                    if robj.check_external_ptr_type::<#self_ty>() {
+                        //eprintln!("finalize {}", #self_ty_name);
+                        let ptr = robj.external_ptr_addr::<#self_ty>();
+                        if !ptr.is_null() {
+                            drop(Box::from_raw(ptr));
+                        }
+                    }
                 }
             }
         }
+    };
+
+    let env_storage = opts.env_storage;
+    let r6 = impl_opts.r6;
+
+    let expanded = TokenStream::from(quote! {
+        // The impl itself copied from the source.
+        #item_impl
+
+        // Function wrappers
+        #( #wrappers )*
+
+        #conversions
 
         #[allow(non_snake_case)]
         fn #meta_name(impls: &mut Vec<extendr_api::metadata::Impl>) {
@@ -182,6 +334,8 @@ pub fn extendr_impl(mut item_impl: ItemImpl) -> TokenStream {
                 doc: #doc_string,
                 name: #self_ty_name,
                 methods,
+                env_storage: #env_storage,
+                r6: #r6,
             });
         }
     });
@@ -1,7 +1,7 @@
 use crate::wrappers;
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse::ParseStream, parse_macro_input, Ident, Token, Type};
+use syn::{parse::ParseStream, parse_macro_input, Ident, LitStr, Token, Type};
 
 pub fn extendr_module(item: TokenStream) -> TokenStream {
     let module = parse_macro_input!(item as Module);
@@ -10,10 +10,13 @@ pub fn extendr_module(item: TokenStream) -> TokenStream {
         fnnames,
         implnames,
         usenames,
+        altrepnames,
+        r_code,
     } = module;
     let modname = modname.unwrap();
     let modname_string = modname.to_string();
     let module_init_name = format_ident!("R_init_{}_extendr", modname);
+    let module_unload_name = format_ident!("R_unload_{}_extendr", modname);
 
     let module_metadata_name = format_ident!("get_{}_metadata", modname);
     let module_metadata_name_string = module_metadata_name.to_string();
@@ -25,6 +28,16 @@ pub fn extendr_module(item: TokenStream) -> TokenStream {
     let wrap_make_module_wrappers =
         format_ident!("{}make_{}_wrappers", wrappers::WRAP_PREFIX, modname);
 
+    let make_module_namespace_name = format_ident!("make_{}_namespace", modname);
+    let make_module_namespace_name_string = make_module_namespace_name.to_string();
+    let wrap_make_module_namespace =
+        format_ident!("{}make_{}_namespace", wrappers::WRAP_PREFIX, modname);
+
+    let register_altrep_name = format_ident!("register_{}_altrep_classes", modname);
+    let use_register_altrep_names = usenames
+        .iter()
+        .map(|id| format_ident!("register_{}_altrep_classes", id));
+
     let fnmetanames = fnnames
         .iter()
         .map(|id| format_ident!("{}{}", wrappers::META_PREFIX, id));
@@ -36,6 +49,13 @@ pub fn extendr_module(item: TokenStream) -> TokenStream {
         .map(|id| format_ident!("get_{}_metadata", id))
         .collect::<Vec<Ident>>();
 
+    let r_code = r_code.iter().map(|code| match code {
+        RCode::Source(path) => quote!(include_str!(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/", #path)
+        )),
+        RCode::Inline(code) => quote!(#code),
+    });
+
     TokenStream::from(quote! {
         #[no_mangle]
         #[allow(non_snake_case)]
@@ -78,10 +98,31 @@ pub fn extendr_module(item: TokenStream) -> TokenStream {
                 hidden: true,
             });
 
+            // Add this function to the list, but set hidden: true.
+            functions.push(extendr_api::metadata::Func {
+                doc: "NAMESPACE generator.",
+                rust_name: #make_module_namespace_name_string,
+                mod_name: #make_module_namespace_name_string,
+                r_name: #make_module_namespace_name_string,
+                args: vec![
+                    extendr_api::metadata::Arg { name: "package_name", arg_type: "&str", default: None },
+                    ],
+                return_type: "String",
+                func_ptr: #wrap_make_module_namespace as * const u8,
+                hidden: true,
+            });
+
+            // R code from this module first, then from the submodules.
+            let r_code: Vec<&'static str> = [#(#r_code),*]
+                .into_iter()
+                #( .chain(#usenames::#usemetanames().r_code) )*
+                .collect();
+
             extendr_api::metadata::Metadata {
                 name: #modname_string,
                 functions,
                 impls,
+                r_code,
             }
         }
 
@@ -117,10 +158,52 @@ pub fn extendr_module(item: TokenStream) -> TokenStream {
             }
         }
 
+        #[no_mangle]
+        #[allow(non_snake_case, clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn #wrap_make_module_namespace(
+            package_name_sexp: extendr_api::SEXP,
+        ) -> extendr_api::SEXP {
+            unsafe {
+                use extendr_api::robj::*;
+                use extendr_api::GetSexp;
+                let robj = Robj::from_sexp(package_name_sexp);
+                let package_name: &str = <&str>::from_robj(&robj).unwrap();
+
+                extendr_api::Robj::from(
+                    #module_metadata_name().make_namespace(package_name)
+                ).get()
+            }
+        }
+
+        /// Register the ALTREP classes of this module and its submodules.
+        ///
+        /// # Safety
+        ///
+        /// `info` must be null or the `DllInfo` that R passed to the init function.
+        #[allow(non_snake_case, unused_variables)]
+        pub unsafe fn #register_altrep_name(info: * mut extendr_api::DllInfo, package: &str) {
+            #( extendr_api::Altrep::register_class::<#altrepnames>(info, package); )*
+            #( #usenames::#use_register_altrep_names(info, package); )*
+        }
+
         #[no_mangle]
         #[allow(non_snake_case, clippy::not_unsafe_ptr_arg_deref)]
         pub extern "C" fn #module_init_name(info: * mut extendr_api::DllInfo) {
-            unsafe { extendr_api::register_call_methods(info, #module_metadata_name()) };
+            let metadata = #module_metadata_name();
+            let r_code = metadata.r_code.clone();
+            unsafe { extendr_api::register_call_methods(info, metadata) };
+            unsafe { #register_altrep_name(info, #modname_string) };
+            unsafe { extendr_api::handles::init_registry(info) };
+            if let Err(e) = extendr_api::eval_module_r_code(#modname_string, &r_code) {
+                extendr_api::throw_r_error(e.to_string());
+            }
+        }
+
+        #[no_mangle]
+        #[allow(non_snake_case, clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn #module_unload_name(_info: * mut extendr_api::DllInfo) {
+            extendr_api::handles::release_all();
+            extendr_api::cache::clear();
         }
     })
 }
@@ -131,6 +214,15 @@ struct Module {
     fnnames: Vec<Ident>,
     implnames: Vec<Type>,
     usenames: Vec<Ident>,
+    altrepnames: Vec<Type>,
+    r_code: Vec<RCode>,
+}
+
+/// R code given by `r_source = "file.R"` or `r_code = "..."`.
+#[derive(Debug)]
+enum RCode {
+    Source(LitStr),
+    Inline(LitStr),
 }
 
 // Custom parser for the module.
@@ -142,6 +234,8 @@ impl syn::parse::Parse for Module {
             fnnames: Vec::new(),
             implnames: Vec::new(),
             usenames: Vec::new(),
+            altrepnames: Vec::new(),
+            r_code: Vec::new(),
         };
         while !input.is_empty() {
             if let Ok(kmod) = input.parse::<Token![mod]>() {
@@ -156,8 +250,23 @@ impl syn::parse::Parse for Module {
                 res.implnames.push(input.parse()?);
             } else if input.parse::<Token![use]>().is_ok() {
                 res.usenames.push(input.parse()?);
+            } else if input.peek(Ident) && input.fork().parse::<Ident>()? == "altrep" {
+                input.parse::<Ident>()?;
+                res.altrepnames.push(input.parse()?);
+            } else if input.peek(Ident) && input.peek2(Token![=]) {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                match key.to_string().as_str() {
+                    "r_source" => res.r_code.push(RCode::Source(value)),
+                    "r_code" => res.r_code.push(RCode::Inline(value)),
+                    _ => return Err(syn::Error::new(key.span(), "expected r_source or r_code")),
+                }
             } else {
-                return Err(syn::Error::new(input.span(), "expected mod, fn or impl"));
+                return Err(syn::Error::new(
+                    input.span(),
+                    "expected mod, fn, impl, use, altrep, r_source or r_code",
+                ));
             }
 
             input.parse::<Token![;]>()?;
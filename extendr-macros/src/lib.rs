@@ -58,6 +58,7 @@
 mod R;
 mod call;
 mod dataframe;
+mod env_storage;
 mod extendr_function;
 mod extendr_impl;
 mod extendr_module;
@@ -80,7 +81,8 @@ pub fn extendr(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     match parse_macro_input!(item as Item) {
         Item::Fn(func) => extendr_function::extendr_function(func, &opts),
-        Item::Impl(item_impl) => extendr_impl::extendr_impl(item_impl),
+        Item::Impl(item_impl) => extendr_impl::extendr_impl(item_impl, &opts),
+        Item::Struct(item_struct) if opts.env_storage => env_storage::env_storage(item_struct),
         other_item => TokenStream::from(quote! {#other_item}),
     }
 }
@@ -95,6 +97,33 @@ pub fn extendr(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///     impl MyTrait;
 /// }
 /// ```
+///
+/// R code can be bundled with the module and is evaluated in the package
+/// namespace when the library is loaded, so small R helpers can live in the
+/// rust crate. `r_source` paths are relative to the crate's `Cargo.toml`
+/// and are included at compile time.
+///```ignore
+/// extendr_module! {
+///     mod name;
+///     fn my_func1;
+///     r_source = "R/helpers.R";
+///     r_code = "my_func1_twice <- function(x) my_func1(my_func1(x))";
+/// }
+/// ```
+///
+/// ALTREP classes, types that implement `AltrepClass`, are registered with
+/// R when the library is loaded, so that saved vectors can be read back.
+///```ignore
+/// extendr_module! {
+///     mod name;
+///     fn squares;
+///     altrep Squares;
+/// }
+/// ```
+///
+/// The module name must be the name of the package, as it is used to find
+/// the namespace.
+///
 /// Outputs:
 ///
 /// ```ignore
@@ -133,9 +162,14 @@ pub fn list(item: TokenStream) -> TokenStream {
 /// This currently works by parsing and evaluating the string in R, but will probably acquire
 /// some shortcuts for simple expessions, for example by caching symbols and constant values.
 ///
+/// Arguments can be named with `name = value`. Names that are not rust
+/// identifiers are given as strings.
+///
 /// ```ignore
 ///     assert_eq!(call!("`+`", 1, 2), r!(3));
 ///     assert_eq!(call!("list", 1, 2), r!([r!(1), r!(2)]));
+///     assert_eq!(call!("paste", "a", "b", sep = "-"), r!("a-b"));
+///     assert_eq!(call!("mean", x, "na.rm" = true), r!(2.0));
 /// ```
 #[proc_macro]
 pub fn call(item: TokenStream) -> TokenStream {
@@ -201,7 +235,36 @@ pub fn Rraw(item: TokenStream) -> TokenStream {
 /// # }
 /// # Ok::<(), extendr_api::Error>(())
 /// ```
-#[proc_macro_derive(TryFromRobj)]
+///
+/// # Field attributes
+/// Fields of structs take the attribute `#[robj(...)]`, shared with `#[derive(IntoRobj)]`:
+///
+/// * `rename = "name"` reads the element `name` rather than the field name.
+/// * `default` gives `Default::default()` when the element is missing or `NULL`.
+/// * `skip` does not read the field, and gives `Default::default()`.
+///
+/// ```ignore
+/// #[derive(IntoRobj, TryFromRobj, PartialEq, Debug)]
+/// struct Point {
+///     #[robj(rename = "x.coord")]
+///     x: f64,
+///     #[robj(default)]
+///     label: String,
+///     #[robj(skip)]
+///     cache: Vec<f64>,
+/// }
+/// let point: Point = R!("list(x.coord = 1.5)")?.try_into()?;
+/// assert_eq!(point, Point { x: 1.5, label: String::new(), cache: vec![] });
+/// assert_eq!(Robj::from(&point), R!("list(x.coord = 1.5, label = '')")?);
+/// ```
+///
+/// A `Vec` of structs becomes a data frame with `#[derive(IntoDataFrameRow)]`,
+/// and a data frame is read into structs with `#[derive(FromDataframeRow)]`.
+///
+/// # Enums
+/// Enums with unit, tuple or struct variants are converted from lists made by `#[derive(IntoRobj)]`,
+/// using the `variant` element to pick the variant.
+#[proc_macro_derive(TryFromRobj, attributes(robj))]
 pub fn derive_try_from_robj(item: TokenStream) -> TokenStream {
     list_struct::derive_try_from_robj(item)
 }
@@ -239,7 +302,24 @@ pub fn derive_try_from_robj(item: TokenStream) -> TokenStream {
 /// Rust functions on that pointer. The implementation from `#[derive(IntoRobj)]` actually converts the Rust structure
 /// into a native R list, which allows manipulation and access to internal fields, but it's a one-way conversion,
 /// and converting it back to Rust will produce a copy of the original struct.
-#[proc_macro_derive(IntoRobj)]
+///
+/// # Enums
+/// Enums become tagged unions: a list whose first element, `variant`, is the name of the variant,
+/// followed by the fields. Struct variants keep their field names and tuple variants have unnamed
+/// elements, so in R `x$variant` tells which case it is.
+/// ```ignore
+/// #[derive(IntoRobj, TryFromRobj)]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Square(f64),
+///     Empty,
+/// }
+/// let circle: Robj = Shape::Circle { radius: 1.0 }.into();
+/// assert_eq!(circle, R!("list(variant = 'Circle', radius = 1)")?);
+/// let square: Robj = Shape::Square(2.0).into();
+/// assert_eq!(square, R!("list(variant = 'Square', 2)")?);
+/// ```
+#[proc_macro_derive(IntoRobj, attributes(robj))]
 pub fn derive_into_robj(item: TokenStream) -> TokenStream {
     list_struct::derive_into_robj(item)
 }
@@ -265,7 +345,42 @@ pub fn derive_into_robj(item: TokenStream) -> TokenStream {
 /// assert_eq!(df[1], r!(["abc", "xyz"]));
 /// ```
 
-#[proc_macro_derive(IntoDataFrameRow)]
+#[proc_macro_derive(IntoDataFrameRow, attributes(dataframe))]
 pub fn derive_into_dataframe(item: TokenStream) -> TokenStream {
     dataframe::derive_into_dataframe(item)
 }
+
+/// Read the rows of a dataframe as structures, the reverse of `IntoDataFrameRow`.
+///
+/// Columns are found by the field names and the fields can be any type
+/// implementing `FromColumnElt`. An `NA` is an error, unless the field is an
+/// `Option` or an R scalar type such as `Rfloat`.
+///
+/// Fields can have a `#[dataframe(...)]` attribute with these options:
+///
+/// - `rename = "name"` reads the column `name` instead.
+/// - `na = "default"` reads `NA` as the default value of the type.
+///
+/// `rename` is also used by `IntoDataFrameRow`, so both derives give the same columns.
+///
+/// # Example
+///
+/// ```ignore
+/// use extendr_api::prelude::*;
+///
+/// #[derive(Debug, PartialEq, FromDataframeRow)]
+/// struct Row {
+///     id: i32,
+///     #[dataframe(rename = "value.x", na = "default")]
+///     value: f64,
+///     label: Option<String>,
+/// }
+///
+/// let df: Dataframe<()> = R!("data.frame(id = 1:2, value.x = c(0.5, NA), label = c('a', NA))")?.try_into()?;
+/// let rows = df.rows::<Row>()?.collect::<Result<Vec<_>>>()?;
+/// assert_eq!(rows[1], Row { id: 2, value: 0.0, label: None });
+/// ```
+#[proc_macro_derive(FromDataframeRow, attributes(dataframe))]
+pub fn derive_from_dataframe_row(item: TokenStream) -> TokenStream {
+    dataframe::derive_from_dataframe_row(item)
+}
@@ -1,50 +1,96 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::{parse_macro_input, Data, DataEnum, DeriveInput, Field, Fields, Ident, Lit};
+
+/// Options from `#[robj(rename = "name", default, skip)]` on a struct field.
+struct FieldOptions {
+    name: String,
+    default: bool,
+    skip: bool,
+}
+
+fn field_options(field: &Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions {
+        name: field.ident.as_ref().unwrap().to_string(),
+        default: false,
+        skip: false,
+    };
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("robj")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                options.name = match meta.value()?.parse()? {
+                    Lit::Str(litstr) => litstr.value(),
+                    _ => return Err(meta.error("expected a string")),
+                };
+            } else if meta.path.is_ident("default") {
+                options.default = true;
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else {
+                return Err(meta.error("expected #[robj(rename = \"name\", default, skip)]"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
 
 /// Implementation of the TryFromRobj macro. Refer to the documentation there
 pub fn derive_try_from_robj(item: TokenStream) -> TokenStream {
     // Parse the tokens into a Struct
     let ast: DeriveInput = parse_macro_input!(item as DeriveInput);
     let struct_name = ast.ident;
-    let inside;
-    if let Data::Struct(inner) = ast.data {
-        inside = inner;
-    } else {
-        panic!("This is a derive macro, only use it on a struct")
+    let inside = match ast.data {
+        Data::Struct(inner) => inner,
+        Data::Enum(inner) => return enum_try_from_robj(&struct_name, inner),
+        _ => panic!("This is a derive macro, only use it on a struct or enum"),
     };
 
     // Iterate each struct field and capture a conversion from Robj for each field
     let mut tokens = Vec::<TokenStream2>::with_capacity(inside.fields.len());
-    for field in inside.fields {
+    for field in &inside.fields {
         let field_name = field.ident.as_ref().unwrap();
-        let field_str = field_name.to_string();
-        // This is like `value$foo` in R
-        tokens.push(quote!(
-            #field_name: value.dollar(#field_str)?.try_into()?
-        ));
+        let options = match field_options(field) {
+            Ok(options) => options,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let name = options.name;
+        tokens.push(if options.skip {
+            quote!(#field_name: Default::default())
+        } else if options.default {
+            // A missing element is NULL.
+            quote!(
+                #field_name: match value.dollar(#name)? {
+                    elt if elt.is_null() => Default::default(),
+                    elt => elt.try_into()?,
+                }
+            )
+        } else {
+            // This is like `value$foo` in R
+            quote!(#field_name: value.dollar(#name)?.try_into()?)
+        });
     }
 
     // Emit the conversion trait impl
     TokenStream::from(quote!(
-        impl std::convert::TryFrom<&Robj> for #struct_name {
+        impl std::convert::TryFrom<&extendr_api::Robj> for #struct_name {
             type Error = extendr_api::Error;
 
-            fn try_from(value: &Robj) -> extendr_api::Result<Self> {
+            fn try_from(value: &extendr_api::Robj) -> extendr_api::Result<Self> {
+                #[allow(unused_imports)]
+                use extendr_api::{Operators, Rinternals};
                 Ok(#struct_name {
                     #(#tokens),*
                 })
             }
         }
 
-        impl std::convert::TryFrom<Robj> for #struct_name {
+        impl std::convert::TryFrom<extendr_api::Robj> for #struct_name {
             type Error = extendr_api::Error;
 
-            fn try_from(value: Robj) -> extendr_api::Result<Self> {
-                Ok(#struct_name {
-                    #(#tokens),*
-                })
+            fn try_from(value: extendr_api::Robj) -> extendr_api::Result<Self> {
+                <#struct_name as std::convert::TryFrom<&extendr_api::Robj>>::try_from(&value)
             }
         }
     ))
@@ -55,35 +101,164 @@ pub fn derive_into_robj(item: TokenStream) -> TokenStream {
     // Parse the tokens into a Struct
     let ast: DeriveInput = parse_macro_input!(item as DeriveInput);
     let struct_name = ast.ident;
-    let inside;
-    if let Data::Struct(inner) = ast.data {
-        inside = inner;
-    } else {
-        panic!("This is a derive macro, only use it on a struct")
+    let inside = match ast.data {
+        Data::Struct(inner) => inner,
+        Data::Enum(inner) => return enum_into_robj(&struct_name, inner),
+        _ => panic!("This is a derive macro, only use it on a struct or enum"),
     };
 
     // Iterate each struct field and capture a token that creates a KeyValue pair (tuple) for
     // each field
     let mut tokens = Vec::<TokenStream2>::with_capacity(inside.fields.len());
 
-    for field in inside.fields {
+    for field in &inside.fields {
         let field_name = field.ident.as_ref().unwrap();
-        let field_str = field_name.to_string();
-        tokens.push(quote!(
-            (#field_str, (&value.#field_name).into())
-        ));
+        let options = match field_options(field) {
+            Ok(options) => options,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        if !options.skip {
+            let name = options.name;
+            tokens.push(quote!(
+                (#name, (&value.#field_name).into())
+            ));
+        }
     }
 
     // The only thing we emit from this macro is the conversion trait impl
     TokenStream::from(quote!(
-        impl std::convert::From<&#struct_name> for Robj {
+        impl std::convert::From<&#struct_name> for extendr_api::Robj {
             fn from(value: &#struct_name) -> Self {
                 extendr_api::List::from_pairs([#(#tokens),*]).into()
             }
         }
-        impl std::convert::From<#struct_name> for Robj {
+        impl std::convert::From<#struct_name> for extendr_api::Robj {
             fn from(value: #struct_name) -> Self {
-                extendr_api::List::from_pairs([#(#tokens),*]).into()
+                extendr_api::Robj::from(&value)
+            }
+        }
+    ))
+}
+
+/// Names of the bindings used to destructure tuple variants.
+fn tuple_bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|i| quote::format_ident!("field{}", i))
+        .collect()
+}
+
+/// Enums convert to a list whose first element, `variant`, is the variant name.
+/// Named fields follow with their own names and tuple fields follow unnamed.
+fn enum_into_robj(enum_name: &Ident, inside: DataEnum) -> TokenStream {
+    let mut arms = Vec::<TokenStream2>::with_capacity(inside.variants.len());
+    for variant in inside.variants {
+        let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
+        let variant_pair = quote!(("variant", extendr_api::Robj::from(#variant_str)));
+        arms.push(match &variant.fields {
+            Fields::Named(fields) => {
+                let names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let strs: Vec<_> = names.iter().map(|n| n.to_string()).collect();
+                quote!(
+                    #enum_name::#variant_name { #(#names),* } => extendr_api::List::from_pairs([
+                        #variant_pair,
+                        #((#strs, extendr_api::Robj::from(#names))),*
+                    ])
+                )
+            }
+            Fields::Unnamed(_) => {
+                let bindings = tuple_bindings(&variant.fields);
+                quote!(
+                    #enum_name::#variant_name(#(#bindings),*) => extendr_api::List::from_pairs([
+                        #variant_pair,
+                        #(("", extendr_api::Robj::from(#bindings))),*
+                    ])
+                )
+            }
+            Fields::Unit => quote!(
+                #enum_name::#variant_name => extendr_api::List::from_pairs([#variant_pair])
+            ),
+        });
+    }
+
+    TokenStream::from(quote!(
+        impl std::convert::From<&#enum_name> for extendr_api::Robj {
+            fn from(value: &#enum_name) -> Self {
+                match value {
+                    #(#arms),*
+                }
+                .into()
+            }
+        }
+        impl std::convert::From<#enum_name> for extendr_api::Robj {
+            fn from(value: #enum_name) -> Self {
+                extendr_api::Robj::from(&value)
+            }
+        }
+    ))
+}
+
+/// The reverse of [enum_into_robj]: dispatch on `variant`, then convert the fields.
+fn enum_try_from_robj(enum_name: &Ident, inside: DataEnum) -> TokenStream {
+    let enum_str = enum_name.to_string();
+    let mut arms = Vec::<TokenStream2>::with_capacity(inside.variants.len());
+    for variant in inside.variants {
+        let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
+        arms.push(match &variant.fields {
+            Fields::Named(fields) => {
+                let names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let strs: Vec<_> = names.iter().map(|n| n.to_string()).collect();
+                quote!(
+                    #variant_str => Ok(#enum_name::#variant_name {
+                        #(#names: list.dollar(#strs)?.try_into()?),*
+                    })
+                )
+            }
+            Fields::Unnamed(_) => {
+                // Tuple fields follow the variant name.
+                let indices = 1..=variant.fields.len();
+                quote!(
+                    #variant_str => Ok(#enum_name::#variant_name(
+                        #(list.elt(#indices)?.try_into()?),*
+                    ))
+                )
+            }
+            Fields::Unit => quote!(#variant_str => Ok(#enum_name::#variant_name)),
+        });
+    }
+
+    TokenStream::from(quote!(
+        impl std::convert::TryFrom<&extendr_api::Robj> for #enum_name {
+            type Error = extendr_api::Error;
+
+            fn try_from(value: &extendr_api::Robj) -> extendr_api::Result<Self> {
+                use extendr_api::Operators;
+                let list = <extendr_api::List as std::convert::TryFrom<&extendr_api::Robj>>::try_from(value)?;
+                let variant = list.dollar("variant")?;
+                match variant.try_as_str()? {
+                    #(#arms,)*
+                    other => Err(extendr_api::Error::Other(format!(
+                        "unknown variant '{}' for {}",
+                        other, #enum_str
+                    ))),
+                }
+            }
+        }
+
+        impl std::convert::TryFrom<extendr_api::Robj> for #enum_name {
+            type Error = extendr_api::Error;
+
+            fn try_from(value: extendr_api::Robj) -> extendr_api::Result<Self> {
+                <#enum_name as std::convert::TryFrom<&extendr_api::Robj>>::try_from(&value)
             }
         }
     ))
@@ -1,6 +1,9 @@
 //! Internal module for parsing R-like variadic arguments.
 
-use syn::{parse::ParseStream, punctuated::Punctuated, Expr, ExprAssign, ExprPath, Token};
+use syn::ext::IdentExt;
+use syn::{
+    parse::ParseStream, punctuated::Punctuated, Expr, ExprAssign, ExprLit, ExprPath, Lit, Token,
+};
 
 #[derive(Debug)]
 pub(crate) struct Pairs {
@@ -27,17 +30,26 @@ impl syn::parse::Parse for Pairs {
 impl Pairs {
     // Having parsed a variadic expression, extract the names and values.
     pub(crate) fn names_and_values(&self) -> Vec<(String, &Expr)> {
-        self.pairs
-            .iter()
-            .map(|e| -> (String, &Expr) {
-                if let Expr::Assign(ExprAssign { left, right, .. }) = e {
-                    if let Expr::Path(ExprPath { path, .. }) = &**left {
-                        let s = path.get_ident().unwrap().to_string();
-                        return (s, right.as_ref());
-                    }
+        self.pairs.iter().map(name_and_value).collect()
+    }
+}
+
+// Split an argument into its name and value: `a = 1`, `r#in = 1` and
+// `"na.rm" = 1` are named, anything else has the name "".
+pub(crate) fn name_and_value(e: &Expr) -> (String, &Expr) {
+    if let Expr::Assign(ExprAssign { left, right, .. }) = e {
+        match &**left {
+            Expr::Path(ExprPath { path, .. }) => {
+                if let Some(ident) = path.get_ident() {
+                    return (ident.unraw().to_string(), right.as_ref());
                 }
-                ("".to_owned(), e)
-            })
-            .collect()
+            }
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(name),
+                ..
+            }) => return (name.value(), right.as_ref()),
+            _ => (),
+        }
     }
+    ("".to_owned(), e)
 }
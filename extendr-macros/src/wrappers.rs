@@ -11,6 +11,13 @@ pub struct ExtendrOptions {
     pub r_name: Option<String>,
     pub mod_name: Option<String>,
     pub use_rng: bool,
+    pub env_storage: bool,
+    pub serialize: bool,
+    pub r6: bool,
+    pub export: Option<bool>,
+    /// `Some(true)` for `coerce = "lenient"`, `Some(false)` for `coerce = "strict"`
+    /// and `None` to follow `options(extendr.coerce)` at run time.
+    pub coerce_lenient: Option<bool>,
 }
 
 // Generate wrappers for a specific function.
@@ -42,17 +49,27 @@ pub fn make_function_wrappers(
 
     let rust_name_str = format!("{}", rust_name);
     let c_name_str = format!("{}", mod_name);
-    let doc_string = get_doc_string(attrs);
+    let doc_string = with_export_tag(get_doc_string(attrs), opts.export);
     let return_type_string = get_return_type(sig);
+    let generics = sig.generics.clone();
 
     let inputs = &mut sig.inputs;
     let has_self = matches!(inputs.iter().next(), Some(FnArg::Receiver(_)));
 
+    let is_mut = match inputs.iter().next() {
+        Some(FnArg::Receiver(ref reciever)) => {
+            reciever.reference.is_some() && reciever.mutability.is_some()
+        }
+        _ => false,
+    };
+
+    // eg. Builder::build(self), which moves the object out of R.
+    let is_value = match inputs.iter().next() {
+        Some(FnArg::Receiver(ref reciever)) => reciever.reference.is_none(),
+        _ => false,
+    };
+
     let call_name = if has_self {
-        let is_mut = match inputs.iter().next() {
-            Some(FnArg::Receiver(ref reciever)) => reciever.mutability.is_some(),
-            _ => false,
-        };
         if is_mut {
             // eg. Person::name(&mut self)
             quote! { extendr_api::unwrap_or_throw(
@@ -79,14 +96,70 @@ pub fn make_function_wrappers(
 
     let convert_args: Vec<syn::Stmt> = inputs.iter().map(translate_to_robj).collect();
 
+    let mut actual_stmts: Vec<syn::Stmt> = Vec::new();
     let actual_args: Punctuated<Expr, Token![,]> = inputs
         .iter()
-        .filter_map(|input| translate_actual(opts, input))
+        .filter_map(|input| {
+            translate_actual(opts, input, &rust_name_str, &generics, &mut actual_stmts)
+        })
         .collect();
 
+    // With environment storage, `self` is loaded from the fields of the
+    // environment and written back after `&mut self` methods.
+    let call_expr = match self_ty {
+        Some(_) if is_value && opts.serialize => {
+            panic!("self by value is not supported with serialize, use &self or &mut self");
+        }
+        // With an external pointer, the arguments are converted before the
+        // object is taken so that a conversion error leaves it intact.
+        // Later calls get an "already consumed" error.
+        Some(self_ty) if is_value && !opts.env_storage => {
+            let arg_names: Vec<_> = (0..actual_args.len())
+                .map(|i| format_ident!("_arg{}", i))
+                .collect();
+            let arg_values = actual_args.iter();
+            quote! {
+                {
+                    #( let #arg_names = #arg_values; )*
+                    let _self_value: #self_ty = *_self_robj.take_external_ptr::<#self_ty>()?;
+                    _self_value.#rust_name(#( #arg_names ),*)
+                }
+            }
+        }
+        Some(self_ty) if has_self && opts.env_storage => {
+            let self_ty_name = type_name(self_ty);
+            if is_mut {
+                quote! {
+                    {
+                        let mut _self_value: #self_ty = extendr_api::storage::from_env_object(&_self_robj, #self_ty_name)?;
+                        let _result = _self_value.#rust_name(#actual_args);
+                        extendr_api::storage::update_env_object(&_self_value, &_self_robj, #self_ty_name)?;
+                        _result
+                    }
+                }
+            } else {
+                quote! {
+                    extendr_api::storage::from_env_object::<#self_ty>(&_self_robj, #self_ty_name)?.#rust_name(#actual_args)
+                }
+            }
+        }
+        // With serialization, the saved state is refreshed after `&mut self` methods.
+        Some(self_ty) if is_mut && opts.serialize => {
+            quote! {
+                {
+                    let _result = #call_name(#actual_args);
+                    // The closure is inside the wrapper's `unsafe` block.
+                    extendr_api::persist::refresh::<#self_ty>(&_self_robj)?;
+                    _result
+                }
+            }
+        }
+        _ => quote! { #call_name(#actual_args) },
+    };
+
     let meta_args: Vec<Expr> = inputs
         .iter_mut()
-        .map(|input| translate_meta_arg(input, self_ty))
+        .map(|input| translate_meta_arg(input, self_ty, &generics))
         .collect();
 
     // Generate wrappers for rust functions to be called from R.
@@ -126,19 +199,30 @@ pub fn make_function_wrappers(
             // pull RNG state before evaluation
             #rng_start
 
+            // warnings deferred by the function are signalled after it returns.
+            let _warnings_scope = extendr_api::conditions::WarningsScope::enter();
+
             let wrap_result_state: std::result::Result<
                 std::result::Result<Robj, extendr_api::Error>,
                 Box<dyn std::any::Any + Send>
             > = unsafe {
+                // the `extendr.` options are read once until the outermost call returns.
+                let _options_scope = extendr_api::options::CallScope::enter();
                 #( #convert_args )*
                 std::panic::catch_unwind(||-> std::result::Result<Robj, extendr_api::Error> {
-                    Ok(extendr_api::Robj::from(#call_name(#actual_args)))
+                    // invalid options are reported with a warning, and the defaults used.
+                    let _options = extendr_api::options::call_options();
+                    #( #actual_stmts )*
+                    Ok(extendr_api::trace::trace_return(#rust_name_str, #call_expr))
                 })
             };
 
             // return RNG state back to r after evaluation
             #rng_end
 
+            // a handler that exits from a warning replaces the result with its jump.
+            let wrap_result_state = _warnings_scope.signal_deferred(wrap_result_state);
+
             // any obj created in above unsafe scope, which are not moved into wrap_result_state are now dropped
             match wrap_result_state {
                 Ok(Ok(zz)) => {
@@ -152,12 +236,11 @@ pub fn make_function_wrappers(
                 }
                 // any panic (induced by user func code or if user func yields a Result-Err as return value)
                 Err(unwind_err) => {
+                    // an R error from R code called by the user function, resume it in R.
+                    let unwind_err = extendr_api::RUnwind::resume_or_return(unwind_err);
+                    // the panic message is included with `options(extendr.panic = "detailed")`.
+                    let err_string = format!("{}\0", extendr_api::options::panic_message(#r_name_str, &*unwind_err));
                     drop(unwind_err); //did not notice any difference if dropped or not.
-                    // It should be possible to downcast the unwind_err Any type to the error
-                    // included in panic. The advantage would be the panic cause could be included
-                    // in the R terminal error message and not only via std-err.
-                    // but it should be handled in a separate function and not in-lined here.
-                    let err_string = format!("user function panicked: {}\0",#r_name_str);
                     // cannot use throw_r_error here for some reason.
                     // handle_panic() exports err string differently than throw_r_error.
                     extendr_api::handle_panic(err_string.as_str(), || panic!());
@@ -213,9 +296,30 @@ pub fn get_doc_string(attrs: &[syn::Attribute]) -> String {
     res
 }
 
+// Add or remove the roxygen `@export` tag for `#[extendr(export = bool)]`.
+pub fn with_export_tag(doc: String, export: Option<bool>) -> String {
+    let is_export_tag = |line: &str| line.trim() == "@export";
+    match export {
+        Some(true) if !doc.lines().any(is_export_tag) => {
+            if doc.is_empty() {
+                " @export".to_string()
+            } else {
+                format!("{}\n @export", doc)
+            }
+        }
+        Some(false) => doc
+            .lines()
+            .filter(|line| !is_export_tag(line))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => doc,
+    }
+}
+
 pub fn get_return_type(sig: &syn::Signature) -> String {
     match &sig.output {
         syn::ReturnType::Default => "()".into(),
+        syn::ReturnType::Type(_, ref rettype) if matches!(**rettype, Type::Never(_)) => "!".into(),
         syn::ReturnType::Type(_, ref rettype) => type_name(rettype),
     }
 }
@@ -274,8 +378,8 @@ pub fn translate_formal(input: &FnArg, self_ty: Option<&syn::Type>) -> FnArg {
         }
         // &self
         FnArg::Receiver(ref reciever) => {
-            if !reciever.attrs.is_empty() || reciever.reference.is_none() {
-                panic!("expected &self or &mut self");
+            if !reciever.attrs.is_empty() || !is_plain_self(reciever) {
+                panic!("expected self, &self or &mut self");
             }
             if self_ty.is_none() {
                 panic!("found &self in non-impl function - have you missed the #[extendr] before the impl?");
@@ -285,15 +389,24 @@ pub fn translate_formal(input: &FnArg, self_ty: Option<&syn::Type>) -> FnArg {
     }
 }
 
+// Return true for `self`, `mut self`, `&self` and `&mut self`, but not `self: Box<Self>`.
+fn is_plain_self(reciever: &syn::Receiver) -> bool {
+    reciever.colon_token.is_none()
+}
+
 // Generate code to make a metadata::Arg.
-fn translate_meta_arg(input: &mut FnArg, self_ty: Option<&syn::Type>) -> Expr {
+fn translate_meta_arg(
+    input: &mut FnArg,
+    self_ty: Option<&syn::Type>,
+    generics: &syn::Generics,
+) -> Expr {
     match input {
         // function argument.
         FnArg::Typed(ref mut pattype) => {
             let pat = pattype.pat.as_ref();
-            let ty = pattype.ty.as_ref();
+            let ty = concrete_arg_type(pattype.ty.as_ref(), generics);
             let name_string = quote! { #pat }.to_string();
-            let type_string = type_name(ty);
+            let type_string = type_name(&ty);
             let default = if let Some(default) = get_named_lit(&mut pattype.attrs, "default") {
                 quote!(Some(#default))
             } else {
@@ -309,8 +422,8 @@ fn translate_meta_arg(input: &mut FnArg, self_ty: Option<&syn::Type>) -> Expr {
         }
         // &self
         FnArg::Receiver(ref reciever) => {
-            if !reciever.attrs.is_empty() || reciever.reference.is_none() {
-                panic!("expected &self or &mut self");
+            if !reciever.attrs.is_empty() || !is_plain_self(reciever) {
+                panic!("expected self, &self or &mut self");
             }
             if self_ty.is_none() {
                 panic!("found &self in non-impl function - have you missed the #[extendr] before the impl?");
@@ -346,30 +459,164 @@ fn translate_to_robj(input: &FnArg) -> syn::Stmt {
     }
 }
 
-// Generate actual argument list for the call (ie. a list of conversions).
-fn translate_actual(opts: &ExtendrOptions, input: &FnArg) -> Option<Expr> {
-    match input {
-        FnArg::Typed(ref pattype) => {
-            let pat = &pattype.pat.as_ref();
-            let ty = &pattype.ty.as_ref();
-            if let syn::Pat::Ident(ref ident) = pat {
-                let varname = format_ident!("_{}_robj", ident.ident);
-                if opts.use_try_from {
-                    Some(parse_quote! {
-                        #varname.try_into()?
-                    })
-                } else {
-                    Some(parse_quote! { <#ty>::from_robj(&#varname)? })
+// Generate the statements that convert an argument for the call, and return
+// the variable that holds the result. Each conversion is logged when
+// conversion tracing is on, and each argument is checked for NA with
+// `options(extendr.na = "error")`.
+//
+// With `coerce = "lenient"`, or `options(extendr.coerce = "lenient")` and no
+// `coerce` setting, an argument that does not convert to its type is retried
+// as an integer or double copy, as R functions would. Only a failed
+// conversion pays for this.
+fn translate_actual(
+    opts: &ExtendrOptions,
+    input: &FnArg,
+    rust_name: &str,
+    generics: &syn::Generics,
+    stmts: &mut Vec<syn::Stmt>,
+) -> Option<Expr> {
+    let pattype = match input {
+        FnArg::Typed(pattype) => pattype,
+        // Do not use self explicitly as an actual arg.
+        FnArg::Receiver(_) => return None,
+    };
+    let ident = match pattype.pat.as_ref() {
+        syn::Pat::Ident(ident) => &ident.ident,
+        _ => return None,
+    };
+    let varname = format_ident!("_{}_robj", ident);
+    let coerced = format_ident!("_{}_coerced", ident);
+    let actual = format_ident!("_{}_actual", ident);
+    let arg_name = ident.to_string();
+    let is_generic = is_generic_arg_type(pattype.ty.as_ref(), generics);
+    let ty = &concrete_arg_type(pattype.ty.as_ref(), generics);
+
+    let convert = |robj: &Ident| -> Expr {
+        if opts.use_try_from && is_generic {
+            parse_quote! { <#ty as std::convert::TryFrom<&extendr_api::Robj>>::try_from(&#robj) }
+        } else if opts.use_try_from {
+            parse_quote! { #robj.try_into() }
+        } else {
+            parse_quote! { <#ty>::from_robj(&#robj) }
+        }
+    };
+    let first = convert(&varname);
+    let retry = convert(&coerced);
+
+    stmts.push(parse_quote! {
+        if _options.checks_args() {
+            extendr_api::trace::trace_arg::<#ty>(#rust_name, #arg_name, &#varname);
+            extendr_api::options::check_na(#rust_name, #arg_name, &#varname)?;
+        }
+    });
+    if opts.coerce_lenient == Some(false) {
+        stmts.push(parse_quote! { let #actual = #first?; });
+        return Some(parse_quote! { #actual });
+    }
+
+    let converts: Expr = if opts.use_try_from && is_generic {
+        parse_quote! { <#ty as std::convert::TryFrom<&extendr_api::Robj>>::try_from(robj).is_ok() }
+    } else if opts.use_try_from {
+        parse_quote! { <#ty as std::convert::TryFrom<extendr_api::Robj>>::try_from(robj.clone()).is_ok() }
+    } else {
+        parse_quote! { <#ty>::from_robj(robj).is_ok() }
+    };
+    let coerce = if opts.coerce_lenient == Some(true) {
+        quote! { extendr_api::coerce::coerce_arg }
+    } else {
+        quote! { extendr_api::coerce::coerce_arg_if_lenient }
+    };
+    // The argument may have been moved by `try_into()`, so the copy is made
+    // from the `SEXP`.
+    stmts.push(parse_quote! { let #coerced; });
+    stmts.push(parse_quote! {
+        let #actual = match #first {
+            Ok(value) => value,
+            Err(err) => match #coerce(
+                &extendr_api::robj::Robj::from_sexp(#ident),
+                |robj: &extendr_api::Robj| #converts,
+            ) {
+                Some(robj) => {
+                    #coerced = robj;
+                    #retry?
+                }
+                None => return Err(extendr_api::Error::from(err)),
+            },
+        };
+    });
+    Some(parse_quote! { #actual })
+}
+
+// Return true for `impl Trait` arguments and arguments whose type is a type parameter.
+fn is_generic_arg_type(ty: &Type, generics: &syn::Generics) -> bool {
+    match ty {
+        Type::ImplTrait(_) => true,
+        Type::Path(syn::TypePath { qself: None, path }) => {
+            path.get_ident().map_or(false, |ident| {
+                generics.type_params().any(|p| &p.ident == ident)
+            })
+        }
+        _ => false,
+    }
+}
+
+// The type to convert an argument to from R.
+// For example:
+// impl Into<Doubles> -> Doubles
+// impl AsRef<str> -> &str
+// T where T: AsRef<[f64]> -> &[f64]
+// Other types are unchanged.
+fn concrete_arg_type(ty: &Type, generics: &syn::Generics) -> Type {
+    let bounds: Vec<&syn::TypeParamBound> = match ty {
+        Type::ImplTrait(syn::TypeImplTrait { bounds, .. }) => bounds.iter().collect(),
+        Type::Path(syn::TypePath { qself: None, path }) => {
+            let ident = match path.get_ident() {
+                Some(ident) => ident,
+                None => return ty.clone(),
+            };
+            let param = match generics.type_params().find(|p| &p.ident == ident) {
+                Some(param) => param,
+                None => return ty.clone(),
+            };
+            let mut bounds: Vec<&syn::TypeParamBound> = param.bounds.iter().collect();
+            if let Some(where_clause) = &generics.where_clause {
+                for predicate in &where_clause.predicates {
+                    if let syn::WherePredicate::Type(predicate) = predicate {
+                        if is_generic_param(&predicate.bounded_ty, ident) {
+                            bounds.extend(predicate.bounds.iter());
+                        }
+                    }
                 }
-            } else {
-                None
             }
+            bounds
         }
-        FnArg::Receiver(_) => {
-            // Do not use self explicitly as an actual arg.
-            None
+        _ => return ty.clone(),
+    };
+    for bound in bounds {
+        if let syn::TypeParamBound::Trait(syn::TraitBound { path, .. }) = bound {
+            let segment = path.segments.last().unwrap();
+            let target = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(syn::GenericArgument::Type(target)) => target,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if segment.ident == "Into" {
+                return target.clone();
+            } else if segment.ident == "AsRef" {
+                return parse_quote!(&#target);
+            }
         }
     }
+    panic!(
+        "expected a bound of Into<T> or AsRef<T> on the argument type {}",
+        quote!(#ty)
+    );
+}
+
+fn is_generic_param(ty: &Type, ident: &Ident) -> bool {
+    matches!(ty, Type::Path(syn::TypePath { qself: None, path }) if path.is_ident(ident))
 }
 
 // Get a single named literal from a list of attributes.